@@ -5,6 +5,8 @@ view = View
 git-description = Git commit {$hash} on {$date}
 
 # Navigation
+compare = Compare
+visualize = Visualize
 dashboard = Dashboard
 collections = Collections
 settings = Settings
@@ -24,6 +26,7 @@ collection-created = Collection created
 collection-deleted = Collection deleted
 delete-collection = Delete Collection
 confirm-delete-collection = Are you sure you want to delete this collection?
+export-collection = Export...
 
 # Settings page
 servers = Servers
@@ -36,17 +39,43 @@ server-name-placeholder = My Server
 server-url = Server URL
 server-url-description = The URL of your ChromaDB server
 server-url-placeholder = http://localhost:8000
-auth-token = Auth Token
-auth-token-description = Token for authentication (optional)
-auth-token-placeholder = Enter token...
-auth-header-type = Auth Header Type
-auth-header-type-description = How to send the authentication token
+auth-method = Authentication
+auth-method-description = How requests authenticate with the server
+tls-proxy = TLS and Proxy
+tls-proxy-description = Certificates for private CAs and mutual TLS, and proxies
 tenant = Tenant
 tenant-description = The tenant name for multi-tenancy support
 tenant-placeholder = default_tenant
 database = Database
 database-description = The database name within the tenant
 database-placeholder = default_database
+connection-limits = Timeouts and Retries
+connect-timeout = Connect Timeout
+connect-timeout-description = Seconds to wait for a connection to the server
+request-timeout = Request Timeout
+request-timeout-description = Seconds to wait for a whole request to finish
+max-retries = Retries
+max-retries-description = How often reads are retried when the server does not respond (0 to disable)
+credential-vault = Credential Vault
+vault-status = Vault
+vault-status-missing = Not created yet. Choose a passphrase to create it.
+vault-status-locked = Locked. Servers with secrets in the vault cannot connect.
+vault-status-unlocked = Unlocked. Secrets are moved into the vault when settings are saved.
+vault-passphrase = Passphrase
+vault-passphrase-confirm = Repeat passphrase
+vault-passphrase-mismatch = The passphrases do not match
+vault-create = Create Vault
+vault-creating = Creating...
+vault-unlock = Unlock
+vault-unlocking = Unlocking...
+vault-lock = Lock
+secret-references = Secret References
+secret-references-description = Instead of a secret, enter a reference that is read when connecting
+vault-secrets-moved = Secrets moved to the vault
+vault-secrets-moved-count = secrets removed from the settings file
+vault-error = Credential vault error
+plain-secrets = Secrets saved in plain text
+plain-secrets-description = Create or unlock the credential vault to keep them out of the settings file.
 delete-server = Delete Server
 delete-server-description = Remove this server configuration
 delete = Delete
@@ -61,6 +90,7 @@ status-disconnected = Not connected
 status-connecting = Connecting...
 status-connected = Connected successfully
 status-error = Error
+error-unauthorized = The server rejected the credentials. Check the authentication settings.
 missing-resources = Missing resources
 create-missing = Create Missing
 creating = Creating...
@@ -89,6 +119,9 @@ documents-count = documents
 loading-documents = Loading documents...
 no-documents = No documents in this collection.
 no-more-documents = No more documents on this page.
+no-matching-documents = No documents match the filter.
+filter = Filter
+filter-active = Filter (active)
 metadata = Metadata
 document-details = Document Details
 document-id = Document ID
@@ -101,3 +134,139 @@ no-document-selected = No document selected
 # Pagination
 page = Page
 items-total = items total
+
+# Compare page
+compare-title = Compare Collections
+compare-description = Matches the documents of two collections by ID and lists those only one collection has and those whose text, metadata or embedding differ.
+compare-collection-a = Collection A
+compare-collection-b = Collection B
+compare-server = Server
+compare-collection = Collection
+compare-collection-placeholder = Collection name
+compare-swap = Swap
+compare-start = Compare
+compare-tolerance = Embedding tolerance
+compare-tolerance-placeholder = Tolerance
+compare-read = Read {$done} documents
+compare-read-of = Read {$done} of {$total} documents
+compare-finished = {$differ} documents differ, {$identical} are identical
+compare-cancelled = Comparison cancelled
+compare-failed = Comparison failed: {$error}
+compare-embeddings-skipped = Embeddings were not compared, because a local directory only holds those it has not indexed yet
+compare-all = All differences
+compare-only-in-a = Only in A
+compare-only-in-b = Only in B
+compare-text-differs = Text differs
+compare-metadata-differs = Metadata differs
+compare-embedding-differs = Embedding differs
+compare-changed-text = text
+compare-changed-metadata = metadata
+compare-changed-embedding = embedding
+compare-id = ID
+compare-differences = Differences
+compare-no-differences = No differences
+compare-not-in-collection = Not in this collection
+compare-no-content = [No content]
+compare-no-metadata = [No metadata]
+compare-embedding = Embedding
+compare-no-embedding = [No embedding]
+compare-embedding-summary = {$dimensions} dimensions, L2 norm {$norm}
+compare-largest-difference = Largest difference {$difference}
+
+# Visualize page
+visualize-title = Visualize '{$collection}'
+visualize-description = Each dot is a document, placed so that documents with similar embeddings are close together. Click a dot to preview its document.
+visualize-hint = Choose Visualize in the menu of a collection in the browser to plot its embeddings.
+visualize-projection = Projection
+visualize-color-by = Color by
+visualize-color-none = None
+visualize-reload = Reload
+visualize-loading = Fetching embeddings...
+visualize-projecting = Projecting {$count} embeddings with {$method}...
+visualize-failed = Visualization failed: {$error}
+visualize-position-mismatch = Got {$positions} positions for {$documents} documents
+visualize-sampled = {$count} of {$total} documents, sampled across the collection
+visualize-documents = {$count} documents
+visualize-skipped = {$count} without embeddings left out
+visualize-no-embeddings = No documents with embeddings to plot
+visualize-other = Other ({$count})
+visualize-missing = Missing ({$count})
+visualize-selected = Selected
+
+# Authentication form
+auth-kind-none = None
+auth-kind-bearer = Bearer
+auth-kind-chroma-token = X-Chroma-Token
+auth-kind-basic = Basic
+auth-kind-headers = Headers
+auth-kind-command = Command
+auth-token = Token
+auth-username = Username
+auth-password = Password
+auth-header-name = Header
+auth-header-value = Value
+auth-add-header = + Header
+auth-command-placeholder = e.g. vault read -field=token secret/chroma
+auth-command-description = The first line the command prints is sent as a Bearer token. It runs again when the server rejects the token.
+auth-username-required = Username is required for Basic authentication
+auth-invalid-header = Invalid header name: {$name}
+auth-command-required = Enter a command that prints a token
+
+# TLS and proxy settings
+tls-show = TLS and proxy...
+tls-hide = Hide TLS and proxy
+tls-insecure = Insecure
+tls-ca-bundle = CA bundle (PEM)
+tls-client-cert = Client certificate (PEM)
+tls-client-key = Client key (PKCS#8 PEM)
+tls-accept-invalid-certs = Accept invalid certificates
+tls-insecure-warning = Certificates are not verified. Anyone on the network path can read and change the traffic, including credentials.
+tls-http-proxy = HTTP proxy
+tls-https-proxy = HTTPS proxy
+tls-proxy-environment = Without a proxy set here, the proxy environment variables are used.
+tls-client-cert-incomplete = A client certificate needs both a certificate and a key file
+tls-proxy-invalid = {$name} must be a URL like http://proxy:3128
+
+# Filter builder
+filter-builder = Builder
+filter-json = JSON
+filter-match = Match
+filter-match-all = All (AND)
+filter-match-any = Any (OR)
+filter-in = in
+filter-not-in = not in
+filter-key-placeholder = key
+filter-value-placeholder = value
+filter-list-placeholder = a, b, c
+filter-add-condition = + Condition
+filter-metadata-keys = Metadata keys
+filter-document-contains = Document contains
+filter-text-placeholder = text
+filter-json-description = A where clause, or {$combined}
+filter-valid = Valid filter
+filter-counting = Counting matches...
+filter-matching = {$count} matching documents
+filter-documents = {$count} documents
+filter-count-failed = Count failed: {$error}
+filter-clear = Clear
+filter-apply = Apply
+filter-condition-no-key = Condition {$number} has no key
+filter-invalid-json = Invalid JSON: {$error}
+filter-not-object = Filter must be a JSON object
+
+# Collection options
+collection-options-show = Advanced options...
+collection-options-hide = Hide advanced options
+collection-options-space = Distance function
+collection-options-space-default = Default
+collection-options-space-l2 = L2
+collection-options-space-cosine = Cosine
+collection-options-space-ip = Inner product
+collection-options-space-fixed = The distance function cannot be changed after the collection is created.
+collection-options-hnsw = HNSW index
+collection-options-default = default
+collection-options-max-neighbors = M (max neighbors)
+collection-options-configuration = Configuration (v2 API, JSON)
+collection-options-not-object = Configuration must be a JSON object
+collection-options-invalid-json = Invalid configuration JSON: {$error}
+collection-options-not-positive = {$name} must be a positive integer
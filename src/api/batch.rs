@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Results of bulk operations that are split into batches.
+//!
+//! Chroma rejects requests with more records than its `max_batch_size`, so
+//! add, upsert, update and delete calls are sent in chunks of at most that
+//! size. Each chunk succeeds or fails on its own.
+
+use super::error::ChromaError;
+use std::fmt;
+
+/// Outcome of one batch of a bulk operation
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// IDs sent in this batch; empty for a delete by filter
+    pub ids: Vec<String>,
+    /// Why the batch failed, if it did
+    pub error: Option<ChromaError>,
+}
+
+/// Per-batch outcome of a bulk operation
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    pub batches: Vec<BatchResult>,
+}
+
+impl BatchReport {
+    pub(super) fn push(&mut self, ids: Vec<String>, result: Result<(), ChromaError>) {
+        self.batches.push(BatchResult {
+            ids,
+            error: result.err(),
+        });
+    }
+
+    /// Number of documents in batches that succeeded
+    pub fn succeeded(&self) -> usize {
+        self.batches
+            .iter()
+            .filter(|batch| batch.error.is_none())
+            .map(|batch| batch.ids.len())
+            .sum()
+    }
+
+    /// Number of documents in batches that failed
+    pub fn failed(&self) -> usize {
+        self.batches
+            .iter()
+            .filter(|batch| batch.error.is_some())
+            .map(|batch| batch.ids.len())
+            .sum()
+    }
+
+    /// Treats the operation as a whole: fails with the batch error if nothing
+    /// was written, or with a partial failure if only some batches failed.
+    pub fn into_result(self) -> Result<(), ChromaError> {
+        let succeeded = self.succeeded();
+        let failed = self.failed();
+        let mut errors = self.batches.into_iter().filter_map(|batch| batch.error);
+
+        match errors.next() {
+            None => Ok(()),
+            Some(error) if succeeded == 0 => Err(error),
+            Some(error) => Err(ChromaError::PartialFailure {
+                failed,
+                total: succeeded + failed,
+                error: Box::new(error),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed_batches = self
+            .batches
+            .iter()
+            .filter(|batch| batch.error.is_some())
+            .count();
+        write!(
+            f,
+            "{} documents written, {} failed in {} of {} batches",
+            self.succeeded(),
+            self.failed(),
+            failed_batches,
+            self.batches.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn test_report_into_result() {
+        let mut report = BatchReport::default();
+        report.push(ids(0..3), Ok(()));
+        assert!(report.clone().into_result().is_ok());
+
+        report.push(
+            ids(3..5),
+            Err(ChromaError::InvalidRequest("too large".to_string())),
+        );
+        assert_eq!((report.succeeded(), report.failed()), (3, 2));
+        match report.into_result() {
+            Err(ChromaError::PartialFailure { failed, total, .. }) => {
+                assert_eq!((failed, total), (2, 5));
+            }
+            other => panic!("expected a partial failure, got {:?}", other),
+        }
+
+        // Nothing written: the batch error is returned as is
+        let mut report = BatchReport::default();
+        report.push(ids(0..1), Err(ChromaError::Connection("down".to_string())));
+        assert!(matches!(
+            report.into_result(),
+            Err(ChromaError::Connection(_))
+        ));
+    }
+}
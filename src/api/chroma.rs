@@ -1,8 +1,24 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use super::auth::{AuthMethod, TokenCommand};
+use super::batch::BatchReport;
+use super::connection::ConnectionOptions;
+use super::error::{ChromaError, HttpError};
+use super::filter::{DocumentFilter, Where, WhereDocument};
+use super::retry::{self, RequestPolicy, RetryingGuard};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/// Metadata attached to a document or collection
+pub type Metadata = HashMap<String, serde_json::Value>;
+
+/// Page size used when counting filtered documents via the get endpoint
+const FILTERED_COUNT_PAGE_SIZE: usize = 10_000;
+
+/// Batch size for servers without the pre-flight checks endpoint
+const FALLBACK_MAX_BATCH_SIZE: usize = 1000;
 
 /// API version for ChromaDB
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -26,6 +42,11 @@ pub struct ChromaClient {
     client: reqwest::Client,
     base_url: String,
     api_version: ApiVersion,
+    policy: RequestPolicy,
+    /// Largest number of records per write, shared by clones once queried
+    max_batch_size: Arc<OnceLock<usize>>,
+    /// Source of the Bearer token if the server uses a token command
+    token_command: Option<TokenCommand>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +55,15 @@ pub struct Collection {
     pub name: String,
     #[serde(default)]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Index and embedding function settings, reported by v2 servers only
+    #[serde(default, rename = "configuration_json")]
+    pub configuration: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Limits reported by the pre-flight checks endpoint
+#[derive(Debug, Clone, Deserialize)]
+struct PreflightChecks {
+    max_batch_size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +86,19 @@ pub struct Tenant {
     pub name: String,
 }
 
+/// The authenticated caller, as reported by the v2 identity endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Identity {
+    #[serde(default)]
+    pub user_id: String,
+    /// Tenant the caller belongs to
+    #[serde(default)]
+    pub tenant: String,
+    /// Databases the caller can access within the tenant
+    #[serde(default)]
+    pub databases: Vec<String>,
+}
+
 /// Database information from ChromaDB
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Database {
@@ -72,6 +115,15 @@ pub struct Document {
     pub document: Option<String>,
     #[serde(default)]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Embedding vector (only present when requested)
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
+    /// URI of externally stored content
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// Loaded data for multimodal collections
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
 }
 
 /// Request body for getting documents
@@ -83,7 +135,174 @@ pub struct GetDocumentsRequest {
     pub limit: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub offset: Option<usize>,
-    pub include: Vec<String>,
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_filter: Option<Where>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub where_document: Option<WhereDocument>,
+    pub include: Vec<Include>,
+}
+
+/// Request body for deleting documents
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteDocumentsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<String>>,
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_filter: Option<Where>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub where_document: Option<WhereDocument>,
+}
+
+/// Request body for adding, upserting or updating documents.
+/// Each list is aligned with `ids`; lists that are left out are not changed on update.
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteDocumentsRequest {
+    pub ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<Vec<Vec<f32>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documents: Option<Vec<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadatas: Option<Vec<Option<Metadata>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uris: Option<Vec<Option<String>>>,
+}
+
+impl WriteDocumentsRequest {
+    /// Builds the column-oriented request from a list of documents.
+    /// Embeddings must be given for all documents or none of them.
+    pub fn from_documents(documents: &[Document]) -> Result<Self, ChromaError> {
+        // Only send a column if at least one document has a value for it
+        fn column<T: Clone>(
+            documents: &[Document],
+            field: impl Fn(&Document) -> &Option<T>,
+        ) -> Option<Vec<Option<T>>> {
+            documents
+                .iter()
+                .any(|doc| field(doc).is_some())
+                .then(|| documents.iter().map(|doc| field(doc).clone()).collect())
+        }
+
+        let embeddings = column(documents, |doc| &doc.embedding)
+            .map(|embeddings| {
+                embeddings
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| {
+                        ChromaError::InvalidRequest(
+                            "Embeddings must be given for all documents or none".to_string(),
+                        )
+                    })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            ids: documents.iter().map(|doc| doc.id.clone()).collect(),
+            embeddings,
+            documents: column(documents, |doc| &doc.document),
+            metadatas: column(documents, |doc| &doc.metadata),
+            uris: column(documents, |doc| &doc.uri),
+        })
+    }
+}
+
+/// Request body for renaming a collection or replacing its metadata.
+/// Fields left as `None` are not changed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModifyCollectionRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_metadata: Option<Metadata>,
+}
+
+/// Distance function used by a collection's HNSW index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceSpace {
+    #[default]
+    L2,
+    Cosine,
+    Ip,
+}
+
+impl DistanceSpace {
+    pub const ALL: [DistanceSpace; 3] =
+        [DistanceSpace::L2, DistanceSpace::Cosine, DistanceSpace::Ip];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DistanceSpace::L2 => "l2",
+            DistanceSpace::Cosine => "cosine",
+            DistanceSpace::Ip => "ip",
+        }
+    }
+}
+
+/// Options for creating a collection. Unset HNSW parameters use the server defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreateCollectionOptions {
+    pub metadata: Option<Metadata>,
+    pub space: Option<DistanceSpace>,
+    pub ef_construction: Option<u32>,
+    pub ef_search: Option<u32>,
+    /// Maximum neighbors per node (`M`)
+    pub max_neighbors: Option<u32>,
+    /// Extra v2 `configuration` object; the HNSW options above are merged into its `hnsw` entry
+    pub configuration: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl CreateCollectionOptions {
+    /// Builds the create request body. The v1 API only understands the legacy
+    /// `hnsw:*` metadata keys, while v2 takes them in the `configuration` object.
+    fn request_body(&self, name: &str, api_version: ApiVersion) -> serde_json::Value {
+        let mut metadata = self.metadata.clone().unwrap_or_default();
+        let mut body = serde_json::json!({ "name": name });
+
+        match api_version {
+            ApiVersion::V1 => {
+                let params = [
+                    ("hnsw:space", self.space.map(|space| space.as_str().into())),
+                    ("hnsw:construction_ef", self.ef_construction.map(Into::into)),
+                    ("hnsw:search_ef", self.ef_search.map(Into::into)),
+                    ("hnsw:M", self.max_neighbors.map(Into::into)),
+                ];
+                for (key, value) in params {
+                    if let Some(value) = value {
+                        metadata.insert(key.to_string(), value);
+                    }
+                }
+            }
+            ApiVersion::V2 => {
+                let mut configuration = self.configuration.clone().unwrap_or_default();
+                let params = [
+                    ("space", self.space.map(|space| space.as_str().into())),
+                    ("ef_construction", self.ef_construction.map(Into::into)),
+                    ("ef_search", self.ef_search.map(Into::into)),
+                    ("max_neighbors", self.max_neighbors.map(Into::into)),
+                ];
+                let mut hnsw = match configuration.remove("hnsw") {
+                    Some(serde_json::Value::Object(hnsw)) => hnsw,
+                    _ => serde_json::Map::new(),
+                };
+                for (key, value) in params {
+                    if let Some(value) = value {
+                        hnsw.insert(key.to_string(), value);
+                    }
+                }
+                if !hnsw.is_empty() {
+                    configuration.insert("hnsw".to_string(), hnsw.into());
+                }
+                if !configuration.is_empty() {
+                    body["configuration"] = configuration.into();
+                }
+            }
+        }
+
+        if !metadata.is_empty() {
+            body["metadata"] = serde_json::to_value(metadata).unwrap_or_default();
+        }
+        body
+    }
 }
 
 /// Response from getting documents
@@ -93,59 +312,117 @@ pub struct GetDocumentsResponse {
     #[serde(default)]
     pub documents: Option<Vec<Option<String>>>,
     #[serde(default)]
-    pub metadatas: Option<Vec<Option<HashMap<String, serde_json::Value>>>>,
-}
-
-#[derive(Debug, Clone)]
-pub enum ChromaError {
-    ConnectionFailed(String),
-    RequestFailed(String),
-    InvalidResponse(String),
+    pub metadatas: Option<Vec<Option<Metadata>>>,
+    #[serde(default)]
+    pub embeddings: Option<Vec<Option<Vec<f32>>>>,
+    #[serde(default)]
+    pub uris: Option<Vec<Option<String>>>,
+    #[serde(default)]
+    pub data: Option<Vec<Option<serde_json::Value>>>,
 }
 
-impl std::fmt::Display for ChromaError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ChromaError::ConnectionFailed(msg) => write!(f, "Connection failed: {}", msg),
-            ChromaError::RequestFailed(msg) => write!(f, "Request failed: {}", msg),
-            ChromaError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+impl GetDocumentsResponse {
+    /// Converts the column-oriented response into a list of documents
+    fn into_documents(self) -> Vec<Document> {
+        let GetDocumentsResponse {
+            ids,
+            documents,
+            metadatas,
+            embeddings,
+            uris,
+            data,
+        } = self;
+
+        // Take the i-th entry of an optional column, treating gaps as missing
+        fn nth<T>(column: &mut Option<std::vec::IntoIter<Option<T>>>) -> Option<T> {
+            column.as_mut().and_then(|values| values.next()).flatten()
         }
+        let mut documents = documents.map(Vec::into_iter);
+        let mut metadatas = metadatas.map(Vec::into_iter);
+        let mut embeddings = embeddings.map(Vec::into_iter);
+        let mut uris = uris.map(Vec::into_iter);
+        let mut data = data.map(Vec::into_iter);
+
+        ids.into_iter()
+            .map(|id| Document {
+                id,
+                document: nth(&mut documents),
+                metadata: nth(&mut metadatas),
+                embedding: nth(&mut embeddings),
+                uri: nth(&mut uris),
+                data: nth(&mut data),
+            })
+            .collect()
     }
 }
 
+/// Fields that can be requested from the get and query endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Include {
+    Documents,
+    Metadatas,
+    Embeddings,
+    Distances,
+    Uris,
+    Data,
+}
+
+/// Request body for a vector similarity query
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryRequest {
+    pub query_embeddings: Vec<Vec<f32>>,
+    pub n_results: usize,
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_filter: Option<Where>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub where_document: Option<WhereDocument>,
+    pub include: Vec<Include>,
+}
+
+/// Response from a query, with one inner list per query embedding
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryResponse {
+    pub ids: Vec<Vec<String>>,
+    #[serde(default)]
+    pub distances: Option<Vec<Vec<f32>>>,
+    #[serde(default)]
+    pub documents: Option<Vec<Vec<Option<String>>>>,
+    #[serde(default)]
+    pub metadatas: Option<Vec<Vec<Option<Metadata>>>>,
+}
+
+/// A single ranked result of a query
+#[derive(Debug, Clone)]
+pub struct QueryMatch {
+    pub document: Document,
+    /// Distance from the query embedding (lower is closer)
+    pub distance: Option<f32>,
+}
+
 impl ChromaClient {
     /// Create a new ChromaDB client
-    /// auth_header_type: "authorization" for Bearer token, "x-chroma-token" for X-Chroma-Token header
     pub fn new(
         base_url: &str,
-        auth_token: &str,
-        auth_header_type: &str,
+        auth: &AuthMethod,
+        connection: &ConnectionOptions,
         api_version: ApiVersion,
+        policy: RequestPolicy,
     ) -> Result<Self, ChromaError> {
-        let mut headers = HeaderMap::new();
-
-        if !auth_token.is_empty() {
-            match auth_header_type {
-                "x-chroma-token" => {
-                    // Use X-Chroma-Token header (token without Bearer prefix)
-                    let header_name = HeaderName::from_static("x-chroma-token");
-                    let auth_value = HeaderValue::from_str(auth_token)
-                        .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-                    headers.insert(header_name, auth_value);
-                }
-                _ => {
-                    // Default: Use Authorization: Bearer header
-                    let auth_value = HeaderValue::from_str(&format!("Bearer {}", auth_token))
-                        .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-                    headers.insert(AUTHORIZATION, auth_value);
-                }
+        let token_command = match auth {
+            AuthMethod::Command { command } => {
+                Some(TokenCommand::new(command, policy.request_timeout))
             }
-        }
+            _ => None,
+        };
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
+        let builder = reqwest::Client::builder().default_headers(auth.headers()?);
+        let client = connection
+            .apply(builder)?
+            .connect_timeout(policy.connect_timeout)
+            .timeout(policy.request_timeout)
             .build()
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+            .map_err(|e| ChromaError::Connection(e.to_string()))?;
 
         // Normalize base URL (remove trailing slash)
         let base_url = base_url.trim_end_matches('/').to_string();
@@ -154,30 +431,43 @@ impl ChromaClient {
             client,
             base_url,
             api_version,
+            policy,
+            max_batch_size: Arc::default(),
+            token_command,
         })
     }
 
-    /// Detect API version by trying v2 first, then falling back to v1
-    pub async fn detect_api_version(
+    /// Create a client, detecting the API version by trying v2 first, then falling back to v1.
+    /// Both attempts share the same connection pool.
+    pub async fn connect(
         base_url: &str,
-        auth_token: &str,
-        auth_header_type: &str,
-    ) -> Result<ApiVersion, ChromaError> {
+        auth: &AuthMethod,
+        connection: &ConnectionOptions,
+        policy: RequestPolicy,
+    ) -> Result<Self, ChromaError> {
         // Try v2 first
-        let client_v2 = Self::new(base_url, auth_token, auth_header_type, ApiVersion::V2)?;
-        if client_v2.heartbeat().await.is_ok() {
-            return Ok(ApiVersion::V2);
-        }
+        let mut client = Self::new(base_url, auth, connection, ApiVersion::V2, policy)?;
+        let v2_error = match client.heartbeat().await {
+            Ok(_) => return Ok(client),
+            Err(e) => e,
+        };
 
-        // Try v1
-        let client_v1 = Self::new(base_url, auth_token, auth_header_type, ApiVersion::V1)?;
-        if client_v1.heartbeat().await.is_ok() {
-            return Ok(ApiVersion::V1);
+        // Try v1, unless the server could not be reached at all
+        if !retry::is_transient(&v2_error) {
+            client.api_version = ApiVersion::V1;
+            if client.heartbeat().await.is_ok() {
+                return Ok(client);
+            }
         }
 
-        Err(ChromaError::ConnectionFailed(
-            "Could not connect to server with v1 or v2 API".to_string(),
-        ))
+        // Rejected credentials and timeouts are more useful than a generic failure
+        match v2_error {
+            ChromaError::Unauthorized(_) | ChromaError::Timeout { .. } => Err(v2_error),
+            _ => Err(ChromaError::Connection(format!(
+                "Could not connect to server with v1 or v2 API ({})",
+                v2_error
+            ))),
+        }
     }
 
     /// Get the API version prefix
@@ -185,53 +475,141 @@ impl ChromaClient {
         format!("{}/api/{}", self.base_url, self.api_version.as_str())
     }
 
-    /// Check server health with heartbeat endpoint
-    pub async fn heartbeat(&self) -> Result<HeartbeatResponse, ChromaError> {
-        let url = format!("{}/heartbeat", self.api_prefix());
+    /// Send a request once, turning transport failures and error responses into `ChromaError`
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ChromaError> {
+        self.send_with_retries(request, 0).await
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
+    /// Send an idempotent request, retrying transient failures with backoff
+    async fn fetch(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ChromaError> {
+        self.send_with_retries(request, self.policy.max_retries)
             .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+    }
 
-        if !response.status().is_success() {
-            return Err(ChromaError::RequestFailed(format!(
-                "Server returned status: {}",
-                response.status()
-            )));
+    /// Send a request, retrying transient failures up to `retries` times
+    async fn send_with_retries(
+        &self,
+        request: reqwest::RequestBuilder,
+        retries: u32,
+    ) -> Result<reqwest::Response, ChromaError> {
+        let mut request = request
+            .build()
+            .map_err(|e| ChromaError::InvalidRequest(e.to_string()))?;
+
+        let mut attempt = 0;
+        loop {
+            let retry = if attempt < retries {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            match (self.execute(request).await, retry) {
+                (Err(error), Some(retry)) if retry::is_transient(&error) => {
+                    attempt += 1;
+                    let _retrying = RetryingGuard::new(&self.base_url);
+                    tokio::time::sleep(self.policy.backoff(attempt)).await;
+                    request = retry;
+                }
+                (result, _) => return result,
+            }
         }
+    }
 
-        response
-            .json::<HeartbeatResponse>()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+    /// Send a single attempt of a request. With a token command, a rejected
+    /// token is refreshed and the request sent once more.
+    async fn execute(
+        &self,
+        mut request: reqwest::Request,
+    ) -> Result<reqwest::Response, ChromaError> {
+        let Some(token_command) = &self.token_command else {
+            return self.execute_once(request).await;
+        };
+
+        let retry = request.try_clone();
+        let token = token_command.authorize(&mut request, None).await?;
+        match (self.execute_once(request).await, retry) {
+            (Err(error), Some(mut retry)) if error.status() == Some(401) => {
+                token_command.authorize(&mut retry, Some(&token)).await?;
+                self.execute_once(retry).await
+            }
+            (result, _) => result,
+        }
     }
 
-    /// Get server version
-    pub async fn get_version(&self) -> Result<String, ChromaError> {
-        let url = format!("{}/version", self.api_prefix());
+    /// Send a request as is
+    async fn execute_once(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, ChromaError> {
+        let method = request.method().to_string();
+        let endpoint = request.url().path().to_string();
 
+        let _permit = retry::permit().await;
         let response = self
             .client
-            .get(&url)
-            .send()
+            .execute(request)
             .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+            .map_err(|e| ChromaError::from_transport(e, &method, &endpoint))?;
 
-        if !response.status().is_success() {
-            return Err(ChromaError::RequestFailed(format!(
-                "Server returned status: {}",
-                response.status()
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ChromaError::from_response(HttpError::new(
+                status.as_u16(),
+                &method,
+                &endpoint,
+                &body,
             )));
         }
 
-        // Version endpoint returns a plain string (with quotes)
-        let version: String = response
+        Ok(response)
+    }
+
+    /// Send a request and parse the JSON response
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ChromaError> {
+        self.send(request)
+            .await?
             .json()
             .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))?;
+            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+    }
+
+    /// Send an idempotent request with retries and parse the JSON response
+    async fn fetch_json<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ChromaError> {
+        self.fetch(request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+    }
+
+    /// Check server health with heartbeat endpoint
+    pub async fn heartbeat(&self) -> Result<HeartbeatResponse, ChromaError> {
+        let url = format!("{}/heartbeat", self.api_prefix());
+
+        self.fetch_json::<HeartbeatResponse>(self.client.get(&url))
+            .await
+    }
+
+    /// Get server version
+    pub async fn get_version(&self) -> Result<String, ChromaError> {
+        let url = format!("{}/version", self.api_prefix());
+
+        // Version endpoint returns a plain string (with quotes)
+        let version: String = self.fetch_json(self.client.get(&url)).await?;
 
         Ok(version)
     }
@@ -248,64 +626,65 @@ impl ChromaClient {
         })
     }
 
-    /// Check if a tenant exists
-    pub async fn get_tenant(&self, tenant: &str) -> Result<Tenant, ChromaError> {
-        let url = format!("{}/tenants/{}", self.api_prefix(), tenant);
+    /// Largest number of records the server accepts in one request.
+    /// Queried once from the pre-flight checks and then cached.
+    pub async fn max_batch_size(&self) -> Result<usize, ChromaError> {
+        if let Some(size) = self.max_batch_size.get() {
+            return Ok(*size);
+        }
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+        let url = format!("{}/pre-flight-checks", self.api_prefix());
+        let result = self
+            .fetch_json::<PreflightChecks>(self.client.get(&url))
+            .await;
+        let size = match result {
+            Ok(checks) => checks.max_batch_size.max(1),
+            // Older servers don't have the endpoint
+            Err(e) if matches!(e.status(), Some(404 | 405)) => FALLBACK_MAX_BATCH_SIZE,
+            Err(e) => return Err(e),
+        };
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Tenant '{}' not found: {} - {}",
-                tenant, status, body
-            )));
+        Ok(*self.max_batch_size.get_or_init(|| size))
+    }
+
+    /// Get the tenant and databases of the authenticated caller.
+    /// Returns `None` if the server has no identity endpoint (v1 and older v2 servers).
+    pub async fn get_user_identity(&self) -> Result<Option<Identity>, ChromaError> {
+        if self.api_version == ApiVersion::V1 {
+            return Ok(None);
         }
 
-        response
-            .json::<Tenant>()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+        let url = format!("{}/auth/identity", self.api_prefix());
+        match self.fetch_json::<Identity>(self.client.get(&url)).await {
+            Ok(identity) => Ok(Some(identity)),
+            Err(ChromaError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
-    /// List all tenants (may not be available on all ChromaDB installations)
-    /// Returns empty list if the endpoint is not available
-    pub async fn list_tenants(&self) -> Result<Vec<Tenant>, ChromaError> {
-        let url = format!("{}/tenants", self.api_prefix());
-        eprintln!("[DEBUG] list_tenants: GET {}", url);
+    /// Check if a tenant exists
+    pub async fn get_tenant(&self, tenant: &str) -> Result<Tenant, ChromaError> {
+        let url = format!("{}/tenants/{}", self.api_prefix(), tenant);
 
-        let response = self.client.get(&url).send().await.map_err(|e| {
-            eprintln!("[DEBUG] list_tenants: Connection error: {}", e);
-            ChromaError::ConnectionFailed(e.to_string())
-        })?;
+        self.fetch_json::<Tenant>(self.client.get(&url)).await
+    }
 
-        eprintln!(
-            "[DEBUG] list_tenants: Response status = {}",
-            response.status()
-        );
+    /// List all tenants (may not be available on all ChromaDB installations)
+    /// Returns `None` if the endpoint is not available
+    pub async fn list_tenants(&self) -> Result<Option<Vec<Tenant>>, ChromaError> {
+        let url = format!("{}/tenants", self.api_prefix());
 
-        if !response.status().is_success() {
+        let response = match self.fetch(self.client.get(&url)).await {
+            Ok(response) => response,
             // Many ChromaDB installations don't support listing tenants
-            // Return empty list instead of error
-            eprintln!("[DEBUG] list_tenants: Non-success status, returning empty list");
-            return Ok(Vec::new());
-        }
+            Err(e) if e.status().is_some() => return Ok(None),
+            Err(e) => return Err(e),
+        };
 
+        // Return empty on parse error
         let body = response.text().await.unwrap_or_default();
-        eprintln!("[DEBUG] list_tenants: Response body = {}", body);
-
-        serde_json::from_str::<Vec<Tenant>>(&body)
-            .map_err(|e| {
-                eprintln!("[DEBUG] list_tenants: Parse error: {}", e);
-                e
-            })
-            .or_else(|_| Ok(Vec::new())) // Return empty on parse error
+        let tenants = serde_json::from_str::<Vec<Tenant>>(&body).unwrap_or_default();
+        Ok(Some(tenants))
     }
 
     /// Check if a database exists within a tenant
@@ -329,26 +708,7 @@ impl ChromaClient {
             ),
         };
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Database '{}' not found in tenant '{}': {} - {}",
-                database, tenant, status, body
-            )));
-        }
-
-        response
-            .json::<Database>()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+        self.fetch_json::<Database>(self.client.get(&url)).await
     }
 
     /// Validate that both tenant and database exist
@@ -370,26 +730,8 @@ impl ChromaClient {
             ApiVersion::V2 => format!("{}/tenants/{}/databases", self.api_prefix(), tenant),
         };
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
+        self.fetch_json::<Vec<Database>>(self.client.get(&url))
             .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to list databases for tenant '{}': {} - {}",
-                tenant, status, body
-            )));
-        }
-
-        response
-            .json::<Vec<Database>>()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
     }
 
     /// Create a new tenant
@@ -398,22 +740,7 @@ impl ChromaClient {
 
         let body = serde_json::json!({ "name": tenant });
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to create tenant '{}': {} - {}",
-                tenant, status, body
-            )));
-        }
+        self.send(self.client.post(&url).json(&body)).await?;
 
         // Return the tenant info - some servers return empty response on create
         Ok(Tenant {
@@ -434,22 +761,7 @@ impl ChromaClient {
 
         let body = serde_json::json!({ "name": database });
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to create database '{}' in tenant '{}': {} - {}",
-                database, tenant, status, body
-            )));
-        }
+        self.send(self.client.post(&url).json(&body)).await?;
 
         // Return the database info
         Ok(Database {
@@ -480,21 +792,7 @@ impl ChromaClient {
             ),
         };
 
-        let response = self
-            .client
-            .delete(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to delete database '{}' in tenant '{}': {} - {}",
-                database, tenant, status, body
-            )));
-        }
+        self.send(self.client.delete(&url)).await?;
 
         Ok(())
     }
@@ -531,34 +829,60 @@ impl ChromaClient {
             ),
         };
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Server returned status: {} - {}",
-                status, body
-            )));
-        }
-
-        response
-            .json::<Vec<Collection>>()
+        self.fetch_json::<Vec<Collection>>(self.client.get(&url))
             .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
     }
 
-    /// Get documents from a collection
+    /// Get documents from a collection, optionally narrowed by a filter
     pub async fn get_documents(
         &self,
         collection_id: &str,
         limit: Option<usize>,
         offset: Option<usize>,
+        filter: &DocumentFilter,
+        tenant: &str,
+        database: &str,
+    ) -> Result<Vec<Document>, ChromaError> {
+        let request = GetDocumentsRequest {
+            ids: None,
+            limit: limit.or(Some(100)), // Default limit
+            offset,
+            where_filter: filter.where_filter.clone(),
+            where_document: filter.where_document.clone(),
+            include: vec![Include::Documents, Include::Metadatas],
+        };
+
+        self.fetch_documents(collection_id, &request, tenant, database)
+            .await
+    }
+
+    /// Get specific documents by ID, including the requested fields
+    pub async fn get_documents_by_ids(
+        &self,
+        collection_id: &str,
+        ids: Vec<String>,
+        include: Vec<Include>,
+        tenant: &str,
+        database: &str,
+    ) -> Result<Vec<Document>, ChromaError> {
+        let request = GetDocumentsRequest {
+            ids: Some(ids),
+            limit: None,
+            offset: None,
+            where_filter: None,
+            where_document: None,
+            include,
+        };
+
+        self.fetch_documents(collection_id, &request, tenant, database)
+            .await
+    }
+
+    /// Send a request to the get endpoint of a collection
+    pub(super) async fn fetch_documents(
+        &self,
+        collection_id: &str,
+        request: &GetDocumentsRequest,
         tenant: &str,
         database: &str,
     ) -> Result<Vec<Document>, ChromaError> {
@@ -579,67 +903,29 @@ impl ChromaClient {
             ),
         };
 
-        let request = GetDocumentsRequest {
-            ids: None,
-            limit: limit.or(Some(100)), // Default limit
-            offset,
-            include: vec!["documents".to_string(), "metadatas".to_string()],
-        };
-
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Server returned status: {} - {}",
-                status, body
-            )));
-        }
-
-        let result: GetDocumentsResponse = response
-            .json()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))?;
-
-        // Convert the response into a Vec<Document>
-        let documents: Vec<Document> = result
-            .ids
-            .into_iter()
-            .enumerate()
-            .map(|(i, id)| {
-                let document = result
-                    .documents
-                    .as_ref()
-                    .and_then(|docs| docs.get(i).cloned().flatten());
-                let metadata = result
-                    .metadatas
-                    .as_ref()
-                    .and_then(|metas| metas.get(i).cloned().flatten());
-                Document {
-                    id,
-                    document,
-                    metadata,
-                }
-            })
-            .collect();
+        let result: GetDocumentsResponse = self
+            .fetch_json(self.client.post(&url).json(request))
+            .await?;
 
-        Ok(documents)
+        Ok(result.into_documents())
     }
 
-    /// Get the count of documents in a collection
+    /// Get the count of documents in a collection.
+    /// The count endpoint does not accept filters, so filtered counts page
+    /// through matching IDs instead.
     pub async fn count_documents(
         &self,
         collection_id: &str,
+        filter: &DocumentFilter,
         tenant: &str,
         database: &str,
     ) -> Result<usize, ChromaError> {
+        if !filter.is_empty() {
+            return self
+                .count_filtered_documents(collection_id, filter, tenant, database)
+                .await;
+        }
+
         let url = match self.api_version {
             ApiVersion::V1 => format!(
                 "{}/databases/{}/collections/{}/count?tenant={}",
@@ -657,34 +943,63 @@ impl ChromaClient {
             ),
         };
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+        let count: usize = self.fetch_json(self.client.get(&url)).await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Server returned status: {} - {}",
-                status, body
-            )));
-        }
+        Ok(count)
+    }
 
-        let count: usize = response
-            .json()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))?;
+    /// Count documents matching a filter by fetching only their IDs
+    async fn count_filtered_documents(
+        &self,
+        collection_id: &str,
+        filter: &DocumentFilter,
+        tenant: &str,
+        database: &str,
+    ) -> Result<usize, ChromaError> {
+        let url = match self.api_version {
+            ApiVersion::V1 => format!(
+                "{}/databases/{}/collections/{}/get?tenant={}",
+                self.api_prefix(),
+                database,
+                collection_id,
+                tenant
+            ),
+            ApiVersion::V2 => format!(
+                "{}/tenants/{}/databases/{}/collections/{}/get",
+                self.api_prefix(),
+                tenant,
+                database,
+                collection_id
+            ),
+        };
 
-        Ok(count)
+        let mut count = 0;
+        loop {
+            let request = GetDocumentsRequest {
+                ids: None,
+                limit: Some(FILTERED_COUNT_PAGE_SIZE),
+                offset: Some(count),
+                where_filter: filter.where_filter.clone(),
+                where_document: filter.where_document.clone(),
+                include: Vec::new(),
+            };
+
+            let page: GetDocumentsResponse = self
+                .fetch_json(self.client.post(&url).json(&request))
+                .await?;
+
+            count += page.ids.len();
+            if page.ids.len() < FILTERED_COUNT_PAGE_SIZE {
+                return Ok(count);
+            }
+        }
     }
 
     /// Create a new collection
     pub async fn create_collection(
         &self,
         name: &str,
+        options: &CreateCollectionOptions,
         tenant: &str,
         database: &str,
     ) -> Result<Collection, ChromaError> {
@@ -703,31 +1018,40 @@ impl ChromaClient {
             ),
         };
 
-        let body = serde_json::json!({
-            "name": name
-        });
+        let body = options.request_body(name, self.api_version);
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&body)
-            .send()
+        self.send_json::<Collection>(self.client.post(&url).json(&body))
             .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to create collection '{}': {} - {}",
-                name, status, body
-            )));
-        }
+    /// Rename a collection and/or replace its metadata
+    pub async fn modify_collection(
+        &self,
+        collection_id: &str,
+        request: &ModifyCollectionRequest,
+        tenant: &str,
+        database: &str,
+    ) -> Result<(), ChromaError> {
+        let url = match self.api_version {
+            ApiVersion::V1 => format!(
+                "{}/databases/{}/collections/{}?tenant={}",
+                self.api_prefix(),
+                database,
+                collection_id,
+                tenant
+            ),
+            ApiVersion::V2 => format!(
+                "{}/tenants/{}/databases/{}/collections/{}",
+                self.api_prefix(),
+                tenant,
+                database,
+                collection_id
+            ),
+        };
 
-        response
-            .json::<Collection>()
-            .await
-            .map_err(|e| ChromaError::InvalidResponse(e.to_string()))
+        self.send(self.client.put(&url).json(request)).await?;
+
+        Ok(())
     }
 
     /// Delete a collection by name
@@ -754,33 +1078,21 @@ impl ChromaClient {
             ),
         };
 
-        let response = self
-            .client
-            .delete(&url)
-            .send()
-            .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to delete collection '{}': {} - {}",
-                collection_name, status, body
-            )));
-        }
+        self.send(self.client.delete(&url)).await?;
 
         Ok(())
     }
 
-    /// Delete documents from a collection by IDs
+    /// Delete documents from a collection by IDs and/or filter.
+    /// When both are given, only the listed documents that match the filter are deleted.
     pub async fn delete_documents(
         &self,
         collection_id: &str,
-        document_ids: Vec<String>,
+        document_ids: Option<Vec<String>>,
+        filter: &DocumentFilter,
         tenant: &str,
         database: &str,
-    ) -> Result<(), ChromaError> {
+    ) -> Result<BatchReport, ChromaError> {
         let url = match self.api_version {
             ApiVersion::V1 => format!(
                 "{}/databases/{}/collections/{}/delete?tenant={}",
@@ -798,27 +1110,239 @@ impl ChromaClient {
             ),
         };
 
-        let body = serde_json::json!({
-            "ids": document_ids
-        });
+        if document_ids.is_none() && filter.is_empty() {
+            return Err(ChromaError::InvalidRequest(
+                "Refusing to delete without IDs or a filter".to_string(),
+            ));
+        }
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&body)
-            .send()
+        let mut report = BatchReport::default();
+
+        // A delete by filter alone is a single request
+        let Some(document_ids) = document_ids else {
+            let request = DeleteDocumentsRequest {
+                ids: None,
+                where_filter: filter.where_filter.clone(),
+                where_document: filter.where_document.clone(),
+            };
+            let result = self.send(self.client.post(&url).json(&request)).await;
+            report.push(Vec::new(), result.map(|_| ()));
+            return Ok(report);
+        };
+
+        let batch_size = self.max_batch_size().await?;
+        for ids in document_ids.chunks(batch_size) {
+            let request = DeleteDocumentsRequest {
+                ids: Some(ids.to_vec()),
+                where_filter: filter.where_filter.clone(),
+                where_document: filter.where_document.clone(),
+            };
+            let result = self.send(self.client.post(&url).json(&request)).await;
+            report.push(ids.to_vec(), result.map(|_| ()));
+        }
+
+        Ok(report)
+    }
+
+    /// Add new documents to a collection. A batch fails if any of its IDs already exist.
+    pub async fn add_documents(
+        &self,
+        collection_id: &str,
+        documents: &[Document],
+        tenant: &str,
+        database: &str,
+    ) -> Result<BatchReport, ChromaError> {
+        self.write_documents("add", collection_id, documents, tenant, database)
             .await
-            .map_err(|e| ChromaError::ConnectionFailed(e.to_string()))?;
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(ChromaError::RequestFailed(format!(
-                "Failed to delete documents: {} - {}",
-                status, body
-            )));
+    /// Add documents, replacing any that already exist with the same IDs
+    pub async fn upsert_documents(
+        &self,
+        collection_id: &str,
+        documents: &[Document],
+        tenant: &str,
+        database: &str,
+    ) -> Result<BatchReport, ChromaError> {
+        self.write_documents("upsert", collection_id, documents, tenant, database)
+            .await
+    }
+
+    /// Update existing documents. Fields that are `None` on every document are left unchanged,
+    /// and metadata keys set to `null` are removed.
+    pub async fn update_documents(
+        &self,
+        collection_id: &str,
+        documents: &[Document],
+        tenant: &str,
+        database: &str,
+    ) -> Result<BatchReport, ChromaError> {
+        self.write_documents("update", collection_id, documents, tenant, database)
+            .await
+    }
+
+    /// Send documents to one of the add/upsert/update endpoints, in batches
+    /// no larger than the server accepts
+    async fn write_documents(
+        &self,
+        endpoint: &str,
+        collection_id: &str,
+        documents: &[Document],
+        tenant: &str,
+        database: &str,
+    ) -> Result<BatchReport, ChromaError> {
+        let url = match self.api_version {
+            ApiVersion::V1 => format!(
+                "{}/databases/{}/collections/{}/{}?tenant={}",
+                self.api_prefix(),
+                database,
+                collection_id,
+                endpoint,
+                tenant
+            ),
+            ApiVersion::V2 => format!(
+                "{}/tenants/{}/databases/{}/collections/{}/{}",
+                self.api_prefix(),
+                tenant,
+                database,
+                collection_id,
+                endpoint
+            ),
+        };
+
+        let batch_size = self.max_batch_size().await?;
+        let mut report = BatchReport::default();
+        for batch in documents.chunks(batch_size) {
+            let ids = batch.iter().map(|doc| doc.id.clone()).collect();
+            let result = match WriteDocumentsRequest::from_documents(batch) {
+                Ok(request) => self
+                    .send(self.client.post(&url).json(&request))
+                    .await
+                    .map(|_| ()),
+                Err(e) => Err(e),
+            };
+            report.push(ids, result);
         }
 
-        Ok(())
+        Ok(report)
+    }
+
+    /// Query a collection for the nearest neighbours of one or more embeddings.
+    /// Returns one ranked list of matches per query embedding, closest first.
+    pub async fn query_documents(
+        &self,
+        collection_id: &str,
+        request: &QueryRequest,
+        tenant: &str,
+        database: &str,
+    ) -> Result<Vec<Vec<QueryMatch>>, ChromaError> {
+        let url = match self.api_version {
+            ApiVersion::V1 => format!(
+                "{}/databases/{}/collections/{}/query?tenant={}",
+                self.api_prefix(),
+                database,
+                collection_id,
+                tenant
+            ),
+            ApiVersion::V2 => format!(
+                "{}/tenants/{}/databases/{}/collections/{}/query",
+                self.api_prefix(),
+                tenant,
+                database,
+                collection_id
+            ),
+        };
+
+        let result: QueryResponse = self
+            .fetch_json(self.client.post(&url).json(request))
+            .await?;
+
+        // Flatten the column-oriented response into per-query lists of matches
+        let matches = result
+            .ids
+            .into_iter()
+            .enumerate()
+            .map(|(q, ids)| {
+                ids.into_iter()
+                    .enumerate()
+                    .map(|(i, id)| {
+                        let document = result
+                            .documents
+                            .as_ref()
+                            .and_then(|docs| docs.get(q))
+                            .and_then(|docs| docs.get(i).cloned().flatten());
+                        let metadata = result
+                            .metadatas
+                            .as_ref()
+                            .and_then(|metas| metas.get(q))
+                            .and_then(|metas| metas.get(i).cloned().flatten());
+                        let distance = result
+                            .distances
+                            .as_ref()
+                            .and_then(|dists| dists.get(q))
+                            .and_then(|dists| dists.get(i).copied());
+                        QueryMatch {
+                            document: Document {
+                                id,
+                                document,
+                                metadata,
+                                ..Default::default()
+                            },
+                            distance,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_create_collection_body() {
+        let options = CreateCollectionOptions {
+            metadata: Some([("owner".to_string(), json!("docs"))].into_iter().collect()),
+            space: Some(DistanceSpace::Cosine),
+            ef_construction: Some(200),
+            max_neighbors: Some(32),
+            configuration: json!({ "hnsw": { "sync_threshold": 500 } })
+                .as_object()
+                .cloned(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.request_body("articles", ApiVersion::V1),
+            json!({
+                "name": "articles",
+                "metadata": {
+                    "owner": "docs",
+                    "hnsw:space": "cosine",
+                    "hnsw:construction_ef": 200,
+                    "hnsw:M": 32,
+                },
+            })
+        );
+        assert_eq!(
+            options.request_body("articles", ApiVersion::V2),
+            json!({
+                "name": "articles",
+                "metadata": { "owner": "docs" },
+                "configuration": {
+                    "hnsw": {
+                        "sync_threshold": 500,
+                        "space": "cosine",
+                        "ef_construction": 200,
+                        "max_neighbors": 32,
+                    },
+                },
+            })
+        );
     }
 }
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TLS and proxy settings of a server connection.
+//!
+//! Certificates and keys are read from PEM files whenever a client is built,
+//! so renewed certificates are picked up after reconnecting.
+
+use super::error::ChromaError;
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use serde::{Deserialize, Serialize};
+
+/// How the HTTP connection to a server is secured and routed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionOptions {
+    /// PEM file with extra root certificates, e.g. of a private CA
+    pub ca_bundle: String,
+    /// PEM file with the client certificate for mutual TLS
+    pub client_cert: String,
+    /// PEM file with the PKCS#8 private key of the client certificate
+    pub client_key: String,
+    /// Skip certificate verification, e.g. for a self-signed server
+    pub accept_invalid_certs: bool,
+    /// Proxy for `http://` servers
+    pub http_proxy: String,
+    /// Proxy for `https://` servers
+    pub https_proxy: String,
+}
+
+impl ConnectionOptions {
+    /// Apply the settings to a client being built. Without a proxy configured,
+    /// the proxy environment variables are used; with one, they are ignored.
+    pub(super) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, ChromaError> {
+        if !self.ca_bundle.trim().is_empty() {
+            let pem = read_pem("CA bundle", &self.ca_bundle)?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .map_err(|e| invalid(format!("Invalid CA bundle: {}", e)))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        match (self.client_cert.trim(), self.client_key.trim()) {
+            ("", "") => {}
+            ("", _) | (_, "") => {
+                return Err(invalid(
+                    "A client certificate needs both a certificate and a key file".to_string(),
+                ));
+            }
+            (cert, key) => {
+                let cert = read_pem("client certificate", cert)?;
+                let key = read_pem("client key", key)?;
+                let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key)
+                    .map_err(|e| invalid(format!("Invalid client certificate: {}", e)))?;
+                builder = builder.identity(identity);
+            }
+        }
+
+        if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if !self.http_proxy.trim().is_empty() {
+            let proxy = Proxy::http(self.http_proxy.trim())
+                .map_err(|e| invalid(format!("Invalid HTTP proxy: {}", e)))?;
+            builder = builder.proxy(proxy);
+        }
+        if !self.https_proxy.trim().is_empty() {
+            let proxy = Proxy::https(self.https_proxy.trim())
+                .map_err(|e| invalid(format!("Invalid HTTPS proxy: {}", e)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(builder)
+    }
+}
+
+fn read_pem(what: &str, path: &str) -> Result<Vec<u8>, ChromaError> {
+    std::fs::read(path.trim())
+        .map_err(|e| invalid(format!("Could not read {} {}: {}", what, path, e)))
+}
+
+fn invalid(message: String) -> ChromaError {
+    ChromaError::InvalidRequest(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_reports_bad_settings() {
+        let build = |options: ConnectionOptions| options.apply(reqwest::Client::builder());
+
+        assert!(build(ConnectionOptions::default()).is_ok());
+        assert!(
+            build(ConnectionOptions {
+                accept_invalid_certs: true,
+                https_proxy: "http://proxy.internal:3128".to_string(),
+                ..Default::default()
+            })
+            .is_ok()
+        );
+
+        let missing = build(ConnectionOptions {
+            ca_bundle: "/nonexistent/ca.pem".to_string(),
+            ..Default::default()
+        });
+        assert!(
+            matches!(missing, Err(ChromaError::InvalidRequest(msg)) if msg.contains("CA bundle"))
+        );
+
+        let half_identity = build(ConnectionOptions {
+            client_cert: "/etc/client.pem".to_string(),
+            ..Default::default()
+        });
+        assert!(half_identity.is_err());
+    }
+}
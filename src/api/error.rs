@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Errors returned by the ChromaDB client.
+
+use serde::Deserialize;
+use std::fmt;
+
+/// An error response from the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpError {
+    pub status: u16,
+    pub method: String,
+    /// Path of the request URL
+    pub endpoint: String,
+    /// Chroma error type, e.g. `NotFoundError`
+    pub kind: Option<String>,
+    /// Error message from the response body, or the raw body if it could not be parsed
+    pub message: String,
+}
+
+/// Error body returned by Chroma. Older FastAPI-based servers use `detail` instead.
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    detail: Option<serde_json::Value>,
+}
+
+impl HttpError {
+    /// Builds the error from a response, parsing the Chroma error body if there is one.
+    pub fn new(status: u16, method: &str, endpoint: &str, body: &str) -> Self {
+        let (kind, message) = match serde_json::from_str::<ErrorBody>(body) {
+            Ok(ErrorBody {
+                error,
+                message,
+                detail,
+            }) => {
+                let detail = detail.map(|detail| match detail {
+                    serde_json::Value::String(text) => text,
+                    other => other.to_string(),
+                });
+                match (error, message.or(detail)) {
+                    (kind, Some(message)) => (kind, message),
+                    // Older servers put the whole message in `error`, e.g. "ValueError('...')"
+                    (Some(error), None) => (None, error),
+                    (None, None) => (None, body.trim().to_string()),
+                }
+            }
+            Err(_) => (None, body.trim().to_string()),
+        };
+
+        Self {
+            status,
+            method: method.to_string(),
+            endpoint: endpoint.to_string(),
+            kind,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(
+                f,
+                "{} {} returned {}",
+                self.method, self.endpoint, self.status
+            )
+        } else {
+            write!(
+                f,
+                "{} ({} {} returned {})",
+                self.message, self.method, self.endpoint, self.status
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ChromaError {
+    /// The server could not be reached
+    Connection(String),
+    /// The request did not complete in time
+    Timeout { method: String, endpoint: String },
+    /// Credentials are missing or were rejected
+    Unauthorized(HttpError),
+    /// The tenant, database, collection or endpoint does not exist
+    NotFound(HttpError),
+    /// The resource already exists
+    Conflict(HttpError),
+    /// Any other error response
+    Http(HttpError),
+    /// The response could not be parsed
+    InvalidResponse(String),
+    /// The request was not sent because its input is invalid
+    InvalidRequest(String),
+    /// The token command failed or printed no token
+    TokenCommand(String),
+    /// A secret referenced by the server settings could not be resolved
+    Credentials(String),
+    /// A local persist directory could not be read
+    Storage(String),
+    /// Some batches of a bulk operation failed while others were written
+    PartialFailure {
+        failed: usize,
+        total: usize,
+        error: Box<ChromaError>,
+    },
+}
+
+impl ChromaError {
+    /// Classifies an error response by status code and Chroma error type.
+    /// Older servers report missing or duplicate resources as 400/500 errors,
+    /// so the error type and message are checked as well.
+    pub fn from_response(error: HttpError) -> Self {
+        let kind = error.kind.as_deref().unwrap_or_default();
+        let message = error.message.to_lowercase();
+
+        match error.status {
+            401 | 403 => ChromaError::Unauthorized(error),
+            404 => ChromaError::NotFound(error),
+            409 => ChromaError::Conflict(error),
+            _ if matches!(kind, "AuthenticationError" | "AuthorizationError") => {
+                ChromaError::Unauthorized(error)
+            }
+            _ if kind == "NotFoundError"
+                || message.contains("does not exist")
+                || message.contains("not found") =>
+            {
+                ChromaError::NotFound(error)
+            }
+            _ if kind == "UniqueConstraintError" || message.contains("already exists") => {
+                ChromaError::Conflict(error)
+            }
+            _ => ChromaError::Http(error),
+        }
+    }
+
+    /// Converts a transport error from reqwest
+    pub fn from_transport(error: reqwest::Error, method: &str, endpoint: &str) -> Self {
+        if error.is_timeout() {
+            ChromaError::Timeout {
+                method: method.to_string(),
+                endpoint: endpoint.to_string(),
+            }
+        } else {
+            ChromaError::Connection(error.to_string())
+        }
+    }
+
+    /// The error response, if the server sent one
+    pub fn http(&self) -> Option<&HttpError> {
+        match self {
+            ChromaError::Unauthorized(error)
+            | ChromaError::NotFound(error)
+            | ChromaError::Conflict(error)
+            | ChromaError::Http(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// HTTP status of the error response, if the server sent one
+    pub fn status(&self) -> Option<u16> {
+        self.http().map(|error| error.status)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ChromaError::NotFound(_))
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, ChromaError::Conflict(_))
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, ChromaError::Unauthorized(_))
+    }
+}
+
+impl fmt::Display for ChromaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChromaError::Connection(msg) => write!(f, "Connection failed: {}", msg),
+            ChromaError::Timeout { method, endpoint } => {
+                write!(f, "Request timed out: {} {}", method, endpoint)
+            }
+            ChromaError::Unauthorized(error) => write!(f, "Not authorized: {}", error),
+            ChromaError::NotFound(error) => write!(f, "Not found: {}", error),
+            ChromaError::Conflict(error) => write!(f, "Already exists: {}", error),
+            ChromaError::Http(error) => write!(f, "Request failed: {}", error),
+            ChromaError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            ChromaError::InvalidRequest(msg) => write!(f, "Invalid request: {}", msg),
+            ChromaError::TokenCommand(msg) => write!(f, "Token command failed: {}", msg),
+            ChromaError::Credentials(msg) => write!(f, "Credentials unavailable: {}", msg),
+            ChromaError::Storage(msg) => write!(f, "Could not read local directory: {}", msg),
+            ChromaError::PartialFailure {
+                failed,
+                total,
+                error,
+            } => write!(f, "{} of {} documents failed: {}", failed, total, error),
+        }
+    }
+}
+
+impl std::error::Error for ChromaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_body() {
+        let error = HttpError::new(
+            404,
+            "GET",
+            "/api/v2/tenants/acme",
+            r#"{"error":"NotFoundError","message":"Tenant acme not found"}"#,
+        );
+        assert_eq!(error.kind.as_deref(), Some("NotFoundError"));
+        assert_eq!(error.message, "Tenant acme not found");
+        assert!(ChromaError::from_response(error).is_not_found());
+
+        let legacy = HttpError::new(
+            500,
+            "POST",
+            "/api/v1/collections",
+            r#"{"error":"ValueError('Collection docs already exists')"}"#,
+        );
+        assert_eq!(
+            legacy.message,
+            "ValueError('Collection docs already exists')"
+        );
+        assert!(ChromaError::from_response(legacy).is_conflict());
+
+        let plain = HttpError::new(502, "GET", "/api/v2/heartbeat", "Bad Gateway\n");
+        assert_eq!(plain.message, "Bad Gateway");
+        assert_eq!(ChromaError::from_response(plain).status(), Some(502));
+    }
+}
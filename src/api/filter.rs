@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Typed `where` and `where_document` filters for ChromaDB requests.
+//!
+//! Chroma expresses filters as nested JSON objects such as
+//! `{"$and": [{"source": {"$eq": "wiki"}}, {"page": {"$gt": 3}}]}`.
+//! These types build and validate that JSON so the UI never has to
+//! assemble it by hand.
+
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Comparison operator for a metadata condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Nin,
+}
+
+impl Operator {
+    /// All operators, in the order they are offered in the UI
+    pub const ALL: [Operator; 8] = [
+        Operator::Eq,
+        Operator::Ne,
+        Operator::Gt,
+        Operator::Gte,
+        Operator::Lt,
+        Operator::Lte,
+        Operator::In,
+        Operator::Nin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Eq => "$eq",
+            Operator::Ne => "$ne",
+            Operator::Gt => "$gt",
+            Operator::Gte => "$gte",
+            Operator::Lt => "$lt",
+            Operator::Lte => "$lte",
+            Operator::In => "$in",
+            Operator::Nin => "$nin",
+        }
+    }
+
+    /// The operator written as `op`, such as `$gte`
+    pub fn from_symbol(op: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|o| o.as_str() == op)
+    }
+
+    /// Whether the operator expects a list of values rather than a single one
+    pub fn takes_list(&self) -> bool {
+        matches!(self, Operator::In | Operator::Nin)
+    }
+}
+
+/// A metadata filter (the `where` clause)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Where {
+    /// A condition on a single metadata key
+    Field {
+        key: String,
+        operator: Operator,
+        value: Value,
+    },
+    /// All of the nested filters must match
+    And(Vec<Where>),
+    /// Any of the nested filters must match
+    Or(Vec<Where>),
+}
+
+impl Where {
+    /// Shorthand for a single field condition
+    pub fn field(key: impl Into<String>, operator: Operator, value: Value) -> Self {
+        Where::Field {
+            key: key.into(),
+            operator,
+            value,
+        }
+    }
+
+    /// Combines filters with `$and`, collapsing the trivial cases
+    /// (Chroma rejects `$and`/`$or` with fewer than two operands).
+    pub fn all(mut filters: Vec<Where>) -> Option<Self> {
+        match filters.len() {
+            0 => None,
+            1 => filters.pop(),
+            _ => Some(Where::And(filters)),
+        }
+    }
+
+    /// Combines filters with `$or`, collapsing the trivial cases
+    pub fn any(mut filters: Vec<Where>) -> Option<Self> {
+        match filters.len() {
+            0 => None,
+            1 => filters.pop(),
+            _ => Some(Where::Or(filters)),
+        }
+    }
+
+    /// Converts the filter into Chroma's JSON representation
+    pub fn to_value(&self) -> Value {
+        match self {
+            Where::Field {
+                key,
+                operator,
+                value,
+            } => {
+                let mut condition = Map::new();
+                condition.insert(operator.as_str().to_string(), value.clone());
+                let mut object = Map::new();
+                object.insert(key.clone(), Value::Object(condition));
+                Value::Object(object)
+            }
+            Where::And(filters) => logical("$and", filters.iter().map(Where::to_value)),
+            Where::Or(filters) => logical("$or", filters.iter().map(Where::to_value)),
+        }
+    }
+
+    /// Parses and validates a filter from Chroma's JSON representation.
+    ///
+    /// Bare values (`{"source": "wiki"}`) are treated as `$eq`, and objects
+    /// with several keys are combined with `$and`.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| "A where filter must be a JSON object".to_string())?;
+        if object.is_empty() {
+            return Err("A where filter cannot be empty".to_string());
+        }
+
+        let mut filters = Vec::with_capacity(object.len());
+        for (key, value) in object {
+            let filter = match key.as_str() {
+                "$and" | "$or" => {
+                    let operands = value
+                        .as_array()
+                        .ok_or_else(|| format!("'{}' expects a list of filters", key))?
+                        .iter()
+                        .map(Where::from_value)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if operands.len() < 2 {
+                        return Err(format!("'{}' needs at least two filters", key));
+                    }
+                    if key == "$and" {
+                        Where::And(operands)
+                    } else {
+                        Where::Or(operands)
+                    }
+                }
+                _ if key.starts_with('$') => {
+                    return Err(format!("Unknown logical operator '{}'", key));
+                }
+                _ => parse_field(key, value)?,
+            };
+            filters.push(filter);
+        }
+
+        Ok(Where::all(filters).expect("object is not empty"))
+    }
+
+    /// Metadata keys referenced anywhere in this filter
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Where::Field { key, .. } => vec![key.as_str()],
+            Where::And(filters) | Where::Or(filters) => {
+                filters.iter().flat_map(Where::keys).collect()
+            }
+        }
+    }
+}
+
+impl Serialize for Where {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// A full-text filter on document contents (the `where_document` clause)
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhereDocument {
+    Contains(String),
+    NotContains(String),
+    Regex(String),
+    NotRegex(String),
+    And(Vec<WhereDocument>),
+    Or(Vec<WhereDocument>),
+}
+
+impl WhereDocument {
+    /// Converts the filter into Chroma's JSON representation
+    pub fn to_value(&self) -> Value {
+        let (op, text) = match self {
+            WhereDocument::Contains(text) => ("$contains", text),
+            WhereDocument::NotContains(text) => ("$not_contains", text),
+            WhereDocument::Regex(text) => ("$regex", text),
+            WhereDocument::NotRegex(text) => ("$not_regex", text),
+            WhereDocument::And(filters) => {
+                return logical("$and", filters.iter().map(WhereDocument::to_value));
+            }
+            WhereDocument::Or(filters) => {
+                return logical("$or", filters.iter().map(WhereDocument::to_value));
+            }
+        };
+        let mut object = Map::new();
+        object.insert(op.to_string(), Value::String(text.clone()));
+        Value::Object(object)
+    }
+
+    /// Parses and validates a document filter from Chroma's JSON representation
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| "A where_document filter must be a JSON object".to_string())?;
+        if object.len() != 1 {
+            return Err("A where_document filter must have exactly one operator".to_string());
+        }
+        let (op, operand) = object.iter().next().expect("length checked above");
+
+        if op == "$and" || op == "$or" {
+            let operands = operand
+                .as_array()
+                .ok_or_else(|| format!("'{}' expects a list of filters", op))?
+                .iter()
+                .map(WhereDocument::from_value)
+                .collect::<Result<Vec<_>, _>>()?;
+            if operands.len() < 2 {
+                return Err(format!("'{}' needs at least two filters", op));
+            }
+            return Ok(if op == "$and" {
+                WhereDocument::And(operands)
+            } else {
+                WhereDocument::Or(operands)
+            });
+        }
+
+        let text = operand
+            .as_str()
+            .ok_or_else(|| format!("'{}' expects a string", op))?
+            .to_string();
+        match op.as_str() {
+            "$contains" => Ok(WhereDocument::Contains(text)),
+            "$not_contains" => Ok(WhereDocument::NotContains(text)),
+            "$regex" => Ok(WhereDocument::Regex(text)),
+            "$not_regex" => Ok(WhereDocument::NotRegex(text)),
+            _ => Err(format!("Unknown document operator '{}'", op)),
+        }
+    }
+}
+
+impl Serialize for WhereDocument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// Combined metadata and document filter applied to get, count and delete
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentFilter {
+    pub where_filter: Option<Where>,
+    pub where_document: Option<WhereDocument>,
+}
+
+impl DocumentFilter {
+    /// Returns true if the filter matches every document
+    pub fn is_empty(&self) -> bool {
+        self.where_filter.is_none() && self.where_document.is_none()
+    }
+}
+
+/// Builds a `{"$and": [...]}` / `{"$or": [...]}` object
+fn logical(op: &str, operands: impl Iterator<Item = Value>) -> Value {
+    let mut object = Map::new();
+    object.insert(op.to_string(), Value::Array(operands.collect()));
+    Value::Object(object)
+}
+
+/// Parses the condition for a single metadata key
+fn parse_field(key: &str, value: &Value) -> Result<Where, String> {
+    let Some(condition) = value.as_object() else {
+        // Bare values are shorthand for equality
+        check_scalar(key, value)?;
+        return Ok(Where::field(key, Operator::Eq, value.clone()));
+    };
+
+    if condition.len() != 1 {
+        return Err(format!(
+            "Condition on '{}' must have exactly one operator",
+            key
+        ));
+    }
+    let (op, operand) = condition.iter().next().expect("length checked above");
+    let operator = Operator::from_symbol(op)
+        .ok_or_else(|| format!("Unknown operator '{}' on '{}'", op, key))?;
+
+    if operator.takes_list() {
+        let values = operand
+            .as_array()
+            .ok_or_else(|| format!("'{}' on '{}' expects a list", op, key))?;
+        for value in values {
+            check_scalar(key, value)?;
+        }
+    } else {
+        check_scalar(key, operand)?;
+        if matches!(
+            operator,
+            Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte
+        ) && !operand.is_number()
+        {
+            return Err(format!("'{}' on '{}' expects a number", op, key));
+        }
+    }
+
+    Ok(Where::field(key, operator, operand.clone()))
+}
+
+/// Chroma metadata values are limited to strings, numbers and booleans
+fn check_scalar(key: &str, value: &Value) -> Result<(), String> {
+    match value {
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => Ok(()),
+        _ => Err(format!(
+            "Value for '{}' must be a string, number or boolean",
+            key
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_where_round_trip() {
+        let filter = Where::And(vec![
+            Where::field("source", Operator::Eq, json!("wiki")),
+            Where::Or(vec![
+                Where::field("page", Operator::Gt, json!(3)),
+                Where::field("tenant_id", Operator::In, json!(["a", "b"])),
+            ]),
+        ]);
+
+        let value = filter.to_value();
+        assert_eq!(
+            value,
+            json!({"$and": [
+                {"source": {"$eq": "wiki"}},
+                {"$or": [{"page": {"$gt": 3}}, {"tenant_id": {"$in": ["a", "b"]}}]}
+            ]})
+        );
+        assert_eq!(Where::from_value(&value).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_where_shorthand() {
+        let filter = Where::from_value(&json!({"source": "wiki"})).unwrap();
+        assert_eq!(filter, Where::field("source", Operator::Eq, json!("wiki")));
+    }
+
+    #[test]
+    fn test_where_rejects_invalid() {
+        assert!(Where::from_value(&json!([])).is_err());
+        assert!(Where::from_value(&json!({"page": {"$gt": "three"}})).is_err());
+        assert!(Where::from_value(&json!({"page": {"$like": 1}})).is_err());
+        assert!(Where::from_value(&json!({"$and": [{"a": 1}]})).is_err());
+    }
+
+    #[test]
+    fn test_where_document_round_trip() {
+        let filter = WhereDocument::Or(vec![
+            WhereDocument::Contains("rust".to_string()),
+            WhereDocument::Regex("^chroma".to_string()),
+        ]);
+        let value = filter.to_value();
+        assert_eq!(
+            value,
+            json!({"$or": [{"$contains": "rust"}, {"$regex": "^chroma"}]})
+        );
+        assert_eq!(WhereDocument::from_value(&value).unwrap(), filter);
+    }
+}
@@ -0,0 +1,953 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! In-process fake Chroma server for tests.
+//!
+//! Serves the v1 or v2 routes used by `ChromaClient` from an in-memory store
+//! over real HTTP on a local port, so the client is exercised end to end
+//! without a running Chroma. Faults can be injected to check how the client
+//! copes with slow, failing and misbehaving servers.
+//!
+//! Filters support the metadata operators the client can build and the
+//! `$contains`/`$not_contains` document operators; regex document filters
+//! match nothing.
+
+pub use super::chroma::ApiVersion;
+
+use super::chroma::{ChromaClient, Document, Identity};
+use super::retry::RequestPolicy;
+use crate::transfer::CollectionSource;
+use reqwest::StatusCode;
+use serde_json::{Map, Value, json};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Version reported by the version endpoint
+pub const VERSION: &str = "1.0.0-mock";
+
+pub const DEFAULT_TENANT: &str = "default_tenant";
+pub const DEFAULT_DATABASE: &str = "default_database";
+
+/// A failure injected into responses
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Wait this long before answering normally
+    Latency(Duration),
+    /// Answer with this status and a Chroma error body
+    Status(u16),
+    /// Answer 200 with a body that is not valid JSON
+    MalformedJson,
+}
+
+/// A document as stored by the server
+#[derive(Debug, Clone, Default)]
+struct Record {
+    id: String,
+    document: Option<String>,
+    metadata: Option<Value>,
+    embedding: Option<Vec<f32>>,
+    uri: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct StoredCollection {
+    id: String,
+    name: String,
+    tenant: String,
+    database: String,
+    metadata: Option<Value>,
+    configuration: Option<Value>,
+    records: Vec<Record>,
+}
+
+impl StoredCollection {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "metadata": self.metadata,
+            "configuration_json": self.configuration,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    api: ApiVersion,
+    /// Databases by tenant
+    tenants: BTreeMap<String, Vec<String>>,
+    collections: Vec<StoredCollection>,
+    next_id: usize,
+    list_tenants: bool,
+    identity: Option<Identity>,
+    max_batch_size: Option<usize>,
+    /// Token every request must carry, as a Bearer or X-Chroma-Token header
+    token: Option<String>,
+    /// Faults for the next requests, used up in order
+    next_faults: VecDeque<Fault>,
+    /// Fault for every request once `next_faults` is used up
+    fault: Option<Fault>,
+    /// `METHOD /path` of every request received
+    requests: Vec<String>,
+}
+
+/// A fake Chroma server listening on a local port until dropped
+pub struct MockServer {
+    url: String,
+    api: ApiVersion,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl MockServer {
+    /// Start a server answering only the routes of one API version, with a
+    /// default tenant and database
+    pub async fn start(api: ApiVersion) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let state = Arc::new(Mutex::new(State {
+            api,
+            tenants: BTreeMap::from([(
+                DEFAULT_TENANT.to_string(),
+                vec![DEFAULT_DATABASE.to_string()],
+            )]),
+            collections: Vec::new(),
+            next_id: 0,
+            list_tenants: false,
+            identity: None,
+            max_batch_size: Some(100),
+            token: None,
+            next_faults: VecDeque::new(),
+            fault: None,
+            requests: Vec::new(),
+        }));
+
+        let task = tokio::spawn(serve(listener, state.clone()));
+        Self {
+            url,
+            api,
+            state,
+            task,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client for this server that fails fast: no retries and short timeouts
+    pub fn client(&self) -> ChromaClient {
+        self.client_with(RequestPolicy {
+            connect_timeout: Duration::from_secs(2),
+            request_timeout: Duration::from_secs(2),
+            max_retries: 0,
+        })
+    }
+
+    pub fn client_with(&self, policy: RequestPolicy) -> ChromaClient {
+        ChromaClient::new(
+            &self.url,
+            &Default::default(),
+            &Default::default(),
+            self.api,
+            policy,
+        )
+        .expect("build mock client")
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reject requests that do not carry this token
+    pub fn require_token(&self, token: &str) {
+        self.state().token = Some(token.to_string());
+    }
+
+    /// Allow listing tenants, which most servers do not
+    pub fn allow_list_tenants(&self) {
+        self.state().list_tenants = true;
+    }
+
+    /// Answer the identity endpoint (v2 only) with this identity
+    pub fn set_identity(&self, identity: Identity) {
+        self.state().identity = Some(identity);
+    }
+
+    /// Limit write batches, or remove the pre-flight checks endpoint with `None`
+    pub fn set_max_batch_size(&self, size: Option<usize>) {
+        self.state().max_batch_size = size;
+    }
+
+    /// Inject a fault into the next request that has none queued yet
+    pub fn fail_next(&self, fault: Fault) {
+        self.state().next_faults.push_back(fault);
+    }
+
+    /// Inject a fault into every request, or stop with `None`
+    pub fn fail_always(&self, fault: Option<Fault>) {
+        self.state().fault = fault;
+    }
+
+    /// `METHOD /path` of every request received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.clone()
+    }
+
+    /// Number of requests whose path ends with `suffix`
+    pub fn request_count(&self, suffix: &str) -> usize {
+        self.state()
+            .requests
+            .iter()
+            .filter(|request| request.ends_with(suffix))
+            .count()
+    }
+
+    /// Create a collection in the default database, returning its ID
+    pub fn add_collection(&self, name: &str) -> String {
+        self.state()
+            .create_collection(DEFAULT_TENANT, DEFAULT_DATABASE, name, None)
+            .expect("collection name is unique")
+    }
+
+    /// Replace the configuration a collection is listed with
+    pub fn set_configuration(&self, collection_id: &str, configuration: Value) {
+        self.state()
+            .collection_mut(collection_id)
+            .expect("collection exists")
+            .configuration = Some(configuration);
+    }
+
+    /// Store documents directly, bypassing the API
+    pub fn insert(&self, collection_id: &str, documents: &[Document]) {
+        let mut state = self.state();
+        let collection = state
+            .collection_mut(collection_id)
+            .expect("collection exists");
+        collection
+            .records
+            .extend(documents.iter().map(|doc| Record {
+                id: doc.id.clone(),
+                document: doc.document.clone(),
+                metadata: doc.metadata.clone().map(|metadata| json!(metadata)),
+                embedding: doc.embedding.clone(),
+                uri: doc.uri.clone(),
+            }));
+    }
+
+    /// IDs of the documents stored in a collection, in insertion order
+    pub fn ids(&self, collection_id: &str) -> Vec<String> {
+        let mut state = self.state();
+        state
+            .collection_mut(collection_id)
+            .map(|collection| collection.records.iter().map(|r| r.id.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A collection in the default database, for transfers handed an open store
+pub fn collection_source(collection_id: &str, name: &str) -> CollectionSource {
+    CollectionSource {
+        server: Default::default(),
+        tenant: DEFAULT_TENANT.to_string(),
+        database: DEFAULT_DATABASE.to_string(),
+        collection_id: collection_id.to_string(),
+        collection_name: name.to_string(),
+    }
+}
+
+/// A document with `page` metadata and the embedding `[page, 0.5]`
+pub fn page_document(id: &str, text: &str, page: i64) -> Document {
+    Document {
+        id: id.to_string(),
+        document: Some(text.to_string()),
+        metadata: Some([("page".to_string(), json!(page))].into()),
+        embedding: Some(vec![page as f32, 0.5]),
+        ..Default::default()
+    }
+}
+
+/// A parsed HTTP request
+#[derive(Debug, Default)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    /// Header names in lowercase
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_token(&self, token: &str) -> bool {
+        self.header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or(self.header("x-chroma-token"))
+            == Some(token)
+    }
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+fn ok(body: Value) -> Response {
+    Response {
+        status: 200,
+        body: body.to_string(),
+    }
+}
+
+fn error(status: u16, kind: &str, message: &str) -> Response {
+    Response {
+        status,
+        body: json!({ "error": kind, "message": message }).to_string(),
+    }
+}
+
+fn not_found(message: &str) -> Response {
+    error(404, "NotFoundError", message)
+}
+
+fn bad_request(message: &str) -> Response {
+    error(400, "InvalidArgumentError", message)
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_connection(stream, state.clone()));
+    }
+}
+
+/// Answer one request and close the connection
+async fn handle_connection(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut stream = BufReader::new(stream);
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+
+    let fault = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .requests
+            .push(format!("{} {}", request.method, request.path));
+        state.next_faults.pop_front().or(state.fault.clone())
+    };
+
+    let response = match fault {
+        Some(Fault::Status(status)) => {
+            let kind = match status {
+                401 => "AuthenticationError",
+                _ => "InternalError",
+            };
+            error(status, kind, "Injected fault")
+        }
+        Some(Fault::MalformedJson) => Response {
+            status: 200,
+            body: r#"{"ids": [["#.to_string(),
+        },
+        Some(Fault::Latency(delay)) => {
+            tokio::time::sleep(delay).await;
+            state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .route(&request)
+        }
+        None => state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .route(&request),
+    };
+
+    let reason = StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Unknown");
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.body.len()
+    );
+    let stream = stream.get_mut();
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    stream.read_line(&mut line).await.ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut request = Request {
+        method,
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+
+    loop {
+        line.clear();
+        stream.read_line(&mut line).await.ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length = request
+        .header("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    request.body = vec![0; length];
+    stream.read_exact(&mut request.body).await.ok()?;
+    Some(request)
+}
+
+impl State {
+    fn route(&mut self, request: &Request) -> Response {
+        if let Some(token) = &self.token
+            && !request.has_token(token)
+        {
+            return error(401, "AuthenticationError", "Missing or invalid token");
+        }
+
+        let prefix = format!("/api/{}/", self.api.as_str());
+        let Some(rest) = request.path.strip_prefix(&prefix) else {
+            return not_found(&format!("No route for {}", request.path));
+        };
+        let mut segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+        // v1 names the tenant in the query string
+        if self.api == ApiVersion::V1 && segments.first() == Some(&"databases") {
+            let tenant = request.query("tenant").unwrap_or(DEFAULT_TENANT);
+            segments.splice(0..0, ["tenants", tenant]);
+        }
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["heartbeat"]) => {
+                ok(json!({ "nanosecond heartbeat": 1_700_000_000_000_000_000i64 }))
+            }
+            ("GET", ["version"]) => ok(json!(VERSION)),
+            ("GET", ["pre-flight-checks"]) => match self.max_batch_size {
+                Some(size) => ok(json!({ "max_batch_size": size })),
+                None => not_found("No pre-flight checks"),
+            },
+            ("GET", ["auth", "identity"]) => match (&self.identity, self.api) {
+                (Some(identity), ApiVersion::V2) => ok(json!(identity)),
+                _ => not_found("No identity endpoint"),
+            },
+            ("GET", ["tenants"]) if self.list_tenants => ok(self
+                .tenants
+                .keys()
+                .map(|name| json!({ "name": name }))
+                .collect()),
+            ("GET", ["tenants"]) => {
+                error(405, "MethodNotAllowed", "Listing tenants is not supported")
+            }
+            ("POST", ["tenants"]) => {
+                let name = body["name"].as_str().unwrap_or_default().to_string();
+                if self.tenants.contains_key(&name) {
+                    return error(
+                        409,
+                        "UniqueConstraintError",
+                        &format!("Tenant {} already exists", name),
+                    );
+                }
+                self.tenants.insert(name, Vec::new());
+                ok(json!({}))
+            }
+            ("GET", ["tenants", tenant]) => match self.tenants.contains_key(*tenant) {
+                true => ok(json!({ "name": tenant })),
+                false => not_found(&format!("Tenant {} not found", tenant)),
+            },
+            ("GET", ["tenants", tenant, "databases"]) => match self.tenants.get(*tenant) {
+                Some(databases) => ok(databases
+                    .iter()
+                    .map(|name| database_json(tenant, name))
+                    .collect()),
+                None => not_found(&format!("Tenant {} not found", tenant)),
+            },
+            ("POST", ["tenants", tenant, "databases"]) => {
+                let name = body["name"].as_str().unwrap_or_default().to_string();
+                let Some(databases) = self.tenants.get_mut(*tenant) else {
+                    return not_found(&format!("Tenant {} not found", tenant));
+                };
+                if databases.contains(&name) {
+                    return error(
+                        409,
+                        "UniqueConstraintError",
+                        &format!("Database {} already exists", name),
+                    );
+                }
+                databases.push(name);
+                ok(json!({}))
+            }
+            ("GET", ["tenants", tenant, "databases", database]) => {
+                match self.has_database(tenant, database) {
+                    true => ok(database_json(tenant, database)),
+                    false => not_found(&format!("Database {} not found", database)),
+                }
+            }
+            ("DELETE", ["tenants", tenant, "databases", database]) => {
+                if !self.has_database(tenant, database) {
+                    return not_found(&format!("Database {} not found", database));
+                }
+                if let Some(databases) = self.tenants.get_mut(*tenant) {
+                    databases.retain(|name| name != database);
+                }
+                self.collections
+                    .retain(|c| c.tenant != *tenant || c.database != *database);
+                ok(json!({}))
+            }
+            (
+                method,
+                [
+                    "tenants",
+                    tenant,
+                    "databases",
+                    database,
+                    "collections",
+                    rest @ ..,
+                ],
+            ) => {
+                if !self.has_database(tenant, database) {
+                    return not_found(&format!("Database {} not found", database));
+                }
+                self.route_collections(method, tenant, database, rest, &body)
+            }
+            _ => not_found(&format!("No route for {} {}", request.method, request.path)),
+        }
+    }
+
+    /// Routes below `/tenants/{tenant}/databases/{database}/collections`
+    fn route_collections(
+        &mut self,
+        method: &str,
+        tenant: &str,
+        database: &str,
+        rest: &[&str],
+        body: &Value,
+    ) -> Response {
+        match (method, rest) {
+            ("GET", []) => ok(self
+                .collections
+                .iter()
+                .filter(|c| c.tenant == tenant && c.database == database)
+                .map(StoredCollection::to_json)
+                .collect()),
+            ("POST", []) => {
+                let name = body["name"].as_str().unwrap_or_default();
+                let metadata = body.get("metadata").filter(|m| !m.is_null()).cloned();
+                let configuration = body.get("configuration").cloned();
+                // Servers list unset settings as null but do not take them back
+                if configuration
+                    .as_ref()
+                    .and_then(Value::as_object)
+                    .is_some_and(|settings| settings.values().any(Value::is_null))
+                {
+                    return bad_request("Invalid configuration: settings must not be null");
+                }
+                match self.create_collection(tenant, database, name, metadata) {
+                    Ok(id) => {
+                        let collection = self.collection_mut(&id).unwrap();
+                        collection.configuration = configuration;
+                        ok(collection.to_json())
+                    }
+                    Err(response) => response,
+                }
+            }
+            ("PUT", [id]) => {
+                let Some(collection) = self.collection_mut(id) else {
+                    return not_found(&format!("Collection {} does not exist", id));
+                };
+                if let Some(name) = body["new_name"].as_str() {
+                    collection.name = name.to_string();
+                }
+                if let Some(metadata) = body.get("new_metadata") {
+                    collection.metadata = Some(metadata.clone());
+                }
+                ok(json!({}))
+            }
+            // Collections are deleted by name
+            ("DELETE", [name]) => {
+                let before = self.collections.len();
+                self.collections
+                    .retain(|c| c.tenant != tenant || c.database != database || c.name != *name);
+                match self.collections.len() < before {
+                    true => ok(json!({})),
+                    false => not_found(&format!("Collection {} does not exist", name)),
+                }
+            }
+            (method, [id, operation]) => {
+                let max_batch_size = self.max_batch_size;
+                let Some(collection) = self.collection_mut(id) else {
+                    return not_found(&format!("Collection {} does not exist", id));
+                };
+                let batch = body["ids"].as_array().map_or(0, Vec::len);
+                if max_batch_size.is_some_and(|max| batch > max) {
+                    return bad_request(&format!(
+                        "Batch size {} exceeds the maximum of {}",
+                        batch,
+                        max_batch_size.unwrap_or_default()
+                    ));
+                }
+                match (method, *operation) {
+                    ("GET", "count") => ok(json!(collection.records.len())),
+                    ("POST", "get") => get(collection, body),
+                    ("POST", "delete") => delete(collection, body),
+                    ("POST", "add") => write(collection, body, Write::Add),
+                    ("POST", "upsert") => write(collection, body, Write::Upsert),
+                    ("POST", "update") => write(collection, body, Write::Update),
+                    ("POST", "query") => query(collection, body),
+                    _ => not_found(&format!("No route for {} {}", method, operation)),
+                }
+            }
+            _ => not_found("No such collection route"),
+        }
+    }
+
+    fn has_database(&self, tenant: &str, database: &str) -> bool {
+        self.tenants
+            .get(tenant)
+            .is_some_and(|databases| databases.iter().any(|name| name == database))
+    }
+
+    fn collection_mut(&mut self, id: &str) -> Option<&mut StoredCollection> {
+        self.collections.iter_mut().find(|c| c.id == id)
+    }
+
+    fn create_collection(
+        &mut self,
+        tenant: &str,
+        database: &str,
+        name: &str,
+        metadata: Option<Value>,
+    ) -> Result<String, Response> {
+        if name.is_empty() {
+            return Err(bad_request("Collection name is required"));
+        }
+        if self
+            .collections
+            .iter()
+            .any(|c| c.tenant == tenant && c.database == database && c.name == name)
+        {
+            return Err(error(
+                409,
+                "UniqueConstraintError",
+                &format!("Collection {} already exists", name),
+            ));
+        }
+
+        self.next_id += 1;
+        let id = format!("00000000-0000-4000-8000-{:012x}", self.next_id);
+        self.collections.push(StoredCollection {
+            id: id.clone(),
+            name: name.to_string(),
+            tenant: tenant.to_string(),
+            database: database.to_string(),
+            metadata,
+            configuration: None,
+            records: Vec::new(),
+        });
+        Ok(id)
+    }
+}
+
+fn database_json(tenant: &str, name: &str) -> Value {
+    json!({ "id": format!("{}/{}", tenant, name), "name": name, "tenant": tenant })
+}
+
+/// Records of a collection matching the `ids`, `where` and `where_document` of a request
+fn matching<'a>(collection: &'a StoredCollection, body: &Value) -> Vec<&'a Record> {
+    let ids = body["ids"].as_array();
+    collection
+        .records
+        .iter()
+        .filter(|record| ids.is_none_or(|ids| ids.iter().any(|id| id == record.id.as_str())))
+        .filter(|record| matches_where(&body["where"], record.metadata.as_ref()))
+        .filter(|record| matches_document(&body["where_document"], record.document.as_deref()))
+        .collect()
+}
+
+fn includes(body: &Value, field: &str) -> bool {
+    body["include"]
+        .as_array()
+        .is_some_and(|include| include.iter().any(|f| f == field))
+}
+
+fn get(collection: &StoredCollection, body: &Value) -> Response {
+    let offset = body["offset"].as_u64().unwrap_or(0) as usize;
+    let limit = body["limit"]
+        .as_u64()
+        .map_or(usize::MAX, |limit| limit as usize);
+    let records: Vec<&Record> = matching(collection, body)
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .collect();
+
+    let column = |field: &str, value: fn(&Record) -> Value| -> Value {
+        match includes(body, field) {
+            true => records.iter().map(|record| value(record)).collect(),
+            false => Value::Null,
+        }
+    };
+    ok(json!({
+        "ids": records.iter().map(|record| record.id.as_str()).collect::<Vec<_>>(),
+        "documents": column("documents", |r| json!(r.document)),
+        "metadatas": column("metadatas", |r| json!(r.metadata)),
+        "embeddings": column("embeddings", |r| json!(r.embedding)),
+        "uris": column("uris", |r| json!(r.uri)),
+    }))
+}
+
+fn delete(collection: &mut StoredCollection, body: &Value) -> Response {
+    let deleted: Vec<String> = matching(collection, body)
+        .into_iter()
+        .map(|record| record.id.clone())
+        .collect();
+    collection
+        .records
+        .retain(|record| !deleted.contains(&record.id));
+    ok(Value::Null)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Write {
+    /// Fails if any ID exists
+    Add,
+    /// Replaces existing records
+    Upsert,
+    /// Changes the given fields of existing records, skipping unknown IDs
+    Update,
+}
+
+fn write(collection: &mut StoredCollection, body: &Value, mode: Write) -> Response {
+    let Some(ids) = body["ids"].as_array() else {
+        return bad_request("ids are required");
+    };
+    let ids: Vec<&str> = ids.iter().filter_map(Value::as_str).collect();
+    if mode == Write::Add
+        && let Some(id) = ids
+            .iter()
+            .find(|id| collection.records.iter().any(|record| record.id == **id))
+    {
+        return error(
+            409,
+            "UniqueConstraintError",
+            &format!("Document {} already exists", id),
+        );
+    }
+
+    // Value of a column for the i-th ID; `None` if the column was left out
+    let cell = |column: &str, i: usize| body.get(column).map(|values| values[i].clone());
+
+    for (i, id) in ids.into_iter().enumerate() {
+        let embedding = cell("embeddings", i).map(|v| serde_json::from_value(v).ok());
+        let document = cell("documents", i).map(|v| v.as_str().map(str::to_string));
+        let metadata = cell("metadatas", i).map(|v| Some(v).filter(|v| !v.is_null()));
+        let uri = cell("uris", i).map(|v| v.as_str().map(str::to_string));
+
+        let existing = collection.records.iter_mut().find(|record| record.id == id);
+        match (mode, existing) {
+            (Write::Update, None) => {}
+            (Write::Update, Some(record)) => {
+                if let Some(embedding) = embedding {
+                    record.embedding = embedding;
+                }
+                if let Some(document) = document {
+                    record.document = document;
+                }
+                if let Some(uri) = uri {
+                    record.uri = uri;
+                }
+                // Metadata is merged, and keys set to null are removed
+                if let Some(Some(Value::Object(update))) = metadata {
+                    let mut merged = match record.metadata.take() {
+                        Some(Value::Object(current)) => current,
+                        _ => Map::new(),
+                    };
+                    for (key, value) in update {
+                        match value {
+                            Value::Null => merged.remove(&key),
+                            value => merged.insert(key, value),
+                        };
+                    }
+                    record.metadata = Some(Value::Object(merged));
+                }
+            }
+            (_, existing) => {
+                let record = Record {
+                    id: id.to_string(),
+                    document: document.flatten(),
+                    metadata: metadata.flatten(),
+                    embedding: embedding.flatten(),
+                    uri: uri.flatten(),
+                };
+                match existing {
+                    Some(existing) => *existing = record,
+                    None => collection.records.push(record),
+                }
+            }
+        }
+    }
+    ok(json!(true))
+}
+
+/// Ranks records with embeddings by squared L2 distance to each query embedding
+fn query(collection: &StoredCollection, body: &Value) -> Response {
+    let Ok(queries) = serde_json::from_value::<Vec<Vec<f32>>>(body["query_embeddings"].clone())
+    else {
+        return bad_request("query_embeddings are required");
+    };
+    let n_results = body["n_results"].as_u64().unwrap_or(10) as usize;
+    let candidates = matching(collection, body);
+
+    let mut ids = Vec::new();
+    let mut distances = Vec::new();
+    let mut documents = Vec::new();
+    let mut metadatas = Vec::new();
+    for query in queries {
+        let mut ranked: Vec<(f32, &Record)> = candidates
+            .iter()
+            .filter_map(|record| {
+                let embedding = record.embedding.as_ref()?;
+                let distance = embedding
+                    .iter()
+                    .zip(&query)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum();
+                Some((distance, *record))
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        ranked.truncate(n_results);
+
+        ids.push(json!(ranked.iter().map(|(_, r)| &r.id).collect::<Vec<_>>()));
+        distances.push(json!(ranked.iter().map(|(d, _)| d).collect::<Vec<_>>()));
+        documents.push(json!(
+            ranked.iter().map(|(_, r)| &r.document).collect::<Vec<_>>()
+        ));
+        metadatas.push(json!(
+            ranked.iter().map(|(_, r)| &r.metadata).collect::<Vec<_>>()
+        ));
+    }
+
+    let column = |field: &str, values: Vec<Value>| match includes(body, field) {
+        true => Value::Array(values),
+        false => Value::Null,
+    };
+    ok(json!({
+        "ids": ids,
+        "distances": column("distances", distances),
+        "documents": column("documents", documents),
+        "metadatas": column("metadatas", metadatas),
+    }))
+}
+
+/// Whether metadata matches a `where` filter; a missing filter matches everything
+fn matches_where(filter: &Value, metadata: Option<&Value>) -> bool {
+    let Some(filter) = filter.as_object() else {
+        return true;
+    };
+    filter.iter().all(|(key, condition)| {
+        let operands = condition.as_array().map(Vec::as_slice).unwrap_or_default();
+        match key.as_str() {
+            "$and" => operands.iter().all(|f| matches_where(f, metadata)),
+            "$or" => operands.iter().any(|f| matches_where(f, metadata)),
+            _ => {
+                let value = metadata.and_then(|metadata| metadata.get(key));
+                match condition.as_object() {
+                    Some(operators) => operators
+                        .iter()
+                        .all(|(operator, operand)| compare(operator, value, operand)),
+                    None => value == Some(condition),
+                }
+            }
+        }
+    })
+}
+
+fn compare(operator: &str, value: Option<&Value>, operand: &Value) -> bool {
+    let ordering = value
+        .and_then(Value::as_f64)
+        .zip(operand.as_f64())
+        .and_then(|(value, operand)| value.partial_cmp(&operand));
+    let listed = || {
+        operand
+            .as_array()
+            .is_some_and(|values| value.is_some_and(|value| values.contains(value)))
+    };
+    match operator {
+        "$eq" => value == Some(operand),
+        "$ne" => value != Some(operand),
+        "$gt" => ordering == Some(Ordering::Greater),
+        "$gte" => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        "$lt" => ordering == Some(Ordering::Less),
+        "$lte" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        "$in" => listed(),
+        "$nin" => !listed(),
+        _ => false,
+    }
+}
+
+/// Whether a document matches a `where_document` filter
+fn matches_document(filter: &Value, document: Option<&str>) -> bool {
+    let Some(filter) = filter.as_object() else {
+        return true;
+    };
+    let text = document.unwrap_or_default();
+    filter.iter().all(|(operator, operand)| {
+        let operands = operand.as_array().map(Vec::as_slice).unwrap_or_default();
+        match (operator.as_str(), operand.as_str()) {
+            ("$and", _) => operands.iter().all(|f| matches_document(f, document)),
+            ("$or", _) => operands.iter().any(|f| matches_document(f, document)),
+            ("$contains", Some(needle)) => text.contains(needle),
+            ("$not_contains", Some(needle)) => !text.contains(needle),
+            _ => false,
+        }
+    })
+}
@@ -1,5 +1,28 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod auth;
+mod batch;
 mod chroma;
+mod connection;
+mod error;
+mod filter;
+#[cfg(test)]
+pub(crate) mod mock_server;
+mod registry;
+mod retry;
+mod stream;
+#[cfg(test)]
+mod tests;
 
-pub use chroma::{ChromaClient, Collection, Document, ServerInfo};
+pub use auth::AuthMethod;
+pub use batch::BatchReport;
+pub use chroma::{
+    ChromaClient, Collection, CreateCollectionOptions, DistanceSpace, Document, Identity, Include,
+    Metadata, ModifyCollectionRequest, QueryMatch, QueryRequest, ServerInfo,
+};
+pub use connection::ConnectionOptions;
+pub use error::ChromaError;
+pub use filter::{DocumentFilter, Operator, Where, WhereDocument};
+pub use registry::ClientRegistry;
+pub use retry::is_retrying;
+pub use stream::{CancelToken, DocumentBatch, StreamOptions, paginate};
@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Shared clients, one per configured server.
+//!
+//! Detecting the API version costs up to two heartbeats, so the detected
+//! client is kept and reused for every later request to the same server,
+//! sharing its connection pool. Entries are dropped explicitly when the
+//! server settings change.
+
+use super::auth::AuthMethod;
+use super::chroma::ChromaClient;
+use super::connection::ConnectionOptions;
+use super::error::ChromaError;
+use super::retry::RequestPolicy;
+use crate::config::ServerConfig;
+use crate::credentials;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// What has been learned about a server beyond its API version
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    /// Whether the server lets us list tenants; `None` until tried
+    pub list_tenants: Option<bool>,
+}
+
+/// The settings that affect how a server is contacted. Other fields of
+/// `ServerConfig`, such as the tenant, do not need a new client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    server_url: String,
+    auth: AuthMethod,
+    connection: ConnectionOptions,
+    policy: RequestPolicy,
+}
+
+impl From<&ServerConfig> for ClientKey {
+    fn from(server: &ServerConfig) -> Self {
+        Self {
+            server_url: server.server_url.trim_end_matches('/').to_string(),
+            auth: server.auth.clone(),
+            connection: server.connection.clone(),
+            policy: RequestPolicy {
+                connect_timeout: Duration::from_secs(server.connect_timeout_secs),
+                request_timeout: Duration::from_secs(server.request_timeout_secs),
+                max_retries: server.max_retries,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    client: ChromaClient,
+    capabilities: Capabilities,
+}
+
+/// Cache of connected clients keyed by server
+#[derive(Debug, Default)]
+pub struct ClientRegistry {
+    entries: Mutex<HashMap<ClientKey, Entry>>,
+}
+
+static REGISTRY: LazyLock<ClientRegistry> = LazyLock::new(ClientRegistry::default);
+
+impl ClientRegistry {
+    /// The registry shared by the whole application
+    pub fn global() -> &'static ClientRegistry {
+        &REGISTRY
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<ClientKey, Entry>> {
+        // The map stays consistent even if a holder panicked
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the client for a server, connecting and detecting its API version on first use
+    pub async fn client(&self, server: &ServerConfig) -> Result<ChromaClient, ChromaError> {
+        let key = ClientKey::from(server);
+        if let Some(entry) = self.entries().get(&key) {
+            return Ok(entry.client.clone());
+        }
+
+        // Secrets are resolved on every connect, so changed files and variables are picked up
+        let auth = credentials::resolve_auth(&server.auth)
+            .map_err(|e| ChromaError::Credentials(e.to_string()))?;
+        let client =
+            ChromaClient::connect(&server.server_url, &auth, &server.connection, key.policy)
+                .await?;
+
+        // Another request may have connected in the meantime; keep the first client
+        let client = self
+            .entries()
+            .entry(key)
+            .or_insert(Entry {
+                client,
+                capabilities: Capabilities::default(),
+            })
+            .client
+            .clone();
+        Ok(client)
+    }
+
+    /// Drop the cached client and connect again, e.g. to test changed settings
+    pub async fn reconnect(&self, server: &ServerConfig) -> Result<ChromaClient, ChromaError> {
+        self.invalidate(server);
+        self.client(server).await
+    }
+
+    /// Capabilities learned so far, or the defaults if the server is not connected
+    pub fn capabilities(&self, server: &ServerConfig) -> Capabilities {
+        self.entries()
+            .get(&ClientKey::from(server))
+            .map(|entry| entry.capabilities.clone())
+            .unwrap_or_default()
+    }
+
+    /// Record something learned about a connected server
+    pub fn update_capabilities(
+        &self,
+        server: &ServerConfig,
+        update: impl FnOnce(&mut Capabilities),
+    ) {
+        if let Some(entry) = self.entries().get_mut(&ClientKey::from(server)) {
+            update(&mut entry.capabilities);
+        }
+    }
+
+    /// Forget the client for a server so the next request connects again
+    pub fn invalidate(&self, server: &ServerConfig) {
+        self.entries().remove(&ClientKey::from(server));
+    }
+
+    /// Forget every client, e.g. after the credential vault was locked
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timeouts, retries and the global limit on in-flight requests.
+//!
+//! Read-only requests that fail with a connection error, a timeout or a
+//! temporary server error are retried with exponential backoff. Servers with
+//! a request waiting for its next attempt can be queried so the UI can show
+//! that it is retrying rather than just loading.
+
+use super::error::ChromaError;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Maximum number of requests in flight across all servers
+const MAX_IN_FLIGHT: usize = 8;
+
+/// Delay before the first retry; doubled for every further attempt
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for the delay between attempts
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+static IN_FLIGHT: Semaphore = Semaphore::const_new(MAX_IN_FLIGHT);
+
+/// Number of requests waiting to be retried, by server base URL
+static RETRYING: LazyLock<Mutex<HashMap<String, usize>>> = LazyLock::new(Default::default);
+
+/// How requests to a server are timed out and retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequestPolicy {
+    /// Time allowed to establish a connection
+    pub connect_timeout: Duration,
+    /// Time allowed for a whole request, including reading the response
+    pub request_timeout: Duration,
+    /// Number of retries for idempotent requests; 0 disables retrying
+    pub max_retries: u32,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+            max_retries: 3,
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Delay before the given retry (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(MAX_DELAY)
+    }
+}
+
+/// Whether a failed request may succeed if sent again
+pub(super) fn is_transient(error: &ChromaError) -> bool {
+    match error {
+        ChromaError::Connection(_) | ChromaError::Timeout { .. } => true,
+        ChromaError::Http(error) => matches!(error.status, 429 | 502 | 503 | 504),
+        _ => false,
+    }
+}
+
+/// Wait for a free slot before sending a request
+pub(super) async fn permit() -> SemaphorePermit<'static> {
+    IN_FLIGHT
+        .acquire()
+        .await
+        .expect("the request semaphore is never closed")
+}
+
+/// Whether a request to the server is waiting to be retried
+pub fn is_retrying(base_url: &str) -> bool {
+    RETRYING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(base_url.trim_end_matches('/'))
+}
+
+/// Marks a server as retrying for as long as it is alive
+pub(super) struct RetryingGuard {
+    base_url: String,
+}
+
+impl RetryingGuard {
+    pub(super) fn new(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        *RETRYING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(base_url.clone())
+            .or_default() += 1;
+        Self { base_url }
+    }
+}
+
+impl Drop for RetryingGuard {
+    fn drop(&mut self) {
+        let mut retrying = RETRYING.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = retrying.get_mut(&self.base_url) {
+            *count -= 1;
+            if *count == 0 {
+                retrying.remove(&self.base_url);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_limit() {
+        let policy = RequestPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(10), MAX_DELAY);
+    }
+
+    #[test]
+    fn test_retrying_guard() {
+        let url = "http://retry-guard.test:8000";
+        let first = RetryingGuard::new(url);
+        let second = RetryingGuard::new(&format!("{}/", url));
+        assert!(is_retrying(url));
+        drop(first);
+        assert!(is_retrying(url));
+        drop(second);
+        assert!(!is_retrying(url));
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Streaming every document of a collection in pages.
+//!
+//! The get endpoint returns one page at a time, so features that need a
+//! whole collection (exports, statistics, deduplication) page through it with
+//! `ChromaClient::stream_documents` instead of writing their own offset loop.
+//! Other sources of documents reuse the same loop through `paginate`.
+
+use super::chroma::{ChromaClient, Document, GetDocumentsRequest, Include};
+use super::error::ChromaError;
+use super::filter::DocumentFilter;
+use futures_util::future::BoxFuture;
+use futures_util::{Stream, stream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Default number of documents fetched per request
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+
+/// What to fetch while streaming a collection
+#[derive(Debug, Clone)]
+pub struct StreamOptions {
+    /// Documents per request
+    pub page_size: usize,
+    /// Fields to include with every document
+    pub include: Vec<Include>,
+    /// Only stream documents matching this filter
+    pub filter: DocumentFilter,
+    /// Documents to skip, to resume an earlier stream where it stopped
+    pub offset: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            include: vec![Include::Documents, Include::Metadatas],
+            filter: DocumentFilter::default(),
+            offset: 0,
+        }
+    }
+}
+
+/// One page of a document stream
+#[derive(Debug, Clone)]
+pub struct DocumentBatch {
+    pub documents: Vec<Document>,
+    /// Documents fetched so far, including this batch
+    pub fetched: usize,
+    /// Number of documents to stream, if it could be counted
+    pub total: Option<usize>,
+}
+
+/// Stops a document stream before its next request.
+/// Clones share the same flag, so one can be kept to cancel a running stream.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Position of a running stream
+struct StreamState<F> {
+    fetch: F,
+    count: Option<BoxFuture<'static, Result<usize, ChromaError>>>,
+    page_size: usize,
+    cancel: CancelToken,
+    fetched: usize,
+    /// Count of documents to stream, once `count` has run
+    total: Option<usize>,
+    done: bool,
+}
+
+/// Page through documents with `fetch(offset, limit)` from `offset` on,
+/// counting them first with `count` for progress. Skipped documents count as
+/// fetched.
+///
+/// This is the loop behind `ChromaClient::stream_documents`, for other
+/// sources of documents to stream the same way.
+pub fn paginate<F>(
+    count: BoxFuture<'static, Result<usize, ChromaError>>,
+    fetch: F,
+    offset: usize,
+    page_size: usize,
+    cancel: CancelToken,
+) -> impl Stream<Item = Result<DocumentBatch, ChromaError>> + Send + 'static
+where
+    F: FnMut(usize, usize) -> BoxFuture<'static, Result<Vec<Document>, ChromaError>>
+        + Send
+        + 'static,
+{
+    let state = StreamState {
+        fetch,
+        count: Some(count),
+        page_size: page_size.max(1),
+        cancel,
+        fetched: offset,
+        total: None,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done || state.cancel.is_cancelled() {
+            return None;
+        }
+
+        // Progress is only informational, so a failed count does not stop the stream
+        if let Some(count) = state.count.take() {
+            state.total = count.await.ok();
+        }
+
+        match (state.fetch)(state.fetched, state.page_size).await {
+            Ok(documents) if documents.is_empty() => None,
+            Ok(documents) => {
+                state.fetched += documents.len();
+                state.done = documents.len() < state.page_size;
+                let batch = DocumentBatch {
+                    documents,
+                    fetched: state.fetched,
+                    total: state.total,
+                };
+                Some((Ok(batch), state))
+            }
+            Err(e) => {
+                state.done = true;
+                Some((Err(e), state))
+            }
+        }
+    })
+}
+
+impl ChromaClient {
+    /// Stream every document of a collection, one page per item.
+    ///
+    /// The stream ends after the last page, after the first error, or once
+    /// `cancel` is triggered; a cancelled stream simply stops, so check the
+    /// token to tell it apart from a complete one.
+    pub fn stream_documents(
+        &self,
+        collection_id: &str,
+        tenant: &str,
+        database: &str,
+        options: StreamOptions,
+        cancel: CancelToken,
+    ) -> impl Stream<Item = Result<DocumentBatch, ChromaError>> + Send + 'static {
+        let client = self.clone();
+        let collection_id = collection_id.to_string();
+        let tenant = tenant.to_string();
+        let database = database.to_string();
+        let (offset, page_size) = (options.offset, options.page_size);
+
+        let count = {
+            let (client, collection_id) = (client.clone(), collection_id.clone());
+            let (tenant, database) = (tenant.clone(), database.clone());
+            let filter = options.filter.clone();
+            Box::pin(async move {
+                client
+                    .count_documents(&collection_id, &filter, &tenant, &database)
+                    .await
+            })
+        };
+
+        let fetch = move |offset, limit| {
+            let (client, collection_id) = (client.clone(), collection_id.clone());
+            let (tenant, database) = (tenant.clone(), database.clone());
+            let request = GetDocumentsRequest {
+                ids: None,
+                limit: Some(limit),
+                offset: Some(offset),
+                where_filter: options.filter.where_filter.clone(),
+                where_document: options.filter.where_document.clone(),
+                include: options.include.clone(),
+            };
+            Box::pin(async move {
+                client
+                    .fetch_documents(&collection_id, &request, &tenant, &database)
+                    .await
+            }) as BoxFuture<'static, _>
+        };
+
+        paginate(count, fetch, offset, page_size, cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::auth::AuthMethod;
+    use crate::api::retry::RequestPolicy;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_cancelled_stream_sends_no_requests() {
+        // Nothing listens on this address, so any request would fail
+        let client = ChromaClient::new(
+            "http://127.0.0.1:9",
+            &AuthMethod::None,
+            &Default::default(),
+            Default::default(),
+            RequestPolicy::default(),
+        )
+        .unwrap();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let batches: Vec<_> = client
+            .stream_documents("id", "tenant", "db", StreamOptions::default(), cancel)
+            .collect()
+            .await;
+        assert!(batches.is_empty());
+    }
+}
@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Tests of `ChromaClient` against the in-process mock server.
+
+use super::chroma::ApiVersion;
+use super::mock_server::{DEFAULT_DATABASE, DEFAULT_TENANT, Fault, MockServer, VERSION};
+use super::retry::RequestPolicy;
+use super::stream::{CancelToken, StreamOptions};
+use super::*;
+use futures_util::StreamExt;
+use serde_json::json;
+use std::time::Duration;
+
+const T: &str = DEFAULT_TENANT;
+const D: &str = DEFAULT_DATABASE;
+
+fn doc(id: &str, text: &str, metadata: serde_json::Value, embedding: &[f32]) -> Document {
+    Document {
+        id: id.to_string(),
+        document: Some(text.to_string()),
+        metadata: serde_json::from_value(metadata).unwrap(),
+        embedding: Some(embedding.to_vec()),
+        ..Default::default()
+    }
+}
+
+fn sample_documents() -> Vec<Document> {
+    vec![
+        doc(
+            "a",
+            "apples and pears",
+            json!({ "kind": "fruit", "rank": 1 }),
+            &[0.0, 0.0],
+        ),
+        doc(
+            "b",
+            "bananas",
+            json!({ "kind": "fruit", "rank": 2 }),
+            &[1.0, 0.0],
+        ),
+        doc(
+            "c",
+            "carrots",
+            json!({ "kind": "vegetable", "rank": 3 }),
+            &[0.0, 3.0],
+        ),
+    ]
+}
+
+fn policy(request_timeout: Duration, max_retries: u32) -> RequestPolicy {
+    RequestPolicy {
+        connect_timeout: Duration::from_secs(2),
+        request_timeout,
+        max_retries,
+    }
+}
+
+fn fruit_filter() -> DocumentFilter {
+    DocumentFilter {
+        where_filter: Some(Where::field("kind", Operator::Eq, json!("fruit"))),
+        where_document: None,
+    }
+}
+
+#[tokio::test]
+async fn test_connect_detects_api_version() {
+    for api in [ApiVersion::V2, ApiVersion::V1] {
+        let server = MockServer::start(api).await;
+        let client = ChromaClient::connect(
+            server.url(),
+            &AuthMethod::None,
+            &ConnectionOptions::default(),
+            policy(Duration::from_secs(2), 0),
+        )
+        .await
+        .unwrap();
+
+        let info = client.get_server_info().await.unwrap();
+        assert_eq!(info.api_version, api.as_str());
+        assert_eq!(info.version, VERSION);
+        assert!(info.heartbeat_ns > 0);
+    }
+
+    let server = MockServer::start(ApiVersion::V1).await;
+    ChromaClient::connect(
+        server.url(),
+        &AuthMethod::None,
+        &ConnectionOptions::default(),
+        policy(Duration::from_secs(2), 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        server.requests(),
+        ["GET /api/v2/heartbeat", "GET /api/v1/heartbeat"]
+    );
+}
+
+#[tokio::test]
+async fn test_connect_errors() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    server.require_token("secret");
+    let connect = |auth: AuthMethod| {
+        let url = server.url().to_string();
+        async move {
+            ChromaClient::connect(
+                &url,
+                &auth,
+                &ConnectionOptions::default(),
+                policy(Duration::from_secs(2), 0),
+            )
+            .await
+        }
+    };
+
+    let rejected = connect(AuthMethod::None).await.unwrap_err();
+    assert!(rejected.is_unauthorized(), "{}", rejected);
+    connect(AuthMethod::Bearer {
+        token: "secret".to_string(),
+    })
+    .await
+    .unwrap();
+    connect(AuthMethod::ChromaToken {
+        token: "secret".to_string(),
+    })
+    .await
+    .unwrap();
+
+    // Nothing listens on the discard port
+    let unreachable = ChromaClient::connect(
+        "http://127.0.0.1:9",
+        &AuthMethod::None,
+        &ConnectionOptions::default(),
+        policy(Duration::from_secs(2), 0),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(unreachable, ChromaError::Connection(_)));
+}
+
+async fn check_tenants_and_databases(api: ApiVersion) {
+    let server = MockServer::start(api).await;
+    let client = server.client();
+
+    assert_eq!(client.get_tenant(T).await.unwrap().name, T);
+    assert!(client.get_tenant("acme").await.unwrap_err().is_not_found());
+    assert!(client.list_tenants().await.unwrap().is_none());
+
+    client.create_tenant("acme").await.unwrap();
+    assert!(
+        client
+            .create_tenant("acme")
+            .await
+            .unwrap_err()
+            .is_conflict()
+    );
+    server.allow_list_tenants();
+    let tenants = client.list_tenants().await.unwrap().unwrap();
+    let names: Vec<_> = tenants.into_iter().map(|t| t.name).collect();
+    assert_eq!(names, ["acme", T]);
+
+    assert_eq!(
+        client.check_tenant_database_status("acme", "docs").await,
+        (true, false)
+    );
+    client.create_database("acme", "docs").await.unwrap();
+    client
+        .validate_tenant_database("acme", "docs")
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_database("acme", "docs").await.unwrap().name,
+        "docs"
+    );
+    let databases = client.list_databases("acme").await.unwrap();
+    assert_eq!(databases.len(), 1);
+    assert_eq!(databases[0].tenant, "acme");
+
+    client.delete_database("acme", "docs").await.unwrap();
+    assert!(
+        client
+            .get_database("acme", "docs")
+            .await
+            .unwrap_err()
+            .is_not_found()
+    );
+    assert!(client.list_databases("acme").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_tenants_and_databases() {
+    check_tenants_and_databases(ApiVersion::V2).await;
+    check_tenants_and_databases(ApiVersion::V1).await;
+}
+
+#[tokio::test]
+async fn test_user_identity() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    let client = server.client();
+    // Older servers have no identity endpoint
+    assert!(client.get_user_identity().await.unwrap().is_none());
+
+    server.set_identity(Identity {
+        user_id: "alice".to_string(),
+        tenant: "acme".to_string(),
+        databases: vec!["docs".to_string()],
+    });
+    let identity = client.get_user_identity().await.unwrap().unwrap();
+    assert_eq!(identity.tenant, "acme");
+    assert_eq!(identity.databases, ["docs"]);
+
+    // v1 has no identity, so it is not even asked
+    let server = MockServer::start(ApiVersion::V1).await;
+    assert!(server.client().get_user_identity().await.unwrap().is_none());
+    assert!(server.requests().is_empty());
+}
+
+async fn check_collections(api: ApiVersion) {
+    let server = MockServer::start(api).await;
+    let client = server.client();
+
+    let options = CreateCollectionOptions {
+        metadata: Some([("owner".to_string(), json!("docs"))].into_iter().collect()),
+        space: Some(DistanceSpace::Cosine),
+        ..Default::default()
+    };
+    let created = client
+        .create_collection("articles", &options, T, D)
+        .await
+        .unwrap();
+    assert_eq!(created.name, "articles");
+    let metadata = created.metadata.unwrap();
+    assert_eq!(metadata["owner"], "docs");
+    // Only v1 stores the distance in the metadata
+    assert_eq!(metadata.contains_key("hnsw:space"), api == ApiVersion::V1);
+
+    let duplicate = client
+        .create_collection("articles", &CreateCollectionOptions::default(), T, D)
+        .await
+        .unwrap_err();
+    assert!(duplicate.is_conflict());
+    let missing_database = client
+        .create_collection("articles", &CreateCollectionOptions::default(), T, "nope")
+        .await
+        .unwrap_err();
+    assert!(missing_database.is_not_found());
+
+    let rename = ModifyCollectionRequest {
+        new_name: Some("posts".to_string()),
+        new_metadata: None,
+    };
+    client
+        .modify_collection(&created.id, &rename, T, D)
+        .await
+        .unwrap();
+    let collections = client.list_collections(T, D).await.unwrap();
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0].name, "posts");
+
+    client.delete_collection("posts", T, D).await.unwrap();
+    assert!(client.list_collections(T, D).await.unwrap().is_empty());
+    assert!(
+        client
+            .delete_collection("posts", T, D)
+            .await
+            .unwrap_err()
+            .is_not_found()
+    );
+}
+
+#[tokio::test]
+async fn test_collections() {
+    check_collections(ApiVersion::V2).await;
+    check_collections(ApiVersion::V1).await;
+}
+
+async fn check_documents(api: ApiVersion) {
+    let server = MockServer::start(api).await;
+    let client = server.client();
+    let id = server.add_collection("food");
+
+    let report = client
+        .add_documents(&id, &sample_documents(), T, D)
+        .await
+        .unwrap();
+    assert_eq!(report.succeeded(), 3);
+    let duplicate = client
+        .add_documents(&id, &sample_documents()[..1], T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap_err();
+    assert!(duplicate.is_conflict());
+
+    // Paging and the default fields
+    let page = client
+        .get_documents(&id, Some(2), Some(1), &DocumentFilter::default(), T, D)
+        .await
+        .unwrap();
+    let ids: Vec<_> = page.iter().map(|d| d.id.as_str()).collect();
+    assert_eq!(ids, ["b", "c"]);
+    assert_eq!(page[0].document.as_deref(), Some("bananas"));
+    assert!(page[0].metadata.is_some());
+    assert!(page[0].embedding.is_none());
+
+    let fruit = client
+        .get_documents(&id, None, None, &fruit_filter(), T, D)
+        .await
+        .unwrap();
+    assert_eq!(fruit.len(), 2);
+    let by_text = DocumentFilter {
+        where_filter: None,
+        where_document: Some(WhereDocument::Contains("pears".to_string())),
+    };
+    let pears = client
+        .get_documents(&id, None, None, &by_text, T, D)
+        .await
+        .unwrap();
+    assert_eq!(pears.len(), 1);
+
+    let details = client
+        .get_documents_by_ids(
+            &id,
+            vec!["c".to_string()],
+            vec![Include::Embeddings, Include::Uris],
+            T,
+            D,
+        )
+        .await
+        .unwrap();
+    assert_eq!(details[0].embedding.as_deref(), Some(&[0.0, 3.0][..]));
+    assert!(details[0].document.is_none());
+
+    let all = DocumentFilter::default();
+    assert_eq!(client.count_documents(&id, &all, T, D).await.unwrap(), 3);
+    assert_eq!(
+        client
+            .count_documents(&id, &fruit_filter(), T, D)
+            .await
+            .unwrap(),
+        2
+    );
+
+    // Update merges metadata and removes keys set to null
+    let update = Document {
+        id: "a".to_string(),
+        metadata: serde_json::from_value(json!({ "rank": null, "ripe": true })).unwrap(),
+        ..Default::default()
+    };
+    client
+        .update_documents(&id, &[update], T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap();
+    let upsert = doc("d", "dates", json!({ "kind": "fruit" }), &[2.0, 2.0]);
+    client
+        .upsert_documents(&id, &[upsert], T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap();
+    let updated = client
+        .get_documents_by_ids(&id, vec!["a".to_string()], vec![Include::Metadatas], T, D)
+        .await
+        .unwrap();
+    let metadata = updated[0].metadata.as_ref().unwrap();
+    assert_eq!(metadata.get("ripe"), Some(&json!(true)));
+    assert!(!metadata.contains_key("rank"));
+    assert_eq!(server.ids(&id), ["a", "b", "c", "d"]);
+
+    client
+        .delete_documents(&id, Some(vec!["b".to_string()]), &all, T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap();
+    client
+        .delete_documents(&id, None, &fruit_filter(), T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap();
+    assert_eq!(server.ids(&id), ["c"]);
+
+    let refused = client.delete_documents(&id, None, &all, T, D).await;
+    assert!(matches!(refused, Err(ChromaError::InvalidRequest(_))));
+    assert_eq!(server.ids(&id), ["c"]);
+}
+
+#[tokio::test]
+async fn test_documents() {
+    check_documents(ApiVersion::V2).await;
+    check_documents(ApiVersion::V1).await;
+}
+
+#[tokio::test]
+async fn test_query_ranks_by_distance() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    let client = server.client();
+    let id = server.add_collection("food");
+    server.insert(&id, &sample_documents());
+
+    let request = QueryRequest {
+        query_embeddings: vec![vec![0.9, 0.0], vec![0.0, 2.0]],
+        n_results: 2,
+        where_filter: None,
+        where_document: None,
+        include: vec![Include::Documents, Include::Distances],
+    };
+    let results = client.query_documents(&id, &request, T, D).await.unwrap();
+    assert_eq!(results.len(), 2);
+    let ids: Vec<_> = results[0].iter().map(|m| m.document.id.as_str()).collect();
+    assert_eq!(ids, ["b", "a"]);
+    assert_eq!(results[0][0].document.document.as_deref(), Some("bananas"));
+    assert!(results[0][0].distance.unwrap() < results[0][1].distance.unwrap());
+    assert_eq!(results[1][0].document.id, "c");
+
+    let fruit_only = QueryRequest {
+        where_filter: fruit_filter().where_filter,
+        ..request
+    };
+    let results = client
+        .query_documents(&id, &fruit_only, T, D)
+        .await
+        .unwrap();
+    assert!(results[1].iter().all(|m| m.document.id != "c"));
+}
+
+#[tokio::test]
+async fn test_writes_are_split_into_batches() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    server.set_max_batch_size(Some(2));
+    let client = server.client();
+    let id = server.add_collection("food");
+
+    let documents: Vec<_> = (0..5)
+        .map(|i| doc(&format!("doc-{}", i), "text", json!({}), &[i as f32]))
+        .collect();
+    let report = client.add_documents(&id, &documents, T, D).await.unwrap();
+    assert_eq!(report.batches.len(), 3);
+    assert_eq!(report.succeeded(), 5);
+    assert_eq!(server.request_count("/add"), 3);
+    // The limit is only asked for once
+    assert_eq!(server.request_count("/pre-flight-checks"), 1);
+
+    let ids = documents.iter().map(|d| d.id.clone()).collect();
+    let all = DocumentFilter::default();
+    let report = client
+        .delete_documents(&id, Some(ids), &all, T, D)
+        .await
+        .unwrap();
+    assert_eq!(report.batches.len(), 3);
+    assert!(server.ids(&id).is_empty());
+
+    // Without the pre-flight checks the fallback size is used
+    let server = MockServer::start(ApiVersion::V1).await;
+    server.set_max_batch_size(None);
+    assert_eq!(server.client().max_batch_size().await.unwrap(), 1000);
+
+    // Other errors are returned, and the limit is asked for again next time
+    let server = MockServer::start(ApiVersion::V2).await;
+    let client = server.client();
+    server.fail_next(Fault::Status(401));
+    assert!(client.max_batch_size().await.is_err());
+    assert_eq!(client.max_batch_size().await.unwrap(), 100);
+}
+
+#[tokio::test]
+async fn test_stream_documents_in_pages() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    let id = server.add_collection("food");
+    server.insert(&id, &sample_documents());
+
+    let options = StreamOptions {
+        page_size: 2,
+        ..Default::default()
+    };
+    let batches: Vec<_> = server
+        .client()
+        .stream_documents(&id, T, D, options, CancelToken::new())
+        .collect()
+        .await;
+    let batches: Vec<_> = batches.into_iter().map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].documents.len(), 2);
+    assert_eq!(batches[1].fetched, 3);
+    assert_eq!(batches[1].total, Some(3));
+
+    // Resuming skips the documents already streamed
+    let options = StreamOptions {
+        page_size: 2,
+        offset: 2,
+        ..Default::default()
+    };
+    let batches: Vec<_> = server
+        .client()
+        .stream_documents(&id, T, D, options, CancelToken::new())
+        .collect()
+        .await;
+    let batches: Vec<_> = batches.into_iter().map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].documents.len(), 1);
+    assert_eq!(batches[0].fetched, 3);
+}
+
+#[tokio::test]
+async fn test_faults() {
+    let server = MockServer::start(ApiVersion::V2).await;
+    let client = server.client();
+
+    server.fail_next(Fault::Status(401));
+    assert!(client.heartbeat().await.unwrap_err().is_unauthorized());
+
+    server.fail_next(Fault::MalformedJson);
+    let malformed = client.list_collections(T, D).await.unwrap_err();
+    assert!(matches!(malformed, ChromaError::InvalidResponse(_)));
+
+    // Slow responses time out
+    let impatient = server.client_with(policy(Duration::from_millis(100), 0));
+    server.fail_next(Fault::Latency(Duration::from_millis(500)));
+    let timeout = impatient.heartbeat().await.unwrap_err();
+    assert!(
+        matches!(timeout, ChromaError::Timeout { .. }),
+        "{}",
+        timeout
+    );
+    server.fail_next(Fault::Latency(Duration::from_millis(20)));
+    impatient.heartbeat().await.unwrap();
+
+    // Temporary errors are retried, others are not
+    let patient = server.client_with(policy(Duration::from_secs(2), 1));
+    server.fail_next(Fault::Status(503));
+    patient.heartbeat().await.unwrap();
+    server.fail_always(Some(Fault::Status(500)));
+    let before = server.request_count("/heartbeat");
+    let failed = patient.heartbeat().await.unwrap_err();
+    assert_eq!(failed.status(), Some(500));
+    assert_eq!(server.request_count("/heartbeat"), before + 1);
+
+    // Writes are never retried
+    let id = server.add_collection("food");
+    server.fail_always(None);
+    patient.max_batch_size().await.unwrap();
+    server.fail_always(Some(Fault::Status(503)));
+    let failed = patient
+        .add_documents(&id, &sample_documents(), T, D)
+        .await
+        .unwrap()
+        .into_result()
+        .unwrap_err();
+    assert_eq!(failed.status(), Some(503));
+    assert_eq!(server.request_count("/add"), 1);
+}
@@ -1,18 +1,41 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::api::{Collection, Document, ServerInfo};
+use crate::api::{
+    AuthMethod, ChromaError, ClientRegistry, Collection, Document, Identity, Include,
+    ModifyCollectionRequest, QueryRequest, ServerInfo,
+};
 use crate::config::{Config, ServerConfig};
+use crate::credentials::{self, CredentialError, VaultState};
 use crate::fl;
 use crate::helpers;
 use crate::pages;
-use crate::pages::browser::{AddServerForm, AddServerStatus, BrowserData, BrowserDialog, BrowserMsg, BrowserState};
+use crate::pages::browser::{AddServerForm, AddServerStatus, BrowserData, BrowserDialog, BrowserMsg, BrowserState, CollectionRef, DocumentForm};
+use crate::pages::compare::{CompareMsg, CompareState, CompareStatus, Side};
+use crate::pages::visualize::{self, VisualizeMsg, VisualizeState, VisualizeStatus};
+use crate::projection;
+use crate::transfer::{self, CollectionSource, DatabaseSource};
+use crate::widgets::auth_form::{AuthForm, AuthMsg};
+use crate::widgets::backup_form::{self, BackupForm, BackupMsg, BackupStatus};
+use crate::widgets::collection_options::{CollectionOptionsForm, CollectionOptionsMsg};
+use crate::widgets::connection_form::{ConnectionForm, ConnectionMsg};
+use crate::widgets::copy_form::{self, CopyForm, CopyMsg, CopyStatus};
+use crate::widgets::export_form::{self, ExportForm, ExportMsg, ExportStatus};
 use crate::widgets::miller_columns::MillerMessage;
+use crate::widgets::filter_builder::MatchCount;
+use crate::widgets::import_form::{self, ImportForm, ImportMsg, ImportStatus};
+use crate::widgets::metadata_editor::MetadataEditor;
+use crate::widgets::restore_form::{self, RestoreForm, RestoreMsg, RestoreStatus};
+use crate::widgets::{FilterBuilder, FilterMsg};
 use cosmic::app::context_drawer;
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::dialog::file_chooser;
 use cosmic::iced::{Length, Subscription};
 use cosmic::prelude::*;
 use cosmic::widget::{self, about::About, icon, menu, nav_bar};
+use futures_util::StreamExt;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
 const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
@@ -44,14 +67,30 @@ pub struct AppModel {
     pub server_name_input: String,
     /// Temporary server URL input (before saving)
     pub server_url_input: String,
-    /// Temporary auth token input (before saving)
-    pub auth_token_input: String,
-    /// Temporary auth header type input (before saving)
-    pub auth_header_type_input: String,
+    /// Temporary authentication settings (before saving)
+    pub auth_form: AuthForm,
+    /// Temporary TLS and proxy settings (before saving)
+    pub connection_form: ConnectionForm,
     /// Temporary tenant input (before saving)
     pub tenant_input: String,
     /// Temporary database input (before saving)
     pub database_input: String,
+    /// Temporary connect timeout input in seconds (before saving)
+    pub connect_timeout_input: String,
+    /// Temporary request timeout input in seconds (before saving)
+    pub request_timeout_input: String,
+    /// Temporary max retries input (before saving)
+    pub max_retries_input: String,
+    /// Whether the credential vault exists and is unlocked
+    pub vault_state: VaultState,
+    /// Passphrase input for unlocking or creating the vault
+    pub vault_passphrase_input: String,
+    /// The passphrase entered again before creating the vault
+    pub vault_confirm_input: String,
+    /// Whether the vault key is being derived
+    pub vault_unlocking: bool,
+    /// Error message when unlocking the vault fails
+    pub vault_error: Option<String>,
     /// Index of server being edited (None for new server dialog)
     pub editing_server_index: Option<usize>,
     /// Currently selected collection
@@ -80,6 +119,10 @@ pub struct AppModel {
     pub items_per_page: usize,
     /// Total count of documents in selected collection (if known)
     pub documents_total: Option<usize>,
+    /// Filter builder state for the documents page
+    pub documents_filter: FilterBuilder,
+    /// Whether the filter panel is shown on the documents page
+    pub show_documents_filter: bool,
     /// Active notifications to display
     pub notifications: Vec<Notification>,
     /// Counter for generating unique notification IDs
@@ -94,10 +137,26 @@ pub struct AppModel {
     pub delete_document_target: Option<Document>,
     /// New collection name input
     pub new_collection_name: String,
+    /// Advanced options for the new collection
+    pub new_collection_options: CollectionOptionsForm,
     /// Whether the new collection dialog is open
     pub show_new_collection_dialog: bool,
+    /// Collection export dialog, shown over every page
+    pub export: Option<ExportForm>,
+    /// Document import dialog, shown over every page
+    pub import: Option<ImportForm>,
+    /// Collection copy dialog, shown over every page
+    pub copy: Option<CopyForm>,
+    /// Database backup dialog, shown over every page
+    pub backup: Option<BackupForm>,
+    /// Database restore dialog, shown over every page
+    pub restore: Option<RestoreForm>,
     /// Browser page state
     pub browser: BrowserState,
+    /// Compare page state
+    pub compare: CompareState,
+    /// Visualize page state
+    pub visualize: VisualizeState,
     /// Current window width (for responsive column sizing)
     pub window_width: f32,
     /// Current window height
@@ -153,6 +212,17 @@ pub struct Notification {
     pub message: String,
 }
 
+/// How a document is written to its collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveMode {
+    /// Add a new document, failing if its ID exists
+    Add,
+    /// Change fields of an existing document
+    Update,
+    /// Add the document, replacing any with the same ID
+    Upsert,
+}
+
 /// Messages emitted by the application and its widgets.
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -164,23 +234,38 @@ pub enum Message {
     // Settings inputs
     ServerNameChanged(String),
     ServerUrlChanged(String),
-    AuthTokenChanged(String),
-    AuthHeaderTypeChanged(String),
+    Auth(AuthMsg),
+    Connection(ConnectionMsg),
     TenantChanged(String),
     DatabaseChanged(String),
+    ConnectTimeoutChanged(String),
+    RequestTimeoutChanged(String),
+    MaxRetriesChanged(String),
+    VaultPassphraseChanged(String),
+    VaultConfirmChanged(String),
+    /// Create the credential vault with the passphrase entered twice
+    CreateVault,
+    /// Unlock the existing credential vault
+    UnlockVault,
+    /// The vault was created or unlocked
+    VaultUnlocked(Result<(), CredentialError>),
+    LockVault,
     SaveSettings,
     ValidateAndSaveSettings,
+    /// Identity of the caller, used to pre-fill the tenant and database before validating
+    SettingsIdentityLoaded(Result<Option<Identity>, ChromaError>),
+    ValidateSettings,
     /// Result contains (tenant_exists, database_exists) for detailed feedback
     SettingsValidationResult(Result<(), (bool, bool)>),
     /// Create missing tenant and/or database
     CreateMissingResources,
-    CreateResourcesResult(Result<(), String>),
+    CreateResourcesResult(Result<(), ChromaError>),
     /// Fetch available tenants
     FetchTenants,
-    TenantsLoaded(Result<Vec<String>, String>),
+    TenantsLoaded(Result<Vec<String>, ChromaError>),
     /// Fetch available databases for current tenant
     FetchDatabases,
-    DatabasesLoaded(Result<Vec<String>, String>),
+    DatabasesLoaded(Result<Vec<String>, ChromaError>),
     /// Select a tenant from the list
     SelectTenant(String),
     /// Select a database from the list
@@ -193,19 +278,21 @@ pub enum Message {
 
     // Connection & data
     TestConnection,
-    ConnectionResult(Result<(), String>),
+    ConnectionResult(Result<(), ChromaError>),
     FetchCollections,
-    CollectionsLoaded(Result<Vec<Collection>, String>),
+    CollectionsLoaded(Result<Vec<Collection>, ChromaError>),
 
     // Collection & documents
     SelectCollection(Collection),
     BackToCollections,
     FetchDocuments,
-    DocumentsLoaded(Result<Vec<Document>, String>),
+    DocumentsLoaded(Result<Vec<Document>, ChromaError>),
+    ToggleDocumentsFilter,
+    DocumentsFilter(FilterMsg),
 
     // Dashboard
     FetchServerInfo,
-    ServerInfoLoaded(Result<ServerInfo, String>),
+    ServerInfoLoaded(Result<ServerInfo, ChromaError>),
 
     // Pagination
     CollectionsNextPage,
@@ -214,7 +301,8 @@ pub enum Message {
     DocumentsPrevPage,
 
     // Document count
-    DocumentCountLoaded(Result<usize, String>),
+    FetchDocumentCount,
+    DocumentCountLoaded(Result<usize, ChromaError>),
 
     // Notifications
     AddNotification(NotificationLevel, String, String),
@@ -229,20 +317,41 @@ pub enum Message {
     OpenNewCollectionDialog,
     CloseNewCollectionDialog,
     NewCollectionNameChanged(String),
+    NewCollectionOptions(CollectionOptionsMsg),
     CreateCollection,
-    CreateCollectionResult(Result<Collection, String>),
+    CreateCollectionResult(Result<Collection, ChromaError>),
 
     // Delete collection
     RequestDeleteCollection(Collection),
     ConfirmDeleteCollection,
     CancelDeleteCollection,
-    DeleteCollectionResult(Result<(), String>),
+    DeleteCollectionResult(Result<(), ChromaError>),
+
+    // Export collection
+    ExportCollection(Collection),
+    Export(ExportMsg),
+
+    // Import documents
+    Import(ImportMsg),
+
+    // Copy collection
+    Copy(CopyMsg),
+
+    // Back up and restore databases
+    Backup(BackupMsg),
+    Restore(RestoreMsg),
+
+    // Compare page
+    Compare(CompareMsg),
+
+    // Visualize page
+    Visualize(VisualizeMsg),
 
     // Delete document
     RequestDeleteDocument(Document),
     ConfirmDeleteDocument,
     CancelDeleteDocument,
-    DeleteDocumentResult(Result<(), String>),
+    DeleteDocumentResult(Result<(), ChromaError>),
 
     // Browser
     Browser(BrowserMsg),
@@ -260,7 +369,7 @@ impl cosmic::Application for AppModel {
     type Message = Message;
 
     /// Unique identifier in RDNN (reverse domain name notation) format.
-    const APP_ID: &'static str = "dev.mmurphy.Chromatic";
+    const APP_ID: &'static str = crate::config::APP_ID;
 
     fn core(&self) -> &cosmic::Core {
         &self.core
@@ -275,7 +384,8 @@ impl cosmic::Application for AppModel {
         core: cosmic::Core,
         _flags: Self::Flags,
     ) -> (Self, Task<cosmic::Action<Self::Message>>) {
-        // Create a nav bar with four pages: Browser, Dashboard, Collections and Settings
+        // Create a nav bar with six pages: Browser, Compare, Visualize, Dashboard, Collections
+        // and Settings
         let mut nav = nav_bar::Model::default();
 
         nav.insert()
@@ -284,6 +394,16 @@ impl cosmic::Application for AppModel {
             .icon(icon::from_name("folder-open-symbolic"))
             .activate();
 
+        nav.insert()
+            .text(fl!("compare"))
+            .data::<Page>(Page::Compare)
+            .icon(icon::from_name("view-dual-symbolic"));
+
+        nav.insert()
+            .text(fl!("visualize"))
+            .data::<Page>(Page::Visualize)
+            .icon(icon::from_name("view-grid-symbolic"));
+
         nav.insert()
             .text(fl!("dashboard"))
             .data::<Page>(Page::Dashboard)
@@ -323,8 +443,9 @@ impl cosmic::Application for AppModel {
         // Compute server names for dropdown
         let server_names: Vec<String> = config.servers.iter().map(|s| s.name.clone()).collect();
 
-        // Initialize browser state before config moves
+        // Initialize browser and compare state before config moves
         let browser = BrowserState::new(&config.servers);
+        let compare = CompareState::new(&config.servers);
 
         // Construct the app model with the runtime's core.
         let mut app = AppModel {
@@ -335,10 +456,18 @@ impl cosmic::Application for AppModel {
             key_binds: HashMap::new(),
             server_name_input: active.name.clone(),
             server_url_input: active.server_url.clone(),
-            auth_token_input: active.auth_token.clone(),
-            auth_header_type_input: active.auth_header_type.clone(),
+            auth_form: AuthForm::from_method(&active.auth),
+            connection_form: ConnectionForm::from_options(&active.connection),
             tenant_input: active.tenant.clone(),
             database_input: active.database.clone(),
+            connect_timeout_input: active.connect_timeout_secs.to_string(),
+            request_timeout_input: active.request_timeout_secs.to_string(),
+            max_retries_input: active.max_retries.to_string(),
+            vault_state: credentials::vault_state(),
+            vault_passphrase_input: String::new(),
+            vault_confirm_input: String::new(),
+            vault_unlocking: false,
+            vault_error: None,
             editing_server_index: Some(config.active_server),
             config,
             config_context,
@@ -357,14 +486,24 @@ impl cosmic::Application for AppModel {
             documents_page: 0,
             items_per_page: 20,
             documents_total: None,
+            documents_filter: FilterBuilder::default(),
+            show_documents_filter: false,
             notifications: Vec::new(),
             notification_id_counter: 0,
             selected_document: None,
             delete_collection_target: None,
             delete_document_target: None,
             new_collection_name: String::new(),
+            new_collection_options: CollectionOptionsForm::default(),
             show_new_collection_dialog: false,
+            export: None,
+            import: None,
+            copy: None,
+            backup: None,
+            restore: None,
             browser,
+            compare,
+            visualize: VisualizeState::default(),
             window_width: 1200.0, // Default, will be updated on resize
             window_height: 800.0,
         };
@@ -423,6 +562,26 @@ impl cosmic::Application for AppModel {
         })
     }
 
+    /// Display a dialog over every page if one is requested.
+    fn dialog(&self) -> Option<Element<'_, Self::Message>> {
+        let space_s = cosmic::theme::spacing().space_s;
+        if let Some(form) = &self.import {
+            return Some(import_form::view(form, Message::Import, space_s));
+        }
+        if let Some(form) = &self.copy {
+            return Some(copy_form::view(form, Message::Copy, space_s));
+        }
+        if let Some(form) = &self.backup {
+            return Some(backup_form::view(form, Message::Backup, space_s));
+        }
+        if let Some(form) = &self.restore {
+            return Some(restore_form::view(form, Message::Restore, space_s));
+        }
+        self.export
+            .as_ref()
+            .map(|form| export_form::view(form, Message::Export, space_s))
+    }
+
     /// Describes the interface based on the current state of the application model.
     fn view(&self) -> Element<'_, Self::Message> {
         let space_s = cosmic::theme::spacing().space_s;
@@ -440,6 +599,17 @@ impl cosmic::Application for AppModel {
                         self.window_height,
                     )
                 }
+                Page::Compare => {
+                    pages::compare::view(&self.compare, Message::Compare, space_s, space_m)
+                }
+                Page::Visualize => visualize::view(
+                    &self.visualize,
+                    Message::Visualize,
+                    space_s,
+                    space_m,
+                    self.window_width,
+                    self.window_height,
+                ),
                 Page::Dashboard => pages::dashboard::view(self, space_s, space_m),
                 Page::Collections => {
                     // Show documents view if a collection is selected
@@ -478,9 +648,21 @@ impl cosmic::Application for AppModel {
     /// Register subscriptions for this application.
     fn subscription(&self) -> Subscription<Self::Message> {
         // Watch for application configuration changes.
-        self.core()
-            .watch_config::<Config>(Self::APP_ID)
-            .map(|update| Message::UpdateConfig(update.config))
+        let mut subscriptions = vec![
+            self.core()
+                .watch_config::<Config>(Self::APP_ID)
+                .map(|update| Message::UpdateConfig(update.config)),
+        ];
+
+        // Check for retried requests while browser columns are loading
+        if self.browser.is_loading() {
+            subscriptions.push(
+                cosmic::iced::time::every(Duration::from_millis(250))
+                    .map(|_| Message::Browser(BrowserMsg::RetryTick)),
+            );
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     /// Handles messages emitted by the application and its widgets.
@@ -500,10 +682,13 @@ impl cosmic::Application for AppModel {
                 let active = self.config.active_config();
                 self.server_name_input = active.name.clone();
                 self.server_url_input = active.server_url.clone();
-                self.auth_token_input = active.auth_token.clone();
-                self.auth_header_type_input = active.auth_header_type.clone();
+                self.auth_form = AuthForm::from_method(&active.auth);
+                self.connection_form = ConnectionForm::from_options(&active.connection);
                 self.tenant_input = active.tenant.clone();
                 self.database_input = active.database.clone();
+                self.connect_timeout_input = active.connect_timeout_secs.to_string();
+                self.request_timeout_input = active.request_timeout_secs.to_string();
+                self.max_retries_input = active.max_retries.to_string();
                 self.editing_server_index = Some(self.config.active_server);
             }
 
@@ -519,12 +704,12 @@ impl cosmic::Application for AppModel {
                 self.server_url_input = url;
             }
 
-            Message::AuthTokenChanged(token) => {
-                self.auth_token_input = token;
+            Message::Auth(msg) => {
+                self.auth_form.update(msg);
             }
 
-            Message::AuthHeaderTypeChanged(header_type) => {
-                self.auth_header_type_input = header_type;
+            Message::Connection(msg) => {
+                self.connection_form.update(msg);
             }
 
             Message::TenantChanged(tenant) => {
@@ -539,6 +724,69 @@ impl cosmic::Application for AppModel {
                 self.server_name_input = name;
             }
 
+            Message::ConnectTimeoutChanged(secs) => {
+                self.connect_timeout_input = secs;
+            }
+
+            Message::RequestTimeoutChanged(secs) => {
+                self.request_timeout_input = secs;
+            }
+
+            Message::MaxRetriesChanged(retries) => {
+                self.max_retries_input = retries;
+            }
+
+            Message::VaultPassphraseChanged(passphrase) => {
+                self.vault_passphrase_input = passphrase;
+            }
+
+            Message::VaultConfirmChanged(passphrase) => {
+                self.vault_confirm_input = passphrase;
+            }
+
+            Message::CreateVault => {
+                if self.vault_unlocking
+                    || self.vault_passphrase_input.is_empty()
+                    || self.vault_confirm_input != self.vault_passphrase_input
+                {
+                    return Task::none();
+                }
+                self.vault_confirm_input.clear();
+                let passphrase = std::mem::take(&mut self.vault_passphrase_input);
+                return self.open_vault(move || credentials::create_vault(&passphrase));
+            }
+
+            Message::UnlockVault => {
+                if self.vault_unlocking || self.vault_passphrase_input.is_empty() {
+                    return Task::none();
+                }
+                let passphrase = std::mem::take(&mut self.vault_passphrase_input);
+                return self.open_vault(move || credentials::unlock(&passphrase));
+            }
+
+            Message::VaultUnlocked(result) => {
+                self.vault_unlocking = false;
+                self.vault_state = credentials::vault_state();
+                match result {
+                    Ok(()) => {
+                        self.secure_plain_secrets();
+                        if let Some(ref context) = self.config_context
+                            && let Err(e) = self.config.write_entry(context)
+                        {
+                            eprintln!("Failed to save config: {}", e);
+                        }
+                    }
+                    Err(e) => self.vault_error = Some(e.to_string()),
+                }
+            }
+
+            Message::LockVault => {
+                credentials::lock();
+                self.vault_state = credentials::vault_state();
+                // Cached clients hold secrets resolved from the vault
+                ClientRegistry::global().clear();
+            }
+
             Message::SelectServer(index) => {
                 if self.config.switch_active_server(index) {
                     // Save the config with new active server
@@ -549,10 +797,13 @@ impl cosmic::Application for AppModel {
                     let active = self.config.active_config();
                     self.server_name_input = active.name.clone();
                     self.server_url_input = active.server_url.clone();
-                    self.auth_token_input = active.auth_token.clone();
-                    self.auth_header_type_input = active.auth_header_type.clone();
+                    self.auth_form = AuthForm::from_method(&active.auth);
+                    self.connection_form = ConnectionForm::from_options(&active.connection);
                     self.tenant_input = active.tenant.clone();
                     self.database_input = active.database.clone();
+                    self.connect_timeout_input = active.connect_timeout_secs.to_string();
+                    self.request_timeout_input = active.request_timeout_secs.to_string();
+                    self.max_retries_input = active.max_retries.to_string();
                     self.editing_server_index = Some(index);
                     // Clear cached data from previous server
                     self.collections.clear();
@@ -578,10 +829,13 @@ impl cosmic::Application for AppModel {
                 let active = self.config.active_config();
                 self.server_name_input = active.name.clone();
                 self.server_url_input = active.server_url.clone();
-                self.auth_token_input = active.auth_token.clone();
-                self.auth_header_type_input = active.auth_header_type.clone();
+                self.auth_form = AuthForm::from_method(&active.auth);
+                self.connection_form = ConnectionForm::from_options(&active.connection);
                 self.tenant_input = active.tenant.clone();
                 self.database_input = active.database.clone();
+                self.connect_timeout_input = active.connect_timeout_secs.to_string();
+                self.request_timeout_input = active.request_timeout_secs.to_string();
+                self.max_retries_input = active.max_retries.to_string();
                 self.editing_server_index = Some(new_index);
                 // Clear cached data
                 self.collections.clear();
@@ -590,7 +844,14 @@ impl cosmic::Application for AppModel {
             }
 
             Message::DeleteServer(index) => {
+                if let Some(server) = self.config.servers.get(index) {
+                    ClientRegistry::global().invalidate(server);
+                }
+                let old_auth = self.config.servers.get(index).map(|s| s.auth.clone());
                 if self.config.remove_server(index) {
+                    if let Some(old_auth) = old_auth {
+                        credentials::forget_replaced(&old_auth, &AuthMethod::None);
+                    }
                     // Save the config
                     if let Some(ref context) = self.config_context {
                         let _ = self.config.write_entry(context);
@@ -602,10 +863,13 @@ impl cosmic::Application for AppModel {
                     let active = self.config.active_config();
                     self.server_name_input = active.name.clone();
                     self.server_url_input = active.server_url.clone();
-                    self.auth_token_input = active.auth_token.clone();
-                    self.auth_header_type_input = active.auth_header_type.clone();
+                    self.auth_form = AuthForm::from_method(&active.auth);
+                    self.connection_form = ConnectionForm::from_options(&active.connection);
                     self.tenant_input = active.tenant.clone();
                     self.database_input = active.database.clone();
+                    self.connect_timeout_input = active.connect_timeout_secs.to_string();
+                    self.request_timeout_input = active.request_timeout_secs.to_string();
+                    self.max_retries_input = active.max_retries.to_string();
                     self.editing_server_index = Some(self.config.active_server);
                     // Clear cached data
                     self.collections.clear();
@@ -616,15 +880,24 @@ impl cosmic::Application for AppModel {
 
             Message::SaveSettings => {
                 // Direct save without validation (internal use)
+                // Drop the cached client for the old settings
+                ClientRegistry::global().invalidate(self.config.active_config());
+                let input = self.input_server_config();
+                let old_auth = self.config.active_config().auth.clone();
                 {
                     let active = self.config.active_config_mut();
                     active.name = self.server_name_input.clone();
                     active.server_url = self.server_url_input.clone();
-                    active.auth_token = self.auth_token_input.clone();
-                    active.auth_header_type = self.auth_header_type_input.clone();
+                    active.auth = input.auth;
+                    active.connection = input.connection;
                     active.tenant = self.tenant_input.clone();
                     active.database = self.database_input.clone();
+                    active.connect_timeout_secs = input.connect_timeout_secs;
+                    active.request_timeout_secs = input.request_timeout_secs;
+                    active.max_retries = input.max_retries;
                 }
+                credentials::forget_replaced(&old_auth, &self.config.active_config().auth);
+                self.secure_plain_secrets();
                 // Update server names for dropdown (name might have changed)
                 self.server_names = self.config.servers.iter().map(|s| s.name.clone()).collect();
 
@@ -652,22 +925,35 @@ impl cosmic::Application for AppModel {
             }
 
             Message::ValidateAndSaveSettings => {
+                if let Err(e) = self.auth_form.build().and(self.connection_form.build()) {
+                    self.settings_status = SettingsStatus::Error(e);
+                    return Task::none();
+                }
                 self.settings_status = SettingsStatus::Validating;
-                let url = self.server_url_input.clone();
-                let token = self.auth_token_input.clone();
-                let auth_header_type = self.auth_header_type_input.clone();
+                let server = self.input_server_config();
+
+                return cosmic::task::future(async move {
+                    let result = helpers::fetch_identity(&server).await;
+                    cosmic::Action::App(Message::SettingsIdentityLoaded(result))
+                });
+            }
+
+            Message::SettingsIdentityLoaded(result) => {
+                // Validation reports connection problems, so errors are ignored here
+                if let Ok(Some(identity)) = result {
+                    self.apply_identity(identity);
+                }
+                return self.update(Message::ValidateSettings);
+            }
+
+            Message::ValidateSettings => {
+                let server = self.input_server_config();
                 let tenant = self.tenant_input.clone();
                 let database = self.database_input.clone();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::validate_tenant_database(
-                        &url,
-                        &token,
-                        &auth_header_type,
-                        &tenant,
-                        &database,
-                    )
-                    .await;
+                    let result =
+                        helpers::validate_tenant_database(&server, &tenant, &database).await;
                     cosmic::Action::App(Message::SettingsValidationResult(result))
                 });
             }
@@ -726,17 +1012,13 @@ impl cosmic::Application for AppModel {
 
                 if let Some((tenant_exists, database_exists)) = missing_info {
                     self.settings_status = SettingsStatus::Creating;
-                    let url = self.server_url_input.clone();
-                    let token = self.auth_token_input.clone();
-                    let auth_header_type = self.auth_header_type_input.clone();
+                    let server = self.input_server_config();
                     let tenant = self.tenant_input.clone();
                     let database = self.database_input.clone();
 
                     return cosmic::task::future(async move {
                         let result = helpers::create_missing_resources(
-                            &url,
-                            &token,
-                            &auth_header_type,
+                            &server,
                             &tenant,
                             &database,
                             tenant_exists,
@@ -768,14 +1050,11 @@ impl cosmic::Application for AppModel {
             }
 
             Message::FetchDatabases => {
-                let url = self.server_url_input.clone();
-                let token = self.auth_token_input.clone();
-                let auth_header_type = self.auth_header_type_input.clone();
+                let server = self.input_server_config();
                 let tenant = self.tenant_input.clone();
 
                 return cosmic::task::future(async move {
-                    let result =
-                        helpers::fetch_databases(&url, &token, &auth_header_type, &tenant).await;
+                    let result = helpers::fetch_databases(&server, &tenant).await;
                     cosmic::Action::App(Message::DatabasesLoaded(result))
                 });
             }
@@ -787,24 +1066,15 @@ impl cosmic::Application for AppModel {
                 }
                 Err(e) => {
                     self.available_databases.clear();
-                    self.databases_load_error = Some(e);
+                    self.databases_load_error = Some(e.to_string());
                 }
             },
 
             Message::FetchTenants => {
-                let url = self.server_url_input.clone();
-                let token = self.auth_token_input.clone();
-                let auth_header_type = self.auth_header_type_input.clone();
-
-                eprintln!(
-                    "[DEBUG] FetchTenants: url={}, auth_header_type={}",
-                    url, auth_header_type
-                );
+                let server = self.input_server_config();
 
                 return cosmic::task::future(async move {
-                    eprintln!("[DEBUG] FetchTenants: Starting fetch...");
-                    let result = helpers::fetch_tenants(&url, &token, &auth_header_type).await;
-                    eprintln!("[DEBUG] FetchTenants: Result = {:?}", result);
+                    let result = helpers::fetch_tenants(&server).await;
                     cosmic::Action::App(Message::TenantsLoaded(result))
                 });
             }
@@ -824,7 +1094,7 @@ impl cosmic::Application for AppModel {
                     Err(e) => {
                         eprintln!("[DEBUG] TenantsLoaded: Error = {}", e);
                         self.available_tenants.clear();
-                        self.tenants_load_error = Some(e);
+                        self.tenants_load_error = Some(e.to_string());
                     }
                 }
             }
@@ -843,12 +1113,10 @@ impl cosmic::Application for AppModel {
 
             Message::TestConnection => {
                 self.connection_status = ConnectionStatus::Connecting;
-                let url = self.server_url_input.clone();
-                let token = self.auth_token_input.clone();
-                let auth_header_type = self.auth_header_type_input.clone();
+                let server = self.input_server_config();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::test_connection(&url, &token, &auth_header_type).await;
+                    let result = helpers::test_connection(&server).await;
                     cosmic::Action::App(Message::ConnectionResult(result))
                 });
             }
@@ -865,12 +1133,17 @@ impl cosmic::Application for AppModel {
                         ));
                     }
                     Err(e) => {
-                        self.connection_status = ConnectionStatus::Error(e.clone());
+                        self.connection_status = ConnectionStatus::Error(e.to_string());
+                        let message = if e.is_unauthorized() {
+                            fl!("error-unauthorized")
+                        } else {
+                            e.to_string()
+                        };
                         // Add error notification
                         return self.update(Message::AddNotification(
                             NotificationLevel::Error,
                             fl!("status-error"),
-                            e,
+                            message,
                         ));
                     }
                 }
@@ -879,21 +1152,12 @@ impl cosmic::Application for AppModel {
             Message::FetchCollections => {
                 self.connection_status = ConnectionStatus::Connecting;
                 let active = self.config.active_config();
-                let url = active.server_url.clone();
-                let token = active.auth_token.clone();
-                let auth_header_type = active.auth_header_type.clone();
+                let server = active.clone();
                 let tenant = active.tenant.clone();
                 let database = active.database.clone();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::fetch_collections(
-                        &url,
-                        &token,
-                        &auth_header_type,
-                        &tenant,
-                        &database,
-                    )
-                    .await;
+                    let result = helpers::fetch_collections(&server, &tenant, &database).await;
                     cosmic::Action::App(Message::CollectionsLoaded(result))
                 });
             }
@@ -904,38 +1168,19 @@ impl cosmic::Application for AppModel {
                     self.connection_status = ConnectionStatus::Connected;
                 }
                 Err(e) => {
-                    self.connection_status = ConnectionStatus::Error(e);
+                    self.connection_status = ConnectionStatus::Error(e.to_string());
                 }
             },
 
             Message::SelectCollection(collection) => {
-                let collection_id = collection.id.clone();
                 self.selected_collection = Some(collection);
                 self.documents.clear();
                 self.documents_page = 0; // Reset to first page
                 self.documents_total = None; // Clear old count
+                self.documents_filter = FilterBuilder::default(); // Filters are per collection
 
-                // Fetch document count
-                let active = self.config.active_config();
-                let url = active.server_url.clone();
-                let token = active.auth_token.clone();
-                let auth_header_type = active.auth_header_type.clone();
-                let tenant = active.tenant.clone();
-                let database = active.database.clone();
-
-                // Spawn count fetch in background
-                let count_task = cosmic::task::future(async move {
-                    let result = helpers::fetch_document_count(
-                        &url,
-                        &token,
-                        &auth_header_type,
-                        &collection_id,
-                        &tenant,
-                        &database,
-                    )
-                    .await;
-                    cosmic::Action::App(Message::DocumentCountLoaded(result))
-                });
+                // Fetch document count in background
+                let count_task = self.update(Message::FetchDocumentCount);
 
                 // Also fetch documents
                 let docs_task = self.update(Message::FetchDocuments);
@@ -952,23 +1197,21 @@ impl cosmic::Application for AppModel {
                 if let Some(ref collection) = self.selected_collection {
                     self.connection_status = ConnectionStatus::Connecting;
                     let active = self.config.active_config();
-                    let url = active.server_url.clone();
-                    let token = active.auth_token.clone();
-                    let auth_header_type = active.auth_header_type.clone();
+                    let server = active.clone();
                     let collection_id = collection.id.clone();
                     let tenant = active.tenant.clone();
                     let database = active.database.clone();
+                    let filter = self.documents_filter.applied.clone();
                     let limit = self.items_per_page;
                     let offset = self.documents_page * self.items_per_page;
 
                     return cosmic::task::future(async move {
                         let result = helpers::fetch_documents(
-                            &url,
-                            &token,
-                            &auth_header_type,
+                            &server,
                             &collection_id,
                             &tenant,
                             &database,
+                            &filter,
                             limit,
                             offset,
                         )
@@ -980,23 +1223,37 @@ impl cosmic::Application for AppModel {
 
             Message::DocumentsLoaded(result) => match result {
                 Ok(documents) => {
+                    self.documents_filter.add_keys_from(&documents);
                     self.documents = documents;
                     self.connection_status = ConnectionStatus::Connected;
                 }
                 Err(e) => {
-                    self.connection_status = ConnectionStatus::Error(e);
+                    self.connection_status = ConnectionStatus::Error(e.to_string());
                 }
             },
 
+            Message::ToggleDocumentsFilter => {
+                self.show_documents_filter = !self.show_documents_filter;
+            }
+
+            Message::DocumentsFilter(filter_msg) => {
+                // Applying or clearing a filter restarts the listing from the first page
+                if self.documents_filter.update(filter_msg).is_some() {
+                    self.documents_page = 0;
+                    self.documents_total = None;
+                    let count_task = self.update(Message::FetchDocumentCount);
+                    let docs_task = self.update(Message::FetchDocuments);
+                    return cosmic::task::batch(vec![count_task, docs_task]);
+                }
+            }
+
             Message::FetchServerInfo => {
                 self.connection_status = ConnectionStatus::Connecting;
                 let active = self.config.active_config();
-                let url = active.server_url.clone();
-                let token = active.auth_token.clone();
-                let auth_header_type = active.auth_header_type.clone();
+                let server = active.clone();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::fetch_server_info(&url, &token, &auth_header_type).await;
+                    let result = helpers::fetch_server_info(&server).await;
                     cosmic::Action::App(Message::ServerInfoLoaded(result))
                 });
             }
@@ -1011,7 +1268,7 @@ impl cosmic::Application for AppModel {
                     }
                     Err(e) => {
                         self.server_info = None;
-                        self.connection_status = ConnectionStatus::Error(e);
+                        self.connection_status = ConnectionStatus::Error(e.to_string());
                     }
                 }
             }
@@ -1045,14 +1302,41 @@ impl cosmic::Application for AppModel {
             }
 
             // Document count
-            Message::DocumentCountLoaded(result) => match result {
-                Ok(count) => {
-                    self.documents_total = Some(count);
+            Message::FetchDocumentCount => {
+                if let Some(ref collection) = self.selected_collection {
+                    let active = self.config.active_config();
+                    let server = active.clone();
+                    let collection_id = collection.id.clone();
+                    let tenant = active.tenant.clone();
+                    let database = active.database.clone();
+                    let filter = self.documents_filter.applied.clone();
+
+                    return cosmic::task::future(async move {
+                        let result = helpers::fetch_document_count(
+                            &server,
+                            &collection_id,
+                            &tenant,
+                            &database,
+                            &filter,
+                        )
+                        .await;
+                        cosmic::Action::App(Message::DocumentCountLoaded(result))
+                    });
                 }
-                Err(e) => {
-                    eprintln!("Failed to load document count: {}", e);
+            }
+
+            Message::DocumentCountLoaded(result) => {
+                self.documents_filter
+                    .set_match_count(result.clone().map_err(|e| e.to_string()));
+                match result {
+                    Ok(count) => {
+                        self.documents_total = Some(count);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load document count: {}", e);
+                    }
                 }
-            },
+            }
 
             // Notifications
             Message::AddNotification(level, title, message) => {
@@ -1100,6 +1384,7 @@ impl cosmic::Application for AppModel {
             // Collection management
             Message::OpenNewCollectionDialog => {
                 self.new_collection_name = String::new();
+                self.new_collection_options = CollectionOptionsForm::default();
                 self.show_new_collection_dialog = true;
             }
 
@@ -1112,35 +1397,38 @@ impl cosmic::Application for AppModel {
                 self.new_collection_name = name;
             }
 
+            Message::NewCollectionOptions(options_msg) => {
+                self.new_collection_options.update(options_msg);
+            }
+
             Message::CreateCollection => {
                 if self.new_collection_name.is_empty() {
                     return Task::none();
                 }
+                // The dialog only enables Create when the options are valid
+                let Ok(options) = self.new_collection_options.build() else {
+                    return Task::none();
+                };
                 let active = self.config.active_config();
-                let url = active.server_url.clone();
-                let token = active.auth_token.clone();
-                let auth_header_type = active.auth_header_type.clone();
+                let server = active.clone();
                 let tenant = active.tenant.clone();
                 let database = active.database.clone();
                 let name = self.new_collection_name.clone();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::create_collection(
-                        &url,
-                        &token,
-                        &auth_header_type,
-                        &name,
-                        &tenant,
-                        &database,
-                    )
-                    .await;
+                    let result =
+                        helpers::create_collection(&server, &name, options, &tenant, &database)
+                            .await;
                     cosmic::Action::App(Message::CreateCollectionResult(result))
                 });
             }
 
             Message::CreateCollectionResult(result) => {
-                self.show_new_collection_dialog = false;
-                self.new_collection_name = String::new();
+                // Keep the dialog open on a name clash so another name can be picked
+                if !matches!(result, Err(ref e) if e.is_conflict()) {
+                    self.show_new_collection_dialog = false;
+                    self.new_collection_name = String::new();
+                }
                 match result {
                     Ok(collection) => {
                         // Add success notification inline
@@ -1158,7 +1446,7 @@ impl cosmic::Application for AppModel {
                         return self.update(Message::AddNotification(
                             NotificationLevel::Error,
                             fl!("error"),
-                            e,
+                            e.to_string(),
                         ));
                     }
                 }
@@ -1172,18 +1460,14 @@ impl cosmic::Application for AppModel {
             Message::ConfirmDeleteCollection => {
                 if let Some(ref collection) = self.delete_collection_target {
                     let active = self.config.active_config();
-                    let url = active.server_url.clone();
-                    let token = active.auth_token.clone();
-                    let auth_header_type = active.auth_header_type.clone();
+                    let server = active.clone();
                     let tenant = active.tenant.clone();
                     let database = active.database.clone();
                     let collection_name = collection.name.clone();
 
                     return cosmic::task::future(async move {
                         let result = helpers::delete_collection(
-                            &url,
-                            &token,
-                            &auth_header_type,
+                            &server,
                             &collection_name,
                             &tenant,
                             &database,
@@ -1198,6 +1482,40 @@ impl cosmic::Application for AppModel {
                 self.delete_collection_target = None;
             }
 
+            Message::ExportCollection(collection) => {
+                // The documents filter belongs to the selected collection only
+                let filter = match &self.selected_collection {
+                    Some(selected) if selected.id == collection.id => {
+                        self.documents_filter.applied.clone()
+                    }
+                    _ => Default::default(),
+                };
+                let active = self.config.active_config();
+                let source = CollectionSource {
+                    server: active.clone(),
+                    tenant: active.tenant.clone(),
+                    database: active.database.clone(),
+                    collection_id: collection.id,
+                    collection_name: collection.name,
+                };
+                self.export = Some(ExportForm::new(source, filter));
+            }
+
+            Message::Export(export_msg) => return self.handle_export_message(export_msg),
+
+            Message::Import(import_msg) => return self.handle_import_message(import_msg),
+
+            Message::Copy(copy_msg) => return self.handle_copy_message(copy_msg),
+
+            Message::Backup(backup_msg) => return self.handle_backup_message(backup_msg),
+
+            Message::Restore(restore_msg) => return self.handle_restore_message(restore_msg),
+
+            Message::Compare(compare_msg) => return self.handle_compare_message(compare_msg),
+            Message::Visualize(visualize_msg) => {
+                return self.handle_visualize_message(visualize_msg);
+            }
+
             Message::DeleteCollectionResult(result) => {
                 let deleted_name = self
                     .delete_collection_target
@@ -1223,7 +1541,7 @@ impl cosmic::Application for AppModel {
                         return self.update(Message::AddNotification(
                             NotificationLevel::Error,
                             fl!("error"),
-                            e,
+                            e.to_string(),
                         ));
                     }
                 }
@@ -1238,9 +1556,7 @@ impl cosmic::Application for AppModel {
                 if let Some(ref document) = self.delete_document_target {
                     if let Some(ref collection) = self.selected_collection {
                         let active = self.config.active_config();
-                        let url = active.server_url.clone();
-                        let token = active.auth_token.clone();
-                        let auth_header_type = active.auth_header_type.clone();
+                        let server = active.clone();
                         let tenant = active.tenant.clone();
                         let database = active.database.clone();
                         let collection_id = collection.id.clone();
@@ -1248,9 +1564,7 @@ impl cosmic::Application for AppModel {
 
                         return cosmic::task::future(async move {
                             let result = helpers::delete_document(
-                                &url,
-                                &token,
-                                &auth_header_type,
+                                &server,
                                 &collection_id,
                                 &document_id,
                                 &tenant,
@@ -1289,7 +1603,7 @@ impl cosmic::Application for AppModel {
                         return self.update(Message::AddNotification(
                             NotificationLevel::Error,
                             fl!("error"),
-                            e,
+                            e.to_string(),
                         ));
                     }
                 }
@@ -1306,6 +1620,10 @@ impl cosmic::Application for AppModel {
     /// Called when a nav item is selected.
     fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<cosmic::Action<Self::Message>> {
         self.nav.activate(id);
+        if self.nav.active_data::<Page>() == Some(&Page::Compare) {
+            // Servers may have been added or removed since the page was last shown
+            self.compare.set_servers(&self.config.servers);
+        }
         self.update_title()
     }
 }
@@ -1327,164 +1645,818 @@ impl AppModel {
         }
     }
 
-    /// Handles browser messages.
-    fn handle_browser_message(&mut self, msg: BrowserMsg) -> Task<cosmic::Action<Message>> {
-        match msg {
-            BrowserMsg::Miller(miller_msg) => {
-                match miller_msg {
-                    MillerMessage::Select { column: _, path, item } => {
-                        // Update selection in miller state using full path
-                        self.browser.miller.select(path);
+    /// Pre-fills the tenant and database inputs from the caller's identity if they were
+    /// left empty or at the defaults, and offers the identity's tenant and databases
+    /// in the settings dropdowns.
+    fn apply_identity(&mut self, identity: Identity) {
+        if identity.tenant.is_empty() {
+            return;
+        }
+        let defaults = ServerConfig::default();
 
-                        // Clear document preview when selecting non-document items
-                        if !matches!(&item.data, BrowserData::Document { .. }) {
-                            self.browser.selected_document = None;
-                        }
+        if self.tenant_input.trim().is_empty() || self.tenant_input == defaults.tenant {
+            self.tenant_input = identity.tenant.clone();
+        }
+        if !self.available_tenants.contains(&identity.tenant) {
+            self.available_tenants.push(identity.tenant.clone());
+        }
 
-                        // If it's a branch item, we need to load children
-                        match &item.data {
-                            BrowserData::Server { index, config } => {
-                                // Load tenants for this server
-                                let server_index = *index;
-                                self.browser.set_tenants_loading(server_index);
-                                let url = config.server_url.clone();
-                                let token = config.auth_token.clone();
-                                let auth_header_type = config.auth_header_type.clone();
+        if self.tenant_input == identity.tenant
+            && let Some(first_database) = identity.databases.first()
+        {
+            if (self.database_input.trim().is_empty() || self.database_input == defaults.database)
+                && !identity.databases.contains(&self.database_input)
+            {
+                self.database_input = first_database.clone();
+            }
+            self.available_databases = identity.databases;
+        }
+    }
 
-                                return cosmic::task::future(async move {
-                                    let result =
-                                        helpers::fetch_tenants(&url, &token, &auth_header_type)
-                                            .await;
-                                    cosmic::Action::App(Message::Browser(BrowserMsg::TenantsLoaded {
-                                        server_index,
-                                        result,
-                                    }))
-                                });
-                            }
-                            BrowserData::Tenant { server_index, name } => {
-                                // Load databases for this tenant
-                                let server_index = *server_index;
-                                let tenant = name.clone();
-                                self.browser.set_databases_loading(server_index, &tenant);
+    /// Creates or unlocks the vault in the background, reporting with `VaultUnlocked`.
+    fn open_vault(
+        &mut self,
+        open: impl FnOnce() -> Result<(), CredentialError> + Send + 'static,
+    ) -> Task<cosmic::Action<Message>> {
+        self.vault_unlocking = true;
+        self.vault_error = None;
+        cosmic::task::future(async move {
+            // Key derivation takes a noticeable moment, keep it off the UI thread
+            let result = tokio::task::spawn_blocking(open)
+                .await
+                .unwrap_or_else(|e| Err(CredentialError::Io(e.to_string())));
+            cosmic::Action::App(Message::VaultUnlocked(result))
+        })
+    }
 
-                                let config = &self.config.servers[server_index];
-                                let url = config.server_url.clone();
-                                let token = config.auth_token.clone();
-                                let auth_header_type = config.auth_header_type.clone();
+    /// Moves plain secrets of every server into the vault if it is unlocked,
+    /// or warns that they stay in the settings file if it is not.
+    /// The caller writes the config.
+    fn secure_plain_secrets(&mut self) {
+        // Only replace the form if it has no unsaved edits
+        let form_saved = self.auth_form.build().as_ref() == Ok(&self.config.active_config().auth);
+        let (level, title, message) =
+            match credentials::migrate_plain_secrets(&mut self.config.servers) {
+                Some(Ok(0)) => return,
+                Some(Ok(moved)) => {
+                    if form_saved {
+                        self.auth_form = AuthForm::from_method(&self.config.active_config().auth);
+                    }
+                    (
+                        NotificationLevel::Success,
+                        fl!("vault-secrets-moved"),
+                        format!("{} {}", moved, fl!("vault-secrets-moved-count")),
+                    )
+                }
+                Some(Err(e)) => (NotificationLevel::Error, fl!("vault-error"), e.to_string()),
+                None if credentials::has_plain_secrets(&self.config.servers) => (
+                    NotificationLevel::Warning,
+                    fl!("plain-secrets"),
+                    fl!("plain-secrets-description"),
+                ),
+                None => return,
+            };
 
-                                return cosmic::task::future(async move {
-                                    let result = helpers::fetch_databases(
-                                        &url,
-                                        &token,
-                                        &auth_header_type,
-                                        &tenant,
-                                    )
-                                    .await;
-                                    cosmic::Action::App(Message::Browser(
-                                        BrowserMsg::DatabasesLoaded {
-                                            server_index,
-                                            tenant,
-                                            result,
-                                        },
-                                    ))
-                                });
-                            }
-                            BrowserData::Database {
-                                server_index,
-                                tenant,
-                                name,
-                            } => {
-                                // Load collections for this database
-                                let server_index = *server_index;
-                                let tenant = tenant.clone();
-                                let database = name.clone();
-                                self.browser
-                                    .set_collections_loading(server_index, &tenant, &database);
+        self.notification_id_counter += 1;
+        self.notifications.push(Notification {
+            id: self.notification_id_counter,
+            level,
+            title,
+            message,
+        });
+    }
 
-                                let config = &self.config.servers[server_index];
-                                let url = config.server_url.clone();
-                                let token = config.auth_token.clone();
-                                let auth_header_type = config.auth_header_type.clone();
+    /// Server config built from the settings form inputs.
+    /// Invalid timeouts and retry counts fall back to the defaults.
+    fn input_server_config(&self) -> ServerConfig {
+        let defaults = ServerConfig::new(&self.server_name_input);
+        ServerConfig {
+            server_url: self.server_url_input.clone(),
+            auth: self.auth_form.build().unwrap_or_default(),
+            connection: self.connection_form.build().unwrap_or_default(),
+            tenant: self.tenant_input.clone(),
+            database: self.database_input.clone(),
+            connect_timeout_secs: parse_timeout(&self.connect_timeout_input)
+                .unwrap_or(defaults.connect_timeout_secs),
+            request_timeout_secs: parse_timeout(&self.request_timeout_input)
+                .unwrap_or(defaults.request_timeout_secs),
+            max_retries: self
+                .max_retries_input
+                .trim()
+                .parse()
+                .unwrap_or(defaults.max_retries),
+            ..defaults
+        }
+    }
 
-                                return cosmic::task::future(async move {
-                                    let result = helpers::fetch_collections(
-                                        &url,
-                                        &token,
-                                        &auth_header_type,
-                                        &tenant,
-                                        &database,
-                                    )
-                                    .await;
-                                    cosmic::Action::App(Message::Browser(
-                                        BrowserMsg::CollectionsLoaded {
-                                            server_index,
-                                            tenant,
-                                            database,
-                                            result,
-                                        },
-                                    ))
-                                });
-                            }
-                            BrowserData::Collection {
-                                server_index,
-                                tenant,
-                                database,
-                                collection,
-                            } => {
-                                // Load documents for this collection
-                                let server_index = *server_index;
-                                let tenant = tenant.clone();
-                                let database = database.clone();
-                                let collection_id = collection.id.clone();
-                                self.browser.set_documents_loading(
-                                    server_index,
-                                    &tenant,
-                                    &database,
-                                    &collection_id,
-                                );
+    /// Loads the documents column for a collection, applying its filter.
+    fn fetch_browser_documents(&mut self, target: CollectionRef) -> Task<cosmic::Action<Message>> {
+        let filter = self.browser.filter_for(&target);
+        let CollectionRef {
+            server_index,
+            tenant,
+            database,
+            collection_id,
+        } = target;
+        self.browser
+            .set_documents_loading(server_index, &tenant, &database, &collection_id);
+
+        let server = self.config.servers[server_index].clone();
+
+        cosmic::task::future(async move {
+            let result = helpers::fetch_documents(
+                &server,
+                &collection_id,
+                &tenant,
+                &database,
+                &filter,
+                100, // limit
+                0,   // offset
+            )
+            .await;
+            cosmic::Action::App(Message::Browser(BrowserMsg::DocumentsLoaded {
+                server_index,
+                tenant,
+                database,
+                collection_id,
+                result,
+            }))
+        })
+    }
 
-                                let config = &self.config.servers[server_index];
-                                let url = config.server_url.clone();
-                                let token = config.auth_token.clone();
-                                let auth_header_type = config.auth_header_type.clone();
+    /// Shows the preview for a document and loads its full details (embedding included).
+    fn fetch_document_preview(
+        &mut self,
+        collection: CollectionRef,
+        document_id: String,
+    ) -> Task<cosmic::Action<Message>> {
+        self.browser.embedding_loading = true;
+        self.browser.embedding_error = None;
+        self.browser.similar_loading = false;
+        self.browser.similar = None;
+        self.browser.preview_collection = Some(collection.clone());
+
+        let server = self.config.servers[collection.server_index].clone();
+
+        cosmic::task::future(async move {
+            let result = helpers::fetch_document_details(
+                &server,
+                &collection.collection_id,
+                &document_id,
+                &collection.tenant,
+                &collection.database,
+            )
+            .await;
+            cosmic::Action::App(Message::Browser(BrowserMsg::DocumentDetailsLoaded {
+                document_id,
+                result,
+            }))
+        })
+    }
 
-                                return cosmic::task::future(async move {
-                                    let result = helpers::fetch_documents(
-                                        &url,
-                                        &token,
-                                        &auth_header_type,
-                                        &collection_id,
-                                        &tenant,
-                                        &database,
-                                        100, // limit
-                                        0,   // offset
-                                    )
-                                    .await;
-                                    cosmic::Action::App(Message::Browser(
-                                        BrowserMsg::DocumentsLoaded {
-                                            server_index,
-                                            tenant,
-                                            database,
-                                            collection_id,
-                                            result,
-                                        },
-                                    ))
-                                });
-                            }
-                            BrowserData::Document { document, .. } => {
-                                // Show document preview
-                                self.browser.selected_document = Some(document.clone());
-                            }
-                            _ => {}
-                        }
-                    }
-                    MillerMessage::Activate { item, .. } => {
-                        // Handle activation (e.g., click on leaf items like "Add" buttons)
-                        match &item.data {
-                            BrowserData::AddServer => {
+    /// Searches the collection of the previewed document for the documents
+    /// nearest to its embedding.
+    fn find_similar_documents(&mut self) -> Task<cosmic::Action<Message>> {
+        let (Some(document), Some(collection)) = (
+            self.browser.selected_document.as_ref(),
+            self.browser.preview_collection.clone(),
+        ) else {
+            return Task::none();
+        };
+        let Some(embedding) = document.embedding.clone() else {
+            return Task::none();
+        };
+        let document_id = document.id.clone();
+        self.browser.similar_loading = true;
+        self.browser.similar = None;
+        let server = self.config.servers[collection.server_index].clone();
+
+        cosmic::task::future(async move {
+            // The document itself comes back as its own nearest neighbour
+            let request = QueryRequest {
+                query_embeddings: vec![embedding],
+                n_results: pages::browser::SIMILAR_COUNT + 1,
+                where_filter: None,
+                where_document: None,
+                include: vec![Include::Documents, Include::Distances],
+            };
+            let result = helpers::query_documents(
+                &server,
+                &collection.collection_id,
+                request,
+                &collection.tenant,
+                &collection.database,
+            )
+            .await
+            .map(|matches| matches.into_iter().next().unwrap_or_default());
+            cosmic::Action::App(Message::Browser(BrowserMsg::SimilarLoaded {
+                document_id,
+                result,
+            }))
+        })
+    }
+
+    /// Adds, upserts or updates a document in a collection.
+    fn save_document(
+        &self,
+        collection: CollectionRef,
+        document: Document,
+        mode: SaveMode,
+    ) -> Task<cosmic::Action<Message>> {
+        let server = self.config.servers[collection.server_index].clone();
+        let document_id = document.id.clone();
+
+        cosmic::task::future(async move {
+            let CollectionRef {
+                tenant,
+                database,
+                collection_id,
+                ..
+            } = &collection;
+            let documents = vec![document];
+            let result = match mode {
+                SaveMode::Add => {
+                    helpers::add_documents(&server, collection_id, documents, tenant, database)
+                        .await
+                }
+                SaveMode::Update => {
+                    helpers::update_documents(&server, collection_id, documents, tenant, database)
+                        .await
+                }
+                SaveMode::Upsert => {
+                    helpers::upsert_documents(&server, collection_id, documents, tenant, database)
+                        .await
+                }
+            };
+            cosmic::Action::App(Message::Browser(BrowserMsg::DocumentSaved {
+                collection,
+                document_id,
+                result,
+            }))
+        })
+    }
+
+    /// Counts the documents matching a collection's filter.
+    fn count_browser_documents(&self, target: CollectionRef) -> Task<cosmic::Action<Message>> {
+        let filter = self.browser.filter_for(&target);
+        let server = self.config.servers[target.server_index].clone();
+
+        cosmic::task::future(async move {
+            let result = helpers::fetch_document_count(
+                &server,
+                &target.collection_id,
+                &target.tenant,
+                &target.database,
+                &filter,
+            )
+            .await;
+            cosmic::Action::App(Message::Browser(BrowserMsg::FilterCountLoaded {
+                target,
+                result,
+            }))
+        })
+    }
+
+    fn handle_export_message(&mut self, msg: ExportMsg) -> Task<cosmic::Action<Message>> {
+        // Progress of an export whose dialog is gone has nowhere to go
+        let Some(form) = self.export.as_mut() else {
+            return Task::none();
+        };
+
+        match msg {
+            ExportMsg::ChooseFile => {
+                form.update(ExportMsg::ChooseFile);
+                let file_name = form.file_name();
+                return cosmic::task::future(async move {
+                    let result = choose_save_file("Export Collection", file_name).await;
+                    cosmic::Action::App(Message::Export(ExportMsg::FileChosen(result)))
+                });
+            }
+            ExportMsg::FileChosen(result) => {
+                form.update(ExportMsg::FileChosen(result));
+                if let ExportStatus::Writing { path, cancel, .. } = &form.status {
+                    let progress = transfer::export_collection(
+                        form.source.clone(),
+                        path.clone(),
+                        form.options(),
+                        cancel.clone(),
+                    );
+                    return cosmic::task::stream(progress.map(|progress| {
+                        cosmic::Action::App(Message::Export(ExportMsg::Progress(progress)))
+                    }));
+                }
+            }
+            ExportMsg::Close => {
+                if !form.is_running() {
+                    self.export = None;
+                }
+            }
+            msg => form.update(msg),
+        }
+
+        Task::none()
+    }
+
+    fn handle_import_message(&mut self, msg: ImportMsg) -> Task<cosmic::Action<Message>> {
+        // Progress of an import whose dialog is gone has nowhere to go
+        let Some(form) = self.import.as_mut() else {
+            return Task::none();
+        };
+
+        match msg {
+            ImportMsg::ChooseFile | ImportMsg::ChooseFolder => {
+                let folder = matches!(msg, ImportMsg::ChooseFolder);
+                form.update(msg);
+                return cosmic::task::future(async move {
+                    let result = choose_open_path("Import Documents", folder).await;
+                    cosmic::Action::App(Message::Import(ImportMsg::FileChosen(result)))
+                });
+            }
+            ImportMsg::FileChosen(result) => {
+                form.update(ImportMsg::FileChosen(result));
+                if let (ImportStatus::Reading, Some(path)) = (&form.status, &form.path) {
+                    let path = path.clone();
+                    return cosmic::task::future(async move {
+                        let result = transfer::read_import_file(path).await;
+                        cosmic::Action::App(Message::Import(ImportMsg::FileRead(result)))
+                    });
+                }
+            }
+            ImportMsg::FileRead(result) => {
+                // The user may have gone back while the file was read
+                if matches!(form.status, ImportStatus::Reading) {
+                    form.update(ImportMsg::FileRead(result));
+                }
+            }
+            ImportMsg::Start => {
+                form.update(ImportMsg::Start);
+                if let ImportStatus::Importing { cancel, .. } = &form.status {
+                    let progress = transfer::import_documents(
+                        form.target.clone(),
+                        form.create.clone(),
+                        form.plan.documents.clone(),
+                        cancel.clone(),
+                    );
+                    return cosmic::task::stream(progress.map(|progress| {
+                        cosmic::Action::App(Message::Import(ImportMsg::Progress(progress)))
+                    }));
+                }
+            }
+            ImportMsg::Progress(progress) => {
+                // Even a failed import may have created the collection or
+                // written some batches
+                let refresh = progress.is_done();
+                form.update(ImportMsg::Progress(progress));
+                if refresh {
+                    return self.refresh_import_target();
+                }
+            }
+            ImportMsg::Close => {
+                if !form.is_running() {
+                    self.import = None;
+                }
+            }
+            msg => form.update(msg),
+        }
+
+        Task::none()
+    }
+
+    /// Reloads the collection an import wrote to, or its database if it was created.
+    fn refresh_import_target(&mut self) -> Task<cosmic::Action<Message>> {
+        let Some(form) = &self.import else {
+            return Task::none();
+        };
+        let server_index = form.server_index;
+        let tenant = form.target.tenant.clone();
+        let database = form.target.database.clone();
+
+        if form.create.is_none() {
+            let target = CollectionRef {
+                server_index,
+                tenant,
+                database,
+                collection_id: form.target.collection_id.clone(),
+            };
+            return self.fetch_browser_documents(target);
+        }
+        self.reload_browser_collections(server_index, tenant, database)
+    }
+
+    fn handle_copy_message(&mut self, msg: CopyMsg) -> Task<cosmic::Action<Message>> {
+        // Progress of a copy whose dialog is gone has nowhere to go
+        let Some(form) = self.copy.as_mut() else {
+            return Task::none();
+        };
+
+        match msg {
+            CopyMsg::Start | CopyMsg::Resume => {
+                let Some(target) = form.target() else {
+                    return Task::none();
+                };
+                if matches!(msg, CopyMsg::Resume) && !form.can_resume() {
+                    return Task::none();
+                }
+                form.update(msg);
+                if let CopyStatus::Copying { cancel, .. } = &form.status {
+                    let progress = transfer::copy_collection(
+                        form.source.clone(),
+                        target,
+                        form.options(),
+                        cancel.clone(),
+                    );
+                    return cosmic::task::stream(progress.map(|update| {
+                        cosmic::Action::App(Message::Copy(match update {
+                            transfer::CopyUpdate::Created(id) => CopyMsg::Created(id),
+                            transfer::CopyUpdate::Progress(progress) => CopyMsg::Progress(progress),
+                        }))
+                    }));
+                }
+            }
+            CopyMsg::Progress(progress) => {
+                // The target collection appears in the browser once the copy stops
+                let reload = progress
+                    .is_done()
+                    .then(|| form.target_index().zip(form.target()))
+                    .flatten();
+                form.update(CopyMsg::Progress(progress));
+                if let Some((server_index, target)) = reload {
+                    return self.reload_browser_collections(
+                        server_index,
+                        target.tenant,
+                        target.database,
+                    );
+                }
+            }
+            CopyMsg::Close => {
+                if !form.is_running() {
+                    self.copy = None;
+                }
+            }
+            msg => form.update(msg),
+        }
+
+        Task::none()
+    }
+
+    fn handle_compare_message(&mut self, msg: CompareMsg) -> Task<cosmic::Action<Message>> {
+        match msg {
+            CompareMsg::Start => {
+                let compare = &mut self.compare;
+                let (Some(a), Some(b), Some(tolerance)) = (
+                    compare.source(Side::A),
+                    compare.source(Side::B),
+                    compare.tolerance(),
+                ) else {
+                    return Task::none();
+                };
+                compare.update(CompareMsg::Start);
+                if let CompareStatus::Comparing { cancel, .. } = &compare.status {
+                    let progress = transfer::compare_collections(a, b, tolerance, cancel.clone());
+                    return cosmic::task::stream(progress.map(|progress| {
+                        cosmic::Action::App(Message::Compare(CompareMsg::Progress(progress)))
+                    }));
+                }
+            }
+            msg => self.compare.update(msg),
+        }
+
+        Task::none()
+    }
+
+    fn handle_visualize_message(&mut self, msg: VisualizeMsg) -> Task<cosmic::Action<Message>> {
+        match msg {
+            VisualizeMsg::PointSelected(_) => {
+                self.visualize.update(msg);
+                if let Some(document) = self.visualize.selected_document() {
+                    self.selected_document = Some(document.clone());
+                    self.context_page = ContextPage::DocumentDetails;
+                    self.core.window.show_context = true;
+                }
+            }
+            VisualizeMsg::Reload => {
+                self.visualize.update(msg);
+                return self.load_visualization();
+            }
+            VisualizeMsg::Loaded(..) | VisualizeMsg::MethodSelected(_) => {
+                // A fetched sample or another method needs a new projection
+                let method = self.visualize.method;
+                let was_projecting = self.visualize.status == VisualizeStatus::Projecting;
+                self.visualize.update(msg);
+                if self.visualize.status == VisualizeStatus::Projecting
+                    && (!was_projecting || method != self.visualize.method)
+                {
+                    return self.project_visualization();
+                }
+            }
+            msg => self.visualize.update(msg),
+        }
+
+        Task::none()
+    }
+
+    /// Fetches a sample of the collection on the Visualize page.
+    fn load_visualization(&self) -> Task<cosmic::Action<Message>> {
+        let Some(source) = self.visualize.source.clone() else {
+            return Task::none();
+        };
+        cosmic::task::future(async move {
+            let result = helpers::sample_documents(
+                &source.server,
+                &source.collection_id,
+                &source.tenant,
+                &source.database,
+                visualize::SAMPLE_SIZE,
+            )
+            .await
+            .map_err(|e| e.to_string());
+            cosmic::Action::App(Message::Visualize(VisualizeMsg::Loaded(
+                source.collection_id,
+                result,
+            )))
+        })
+    }
+
+    /// Projects the sample on the Visualize page with the chosen method.
+    fn project_visualization(&self) -> Task<cosmic::Action<Message>> {
+        let (sample, method) = (self.visualize.sample, self.visualize.method);
+        let embeddings = self.visualize.embeddings();
+        cosmic::task::future(async move {
+            let result = projection::project_embeddings(embeddings, method).await;
+            cosmic::Action::App(Message::Visualize(VisualizeMsg::Projected(
+                sample, method, result,
+            )))
+        })
+    }
+
+    fn handle_backup_message(&mut self, msg: BackupMsg) -> Task<cosmic::Action<Message>> {
+        // Progress of a backup whose dialog is gone has nowhere to go
+        let Some(form) = self.backup.as_mut() else {
+            return Task::none();
+        };
+
+        match msg {
+            BackupMsg::ChooseFile => {
+                form.update(BackupMsg::ChooseFile);
+                let file_name = transfer::backup_file_name(&form.source.database);
+                return cosmic::task::future(async move {
+                    let result = choose_save_file("Back Up Database", file_name).await;
+                    cosmic::Action::App(Message::Backup(BackupMsg::FileChosen(result)))
+                });
+            }
+            BackupMsg::FileChosen(result) => {
+                form.update(BackupMsg::FileChosen(result));
+                if let BackupStatus::Writing { path, cancel, .. } = &form.status {
+                    let progress = transfer::backup_database(
+                        form.source.clone(),
+                        path.clone(),
+                        cancel.clone(),
+                    );
+                    return cosmic::task::stream(progress.map(|progress| {
+                        cosmic::Action::App(Message::Backup(BackupMsg::Progress(progress)))
+                    }));
+                }
+            }
+            BackupMsg::Close => {
+                if !form.is_running() {
+                    self.backup = None;
+                }
+            }
+            msg => form.update(msg),
+        }
+
+        Task::none()
+    }
+
+    fn handle_restore_message(&mut self, msg: RestoreMsg) -> Task<cosmic::Action<Message>> {
+        // Progress of a restore whose dialog is gone has nowhere to go
+        let Some(form) = self.restore.as_mut() else {
+            return Task::none();
+        };
+
+        match msg {
+            RestoreMsg::ChooseFile => {
+                form.update(RestoreMsg::ChooseFile);
+                return cosmic::task::future(async move {
+                    let result = choose_open_path("Restore Backup", false).await;
+                    cosmic::Action::App(Message::Restore(RestoreMsg::FileChosen(result)))
+                });
+            }
+            RestoreMsg::FileChosen(result) => {
+                form.update(RestoreMsg::FileChosen(result));
+                if let (RestoreStatus::Reading, Some(path)) = (&form.status, &form.path) {
+                    let path = path.clone();
+                    return cosmic::task::future(async move {
+                        let result = transfer::read_backup(path).await;
+                        cosmic::Action::App(Message::Restore(RestoreMsg::SummaryRead(result)))
+                    });
+                }
+            }
+            RestoreMsg::SummaryRead(result) => {
+                // The user may have gone back while the file was read
+                if matches!(form.status, RestoreStatus::Reading) {
+                    form.update(RestoreMsg::SummaryRead(result));
+                }
+            }
+            RestoreMsg::Start => {
+                form.update(RestoreMsg::Start);
+                if let (RestoreStatus::Restoring { cancel, .. }, Some(path)) =
+                    (&form.status, &form.path)
+                {
+                    let progress = transfer::restore_database(
+                        path.clone(),
+                        form.target.clone(),
+                        form.conflict,
+                        cancel.clone(),
+                    );
+                    return cosmic::task::stream(progress.map(|progress| {
+                        cosmic::Action::App(Message::Restore(RestoreMsg::Progress(progress)))
+                    }));
+                }
+            }
+            RestoreMsg::Progress(progress) => {
+                // Even a restore that stopped early may have created collections
+                let reload = progress.is_done().then(|| {
+                    let target = &form.target;
+                    (
+                        form.server_index,
+                        target.tenant.clone(),
+                        target.database.clone(),
+                    )
+                });
+                form.update(RestoreMsg::Progress(progress));
+                if let Some((server_index, tenant, database)) = reload {
+                    return self.reload_browser_collections(server_index, tenant, database);
+                }
+            }
+            RestoreMsg::Close => {
+                if !form.is_running() {
+                    self.restore = None;
+                }
+            }
+            msg => form.update(msg),
+        }
+
+        Task::none()
+    }
+
+    /// Reloads the collections column of a database in the browser.
+    fn reload_browser_collections(
+        &mut self,
+        server_index: usize,
+        tenant: String,
+        database: String,
+    ) -> Task<cosmic::Action<Message>> {
+        self.browser
+            .set_collections_loading(server_index, &tenant, &database);
+        let server = self.config.servers[server_index].clone();
+        cosmic::task::future(async move {
+            let result = helpers::fetch_collections(&server, &tenant, &database).await;
+            cosmic::Action::App(Message::Browser(BrowserMsg::CollectionsLoaded {
+                server_index,
+                tenant,
+                database,
+                result,
+            }))
+        })
+    }
+
+    /// Handles browser messages.
+    fn handle_browser_message(&mut self, msg: BrowserMsg) -> Task<cosmic::Action<Message>> {
+        match msg {
+            BrowserMsg::RetryTick => {
+                self.browser.update_retrying(&self.config.servers);
+            }
+
+            BrowserMsg::Miller(miller_msg) => {
+                match miller_msg {
+                    MillerMessage::Select { column: _, path, item } => {
+                        // Update selection in miller state using full path
+                        self.browser.miller.select(path);
+
+                        // Clear document preview when selecting non-document items
+                        if !matches!(&item.data, BrowserData::Document { .. }) {
+                            self.browser.selected_document = None;
+                            self.browser.embedding_loading = false;
+                            self.browser.editing_document = None;
+                        }
+
+                        // If it's a branch item, we need to load children
+                        match &item.data {
+                            BrowserData::Server { index, config }
+                            | BrowserData::LocalDirectory { index, config } => {
+                                // Load tenants for this server
+                                let server_index = *index;
+                                self.browser.set_tenants_loading(server_index);
+                                let server = config.clone();
+
+                                return cosmic::task::future(async move {
+                                    let result = helpers::fetch_tenants(&server).await;
+                                    cosmic::Action::App(Message::Browser(BrowserMsg::TenantsLoaded {
+                                        server_index,
+                                        result,
+                                    }))
+                                });
+                            }
+                            BrowserData::Tenant { server_index, name } => {
+                                // Load databases for this tenant
+                                let server_index = *server_index;
+                                let tenant = name.clone();
+                                self.browser.set_databases_loading(server_index, &tenant);
+
+                                let server = self.config.servers[server_index].clone();
+
+                                return cosmic::task::future(async move {
+                                    let result = helpers::fetch_databases(&server, &tenant).await;
+                                    cosmic::Action::App(Message::Browser(
+                                        BrowserMsg::DatabasesLoaded {
+                                            server_index,
+                                            tenant,
+                                            result,
+                                        },
+                                    ))
+                                });
+                            }
+                            BrowserData::Database {
+                                server_index,
+                                tenant,
+                                name,
+                            } => {
+                                // Load collections for this database
+                                let server_index = *server_index;
+                                let tenant = tenant.clone();
+                                let database = name.clone();
+                                self.browser
+                                    .set_collections_loading(server_index, &tenant, &database);
+
+                                let server = self.config.servers[server_index].clone();
+
+                                return cosmic::task::future(async move {
+                                    let result =
+                                        helpers::fetch_collections(&server, &tenant, &database)
+                                            .await;
+                                    cosmic::Action::App(Message::Browser(
+                                        BrowserMsg::CollectionsLoaded {
+                                            server_index,
+                                            tenant,
+                                            database,
+                                            result,
+                                        },
+                                    ))
+                                });
+                            }
+                            BrowserData::Collection {
+                                server_index,
+                                tenant,
+                                database,
+                                collection,
+                            } => {
+                                // Load documents for this collection, keeping its filter
+                                let target = CollectionRef {
+                                    server_index: *server_index,
+                                    tenant: tenant.clone(),
+                                    database: database.clone(),
+                                    collection_id: collection.id.clone(),
+                                };
+                                self.browser.set_filter_target(target.clone());
+                                return self.fetch_browser_documents(target);
+                            }
+                            BrowserData::Document {
+                                server_index,
+                                tenant,
+                                database,
+                                collection_id,
+                                document,
+                            } => {
+                                // Show document preview, then load its embedding
+                                self.browser.selected_document = Some(document.clone());
+                                self.browser.editing_document = None;
+                                let collection = CollectionRef {
+                                    server_index: *server_index,
+                                    tenant: tenant.clone(),
+                                    database: database.clone(),
+                                    collection_id: collection_id.clone(),
+                                };
+                                return self
+                                    .fetch_document_preview(collection, document.id.clone());
+                            }
+                            _ => {}
+                        }
+                    }
+                    MillerMessage::Activate { item, .. } => {
+                        // Handle activation (e.g., click on leaf items like "Add" buttons)
+                        match &item.data {
+                            BrowserData::AddServer => {
                                 // Show inline form instead of dialog
                                 self.browser.adding_server = Some(AddServerForm::new());
                             }
+                            BrowserData::OpenLocalDirectory => {
+                                self.browser.dialog = Some(BrowserDialog::OpenLocalDirectory {
+                                    path: String::new(),
+                                    checking: false,
+                                    error: None,
+                                });
+                            }
                             BrowserData::AddTenant { server_index } => {
                                 self.browser.dialog = Some(BrowserDialog::AddTenant {
                                     server_index: *server_index,
@@ -1511,11 +2483,20 @@ impl AppModel {
                                     tenant: tenant.clone(),
                                     database: database.clone(),
                                     name: String::new(),
+                                    options: CollectionOptionsForm::default(),
+                                    error: None,
                                 });
                             }
-                            BrowserData::Document { document, .. } => {
-                                // Show document preview
-                                self.browser.selected_document = Some(document.clone());
+                            BrowserData::AddDocument(collection) => {
+                                self.browser.dialog = Some(BrowserDialog::AddDocument {
+                                    collection: collection.clone(),
+                                    form: DocumentForm::default(),
+                                });
+                            }
+                            BrowserData::FilterDocuments(target) => {
+                                return self.handle_browser_message(BrowserMsg::OpenFilter(
+                                    target.clone(),
+                                ));
                             }
                             _ => {}
                         }
@@ -1555,7 +2536,7 @@ impl AppModel {
                             return self.browser.scroll_to_end();
                         }
                     }
-                    self.browser.set_tenants_error(server_index, e);
+                    self.browser.set_tenants_error(server_index, e.to_string());
                 }
             },
 
@@ -1570,20 +2551,16 @@ impl AppModel {
                     return self.browser.scroll_to_end();
                 }
                 Err(e) => {
-                    // Check if the error indicates tenant doesn't exist
-                    let tenant_not_found = e.to_lowercase().contains("tenant")
-                        && (e.to_lowercase().contains("not found")
-                            || e.to_lowercase().contains("does not exist")
-                            || e.contains("404"));
-
-                    if tenant_not_found {
+                    // Listing databases of a missing tenant is reported as not found
+                    if e.is_not_found() {
                         // Show confirmation dialog to create tenant on server
                         self.browser.dialog = Some(BrowserDialog::ConfirmCreateTenant {
                             server_index,
                             tenant: tenant.clone(),
                         });
                     } else {
-                        self.browser.set_databases_error(server_index, &tenant, e);
+                        self.browser
+                            .set_databases_error(server_index, &tenant, e.to_string());
                     }
                 }
             },
@@ -1601,8 +2578,12 @@ impl AppModel {
                     return self.browser.scroll_to_end();
                 }
                 Err(e) => {
-                    self.browser
-                        .set_collections_error(server_index, &tenant, &database, e);
+                    self.browser.set_collections_error(
+                        server_index,
+                        &tenant,
+                        &database,
+                        e.to_string(),
+                    );
                 }
             },
 
@@ -1625,8 +2606,13 @@ impl AppModel {
                     return self.browser.scroll_to_end();
                 }
                 Err(e) => {
-                    self.browser
-                        .set_documents_error(server_index, &tenant, &database, &collection_id, e);
+                    self.browser.set_documents_error(
+                        server_index,
+                        &tenant,
+                        &database,
+                        &collection_id,
+                        e.to_string(),
+                    );
                 }
             },
 
@@ -1634,11 +2620,14 @@ impl AppModel {
                 if let Some(ref mut dialog) = self.browser.dialog {
                     match dialog {
                         BrowserDialog::AddServer { name } => *name = value,
+                        BrowserDialog::OpenLocalDirectory { path, .. } => *path = value,
                         BrowserDialog::AddTenant { name, .. } => *name = value,
                         BrowserDialog::AddDatabase { name, .. } => *name = value,
                         BrowserDialog::AddCollection { name, .. } => *name = value,
-                        // ConfirmCreateTenant has no text input
-                        BrowserDialog::ConfirmCreateTenant { .. } => {}
+                        BrowserDialog::EditCollection { name, .. } => *name = value,
+                        // These dialogs have no single text input
+                        BrowserDialog::ConfirmCreateTenant { .. }
+                        | BrowserDialog::AddDocument { .. } => {}
                     }
                 }
             }
@@ -1662,6 +2651,22 @@ impl AppModel {
                             self.server_names =
                                 self.config.servers.iter().map(|s| s.name.clone()).collect();
                         }
+                        BrowserDialog::OpenLocalDirectory { path, .. } => {
+                            // Check the directory before adding it, keeping the dialog open
+                            let server = ServerConfig::local(Path::new(path.trim()));
+                            self.browser.dialog = Some(BrowserDialog::OpenLocalDirectory {
+                                path,
+                                checking: true,
+                                error: None,
+                            });
+
+                            return cosmic::task::future(async move {
+                                let result = helpers::test_connection(&server).await;
+                                cosmic::Action::App(Message::Browser(
+                                    BrowserMsg::LocalDirectoryChecked { server, result },
+                                ))
+                            });
+                        }
                         BrowserDialog::AddTenant { server_index, name } => {
                             // Add tenant to local cache and persist to config
                             let mut tenants = self
@@ -1688,15 +2693,10 @@ impl AppModel {
                             tenant,
                         } => {
                             // User confirmed creating tenant on server
-                            let config = &self.config.servers[server_index];
-                            let url = config.server_url.clone();
-                            let token = config.auth_token.clone();
-                            let auth_header_type = config.auth_header_type.clone();
+                            let server = self.config.servers[server_index].clone();
 
                             return cosmic::task::future(async move {
-                                let result =
-                                    helpers::create_tenant(&url, &token, &auth_header_type, &tenant)
-                                        .await;
+                                let result = helpers::create_tenant(&server, &tenant).await;
                                 cosmic::Action::App(Message::Browser(BrowserMsg::TenantCreated {
                                     server_index,
                                     tenant,
@@ -1710,20 +2710,11 @@ impl AppModel {
                             name,
                         } => {
                             // Create database
-                            let config = &self.config.servers[server_index];
-                            let url = config.server_url.clone();
-                            let token = config.auth_token.clone();
-                            let auth_header_type = config.auth_header_type.clone();
+                            let server = self.config.servers[server_index].clone();
 
                             return cosmic::task::future(async move {
-                                let result = helpers::create_database(
-                                    &url,
-                                    &token,
-                                    &auth_header_type,
-                                    &name,
-                                    &tenant,
-                                )
-                                .await;
+                                let result =
+                                    helpers::create_database(&server, &name, &tenant).await;
                                 cosmic::Action::App(Message::Browser(BrowserMsg::DatabaseCreated {
                                     server_index,
                                     tenant,
@@ -1732,24 +2723,114 @@ impl AppModel {
                                 }))
                             });
                         }
+                        BrowserDialog::EditCollection {
+                            collection,
+                            original_name,
+                            name,
+                            metadata,
+                            ..
+                        } => {
+                            let new_name = name.trim().to_string();
+                            let request = match metadata.build() {
+                                Ok(new_metadata) => ModifyCollectionRequest {
+                                    new_name: (new_name != original_name).then_some(new_name),
+                                    new_metadata,
+                                },
+                                Err(e) => {
+                                    self.browser.dialog = Some(BrowserDialog::EditCollection {
+                                        collection,
+                                        original_name,
+                                        name,
+                                        metadata,
+                                        saving: false,
+                                        error: Some(e),
+                                    });
+                                    return Task::none();
+                                }
+                            };
+                            self.browser.dialog = Some(BrowserDialog::EditCollection {
+                                collection: collection.clone(),
+                                original_name,
+                                name,
+                                metadata,
+                                saving: true,
+                                error: None,
+                            });
+
+                            let server = self.config.servers[collection.server_index].clone();
+
+                            return cosmic::task::future(async move {
+                                let result = helpers::modify_collection(
+                                    &server,
+                                    &collection.collection_id,
+                                    request,
+                                    &collection.tenant,
+                                    &collection.database,
+                                )
+                                .await;
+                                cosmic::Action::App(Message::Browser(
+                                    BrowserMsg::CollectionModified { collection, result },
+                                ))
+                            });
+                        }
+                        BrowserDialog::AddDocument {
+                            collection,
+                            mut form,
+                        } => {
+                            let document = match form.build_new() {
+                                Ok(document) => document,
+                                Err(e) => {
+                                    // Keep the dialog open to fix the input
+                                    form.error = Some(e);
+                                    self.browser.dialog =
+                                        Some(BrowserDialog::AddDocument { collection, form });
+                                    return Task::none();
+                                }
+                            };
+                            let mode = if form.upsert {
+                                SaveMode::Upsert
+                            } else {
+                                SaveMode::Add
+                            };
+                            form.saving = true;
+                            self.browser.dialog = Some(BrowserDialog::AddDocument {
+                                collection: collection.clone(),
+                                form,
+                            });
+                            return self.save_document(collection, document, mode);
+                        }
                         BrowserDialog::AddCollection {
                             server_index,
                             tenant,
                             database,
                             name,
+                            options,
+                            error: _,
                         } => {
+                            let create_options = match options.build() {
+                                Ok(create_options) => create_options,
+                                Err(e) => {
+                                    // Keep the dialog open to fix the input
+                                    self.browser.dialog = Some(BrowserDialog::AddCollection {
+                                        server_index,
+                                        tenant,
+                                        database,
+                                        name,
+                                        options,
+                                        error: Some(e),
+                                    });
+                                    return Task::none();
+                                }
+                            };
+
                             // Create collection
-                            let config = &self.config.servers[server_index];
-                            let url = config.server_url.clone();
-                            let token = config.auth_token.clone();
-                            let auth_header_type = config.auth_header_type.clone();
+                            let server = self.config.servers[server_index].clone();
 
                             return cosmic::task::future(async move {
                                 let result = helpers::create_collection(
-                                    &url,
-                                    &token,
-                                    &auth_header_type,
+                                    &server,
                                     &name,
+                                    create_options,
                                     &tenant,
                                     &database,
                                 )
@@ -1781,10 +2862,7 @@ impl AppModel {
                 Ok(()) => {
                     // Tenant created successfully - now load databases for this tenant
                     self.browser.set_databases_loading(server_index, &tenant);
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
+                    let server = self.config.servers[server_index].clone();
 
                     // Show success notification
                     self.notification_id_counter += 1;
@@ -1796,9 +2874,7 @@ impl AppModel {
                     });
 
                     return cosmic::task::future(async move {
-                        let result =
-                            helpers::fetch_databases(&url, &token, &auth_header_type, &tenant)
-                                .await;
+                        let result = helpers::fetch_databases(&server, &tenant).await;
                         cosmic::Action::App(Message::Browser(BrowserMsg::DatabasesLoaded {
                             server_index,
                             tenant,
@@ -1812,7 +2888,7 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: format!("Failed to create tenant '{}'", tenant),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
@@ -1826,15 +2902,10 @@ impl AppModel {
                 Ok(()) => {
                     // Refresh databases for this tenant
                     self.browser.set_databases_loading(server_index, &tenant);
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
+                    let server = self.config.servers[server_index].clone();
 
                     return cosmic::task::future(async move {
-                        let result =
-                            helpers::fetch_databases(&url, &token, &auth_header_type, &tenant)
-                                .await;
+                        let result = helpers::fetch_databases(&server, &tenant).await;
                         cosmic::Action::App(Message::Browser(BrowserMsg::DatabasesLoaded {
                             server_index,
                             tenant,
@@ -1848,7 +2919,7 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: format!("Failed to create database '{}'", database),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
@@ -1863,20 +2934,10 @@ impl AppModel {
                     // Refresh collections for this database
                     self.browser
                         .set_collections_loading(server_index, &tenant, &database);
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
+                    let server = self.config.servers[server_index].clone();
 
                     return cosmic::task::future(async move {
-                        let result = helpers::fetch_collections(
-                            &url,
-                            &token,
-                            &auth_header_type,
-                            &tenant,
-                            &database,
-                        )
-                        .await;
+                        let result = helpers::fetch_collections(&server, &tenant, &database).await;
                         cosmic::Action::App(Message::Browser(BrowserMsg::CollectionsLoaded {
                             server_index,
                             tenant,
@@ -1891,7 +2952,7 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: "Failed to create collection".to_string(),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
@@ -1901,117 +2962,454 @@ impl AppModel {
                 self.browser.adding_server = Some(AddServerForm::new());
             }
 
-            BrowserMsg::CancelAddServer => {
-                self.browser.adding_server = None;
+            BrowserMsg::CancelAddServer => {
+                self.browser.adding_server = None;
+            }
+
+            BrowserMsg::AddServerNameChanged(name) => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    form.name = name;
+                }
+            }
+
+            BrowserMsg::AddServerUrlChanged(url) => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    form.url = url;
+                    // Reset status when URL changes
+                    form.status = AddServerStatus::Editing;
+                }
+            }
+
+            BrowserMsg::AddServerAuth(msg) => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    form.auth.update(msg);
+                    // Reset status when auth changes
+                    form.status = AddServerStatus::Editing;
+                }
+            }
+
+            BrowserMsg::AddServerConnection(msg) => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    form.connection.update(msg);
+                    // Reset status when TLS or proxy settings change
+                    form.status = AddServerStatus::Editing;
+                }
+            }
+
+            BrowserMsg::TestNewServerConnection => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    if let Err(e) = form.auth.build().and(form.connection.build()) {
+                        form.status = AddServerStatus::TestFailed(e);
+                        return Task::none();
+                    }
+                    form.status = AddServerStatus::Testing;
+                    let server = form.server_config();
+
+                    return cosmic::task::future(async move {
+                        let result = helpers::test_connection(&server).await;
+                        cosmic::Action::App(Message::Browser(BrowserMsg::TestNewServerResult(
+                            result,
+                        )))
+                    });
+                }
+            }
+
+            BrowserMsg::TestNewServerResult(result) => {
+                if let Some(ref mut form) = self.browser.adding_server {
+                    form.status = match result {
+                        Ok(()) => AddServerStatus::TestSuccess,
+                        Err(e) => AddServerStatus::TestFailed(e.to_string()),
+                    };
+                }
+            }
+
+            BrowserMsg::SaveNewServer => {
+                if let Some(form) = self.browser.adding_server.take() {
+                    // Only save if test was successful
+                    if form.status == AddServerStatus::TestSuccess && !form.name.is_empty() {
+                        let server_index = self.config.add_server(form.server_config());
+                        self.secure_plain_secrets();
+                        let server = self.config.servers[server_index].clone();
+                        if let Some(ref context) = self.config_context {
+                            let _ = self.config.write_entry(context);
+                        }
+
+                        // Refresh browser with new servers
+                        self.browser.refresh_servers(&self.config.servers);
+                        self.server_names =
+                            self.config.servers.iter().map(|s| s.name.clone()).collect();
+
+                        self.notification_id_counter += 1;
+                        self.notifications.push(Notification {
+                            id: self.notification_id_counter,
+                            level: NotificationLevel::Success,
+                            title: "Server added".to_string(),
+                            message: "New server has been added successfully.".to_string(),
+                        });
+
+                        // Look up the tenant and databases the credentials belong to
+                        return cosmic::task::future(async move {
+                            let result = helpers::fetch_identity(&server).await;
+                            cosmic::Action::App(Message::Browser(BrowserMsg::IdentityLoaded {
+                                server_index,
+                                result,
+                            }))
+                        });
+                    }
+                }
+            }
+
+            BrowserMsg::LocalDirectoryChecked { server, result } => {
+                // The dialog may have been cancelled while checking
+                let Some(BrowserDialog::OpenLocalDirectory { path, .. }) =
+                    self.browser.dialog.take()
+                else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(()) => {
+                        self.config.add_server(server);
+                        if let Some(ref context) = self.config_context {
+                            let _ = self.config.write_entry(context);
+                        }
+                        self.browser.refresh_servers(&self.config.servers);
+                        self.server_names =
+                            self.config.servers.iter().map(|s| s.name.clone()).collect();
+                    }
+                    Err(e) => {
+                        self.browser.dialog = Some(BrowserDialog::OpenLocalDirectory {
+                            path,
+                            checking: false,
+                            error: Some(e.to_string()),
+                        });
+                    }
+                }
+            }
+
+            BrowserMsg::IdentityLoaded {
+                server_index,
+                result,
+            } => {
+                let Ok(Some(identity)) = result else {
+                    return Task::none();
+                };
+                if identity.tenant.is_empty() || server_index >= self.config.servers.len() {
+                    return Task::none();
+                }
+
+                // Use the identity's tenant and, unless the current one is accessible,
+                // its first database
+                let server = &mut self.config.servers[server_index];
+                server.tenant = identity.tenant.clone();
+                if let Some(first_database) = identity.databases.first()
+                    && !identity.databases.contains(&server.database)
+                {
+                    server.database = first_database.clone();
+                }
+                server.add_tenant(&identity.tenant);
+                let tenants = server.tenants.clone();
+                if server_index == self.config.active_server {
+                    self.tenant_input = server.tenant.clone();
+                    self.database_input = server.database.clone();
+                }
+                if let Some(ref context) = self.config_context {
+                    let _ = self.config.write_entry(context);
+                }
+
+                // Fill the tenants and databases columns so the server can be browsed right away
+                self.browser.set_tenants(server_index, tenants);
+                if !identity.databases.is_empty() {
+                    self.browser
+                        .set_databases(server_index, &identity.tenant, identity.databases);
+                }
+            }
+
+            // Delete handlers
+            BrowserMsg::RequestDeleteDatabase {
+                server_index,
+                tenant,
+                name,
+            } => {
+                let server = self.config.servers[server_index].clone();
+
+                return cosmic::task::future(async move {
+                    let result = helpers::delete_database(&server, &name, &tenant).await;
+                    cosmic::Action::App(Message::Browser(BrowserMsg::DatabaseDeleted {
+                        server_index,
+                        tenant,
+                        result,
+                    }))
+                });
+            }
+
+            BrowserMsg::RequestBackupDatabase {
+                server_index,
+                tenant,
+                name,
+            } => {
+                self.browser.open_menu = None;
+                self.backup = Some(BackupForm::new(DatabaseSource {
+                    server: self.config.servers[server_index].clone(),
+                    tenant,
+                    database: name,
+                }));
+            }
+
+            BrowserMsg::RequestRestoreDatabase {
+                server_index,
+                tenant,
+                name,
+            } => {
+                self.browser.open_menu = None;
+                let target = DatabaseSource {
+                    server: self.config.servers[server_index].clone(),
+                    tenant,
+                    database: name,
+                };
+                self.restore = Some(RestoreForm::new(server_index, target.clone()));
+
+                // Collections already in the database are marked in the preview
+                return cosmic::task::future(async move {
+                    let names = helpers::fetch_collections(
+                        &target.server,
+                        &target.tenant,
+                        &target.database,
+                    )
+                    .await
+                    .map(|collections| collections.into_iter().map(|c| c.name).collect())
+                    .unwrap_or_default();
+                    cosmic::Action::App(Message::Restore(RestoreMsg::ExistingLoaded(names)))
+                });
             }
 
-            BrowserMsg::AddServerNameChanged(name) => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.name = name;
-                }
+            BrowserMsg::RequestEditCollection {
+                server_index,
+                tenant,
+                database,
+                collection,
+            } => {
+                self.browser.open_menu = None;
+                self.browser.dialog = Some(BrowserDialog::EditCollection {
+                    collection: CollectionRef {
+                        server_index,
+                        tenant,
+                        database,
+                        collection_id: collection.id,
+                    },
+                    original_name: collection.name.clone(),
+                    name: collection.name,
+                    metadata: MetadataEditor::from_metadata(collection.metadata.as_ref()),
+                    saving: false,
+                    error: None,
+                });
             }
 
-            BrowserMsg::AddServerUrlChanged(url) => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.url = url;
-                    // Reset status when URL changes
-                    form.status = AddServerStatus::Editing;
+            BrowserMsg::CollectionOptions(options_msg) => {
+                if let Some(BrowserDialog::AddCollection {
+                    ref mut options,
+                    ref mut error,
+                    ..
+                }) = self.browser.dialog
+                {
+                    options.update(options_msg);
+                    *error = None;
                 }
             }
 
-            BrowserMsg::AddServerAuthTokenChanged(token) => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.auth_token = token;
-                    // Reset status when auth changes
-                    form.status = AddServerStatus::Editing;
+            BrowserMsg::EditCollectionMetadata(metadata_msg) => {
+                if let Some(BrowserDialog::EditCollection {
+                    ref mut metadata,
+                    ref mut error,
+                    ..
+                }) = self.browser.dialog
+                {
+                    metadata.update(metadata_msg);
+                    *error = None;
                 }
             }
 
-            BrowserMsg::AddServerAuthHeaderTypeChanged(auth_type) => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.auth_header_type = auth_type;
-                    // Reset status when auth type changes
-                    form.status = AddServerStatus::Editing;
-                }
-            }
+            BrowserMsg::CollectionModified { collection, result } => match result {
+                Ok(()) => {
+                    self.browser.dialog = None;
+                    self.notification_id_counter += 1;
+                    self.notifications.push(Notification {
+                        id: self.notification_id_counter,
+                        level: NotificationLevel::Success,
+                        title: "Collection updated".to_string(),
+                        message: "Collection name and metadata have been saved.".to_string(),
+                    });
 
-            BrowserMsg::TestNewServerConnection => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.status = AddServerStatus::Testing;
-                    let url = form.url.clone();
-                    let token = form.auth_token.clone();
-                    let auth_header_type = form.auth_header_type.clone();
+                    // Refresh collections for this database
+                    let CollectionRef {
+                        server_index,
+                        tenant,
+                        database,
+                        ..
+                    } = collection;
+                    self.browser
+                        .set_collections_loading(server_index, &tenant, &database);
+                    let server = self.config.servers[server_index].clone();
 
                     return cosmic::task::future(async move {
-                        let result =
-                            helpers::test_connection(&url, &token, &auth_header_type).await;
-                        cosmic::Action::App(Message::Browser(BrowserMsg::TestNewServerResult(
+                        let result = helpers::fetch_collections(&server, &tenant, &database).await;
+                        cosmic::Action::App(Message::Browser(BrowserMsg::CollectionsLoaded {
+                            server_index,
+                            tenant,
+                            database,
                             result,
-                        )))
+                        }))
                     });
                 }
+                Err(e) => {
+                    if let Some(BrowserDialog::EditCollection {
+                        ref mut saving,
+                        ref mut error,
+                        ..
+                    }) = self.browser.dialog
+                    {
+                        *saving = false;
+                        *error = Some(e.to_string());
+                    }
+                }
+            },
+
+            BrowserMsg::RequestExportCollection {
+                server_index,
+                tenant,
+                database,
+                collection,
+            } => {
+                self.browser.open_menu = None;
+                let filter = self.browser.filter_for(&CollectionRef {
+                    server_index,
+                    tenant: tenant.clone(),
+                    database: database.clone(),
+                    collection_id: collection.id.clone(),
+                });
+                let source = CollectionSource {
+                    server: self.config.servers[server_index].clone(),
+                    tenant,
+                    database,
+                    collection_id: collection.id,
+                    collection_name: collection.name,
+                };
+                self.export = Some(ExportForm::new(source, filter));
             }
 
-            BrowserMsg::TestNewServerResult(result) => {
-                if let Some(ref mut form) = self.browser.adding_server {
-                    form.status = match result {
-                        Ok(()) => AddServerStatus::TestSuccess,
-                        Err(e) => AddServerStatus::TestFailed(e),
-                    };
+            BrowserMsg::RequestCopyCollection {
+                server_index,
+                tenant,
+                database,
+                collection,
+            } => {
+                self.browser.open_menu = None;
+                let create = transfer::copy_options(&collection);
+                let source = CollectionSource {
+                    server: self.config.servers[server_index].clone(),
+                    tenant,
+                    database,
+                    collection_id: collection.id,
+                    collection_name: collection.name,
+                };
+                self.copy = Some(CopyForm::new(
+                    source,
+                    create,
+                    server_index,
+                    &self.config.servers,
+                ));
+            }
+
+            BrowserMsg::RequestCompareCollection {
+                server_index,
+                tenant,
+                database,
+                collection_name,
+            } => {
+                self.browser.open_menu = None;
+                self.compare.set_servers(&self.config.servers);
+                self.compare
+                    .set_a(server_index, tenant, database, collection_name);
+                let page = self
+                    .nav
+                    .iter()
+                    .find(|id| self.nav.data::<Page>(*id) == Some(&Page::Compare));
+                if let Some(id) = page {
+                    self.nav.activate(id);
                 }
+                return self.update_title();
             }
 
-            BrowserMsg::SaveNewServer => {
-                if let Some(form) = self.browser.adding_server.take() {
-                    // Only save if test was successful
-                    if form.status == AddServerStatus::TestSuccess && !form.name.is_empty() {
-                        let mut new_server = ServerConfig::new(&form.name);
-                        new_server.server_url = form.url;
-                        new_server.auth_token = form.auth_token;
-                        new_server.auth_header_type = form.auth_header_type;
-
-                        self.config.add_server(new_server);
-                        if let Some(ref context) = self.config_context {
-                            let _ = self.config.write_entry(context);
-                        }
-
-                        // Refresh browser with new servers
-                        self.browser.refresh_servers(&self.config.servers);
-                        self.server_names =
-                            self.config.servers.iter().map(|s| s.name.clone()).collect();
-
-                        self.notification_id_counter += 1;
-                        self.notifications.push(Notification {
-                            id: self.notification_id_counter,
-                            level: NotificationLevel::Success,
-                            title: "Server added".to_string(),
-                            message: "New server has been added successfully.".to_string(),
-                        });
-                    }
+            BrowserMsg::RequestVisualizeCollection {
+                server_index,
+                tenant,
+                database,
+                collection,
+            } => {
+                self.browser.open_menu = None;
+                let Some(server) = self.config.servers.get(server_index) else {
+                    return Task::none();
+                };
+                self.visualize.open(CollectionSource {
+                    server: server.clone(),
+                    tenant,
+                    database,
+                    collection_id: collection.id,
+                    collection_name: collection.name,
+                });
+                let page = self
+                    .nav
+                    .iter()
+                    .find(|id| self.nav.data::<Page>(*id) == Some(&Page::Visualize));
+                if let Some(id) = page {
+                    self.nav.activate(id);
                 }
+                return cosmic::task::batch([self.update_title(), self.load_visualization()]);
             }
 
-            // Delete handlers
-            BrowserMsg::RequestDeleteDatabase {
+            BrowserMsg::RequestImportCollection {
                 server_index,
                 tenant,
-                name,
+                database,
+                collection,
             } => {
-                let config = &self.config.servers[server_index];
-                let url = config.server_url.clone();
-                let token = config.auth_token.clone();
-                let auth_header_type = config.auth_header_type.clone();
-
-                return cosmic::task::future(async move {
-                    let result =
-                        helpers::delete_database(&url, &token, &auth_header_type, &name, &tenant)
-                            .await;
-                    cosmic::Action::App(Message::Browser(BrowserMsg::DatabaseDeleted {
-                        server_index,
-                        tenant,
-                        result,
-                    }))
-                });
+                self.browser.open_menu = None;
+                let target = CollectionSource {
+                    server: self.config.servers[server_index].clone(),
+                    tenant,
+                    database,
+                    collection_id: collection.id,
+                    collection_name: collection.name,
+                };
+                self.import = Some(ImportForm::new(server_index, target, None));
+            }
+
+            BrowserMsg::ImportIntoNewCollection => {
+                if let Some(BrowserDialog::AddCollection {
+                    server_index,
+                    ref tenant,
+                    ref database,
+                    ref name,
+                    ref options,
+                    ref mut error,
+                }) = self.browser.dialog
+                {
+                    match options.build() {
+                        Ok(create_options) => {
+                            // The ID is only known once the import creates the collection
+                            let target = CollectionSource {
+                                server: self.config.servers[server_index].clone(),
+                                tenant: tenant.clone(),
+                                database: database.clone(),
+                                collection_id: String::new(),
+                                collection_name: name.clone(),
+                            };
+                            self.import =
+                                Some(ImportForm::new(server_index, target, Some(create_options)));
+                            self.browser.dialog = None;
+                        }
+                        Err(e) => *error = Some(e),
+                    }
+                }
             }
 
             BrowserMsg::RequestDeleteCollection {
@@ -2021,21 +3419,12 @@ impl AppModel {
                 collection_id,
                 collection_name: _,
             } => {
-                let config = &self.config.servers[server_index];
-                let url = config.server_url.clone();
-                let token = config.auth_token.clone();
-                let auth_header_type = config.auth_header_type.clone();
+                let server = self.config.servers[server_index].clone();
 
                 return cosmic::task::future(async move {
-                    let result = helpers::delete_collection(
-                        &url,
-                        &token,
-                        &auth_header_type,
-                        &collection_id,
-                        &tenant,
-                        &database,
-                    )
-                    .await;
+                    let result =
+                        helpers::delete_collection(&server, &collection_id, &tenant, &database)
+                            .await;
                     cosmic::Action::App(Message::Browser(BrowserMsg::CollectionDeleted {
                         server_index,
                         tenant,
@@ -2052,16 +3441,11 @@ impl AppModel {
                 collection_id,
                 document_id,
             } => {
-                let config = &self.config.servers[server_index];
-                let url = config.server_url.clone();
-                let token = config.auth_token.clone();
-                let auth_header_type = config.auth_header_type.clone();
+                let server = self.config.servers[server_index].clone();
 
                 return cosmic::task::future(async move {
                     let result = helpers::delete_document(
-                        &url,
-                        &token,
-                        &auth_header_type,
+                        &server,
                         &collection_id,
                         &document_id,
                         &tenant,
@@ -2086,10 +3470,7 @@ impl AppModel {
                 Ok(()) => {
                     // Refresh databases list
                     self.browser.set_databases_loading(server_index, &tenant);
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
+                    let server = self.config.servers[server_index].clone();
 
                     self.notification_id_counter += 1;
                     self.notifications.push(Notification {
@@ -2100,9 +3481,7 @@ impl AppModel {
                     });
 
                     return cosmic::task::future(async move {
-                        let result =
-                            helpers::fetch_databases(&url, &token, &auth_header_type, &tenant)
-                                .await;
+                        let result = helpers::fetch_databases(&server, &tenant).await;
                         cosmic::Action::App(Message::Browser(BrowserMsg::DatabasesLoaded {
                             server_index,
                             tenant,
@@ -2116,7 +3495,7 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: "Failed to delete database".to_string(),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
@@ -2131,10 +3510,7 @@ impl AppModel {
                     // Refresh collections list
                     self.browser
                         .set_collections_loading(server_index, &tenant, &database);
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
+                    let server = self.config.servers[server_index].clone();
 
                     self.notification_id_counter += 1;
                     self.notifications.push(Notification {
@@ -2145,14 +3521,7 @@ impl AppModel {
                     });
 
                     return cosmic::task::future(async move {
-                        let result = helpers::fetch_collections(
-                            &url,
-                            &token,
-                            &auth_header_type,
-                            &tenant,
-                            &database,
-                        )
-                        .await;
+                        let result = helpers::fetch_collections(&server, &tenant, &database).await;
                         cosmic::Action::App(Message::Browser(BrowserMsg::CollectionsLoaded {
                             server_index,
                             tenant,
@@ -2167,7 +3536,7 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: "Failed to delete collection".to_string(),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
@@ -2180,18 +3549,6 @@ impl AppModel {
                 result,
             } => match result {
                 Ok(()) => {
-                    // Refresh documents list
-                    self.browser.set_documents_loading(
-                        server_index,
-                        &tenant,
-                        &database,
-                        &collection_id,
-                    );
-                    let config = &self.config.servers[server_index];
-                    let url = config.server_url.clone();
-                    let token = config.auth_token.clone();
-                    let auth_header_type = config.auth_header_type.clone();
-
                     self.notification_id_counter += 1;
                     self.notifications.push(Notification {
                         id: self.notification_id_counter,
@@ -2200,25 +3557,12 @@ impl AppModel {
                         message: "Document has been deleted successfully.".to_string(),
                     });
 
-                    return cosmic::task::future(async move {
-                        let result = helpers::fetch_documents(
-                            &url,
-                            &token,
-                            &auth_header_type,
-                            &collection_id,
-                            &tenant,
-                            &database,
-                            100,
-                            0,
-                        )
-                        .await;
-                        cosmic::Action::App(Message::Browser(BrowserMsg::DocumentsLoaded {
-                            server_index,
-                            tenant,
-                            database,
-                            collection_id,
-                            result,
-                        }))
+                    // Refresh documents list
+                    return self.fetch_browser_documents(CollectionRef {
+                        server_index,
+                        tenant,
+                        database,
+                        collection_id,
                     });
                 }
                 Err(e) => {
@@ -2227,12 +3571,203 @@ impl AppModel {
                         id: self.notification_id_counter,
                         level: NotificationLevel::Error,
                         title: "Failed to delete document".to_string(),
-                        message: e,
+                        message: e.to_string(),
                     });
                 }
             },
 
             // Menu actions
+            BrowserMsg::DocumentDetailsLoaded {
+                document_id,
+                result,
+            } => {
+                // Ignore results for a document that is no longer previewed
+                let is_current = self
+                    .browser
+                    .selected_document
+                    .as_ref()
+                    .is_some_and(|doc| doc.id == document_id);
+                if is_current {
+                    self.browser.embedding_loading = false;
+                    match result {
+                        Ok(Some(document)) => self.browser.selected_document = Some(document),
+                        Ok(None) => {
+                            self.browser.embedding_error =
+                                Some("Document no longer exists".to_string());
+                        }
+                        Err(e) => self.browser.embedding_error = Some(e.to_string()),
+                    }
+                }
+            }
+
+            BrowserMsg::FindSimilar => {
+                if !self.browser.similar_loading {
+                    return self.find_similar_documents();
+                }
+            }
+
+            BrowserMsg::SimilarLoaded {
+                document_id,
+                result,
+            } => {
+                // Ignore results for a document that is no longer previewed
+                let is_current = self
+                    .browser
+                    .selected_document
+                    .as_ref()
+                    .is_some_and(|doc| doc.id == document_id);
+                if is_current && self.browser.similar_loading {
+                    self.browser.similar_loading = false;
+                    self.browser.similar = Some(
+                        result
+                            .map(|matches| {
+                                matches
+                                    .into_iter()
+                                    .filter(|found| found.document.id != document_id)
+                                    .take(pages::browser::SIMILAR_COUNT)
+                                    .collect()
+                            })
+                            .map_err(|e| e.to_string()),
+                    );
+                }
+            }
+
+            BrowserMsg::CopyToClipboard(text) => {
+                return cosmic::task::future(async move {
+                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                        let _ = clipboard.set_text(&text);
+                    }
+                    cosmic::Action::App(Message::AddNotification(
+                        NotificationLevel::Success,
+                        fl!("notification-copied"),
+                        String::new(),
+                    ))
+                });
+            }
+
+            BrowserMsg::DocumentForm(form_msg) => {
+                if let Some(BrowserDialog::AddDocument { ref mut form, .. }) = self.browser.dialog {
+                    form.update(form_msg);
+                } else if let Some(ref mut form) = self.browser.editing_document {
+                    form.update(form_msg);
+                }
+            }
+
+            BrowserMsg::StartEditDocument => {
+                self.browser.editing_document = self
+                    .browser
+                    .selected_document
+                    .as_ref()
+                    .map(DocumentForm::edit);
+            }
+
+            BrowserMsg::CancelEditDocument => {
+                self.browser.editing_document = None;
+            }
+
+            BrowserMsg::SaveDocumentEdit => {
+                let Some(collection) = self.browser.preview_collection.clone() else {
+                    return Task::none();
+                };
+                if let Some(ref mut form) = self.browser.editing_document {
+                    match form.build_update() {
+                        Ok(document) => {
+                            form.saving = true;
+                            return self.save_document(collection, document, SaveMode::Update);
+                        }
+                        Err(e) => form.error = Some(e),
+                    }
+                }
+            }
+
+            BrowserMsg::DocumentSaved {
+                collection,
+                document_id,
+                result,
+            } => match result {
+                Ok(()) => {
+                    if matches!(self.browser.dialog, Some(BrowserDialog::AddDocument { .. })) {
+                        self.browser.dialog = None;
+                    }
+                    self.browser.editing_document = None;
+
+                    self.notification_id_counter += 1;
+                    self.notifications.push(Notification {
+                        id: self.notification_id_counter,
+                        level: NotificationLevel::Success,
+                        title: "Document saved".to_string(),
+                        message: format!("Document '{}' has been saved.", document_id),
+                    });
+
+                    // Refresh the documents column, and the preview if it shows this document
+                    let mut tasks = vec![self.fetch_browser_documents(collection.clone())];
+                    let is_previewed = self
+                        .browser
+                        .selected_document
+                        .as_ref()
+                        .is_some_and(|doc| doc.id == document_id);
+                    if is_previewed {
+                        tasks.push(self.fetch_document_preview(collection, document_id));
+                    }
+                    return cosmic::task::batch(tasks);
+                }
+                Err(e) => {
+                    // Show the error next to the form that was saved
+                    if let Some(BrowserDialog::AddDocument { ref mut form, .. }) =
+                        self.browser.dialog
+                    {
+                        form.saving = false;
+                        form.error = Some(e.to_string());
+                    } else if let Some(ref mut form) = self.browser.editing_document {
+                        form.saving = false;
+                        form.error = Some(e.to_string());
+                    }
+                }
+            },
+
+            BrowserMsg::OpenFilter(target) => {
+                self.browser.set_filter_target(target.clone());
+                self.browser.show_filter = true;
+                self.browser.open_menu = None;
+
+                // Seed the known keys from documents already in the column
+                let cache_key = format!(
+                    "{}:{}:{}:{}",
+                    target.server_index, target.tenant, target.database, target.collection_id
+                );
+                if let Some(documents) = self.browser.documents_cache.get(&cache_key) {
+                    self.browser.filter.add_keys_from(documents);
+                }
+
+                if self.browser.filter.match_count == MatchCount::Unknown {
+                    self.browser.filter.match_count = MatchCount::Counting;
+                    return self.count_browser_documents(target);
+                }
+            }
+
+            BrowserMsg::CloseFilter => {
+                self.browser.show_filter = false;
+            }
+
+            BrowserMsg::Filter(filter_msg) => {
+                if self.browser.filter.update(filter_msg).is_some() {
+                    if let Some(target) = self.browser.filter_target.clone() {
+                        let docs_task = self.fetch_browser_documents(target.clone());
+                        let count_task = self.count_browser_documents(target);
+                        return cosmic::task::batch(vec![docs_task, count_task]);
+                    }
+                }
+            }
+
+            BrowserMsg::FilterCountLoaded { target, result } => {
+                // Ignore counts for a collection that is no longer being filtered
+                if self.browser.filter_target.as_ref() == Some(&target) {
+                    self.browser
+                        .filter
+                        .set_match_count(result.map_err(|e| e.to_string()));
+                }
+            }
+
             BrowserMsg::ToggleMenu(menu_id) => {
                 // Toggle menu: if same menu is open, close it; otherwise open the new one
                 if self.browser.open_menu.as_ref() == Some(&menu_id) {
@@ -2250,11 +3785,45 @@ impl AppModel {
     }
 }
 
+/// Asks where to save a file through the desktop portal; `None` if the user cancelled.
+async fn choose_save_file(title: &str, file_name: String) -> Result<Option<PathBuf>, String> {
+    let dialog = file_chooser::save::Dialog::new()
+        .title(title)
+        .file_name(file_name);
+    match dialog.save_file().await {
+        Ok(response) => Ok(response.url().and_then(|url| url.to_file_path().ok())),
+        Err(file_chooser::Error::Cancelled) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Asks the desktop for a file, or a folder if `folder` is set; `None` if the dialog was cancelled.
+async fn choose_open_path(title: &str, folder: bool) -> Result<Option<PathBuf>, String> {
+    let dialog = file_chooser::open::Dialog::new().title(title);
+    let response = if folder {
+        dialog.open_folder().await
+    } else {
+        dialog.open_file().await
+    };
+    match response {
+        Ok(response) => Ok(response.url().to_file_path().ok()),
+        Err(file_chooser::Error::Cancelled) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses a timeout in whole seconds; zero is rejected as it would fail every request.
+fn parse_timeout(input: &str) -> Option<u64> {
+    input.trim().parse().ok().filter(|secs| *secs > 0)
+}
+
 /// The page to display in the application.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Page {
     #[default]
     Browser,
+    Compare,
+    Visualize,
     Dashboard,
     Collections,
     Settings,
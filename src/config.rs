@@ -1,26 +1,99 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use crate::api::{AuthMethod, ConnectionOptions};
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Unique identifier of the application in RDNN (reverse domain name
+/// notation) format, also naming its configuration and data directories
+pub const APP_ID: &str = "dev.mmurphy.Chromatic";
+
+/// Prefix of server URLs that point at a local Chroma persist directory
+const LOCAL_URL_PREFIX: &str = "file://";
 
 /// A single server configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(from = "StoredServerConfig")]
 pub struct ServerConfig {
     /// Display name for this configuration
     pub name: String,
     /// ChromaDB server URL (e.g., http://localhost:8000)
     pub server_url: String,
-    /// Authentication token for the ChromaDB server
-    pub auth_token: String,
-    /// Authentication header type: "authorization" (Bearer) or "x-chroma-token"
-    pub auth_header_type: String,
+    /// How requests authenticate with the server
+    pub auth: AuthMethod,
+    /// TLS and proxy settings
+    pub connection: ConnectionOptions,
     /// Tenant name (default: default_tenant)
     pub tenant: String,
     /// Database name (default: default_database)
     pub database: String,
     /// List of known tenants for this server (stored locally)
-    #[serde(default)]
     pub tenants: Vec<String>,
+    /// Seconds allowed to connect to the server
+    pub connect_timeout_secs: u64,
+    /// Seconds allowed for a whole request
+    pub request_timeout_secs: u64,
+    /// How often idempotent requests are retried after a transient failure
+    pub max_retries: u32,
+}
+
+/// `ServerConfig` as stored, which may come from a version that kept a token
+/// and header type instead of `auth`
+#[derive(Deserialize)]
+struct StoredServerConfig {
+    name: String,
+    server_url: String,
+    #[serde(default)]
+    auth: Option<AuthMethod>,
+    #[serde(default)]
+    auth_token: String,
+    #[serde(default)]
+    auth_header_type: String,
+    #[serde(default)]
+    connection: ConnectionOptions,
+    tenant: String,
+    database: String,
+    #[serde(default)]
+    tenants: Vec<String>,
+    #[serde(default = "default_connect_timeout_secs")]
+    connect_timeout_secs: u64,
+    #[serde(default = "default_request_timeout_secs")]
+    request_timeout_secs: u64,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+}
+
+impl From<StoredServerConfig> for ServerConfig {
+    fn from(stored: StoredServerConfig) -> Self {
+        let auth = stored.auth.unwrap_or_else(|| {
+            AuthMethod::from_legacy(&stored.auth_token, &stored.auth_header_type)
+        });
+        Self {
+            name: stored.name,
+            server_url: stored.server_url,
+            auth,
+            connection: stored.connection,
+            tenant: stored.tenant,
+            database: stored.database,
+            tenants: stored.tenants,
+            connect_timeout_secs: stored.connect_timeout_secs,
+            request_timeout_secs: stored.request_timeout_secs,
+            max_retries: stored.max_retries,
+        }
+    }
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_retries() -> u32 {
+    3
 }
 
 impl Default for ServerConfig {
@@ -28,11 +101,14 @@ impl Default for ServerConfig {
         Self {
             name: String::from("Local Server"),
             server_url: String::from("http://localhost:8000"),
-            auth_token: String::new(),
-            auth_header_type: String::from("authorization"),
+            auth: AuthMethod::None,
+            connection: ConnectionOptions::default(),
             tenant: String::from("default_tenant"),
             database: String::from("default_database"),
             tenants: Vec::new(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            max_retries: default_max_retries(),
         }
     }
 }
@@ -45,6 +121,27 @@ impl ServerConfig {
         }
     }
 
+    /// A configuration that opens a Chroma persist directory directly,
+    /// named after the directory
+    pub fn local(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self {
+            server_url: format!("{}{}", LOCAL_URL_PREFIX, path.display()),
+            ..Self::new(&name)
+        }
+    }
+
+    /// The persist directory, if this configuration reads one instead of
+    /// connecting to a server
+    pub fn local_path(&self) -> Option<PathBuf> {
+        self.server_url
+            .strip_prefix(LOCAL_URL_PREFIX)
+            .map(PathBuf::from)
+    }
+
     /// Add a tenant to the local list if not already present
     pub fn add_tenant(&mut self, tenant: &str) -> bool {
         if !self.tenants.contains(&tenant.to_string()) {
@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Storage for the secrets used to authenticate with servers.
+//!
+//! Secret fields of the server settings hold either a reference that is
+//! resolved when connecting, or the secret itself as written by older versions:
+//!
+//! - `env:NAME` reads an environment variable
+//! - `file:/path/to/secret` reads a file, ignoring surrounding whitespace
+//! - `vault:ID` looks up an entry of the encrypted vault
+//!
+//! The vault is a local file encrypted with XChaCha20-Poly1305 under a key
+//! derived from a passphrase with Argon2id. Once unlocked it stays in memory
+//! for the session, and plain secrets are moved into it when settings are saved.
+
+use crate::api::AuthMethod;
+use crate::config::{APP_ID, ServerConfig};
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+/// File format version written to new vaults
+const VAULT_VERSION: u32 = 1;
+
+/// Length of the Argon2 salt in bytes
+const SALT_LEN: usize = 16;
+
+/// The unlocked vault, if any
+static SESSION: LazyLock<Mutex<Option<Vault>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialError {
+    /// A `vault:` reference was used while the vault is locked
+    VaultLocked,
+    /// The passphrase does not decrypt the vault
+    WrongPassphrase,
+    /// A vault was to be created where one already exists
+    VaultExists,
+    /// Neither `XDG_DATA_HOME` nor `HOME` says where to keep the vault
+    NoDataDirectory,
+    /// The vault has no entry for a reference
+    MissingEntry(String),
+    /// An `env:` reference names an unset variable
+    MissingEnv(String),
+    /// A `file:` reference could not be read
+    File { path: String, message: String },
+    /// The vault file could not be read or written
+    Io(String),
+    /// The vault file is damaged or from a newer version
+    Corrupt(String),
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::VaultLocked => {
+                write!(f, "the credential vault is locked; unlock it in Settings")
+            }
+            CredentialError::WrongPassphrase => write!(f, "wrong vault passphrase"),
+            CredentialError::VaultExists => write!(f, "a credential vault already exists"),
+            CredentialError::NoDataDirectory => {
+                write!(f, "no directory to keep the vault in; set HOME")
+            }
+            CredentialError::MissingEntry(id) => write!(f, "no vault entry {}", id),
+            CredentialError::MissingEnv(name) => {
+                write!(f, "environment variable {} is not set", name)
+            }
+            CredentialError::File { path, message } => {
+                write!(f, "could not read {}: {}", path, message)
+            }
+            CredentialError::Io(msg) => write!(f, "vault file error: {}", msg),
+            CredentialError::Corrupt(msg) => write!(f, "damaged vault file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
+/// Where a secret comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef<'a> {
+    /// The secret itself, stored in the settings
+    Plain(&'a str),
+    Env(&'a str),
+    File(&'a str),
+    Vault(&'a str),
+}
+
+impl<'a> SecretRef<'a> {
+    pub fn parse(value: &'a str) -> Self {
+        if let Some(name) = value.strip_prefix("env:") {
+            SecretRef::Env(name.trim())
+        } else if let Some(path) = value.strip_prefix("file:") {
+            SecretRef::File(path.trim())
+        } else if let Some(id) = value.strip_prefix("vault:") {
+            SecretRef::Vault(id.trim())
+        } else {
+            SecretRef::Plain(value)
+        }
+    }
+}
+
+/// Whether the vault exists and is unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultState {
+    /// No vault file has been created yet
+    Missing,
+    Locked,
+    Unlocked,
+}
+
+/// Contents of the vault file
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An unlocked vault
+pub struct Vault {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    cipher: XChaCha20Poly1305,
+    secrets: BTreeMap<String, String>,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("entries", &self.secrets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Vault {
+    /// Location of the vault file, next to the other data of the application
+    pub fn default_path() -> Result<PathBuf, CredentialError> {
+        let data_dir = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .ok_or(CredentialError::NoDataDirectory)?;
+        Ok(data_dir.join(APP_ID).join("vault.json"))
+    }
+
+    /// Create an empty vault protected by a passphrase
+    pub fn create(path: &Path, passphrase: &str) -> Result<Self, CredentialError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let vault = Self {
+            path: path.to_path_buf(),
+            salt,
+            cipher: derive_cipher(passphrase, &salt)?,
+            secrets: BTreeMap::new(),
+        };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    /// Decrypt an existing vault
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self, CredentialError> {
+        let raw = std::fs::read(path).map_err(|e| CredentialError::Io(e.to_string()))?;
+        let file: VaultFile =
+            serde_json::from_slice(&raw).map_err(|e| CredentialError::Corrupt(e.to_string()))?;
+        if file.version > VAULT_VERSION {
+            return Err(CredentialError::Corrupt(format!(
+                "version {} is not supported",
+                file.version
+            )));
+        }
+
+        let salt: [u8; SALT_LEN] = decode(&file.salt)?
+            .try_into()
+            .map_err(|_| CredentialError::Corrupt("invalid salt".to_string()))?;
+        let nonce = decode(&file.nonce)?;
+        if nonce.len() != 24 {
+            return Err(CredentialError::Corrupt("invalid nonce".to_string()));
+        }
+
+        let cipher = derive_cipher(passphrase, &salt)?;
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                decode(&file.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| CredentialError::WrongPassphrase)?;
+        let secrets = serde_json::from_slice(&plaintext)
+            .map_err(|e| CredentialError::Corrupt(e.to_string()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            salt,
+            cipher,
+            secrets,
+        })
+    }
+
+    /// Encrypt the entries with a fresh nonce and replace the vault file
+    fn save(&self) -> Result<(), CredentialError> {
+        let plaintext =
+            serde_json::to_vec(&self.secrets).map_err(|e| CredentialError::Io(e.to_string()))?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|e| CredentialError::Io(e.to_string()))?;
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            salt: BASE64.encode(self.salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        let json =
+            serde_json::to_vec_pretty(&file).map_err(|e| CredentialError::Io(e.to_string()))?;
+
+        let io_error = |e: std::io::Error| CredentialError::Io(e.to_string());
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        // Write next to the vault and rename, so a failed write keeps the old file
+        let partial = self.path.with_extension("json.partial");
+        write_private(&partial, &json).map_err(io_error)?;
+        std::fs::rename(&partial, &self.path).map_err(io_error)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.secrets.get(id).map(String::as_str)
+    }
+
+    /// Add a secret under a new ID without saving, returning the reference to it
+    fn insert(&mut self, secret: &str) -> String {
+        let id = loop {
+            let id = format!("{:08x}", OsRng.next_u32());
+            if !self.secrets.contains_key(&id) {
+                break id;
+            }
+        };
+        self.secrets.insert(id.clone(), secret.to_string());
+        format!("vault:{}", id)
+    }
+
+    /// Remove entries that are no longer referenced
+    pub fn remove(&mut self, ids: &[&str]) -> Result<(), CredentialError> {
+        let before = self.secrets.len();
+        for id in ids {
+            self.secrets.remove(*id);
+        }
+        if self.secrets.len() == before {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    /// Move the plain secrets of the servers into the vault, returning how many moved.
+    /// The vault is saved once, and nothing changes if that fails.
+    pub fn migrate(&mut self, servers: &mut [ServerConfig]) -> Result<usize, CredentialError> {
+        let saved = self.secrets.clone();
+        let mut migrated = servers.to_vec();
+        let mut moved = 0;
+        for server in &mut migrated {
+            for secret in server.auth.secrets_mut() {
+                if is_plain(secret) {
+                    *secret = self.insert(secret);
+                    moved += 1;
+                }
+            }
+        }
+        if moved == 0 {
+            return Ok(0);
+        }
+        if let Err(e) = self.save() {
+            self.secrets = saved;
+            return Err(e);
+        }
+        servers.clone_from_slice(&migrated);
+        Ok(moved)
+    }
+}
+
+/// Whether a secret field holds the secret itself
+fn is_plain(value: &str) -> bool {
+    !value.is_empty() && matches!(SecretRef::parse(value), SecretRef::Plain(_))
+}
+
+/// Resolve a secret field to the secret itself
+fn resolve(vault: Option<&Vault>, value: &str) -> Result<String, CredentialError> {
+    match SecretRef::parse(value) {
+        SecretRef::Plain(secret) => Ok(secret.to_string()),
+        SecretRef::Env(name) => {
+            std::env::var(name).map_err(|_| CredentialError::MissingEnv(name.to_string()))
+        }
+        SecretRef::File(path) => std::fs::read_to_string(path)
+            .map(|secret| secret.trim().to_string())
+            .map_err(|e| CredentialError::File {
+                path: path.to_string(),
+                message: e.to_string(),
+            }),
+        SecretRef::Vault(id) => vault
+            .ok_or(CredentialError::VaultLocked)?
+            .get(id)
+            .map(str::to_string)
+            .ok_or_else(|| CredentialError::MissingEntry(id.to_string())),
+    }
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, CredentialError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CredentialError::Corrupt(e.to_string()))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, CredentialError> {
+    BASE64
+        .decode(value)
+        .map_err(|e| CredentialError::Corrupt(e.to_string()))
+}
+
+/// Write a file only the current user can read
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents)
+}
+
+fn session() -> MutexGuard<'static, Option<Vault>> {
+    SESSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the vault exists and is unlocked in this session
+pub fn vault_state() -> VaultState {
+    if session().is_some() {
+        VaultState::Unlocked
+    } else if Vault::default_path().is_ok_and(|path| path.exists()) {
+        VaultState::Locked
+    } else {
+        VaultState::Missing
+    }
+}
+
+/// Create the vault and unlock it for this session. An existing vault is
+/// never replaced. Deriving the key is deliberately slow, so call this off
+/// the UI thread.
+pub fn create_vault(passphrase: &str) -> Result<(), CredentialError> {
+    let path = Vault::default_path()?;
+    if path.exists() {
+        return Err(CredentialError::VaultExists);
+    }
+    *session() = Some(Vault::create(&path, passphrase)?);
+    Ok(())
+}
+
+/// Unlock the existing vault for this session.
+/// Deriving the key is deliberately slow, so call this off the UI thread.
+pub fn unlock(passphrase: &str) -> Result<(), CredentialError> {
+    let vault = Vault::open(&Vault::default_path()?, passphrase)?;
+    *session() = Some(vault);
+    Ok(())
+}
+
+/// Forget the vault key until it is unlocked again
+pub fn lock() {
+    *session() = None;
+}
+
+/// Replace every secret reference of an authentication method with the secret
+pub fn resolve_auth(auth: &AuthMethod) -> Result<AuthMethod, CredentialError> {
+    let session = session();
+    let mut resolved = auth.clone();
+    for secret in resolved.secrets_mut() {
+        *secret = resolve(session.as_ref(), secret)?;
+    }
+    Ok(resolved)
+}
+
+/// Move plain secrets into the vault if it is unlocked.
+/// Returns how many were moved, or `None` if the vault is locked.
+pub fn migrate_plain_secrets(
+    servers: &mut [ServerConfig],
+) -> Option<Result<usize, CredentialError>> {
+    session().as_mut().map(|vault| vault.migrate(servers))
+}
+
+/// Whether any server keeps a secret in the settings file
+pub fn has_plain_secrets(servers: &[ServerConfig]) -> bool {
+    servers
+        .iter()
+        .any(|server| server.auth.secrets().into_iter().any(is_plain))
+}
+
+/// Drop the vault entries `old` referenced that `new` no longer does
+pub fn forget_replaced(old: &AuthMethod, new: &AuthMethod) {
+    let kept = new.secrets();
+    let unused: Vec<&str> = old
+        .secrets()
+        .into_iter()
+        .filter(|secret| !kept.contains(secret))
+        .filter_map(|secret| match SecretRef::parse(secret) {
+            SecretRef::Vault(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(vault) = session().as_mut()
+        && let Err(e) = vault.remove(&unused)
+    {
+        eprintln!("Failed to remove unused vault entries: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("chromatic-vault-{}.json", std::process::id()));
+        let mut vault = Vault::create(&path, "correct horse").unwrap();
+
+        let mut servers = vec![ServerConfig {
+            auth: AuthMethod::Bearer {
+                token: "plain-token".to_string(),
+            },
+            ..ServerConfig::default()
+        }];
+        assert_eq!(vault.migrate(&mut servers), Ok(1));
+        let token = servers[0].auth.secrets()[0].to_string();
+        assert!(token.starts_with("vault:"));
+        // Already migrated secrets are left alone
+        assert_eq!(vault.migrate(&mut servers), Ok(0));
+
+        let reopened = Vault::open(&path, "correct horse").unwrap();
+        assert_eq!(resolve(Some(&reopened), &token).unwrap(), "plain-token");
+        assert_eq!(resolve(None, &token), Err(CredentialError::VaultLocked));
+        assert_eq!(
+            Vault::open(&path, "wrong").unwrap_err(),
+            CredentialError::WrongPassphrase
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_failed_migration_changes_nothing() {
+        let path = std::env::temp_dir().join(format!(
+            "chromatic-vault-unsaved-{}.json",
+            std::process::id()
+        ));
+        let mut vault = Vault::create(&path, "correct horse").unwrap();
+        // A file cannot be the parent of the vault
+        vault.path = path.join("vault.json");
+
+        let mut servers = vec![ServerConfig {
+            auth: AuthMethod::Basic {
+                username: "user".to_string(),
+                password: "plain-password".to_string(),
+            },
+            ..ServerConfig::default()
+        }];
+        let before = servers.clone();
+        assert!(matches!(
+            vault.migrate(&mut servers),
+            Err(CredentialError::Io(_))
+        ));
+        assert_eq!(servers, before);
+        assert!(vault.secrets.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        assert_eq!(resolve(None, "literal").unwrap(), "literal");
+        assert_eq!(
+            resolve(None, "env:CHROMATIC_TEST_UNSET_VARIABLE"),
+            Err(CredentialError::MissingEnv(
+                "CHROMATIC_TEST_UNSET_VARIABLE".to_string()
+            ))
+        );
+
+        let path = std::env::temp_dir().join(format!("chromatic-secret-{}", std::process::id()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        let reference = format!("file:{}", path.display());
+        assert_eq!(resolve(None, &reference).unwrap(), "from-file");
+        let _ = std::fs::remove_file(&path);
+    }
+}
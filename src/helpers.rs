@@ -1,54 +1,44 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! Async helper functions for the Chromatic application.
-//! These functions handle ChromaDB API interactions.
+//! These functions open the backend of a server and forward to it.
 
-use crate::api::{ChromaClient, Collection, Document, ServerInfo};
+use crate::api::{
+    CancelToken, ChromaError, ClientRegistry, Collection, CreateCollectionOptions, Document,
+    DocumentFilter, Identity, Include, ModifyCollectionRequest, QueryMatch, QueryRequest,
+    ServerInfo, StreamOptions,
+};
+use crate::config::ServerConfig;
+use crate::store::{self, VectorStore};
+use futures_util::StreamExt;
 
-/// Helper to create a client with auto-detected API version
-pub async fn create_client(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
-) -> Result<ChromaClient, String> {
-    let api_version = ChromaClient::detect_api_version(url, token, auth_header_type)
-        .await
-        .map_err(|e| e.to_string())?;
-    ChromaClient::new(url, token, auth_header_type, api_version).map_err(|e| e.to_string())
-}
+/// Evenly spaced stretches a sample of a large collection is read from
+const SAMPLE_WINDOWS: usize = 10;
 
 /// Test connection to ChromaDB server
-pub async fn test_connection(url: &str, token: &str, auth_header_type: &str) -> Result<(), String> {
-    // Just detect API version - if it succeeds, connection works
-    let _api_version = ChromaClient::detect_api_version(url, token, auth_header_type)
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn test_connection(server: &ServerConfig) -> Result<(), ChromaError> {
+    // Connect afresh - if the API version can be detected, the connection works
+    store::reopen(server).await?;
     Ok(())
 }
 
 /// Fetch server information
-pub async fn fetch_server_info(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
-) -> Result<ServerInfo, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client.get_server_info().await.map_err(|e| e.to_string())
+pub async fn fetch_server_info(server: &ServerConfig) -> Result<ServerInfo, ChromaError> {
+    let store = store::open(server).await?;
+    store.server_info().await
 }
 
 /// Validate tenant and database, returning (tenant_exists, database_exists) on failure
 pub async fn validate_tenant_database(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     tenant: &str,
     database: &str,
 ) -> Result<(), (bool, bool)> {
-    let client = create_client(url, token, auth_header_type)
+    let store = store::open(server).await.map_err(|_| (false, false))?;
+    let (tenant_exists, database_exists) = store
+        .tenant_database_status(tenant, database)
         .await
         .map_err(|_| (false, false))?;
-    let (tenant_exists, database_exists) =
-        client.check_tenant_database_status(tenant, database).await;
     if tenant_exists && database_exists {
         Ok(())
     } else {
@@ -58,30 +48,22 @@ pub async fn validate_tenant_database(
 
 /// Create missing tenant and/or database
 pub async fn create_missing_resources(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     tenant: &str,
     database: &str,
     tenant_exists: bool,
     database_exists: bool,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
 
     // Create tenant if needed
     if !tenant_exists {
-        client
-            .create_tenant(tenant)
-            .await
-            .map_err(|e| e.to_string())?;
+        store.create_tenant(tenant).await?;
     }
 
     // Create database if needed
     if !database_exists {
-        client
-            .create_database(tenant, database)
-            .await
-            .map_err(|e| e.to_string())?;
+        store.create_database(tenant, database).await?;
     }
 
     Ok(())
@@ -89,181 +71,522 @@ pub async fn create_missing_resources(
 
 /// Fetch available databases for a tenant
 pub async fn fetch_databases(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     tenant: &str,
-) -> Result<Vec<String>, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    let databases = client
-        .list_databases(tenant)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(databases.into_iter().map(|db| db.name).collect())
+) -> Result<Vec<String>, ChromaError> {
+    let store = store::open(server).await?;
+    store.list_databases(tenant).await
+}
+
+/// Fetch available tenants, or an empty list if the server can't list them
+pub async fn fetch_tenants(server: &ServerConfig) -> Result<Vec<String>, ChromaError> {
+    let store = store::open(server).await?;
+    let registry = ClientRegistry::global();
+    if registry.capabilities(server).list_tenants == Some(false) {
+        return Ok(identity_tenants(store.as_ref()).await);
+    }
+
+    let tenants = store.list_tenants().await?;
+    registry.update_capabilities(server, |capabilities| {
+        capabilities.list_tenants = Some(tenants.is_some());
+    });
+    let Some(tenants) = tenants else {
+        return Ok(identity_tenants(store.as_ref()).await);
+    };
+    Ok(tenants)
 }
 
-/// Fetch available tenants
-pub async fn fetch_tenants(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
-) -> Result<Vec<String>, String> {
-    eprintln!("[DEBUG] fetch_tenants: Creating client for url={}", url);
-    let client = create_client(url, token, auth_header_type).await?;
-    eprintln!("[DEBUG] fetch_tenants: Client created, calling list_tenants...");
-    let tenants = client.list_tenants().await.map_err(|e| {
-        eprintln!("[DEBUG] fetch_tenants: Error from list_tenants: {}", e);
-        e.to_string()
-    })?;
-    eprintln!("[DEBUG] fetch_tenants: Got {} tenants", tenants.len());
-    Ok(tenants.into_iter().map(|t| t.name).collect())
+/// The caller's own tenant, for servers that don't allow listing tenants.
+/// Empty if the server can't tell either.
+async fn identity_tenants(store: &dyn VectorStore) -> Vec<String> {
+    let identity = store.identity().await.ok().flatten();
+    identity
+        .map(|identity| identity.tenant)
+        .filter(|tenant| !tenant.is_empty())
+        .into_iter()
+        .collect()
+}
+
+/// Fetch the tenant and databases of the authenticated user, if the server reports them
+pub async fn fetch_identity(server: &ServerConfig) -> Result<Option<Identity>, ChromaError> {
+    let store = store::open(server).await?;
+    store.identity().await
 }
 
 /// Fetch collections from the server
 pub async fn fetch_collections(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     tenant: &str,
     database: &str,
-) -> Result<Vec<Collection>, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .list_collections(tenant, database)
-        .await
-        .map_err(|e| e.to_string())
+) -> Result<Vec<Collection>, ChromaError> {
+    let store = store::open(server).await?;
+    store.list_collections(tenant, database).await
 }
 
-/// Fetch documents from a collection with pagination
+/// Fetch documents from a collection with pagination and an optional filter
 pub async fn fetch_documents(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     collection_id: &str,
     tenant: &str,
     database: &str,
+    filter: &DocumentFilter,
     limit: usize,
     offset: usize,
-) -> Result<Vec<Document>, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .get_documents(collection_id, Some(limit), Some(offset), tenant, database)
+) -> Result<Vec<Document>, ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .get_documents(
+            collection_id,
+            Some(limit),
+            Some(offset),
+            filter,
+            tenant,
+            database,
+        )
         .await
-        .map_err(|e| e.to_string())
 }
 
-/// Fetch document count for a collection
+/// Fetch a single document with its embedding, URI and stored content
+pub async fn fetch_document_details(
+    server: &ServerConfig,
+    collection_id: &str,
+    document_id: &str,
+    tenant: &str,
+    database: &str,
+) -> Result<Option<Document>, ChromaError> {
+    let store = store::open(server).await?;
+    let documents = store
+        .get_documents_by_ids(
+            collection_id,
+            vec![document_id.to_string()],
+            vec![
+                Include::Documents,
+                Include::Metadatas,
+                Include::Embeddings,
+                Include::Uris,
+                Include::Data,
+            ],
+            tenant,
+            database,
+        )
+        .await?;
+    Ok(documents.into_iter().next())
+}
+
+/// Fetch document count for a collection, counting only matches if a filter is given
 pub async fn fetch_document_count(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     collection_id: &str,
     tenant: &str,
     database: &str,
-) -> Result<usize, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .count_documents(collection_id, tenant, database)
+    filter: &DocumentFilter,
+) -> Result<usize, ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .count_documents(collection_id, filter, tenant, database)
+        .await
+}
+
+/// Fetch up to `limit` documents with their embeddings, along with the number
+/// of documents in the collection. Larger collections are sampled from evenly
+/// spaced stretches rather than read from the start only.
+pub async fn sample_documents(
+    server: &ServerConfig,
+    collection_id: &str,
+    tenant: &str,
+    database: &str,
+    limit: usize,
+) -> Result<(Vec<Document>, usize), ChromaError> {
+    let store = store::open(server).await?;
+    let total = store
+        .count_documents(collection_id, &DocumentFilter::default(), tenant, database)
+        .await?;
+    let windows = if total > limit { SAMPLE_WINDOWS } else { 1 };
+    let per_window = limit.div_ceil(windows);
+
+    let mut documents = Vec::with_capacity(limit.min(total));
+    for window in 0..windows {
+        let options = StreamOptions {
+            page_size: per_window,
+            include: vec![Include::Documents, Include::Metadatas, Include::Embeddings],
+            offset: window * total / windows,
+            ..Default::default()
+        };
+        let mut pages =
+            store.stream_documents(collection_id, tenant, database, options, CancelToken::new());
+        let mut taken = 0;
+        while taken < per_window {
+            let Some(page) = pages.next().await else {
+                break;
+            };
+            let page = page?;
+            taken += page.documents.len();
+            documents.extend(page.documents);
+        }
+    }
+    documents.truncate(limit);
+    Ok((documents, total))
+}
+
+/// Query a collection for the nearest neighbours of the given embeddings
+pub async fn query_documents(
+    server: &ServerConfig,
+    collection_id: &str,
+    request: QueryRequest,
+    tenant: &str,
+    database: &str,
+) -> Result<Vec<Vec<QueryMatch>>, ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .query_documents(collection_id, &request, tenant, database)
         .await
-        .map_err(|e| e.to_string())
 }
 
 /// Create a new collection
 pub async fn create_collection(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     name: &str,
+    options: CreateCollectionOptions,
+    tenant: &str,
+    database: &str,
+) -> Result<Collection, ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .create_collection(name, &options, tenant, database)
+        .await
+}
+
+/// Rename a collection and/or replace its metadata
+pub async fn modify_collection(
+    server: &ServerConfig,
+    collection_id: &str,
+    request: ModifyCollectionRequest,
     tenant: &str,
     database: &str,
-) -> Result<Collection, String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .create_collection(name, tenant, database)
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .modify_collection(collection_id, &request, tenant, database)
         .await
-        .map_err(|e| e.to_string())
 }
 
 /// Delete a collection by name
 pub async fn delete_collection(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     collection_name: &str,
     tenant: &str,
     database: &str,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
         .delete_collection(collection_name, tenant, database)
         .await
-        .map_err(|e| e.to_string())
 }
 
 /// Delete a single document from a collection
 pub async fn delete_document(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     collection_id: &str,
     document_id: &str,
     tenant: &str,
     database: &str,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
         .delete_documents(
             collection_id,
-            vec![document_id.to_string()],
+            Some(vec![document_id.to_string()]),
+            &DocumentFilter::default(),
             tenant,
             database,
         )
-        .await
-        .map_err(|e| e.to_string())
+        .await?
+        .into_result()
+}
+
+/// Add new documents to a collection
+pub async fn add_documents(
+    server: &ServerConfig,
+    collection_id: &str,
+    documents: Vec<Document>,
+    tenant: &str,
+    database: &str,
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .add_documents(collection_id, &documents, tenant, database)
+        .await?
+        .into_result()
+}
+
+/// Add or replace documents in a collection
+pub async fn upsert_documents(
+    server: &ServerConfig,
+    collection_id: &str,
+    documents: Vec<Document>,
+    tenant: &str,
+    database: &str,
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .upsert_documents(collection_id, &documents, tenant, database)
+        .await?
+        .into_result()
+}
+
+/// Update existing documents in a collection
+pub async fn update_documents(
+    server: &ServerConfig,
+    collection_id: &str,
+    documents: Vec<Document>,
+    tenant: &str,
+    database: &str,
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store
+        .update_documents(collection_id, &documents, tenant, database)
+        .await?
+        .into_result()
 }
 
 /// Create a new tenant
-pub async fn create_tenant(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
-    name: &str,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .create_tenant(name)
-        .await
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+pub async fn create_tenant(server: &ServerConfig, name: &str) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store.create_tenant(name).await
 }
 
 /// Create a new database
 pub async fn create_database(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     name: &str,
     tenant: &str,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .create_database(tenant, name)
-        .await
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store.create_database(tenant, name).await
 }
 
 /// Delete a database
 pub async fn delete_database(
-    url: &str,
-    token: &str,
-    auth_header_type: &str,
+    server: &ServerConfig,
     name: &str,
     tenant: &str,
-) -> Result<(), String> {
-    let client = create_client(url, token, auth_header_type).await?;
-    client
-        .delete_database(tenant, name)
+) -> Result<(), ChromaError> {
+    let store = store::open(server).await?;
+    store.delete_database(tenant, name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{ApiVersion, DEFAULT_DATABASE, DEFAULT_TENANT, MockServer};
+    use serde_json::json;
+
+    const T: &str = DEFAULT_TENANT;
+    const D: &str = DEFAULT_DATABASE;
+
+    /// Settings for a mock server, without a client left over from an
+    /// earlier server on the same port
+    fn server_config(server: &MockServer) -> ServerConfig {
+        let config = ServerConfig {
+            server_url: server.url().to_string(),
+            max_retries: 0,
+            ..ServerConfig::new("mock")
+        };
+        ClientRegistry::global().invalidate(&config);
+        config
+    }
+
+    fn document(id: &str, text: &str, embedding: &[f32]) -> Document {
+        Document {
+            id: id.to_string(),
+            document: Some(text.to_string()),
+            metadata: serde_json::from_value(json!({ "source": "test" })).unwrap(),
+            embedding: Some(embedding.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_and_server_info() {
+        for api in [ApiVersion::V2, ApiVersion::V1] {
+            let server = MockServer::start(api).await;
+            let config = server_config(&server);
+            test_connection(&config).await.unwrap();
+            let info = fetch_server_info(&config).await.unwrap();
+            assert_eq!(info.api_version, api.as_str());
+        }
+
+        let server = MockServer::start(ApiVersion::V2).await;
+        server.require_token("secret");
+        let error = test_connection(&server_config(&server)).await.unwrap_err();
+        assert!(error.is_unauthorized());
+    }
+
+    #[tokio::test]
+    async fn test_tenants_and_databases() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let config = server_config(&server);
+
+        assert_eq!(
+            validate_tenant_database(&config, "acme", "docs").await,
+            Err((false, false))
+        );
+        create_missing_resources(&config, "acme", "docs", false, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            validate_tenant_database(&config, "acme", "docs").await,
+            Ok(())
+        );
+
+        create_database(&config, "wiki", "acme").await.unwrap();
+        delete_database(&config, "docs", "acme").await.unwrap();
+        assert_eq!(fetch_databases(&config, "acme").await.unwrap(), ["wiki"]);
+
+        // Without tenant listing, the caller's own tenant is offered
+        create_tenant(&config, "beta").await.unwrap();
+        assert!(fetch_tenants(&config).await.unwrap().is_empty());
+        server.set_identity(Identity {
+            user_id: "alice".to_string(),
+            tenant: "acme".to_string(),
+            databases: vec!["wiki".to_string()],
+        });
+        assert_eq!(fetch_tenants(&config).await.unwrap(), ["acme"]);
+        let identity = fetch_identity(&config).await.unwrap().unwrap();
+        assert_eq!(identity.databases, ["wiki"]);
+
+        let listing = MockServer::start(ApiVersion::V1).await;
+        listing.allow_list_tenants();
+        let config = server_config(&listing);
+        assert_eq!(fetch_tenants(&config).await.unwrap(), [T]);
+        assert!(fetch_identity(&config).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_collections_and_documents() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let config = server_config(&server);
+
+        let collection = create_collection(&config, "notes", Default::default(), T, D)
+            .await
+            .unwrap();
+        let id = collection.id;
+        let rename = ModifyCollectionRequest {
+            new_name: Some("memos".to_string()),
+            new_metadata: None,
+        };
+        modify_collection(&config, &id, rename, T, D).await.unwrap();
+        let collections = fetch_collections(&config, T, D).await.unwrap();
+        assert_eq!(collections[0].name, "memos");
+
+        let documents = vec![
+            document("a", "first", &[0.0, 1.0]),
+            document("b", "second", &[1.0, 0.0]),
+        ];
+        add_documents(&config, &id, documents, T, D).await.unwrap();
+        upsert_documents(
+            &config,
+            &id,
+            vec![document("c", "third", &[1.0, 1.0])],
+            T,
+            D,
+        )
+        .await
+        .unwrap();
+        update_documents(
+            &config,
+            &id,
+            vec![document("a", "changed", &[0.0, 1.0])],
+            T,
+            D,
+        )
         .await
-        .map_err(|e| e.to_string())
+        .unwrap();
+
+        let all = DocumentFilter::default();
+        let page = fetch_documents(&config, &id, T, D, &all, 2, 1)
+            .await
+            .unwrap();
+        let ids: Vec<_> = page.iter().map(|doc| doc.id.as_str()).collect();
+        assert_eq!(ids, ["b", "c"]);
+        assert_eq!(
+            fetch_document_count(&config, &id, T, D, &all)
+                .await
+                .unwrap(),
+            3
+        );
+
+        let details = fetch_document_details(&config, &id, "a", T, D)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(details.document.as_deref(), Some("changed"));
+        assert_eq!(details.embedding.as_deref(), Some(&[0.0, 1.0][..]));
+        assert!(
+            fetch_document_details(&config, &id, "missing", T, D)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let request = QueryRequest {
+            query_embeddings: vec![vec![1.0, 0.1]],
+            n_results: 1,
+            where_filter: None,
+            where_document: None,
+            include: Vec::new(),
+        };
+        let matches = query_documents(&config, &id, request, T, D).await.unwrap();
+        assert_eq!(matches[0][0].document.id, "b");
+
+        delete_document(&config, &id, "a", T, D).await.unwrap();
+        assert_eq!(server.ids(&id), ["b", "c"]);
+        delete_collection(&config, "memos", T, D).await.unwrap();
+        assert!(fetch_collections(&config, T, D).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_partially_failed_write() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        server.set_max_batch_size(Some(1));
+        let config = server_config(&server);
+        let id = server.add_collection("notes");
+        server.insert(&id, &[document("b", "existing", &[0.0])]);
+
+        let documents = vec![document("a", "new", &[0.0]), document("b", "clash", &[0.0])];
+        let error = add_documents(&config, &id, documents, T, D)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ChromaError::PartialFailure {
+                failed: 1,
+                total: 2,
+                ..
+            }
+        ));
+        assert_eq!(server.ids(&id), ["b", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_sample_documents() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let config = server_config(&server);
+        let id = server.add_collection("notes");
+        let documents: Vec<_> = (0..40)
+            .map(|i| document(&format!("{:02}", i), "text", &[i as f32]))
+            .collect();
+        server.insert(&id, &documents);
+
+        let (all, total) = sample_documents(&config, &id, T, D, 50).await.unwrap();
+        assert_eq!((all.len(), total), (40, 40));
+        assert!(all.iter().all(|doc| doc.embedding.is_some()));
+
+        // A sample is spread over the whole collection
+        let (sample, total) = sample_documents(&config, &id, T, D, 20).await.unwrap();
+        assert_eq!((sample.len(), total), (20, 40));
+        let ids: Vec<_> = sample.iter().take(4).map(|doc| doc.id.as_str()).collect();
+        assert_eq!(ids, ["00", "01", "04", "05"]);
+        assert_eq!(sample.last().unwrap().id, "37");
+    }
 }
@@ -3,9 +3,13 @@
 mod api;
 mod app;
 mod config;
+mod credentials;
 mod helpers;
 mod i18n;
 mod pages;
+mod projection;
+mod store;
+mod transfer;
 mod widgets;
 
 fn main() -> cosmic::iced::Result {
@@ -3,16 +3,23 @@
 //! Browser page using Miller columns for hierarchical navigation.
 //!
 //! Column hierarchy:
-//! 1. Server configs (+ Add New)
+//! 1. Server configs and local persist directories (+ Add New)
 //! 2. Tenants (+ Add New)
 //! 3. Databases (+ Add New)
 //! 4. Collections (+ Add New)
 //! 5. Documents
 //! 6. Document preview
 
-use crate::api::{Collection, Document};
+use crate::api::{self, ChromaError, Collection, Document, DocumentFilter, Identity, QueryMatch};
 use crate::config::ServerConfig;
+use crate::store::{self, StoreCapabilities};
+use crate::widgets::auth_form::{self, AuthForm, AuthMsg};
+use crate::widgets::collection_options::{self, CollectionOptionsForm, CollectionOptionsMsg};
+use crate::widgets::connection_form::{self, ConnectionForm, ConnectionMsg};
+use crate::widgets::filter_builder::{self, FilterBuilder, FilterMsg};
+use crate::widgets::metadata_editor::{self, MetadataEditor, MetadataMsg};
 use crate::widgets::miller_columns::{MillerItem, MillerItemType, MillerMessage, MillerState};
+use cosmic::iced::alignment::Horizontal;
 use cosmic::iced::{Alignment, Length};
 use cosmic::iced_widget::scrollable::{snap_to, RelativeOffset};
 use cosmic::prelude::*;
@@ -24,8 +31,12 @@ use std::collections::HashMap;
 pub enum BrowserData {
     /// A server configuration
     Server { index: usize, config: ServerConfig },
+    /// A Chroma persist directory opened without a server
+    LocalDirectory { index: usize, config: ServerConfig },
     /// Add new server action
     AddServer,
+    /// Open a local persist directory action
+    OpenLocalDirectory,
     /// A tenant
     Tenant { server_index: usize, name: String },
     /// Add new tenant action
@@ -61,6 +72,19 @@ pub enum BrowserData {
     },
     /// Document preview
     DocumentPreview { document: Document },
+    /// Open the filter panel for a collection's documents
+    FilterDocuments(CollectionRef),
+    /// Add new document action
+    AddDocument(CollectionRef),
+}
+
+/// Identifies a collection on a configured server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionRef {
+    pub server_index: usize,
+    pub tenant: String,
+    pub database: String,
+    pub collection_id: String,
 }
 
 /// Form state for adding a new server.
@@ -68,8 +92,8 @@ pub enum BrowserData {
 pub struct AddServerForm {
     pub name: String,
     pub url: String,
-    pub auth_token: String,
-    pub auth_header_type: String,
+    pub auth: AuthForm,
+    pub connection: ConnectionForm,
     pub status: AddServerStatus,
 }
 
@@ -88,11 +112,109 @@ impl AddServerForm {
         Self {
             name: String::new(),
             url: String::from("http://localhost:8000"),
-            auth_token: String::new(),
-            auth_header_type: String::from("authorization"),
+            auth: AuthForm::default(),
+            connection: ConnectionForm::default(),
             status: AddServerStatus::Editing,
         }
     }
+
+    /// Server config for the values entered so far.
+    pub fn server_config(&self) -> ServerConfig {
+        ServerConfig {
+            server_url: self.url.clone(),
+            auth: self.auth.build().unwrap_or_default(),
+            connection: self.connection.build().unwrap_or_default(),
+            ..ServerConfig::new(&self.name)
+        }
+    }
+}
+
+/// Form state for adding or editing a document.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentForm {
+    pub id: String,
+    pub text: String,
+    /// Embedding as a JSON array (add only; empty to let the server embed the text)
+    pub embedding: String,
+    pub metadata: MetadataEditor,
+    /// Replace an existing document with the same ID instead of failing
+    pub upsert: bool,
+    /// Text of the document being edited, to only send changed content
+    original_text: Option<String>,
+    /// Error from validation or from the last save attempt
+    pub error: Option<String>,
+    /// Whether a save is in progress
+    pub saving: bool,
+}
+
+/// Messages for the document form.
+#[derive(Debug, Clone)]
+pub enum DocumentFormMsg {
+    IdChanged(String),
+    TextChanged(String),
+    EmbeddingChanged(String),
+    UpsertToggled(bool),
+    Metadata(MetadataMsg),
+}
+
+impl DocumentForm {
+    /// Creates a form for editing an existing document.
+    pub fn edit(document: &Document) -> Self {
+        Self {
+            id: document.id.clone(),
+            text: document.document.clone().unwrap_or_default(),
+            metadata: MetadataEditor::from_metadata(document.metadata.as_ref()),
+            original_text: document.document.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, message: DocumentFormMsg) {
+        match message {
+            DocumentFormMsg::IdChanged(id) => self.id = id,
+            DocumentFormMsg::TextChanged(text) => self.text = text,
+            DocumentFormMsg::EmbeddingChanged(embedding) => self.embedding = embedding,
+            DocumentFormMsg::UpsertToggled(upsert) => self.upsert = upsert,
+            DocumentFormMsg::Metadata(msg) => self.metadata.update(msg),
+        }
+        self.error = None;
+    }
+
+    /// Builds a new document from the form.
+    pub fn build_new(&self) -> Result<Document, String> {
+        let id = self.id.trim();
+        if id.is_empty() {
+            return Err("Document ID is required".to_string());
+        }
+        let embedding = if self.embedding.trim().is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::from_str::<Vec<f32>>(&self.embedding)
+                    .map_err(|_| "Embedding must be a JSON array of numbers".to_string())?,
+            )
+        };
+
+        Ok(Document {
+            id: id.to_string(),
+            document: (!self.text.is_empty()).then(|| self.text.clone()),
+            metadata: self.metadata.build()?,
+            embedding,
+            ..Default::default()
+        })
+    }
+
+    /// Builds the update for an edited document: unchanged text is left out
+    /// and removed metadata keys are cleared.
+    pub fn build_update(&self) -> Result<Document, String> {
+        let text_changed = self.original_text.as_deref().unwrap_or_default() != self.text;
+        Ok(Document {
+            id: self.id.clone(),
+            document: text_changed.then(|| self.text.clone()),
+            metadata: self.metadata.build_update()?,
+            ..Default::default()
+        })
+    }
 }
 
 /// Unique identifier for items that can have a menu opened.
@@ -133,6 +255,18 @@ pub struct BrowserState {
     pub documents_cache: HashMap<String, Vec<Document>>,
     /// Currently selected document for preview
     pub selected_document: Option<Document>,
+    /// Whether the previewed document's embedding is being loaded
+    pub embedding_loading: bool,
+    /// Error from loading the previewed document's embedding
+    pub embedding_error: Option<String>,
+    /// Whether documents similar to the previewed one are being searched for
+    pub similar_loading: bool,
+    /// Documents nearest to the previewed one, once searched for
+    pub similar: Option<Result<Vec<QueryMatch>, String>>,
+    /// Collection of the previewed document
+    pub preview_collection: Option<CollectionRef>,
+    /// Edit form for the previewed document (edit mode)
+    pub editing_document: Option<DocumentForm>,
     /// Dialog state for adding new items
     pub dialog: Option<BrowserDialog>,
     /// Add server form (shown inline instead of dialog)
@@ -141,6 +275,14 @@ pub struct BrowserState {
     pub scrollable_id: Id,
     /// ID of the item whose menu is currently open (if any)
     pub open_menu: Option<MenuItemId>,
+    /// Filter builder for the documents column
+    pub filter: FilterBuilder,
+    /// Collection the filter applies to
+    pub filter_target: Option<CollectionRef>,
+    /// Whether the filter panel is shown
+    pub show_filter: bool,
+    /// What each server's backend supports, by server index
+    pub capabilities: Vec<StoreCapabilities>,
 }
 
 impl Default for BrowserState {
@@ -152,10 +294,20 @@ impl Default for BrowserState {
             collections_cache: HashMap::new(),
             documents_cache: HashMap::new(),
             selected_document: None,
+            embedding_loading: false,
+            embedding_error: None,
+            similar_loading: false,
+            similar: None,
+            preview_collection: None,
+            editing_document: None,
             dialog: None,
             adding_server: None,
             scrollable_id: Id::unique(),
             open_menu: None,
+            filter: FilterBuilder::default(),
+            filter_target: None,
+            show_filter: false,
+            capabilities: Vec::new(),
         }
     }
 }
@@ -166,6 +318,12 @@ pub enum BrowserDialog {
     AddServer {
         name: String,
     },
+    /// Path of a persist directory to open
+    OpenLocalDirectory {
+        path: String,
+        checking: bool,
+        error: Option<String>,
+    },
     AddTenant {
         server_index: usize,
         name: String,
@@ -180,12 +338,27 @@ pub enum BrowserDialog {
         tenant: String,
         database: String,
         name: String,
+        options: CollectionOptionsForm,
+        error: Option<String>,
     },
     /// Confirmation dialog when tenant doesn't exist on server
     ConfirmCreateTenant {
         server_index: usize,
         tenant: String,
     },
+    AddDocument {
+        collection: CollectionRef,
+        form: DocumentForm,
+    },
+    /// Rename a collection and edit its metadata
+    EditCollection {
+        collection: CollectionRef,
+        original_name: String,
+        name: String,
+        metadata: MetadataEditor,
+        saving: bool,
+        error: Option<String>,
+    },
 }
 
 impl BrowserState {
@@ -208,10 +381,20 @@ impl BrowserState {
             collections_cache: HashMap::new(),
             documents_cache: HashMap::new(),
             selected_document: None,
+            embedding_loading: false,
+            embedding_error: None,
+            similar_loading: false,
+            similar: None,
+            preview_collection: None,
+            editing_document: None,
             dialog: None,
             adding_server: None,
             scrollable_id: Id::unique(),
             open_menu: None,
+            filter: FilterBuilder::default(),
+            filter_target: None,
+            show_filter: false,
+            capabilities: servers.iter().map(store::capabilities).collect(),
         }
     }
 
@@ -223,33 +406,87 @@ impl BrowserState {
         )
     }
 
+    /// Returns true if any column is waiting for its children.
+    pub fn is_loading(&self) -> bool {
+        self.miller
+            .children
+            .values()
+            .any(|state| state.is_loading())
+    }
+
+    /// Shows loading columns as retrying while a request to their server is retried.
+    pub fn update_retrying(&mut self, servers: &[ServerConfig]) {
+        let loading: Vec<String> = self
+            .miller
+            .children
+            .iter()
+            .filter(|(_, state)| state.is_loading())
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in loading {
+            // Column IDs start with "<kind>:<server index>"
+            let server = id
+                .split(':')
+                .nth(1)
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| servers.get(index));
+            if let Some(server) = server {
+                self.miller
+                    .set_retrying(&id, api::is_retrying(&server.server_url));
+            }
+        }
+    }
+
     /// Rebuilds the root items from server configs.
     pub fn refresh_servers(&mut self, servers: &[ServerConfig]) {
+        self.capabilities = servers.iter().map(store::capabilities).collect();
         self.miller.set_roots(Self::build_server_items(servers));
     }
 
+    /// What the backend of a server supports.
+    pub fn capabilities(&self, server_index: usize) -> StoreCapabilities {
+        self.capabilities
+            .get(server_index)
+            .copied()
+            .unwrap_or(StoreCapabilities::FULL)
+    }
+
     /// Builds Miller items for server configs.
     fn build_server_items(servers: &[ServerConfig]) -> Vec<MillerItem<BrowserData>> {
         let mut items = Vec::with_capacity(servers.len() + 1);
 
-        // Add server items
+        // Add server and local directory items
         for (index, config) in servers.iter().enumerate() {
-            items.push(MillerItem::branch(
-                format!("server:{}", index),
-                &config.name,
+            let data = if config.local_path().is_some() {
+                BrowserData::LocalDirectory {
+                    index,
+                    config: config.clone(),
+                }
+            } else {
                 BrowserData::Server {
                     index,
                     config: config.clone(),
-                },
+                }
+            };
+            items.push(MillerItem::branch(
+                format!("server:{}", index),
+                &config.name,
+                data,
             ));
         }
 
-        // Add "Add New Server" item
+        // Add "Add New Server" and "Open Directory" items
         items.push(MillerItem::leaf(
             "add:server",
             "+ Add Server",
             BrowserData::AddServer,
         ));
+        items.push(MillerItem::leaf(
+            "add:local",
+            "+ Open Directory",
+            BrowserData::OpenLocalDirectory,
+        ));
 
         items
     }
@@ -258,7 +495,8 @@ impl BrowserState {
     pub fn set_tenants(&mut self, server_index: usize, tenants: Vec<String>) {
         self.tenants_cache.insert(server_index, tenants.clone());
 
-        let items = Self::build_tenant_items(server_index, &tenants);
+        let capabilities = self.capabilities(server_index);
+        let items = Self::build_tenant_items(server_index, &tenants, capabilities);
         self.miller
             .set_children(format!("server:{}", server_index), items);
     }
@@ -275,7 +513,11 @@ impl BrowserState {
     }
 
     /// Builds Miller items for tenants.
-    fn build_tenant_items(server_index: usize, tenants: &[String]) -> Vec<MillerItem<BrowserData>> {
+    fn build_tenant_items(
+        server_index: usize,
+        tenants: &[String],
+        capabilities: StoreCapabilities,
+    ) -> Vec<MillerItem<BrowserData>> {
         let mut items = Vec::with_capacity(tenants.len() + 1);
 
         for name in tenants {
@@ -290,11 +532,13 @@ impl BrowserState {
         }
 
         // Add "Add New Tenant" item
-        items.push(MillerItem::leaf(
-            format!("add:tenant:{}", server_index),
-            "+ Add Tenant",
-            BrowserData::AddTenant { server_index },
-        ));
+        if capabilities.manage_databases {
+            items.push(MillerItem::leaf(
+                format!("add:tenant:{}", server_index),
+                "+ Add Tenant",
+                BrowserData::AddTenant { server_index },
+            ));
+        }
 
         items
     }
@@ -304,7 +548,8 @@ impl BrowserState {
         let cache_key = format!("{}:{}", server_index, tenant);
         self.databases_cache.insert(cache_key, databases.clone());
 
-        let items = Self::build_database_items(server_index, tenant, &databases);
+        let capabilities = self.capabilities(server_index);
+        let items = Self::build_database_items(server_index, tenant, &databases, capabilities);
         self.miller
             .set_children(format!("tenant:{}:{}", server_index, tenant), items);
     }
@@ -326,6 +571,7 @@ impl BrowserState {
         server_index: usize,
         tenant: &str,
         databases: &[String],
+        capabilities: StoreCapabilities,
     ) -> Vec<MillerItem<BrowserData>> {
         let mut items = Vec::with_capacity(databases.len() + 1);
 
@@ -342,14 +588,16 @@ impl BrowserState {
         }
 
         // Add "Add New Database" item
-        items.push(MillerItem::leaf(
-            format!("add:database:{}:{}", server_index, tenant),
-            "+ Add Database",
-            BrowserData::AddDatabase {
-                server_index,
-                tenant: tenant.to_string(),
-            },
-        ));
+        if capabilities.manage_databases {
+            items.push(MillerItem::leaf(
+                format!("add:database:{}:{}", server_index, tenant),
+                "+ Add Database",
+                BrowserData::AddDatabase {
+                    server_index,
+                    tenant: tenant.to_string(),
+                },
+            ));
+        }
 
         items
     }
@@ -366,7 +614,14 @@ impl BrowserState {
         self.collections_cache
             .insert(cache_key, collections.clone());
 
-        let items = Self::build_collection_items(server_index, tenant, database, &collections);
+        let capabilities = self.capabilities(server_index);
+        let items = Self::build_collection_items(
+            server_index,
+            tenant,
+            database,
+            &collections,
+            capabilities,
+        );
         self.miller.set_children(
             format!("database:{}:{}:{}", server_index, tenant, database),
             items,
@@ -401,6 +656,7 @@ impl BrowserState {
         tenant: &str,
         database: &str,
         collections: &[Collection],
+        capabilities: StoreCapabilities,
     ) -> Vec<MillerItem<BrowserData>> {
         let mut items = Vec::with_capacity(collections.len() + 1);
 
@@ -421,19 +677,39 @@ impl BrowserState {
         }
 
         // Add "Add New Collection" item
-        items.push(MillerItem::leaf(
-            format!("add:collection:{}:{}:{}", server_index, tenant, database),
-            "+ Add Collection",
-            BrowserData::AddCollection {
-                server_index,
-                tenant: tenant.to_string(),
-                database: database.to_string(),
-            },
-        ));
+        if capabilities.writable {
+            items.push(MillerItem::leaf(
+                format!("add:collection:{}:{}:{}", server_index, tenant, database),
+                "+ Add Collection",
+                BrowserData::AddCollection {
+                    server_index,
+                    tenant: tenant.to_string(),
+                    database: database.to_string(),
+                },
+            ));
+        }
 
         items
     }
 
+    /// Returns the filter applied to a collection's documents (empty if none).
+    pub fn filter_for(&self, target: &CollectionRef) -> DocumentFilter {
+        if self.filter_target.as_ref() == Some(target) {
+            self.filter.applied.clone()
+        } else {
+            DocumentFilter::default()
+        }
+    }
+
+    /// Points the filter at a collection, resetting it when the collection changes.
+    pub fn set_filter_target(&mut self, target: CollectionRef) {
+        if self.filter_target.as_ref() != Some(&target) {
+            self.filter = FilterBuilder::default();
+            self.filter_target = Some(target);
+            self.show_filter = false;
+        }
+    }
+
     /// Sets documents for a collection.
     pub fn set_documents(
         &mut self,
@@ -446,8 +722,55 @@ impl BrowserState {
         let cache_key = format!("{}:{}:{}:{}", server_index, tenant, database, collection_id);
         self.documents_cache.insert(cache_key, documents.clone());
 
-        let items =
-            Self::build_document_items(server_index, tenant, database, collection_id, &documents);
+        let target = CollectionRef {
+            server_index,
+            tenant: tenant.to_string(),
+            database: database.to_string(),
+            collection_id: collection_id.to_string(),
+        };
+        let filter_active = self.filter_target.as_ref() == Some(&target) && self.filter.is_active();
+        if self.filter_target.as_ref() == Some(&target) {
+            self.filter.add_keys_from(&documents);
+        }
+
+        let mut items = Vec::with_capacity(documents.len() + 2);
+        // Add "Filter Documents" item at the top of the column
+        items.push(MillerItem::leaf(
+            format!(
+                "filter:{}:{}:{}:{}",
+                server_index, tenant, database, collection_id
+            ),
+            if filter_active {
+                "Filter Documents (active)"
+            } else {
+                "Filter Documents..."
+            },
+            BrowserData::FilterDocuments(target),
+        ));
+        items.extend(Self::build_document_items(
+            server_index,
+            tenant,
+            database,
+            collection_id,
+            &documents,
+        ));
+
+        // Add "Add New Document" item
+        if self.capabilities(server_index).writable {
+            items.push(MillerItem::leaf(
+                format!(
+                    "add:document:{}:{}:{}:{}",
+                    server_index, tenant, database, collection_id
+                ),
+                "+ Add Document",
+                BrowserData::AddDocument(CollectionRef {
+                    server_index,
+                    tenant: tenant.to_string(),
+                    database: database.to_string(),
+                    collection_id: collection_id.to_string(),
+                }),
+            ));
+        }
         self.miller.set_children(
             format!(
                 "collection:{}:{}:{}:{}",
@@ -537,23 +860,25 @@ impl BrowserState {
 pub enum BrowserMsg {
     /// Miller column message
     Miller(MillerMessage<BrowserData>),
+    /// Periodic check for requests that are being retried
+    RetryTick,
     /// Tenants loaded for a server
     TenantsLoaded {
         server_index: usize,
-        result: Result<Vec<String>, String>,
+        result: Result<Vec<String>, ChromaError>,
     },
     /// Databases loaded for a tenant
     DatabasesLoaded {
         server_index: usize,
         tenant: String,
-        result: Result<Vec<String>, String>,
+        result: Result<Vec<String>, ChromaError>,
     },
     /// Collections loaded for a database
     CollectionsLoaded {
         server_index: usize,
         tenant: String,
         database: String,
-        result: Result<Vec<Collection>, String>,
+        result: Result<Vec<Collection>, ChromaError>,
     },
     /// Documents loaded for a collection
     DocumentsLoaded {
@@ -561,7 +886,7 @@ pub enum BrowserMsg {
         tenant: String,
         database: String,
         collection_id: String,
-        result: Result<Vec<Document>, String>,
+        result: Result<Vec<Document>, ChromaError>,
     },
     /// Dialog input changed
     DialogInputChanged(String),
@@ -575,21 +900,21 @@ pub enum BrowserMsg {
     TenantCreated {
         server_index: usize,
         tenant: String,
-        result: Result<(), String>,
+        result: Result<(), ChromaError>,
     },
     /// Database created
     DatabaseCreated {
         server_index: usize,
         tenant: String,
         database: String,
-        result: Result<(), String>,
+        result: Result<(), ChromaError>,
     },
     /// Collection created
     CollectionCreated {
         server_index: usize,
         tenant: String,
         database: String,
-        result: Result<Collection, String>,
+        result: Result<Collection, ChromaError>,
     },
 
     // Add Server form messages
@@ -601,16 +926,26 @@ pub enum BrowserMsg {
     AddServerNameChanged(String),
     /// Server URL changed in add form
     AddServerUrlChanged(String),
-    /// Auth token changed in add form
-    AddServerAuthTokenChanged(String),
-    /// Auth header type changed in add form
-    AddServerAuthHeaderTypeChanged(String),
+    /// Authentication changed in add form
+    AddServerAuth(AuthMsg),
+    /// TLS or proxy settings changed in add form
+    AddServerConnection(ConnectionMsg),
     /// Test connection for new server
     TestNewServerConnection,
     /// Test connection result
-    TestNewServerResult(Result<(), String>),
+    TestNewServerResult(Result<(), ChromaError>),
     /// Save the new server
     SaveNewServer,
+    /// A local persist directory was checked before adding it
+    LocalDirectoryChecked {
+        server: ServerConfig,
+        result: Result<(), ChromaError>,
+    },
+    /// Identity of the caller on a newly added server
+    IdentityLoaded {
+        server_index: usize,
+        result: Result<Option<Identity>, ChromaError>,
+    },
 
     // Delete actions
     /// Request to delete a database
@@ -619,6 +954,18 @@ pub enum BrowserMsg {
         tenant: String,
         name: String,
     },
+    /// Request to back up every collection of a database to a file
+    RequestBackupDatabase {
+        server_index: usize,
+        tenant: String,
+        name: String,
+    },
+    /// Request to restore a backup into a database
+    RequestRestoreDatabase {
+        server_index: usize,
+        tenant: String,
+        name: String,
+    },
     /// Request to delete a collection
     RequestDeleteCollection {
         server_index: usize,
@@ -627,6 +974,59 @@ pub enum BrowserMsg {
         collection_id: String,
         collection_name: String,
     },
+    /// Request to rename or edit the metadata of a collection
+    RequestEditCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection: Collection,
+    },
+    /// Request to export a collection to a file
+    RequestExportCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection: Collection,
+    },
+    /// Request to import documents from a file into a collection
+    RequestImportCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection: Collection,
+    },
+    /// Create the collection of the add collection dialog from a file
+    ImportIntoNewCollection,
+    /// Request to copy a collection to another server, tenant or database
+    RequestCopyCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection: Collection,
+    },
+    /// Request to compare a collection with another one
+    RequestCompareCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection_name: String,
+    },
+    /// Request to plot the embeddings of a collection
+    RequestVisualizeCollection {
+        server_index: usize,
+        tenant: String,
+        database: String,
+        collection: Collection,
+    },
+    /// Metadata editor input in the edit collection dialog
+    EditCollectionMetadata(MetadataMsg),
+    /// Advanced options input in the add collection dialog
+    CollectionOptions(CollectionOptionsMsg),
+    /// Collection renamed / metadata updated
+    CollectionModified {
+        collection: CollectionRef,
+        result: Result<(), ChromaError>,
+    },
     /// Request to delete a document
     RequestDeleteDocument {
         server_index: usize,
@@ -639,14 +1039,14 @@ pub enum BrowserMsg {
     DatabaseDeleted {
         server_index: usize,
         tenant: String,
-        result: Result<(), String>,
+        result: Result<(), ChromaError>,
     },
     /// Collection deleted result
     CollectionDeleted {
         server_index: usize,
         tenant: String,
         database: String,
-        result: Result<(), String>,
+        result: Result<(), ChromaError>,
     },
     /// Document deleted result
     DocumentDeleted {
@@ -654,7 +1054,51 @@ pub enum BrowserMsg {
         tenant: String,
         database: String,
         collection_id: String,
-        result: Result<(), String>,
+        result: Result<(), ChromaError>,
+    },
+
+    /// Full document (with embedding) loaded for the preview
+    DocumentDetailsLoaded {
+        document_id: String,
+        result: Result<Option<Document>, ChromaError>,
+    },
+    /// Search the collection for the documents nearest to the previewed one
+    FindSimilar,
+    /// Documents nearest to a previewed document
+    SimilarLoaded {
+        document_id: String,
+        result: Result<Vec<QueryMatch>, ChromaError>,
+    },
+    /// Copy text to the clipboard
+    CopyToClipboard(String),
+
+    // Document editing
+    /// Document form input (add dialog or preview edit mode)
+    DocumentForm(DocumentFormMsg),
+    /// Switch the preview to edit mode
+    StartEditDocument,
+    /// Leave edit mode without saving
+    CancelEditDocument,
+    /// Save the edited document
+    SaveDocumentEdit,
+    /// Document added or updated
+    DocumentSaved {
+        collection: CollectionRef,
+        document_id: String,
+        result: Result<(), ChromaError>,
+    },
+
+    // Filter panel messages
+    /// Show the filter panel for a collection
+    OpenFilter(CollectionRef),
+    /// Hide the filter panel (the applied filter stays in effect)
+    CloseFilter,
+    /// Filter builder message
+    Filter(FilterMsg),
+    /// Match count loaded for the filtered collection
+    FilterCountLoaded {
+        target: CollectionRef,
+        result: Result<usize, ChromaError>,
     },
 
     // Menu actions
@@ -694,16 +1138,13 @@ pub fn view<'a, Message: Clone + 'static>(
     // Use window height for columns (minus some padding for header/notifications)
     let column_height = Length::Fixed((window_height - 100.0).max(400.0));
 
-    let open_menu = &state.open_menu;
     let miller_view: Element<'a, Message> = MillerColumns::new(&state.miller, move |msg| {
         on_message(BrowserMsg::Miller(msg))
     })
     .column_width(Length::Fixed(column_width))
     .column_height(column_height)
     .spacing(space_s)
-    .item_view(move |item, is_selected| {
-        render_browser_item(item, is_selected, open_menu, on_message)
-    })
+    .item_view(move |item, is_selected| render_browser_item(item, is_selected, state, on_message))
     .into();
 
     // Build inner content based on state
@@ -719,13 +1160,29 @@ pub fn view<'a, Message: Clone + 'static>(
             ))
             .spacing(space_m)
             .into()
-    } else if let Some(ref doc) = state.selected_document {
-        // Show miller columns + document preview
-        widget::row::with_capacity(2)
+    } else if state.show_filter || state.selected_document.is_some() {
+        // Show miller columns + filter panel and/or document preview
+        let mut row = widget::row::with_capacity(3)
             .push(miller_view)
-            .push(render_document_preview(doc, space_s, column_height))
-            .spacing(space_m)
-            .into()
+            .spacing(space_m);
+        if state.show_filter {
+            row = row.push(render_filter_panel(
+                state,
+                on_message,
+                space_s,
+                column_height,
+            ));
+        }
+        if let Some(ref doc) = state.selected_document {
+            row = row.push(render_document_preview(
+                doc,
+                state,
+                on_message,
+                space_s,
+                column_height,
+            ));
+        }
+        row.into()
     } else {
         miller_view
     };
@@ -755,9 +1212,10 @@ pub fn view<'a, Message: Clone + 'static>(
 fn render_browser_item<'a, Message: Clone + 'static>(
     item: &MillerItem<BrowserData>,
     is_selected: bool,
-    open_menu: &Option<MenuItemId>,
+    state: &BrowserState,
     on_message: impl Fn(BrowserMsg) -> Message + Copy + 'a,
 ) -> Element<'a, Message> {
+    let open_menu = &state.open_menu;
     match &item.data {
         // Documents get a detailed card view
         BrowserData::Document {
@@ -769,6 +1227,7 @@ fn render_browser_item<'a, Message: Clone + 'static>(
         } => render_document_card(
             document,
             is_selected,
+            state.capabilities(*server_index).writable,
             *server_index,
             tenant.clone(),
             database.clone(),
@@ -786,6 +1245,7 @@ fn render_browser_item<'a, Message: Clone + 'static>(
         } => render_collection_card(
             collection,
             is_selected,
+            state.capabilities(*server_index),
             *server_index,
             tenant.clone(),
             database.clone(),
@@ -801,6 +1261,7 @@ fn render_browser_item<'a, Message: Clone + 'static>(
         } => render_database_card(
             name,
             is_selected,
+            state.capabilities(*server_index),
             *server_index,
             tenant.clone(),
             open_menu,
@@ -819,7 +1280,9 @@ fn render_simple_item<'a, Message: 'static>(
 ) -> Element<'a, Message> {
     let (icon_name, label_style) = match &item.data {
         BrowserData::Server { .. } => ("network-server-symbolic", false),
+        BrowserData::LocalDirectory { .. } => ("folder-open-symbolic", false),
         BrowserData::AddServer => ("list-add-symbolic", true),
+        BrowserData::OpenLocalDirectory => ("document-open-symbolic", true),
         BrowserData::Tenant { .. } => ("system-users-symbolic", false),
         BrowserData::AddTenant { .. } => ("list-add-symbolic", true),
         BrowserData::Database { .. } => ("drive-harddisk-symbolic", false),
@@ -828,12 +1291,14 @@ fn render_simple_item<'a, Message: 'static>(
         BrowserData::AddCollection { .. } => ("list-add-symbolic", true),
         BrowserData::Document { .. } => ("text-x-generic-symbolic", false),
         BrowserData::DocumentPreview { .. } => ("text-x-generic-symbolic", false),
+        BrowserData::FilterDocuments(_) => ("edit-find-symbolic", true),
+        BrowserData::AddDocument(_) => ("list-add-symbolic", true),
     };
 
     let has_children = item.item_type == MillerItemType::Branch;
 
     let label = item.label.clone();
-    let mut row = widget::row::with_capacity(3)
+    let mut row = widget::row::with_capacity(4)
         .push(icon::from_name(icon_name).size(16))
         .push(
             widget::text::body(label)
@@ -847,6 +1312,12 @@ fn render_simple_item<'a, Message: 'static>(
         .align_y(Alignment::Center)
         .spacing(8);
 
+    if let BrowserData::Server { config, .. } = &item.data
+        && config.connection.accept_invalid_certs
+    {
+        row = row.push(connection_form::insecure_badge());
+    }
+
     if has_children {
         row = row.push(icon::from_name("go-next-symbolic").size(12));
     }
@@ -868,6 +1339,7 @@ fn render_simple_item<'a, Message: 'static>(
 fn render_document_card<'a, Message: Clone + 'static>(
     doc: &Document,
     is_selected: bool,
+    editable: bool,
     server_index: usize,
     tenant: String,
     database: String,
@@ -948,7 +1420,7 @@ fn render_document_card<'a, Message: Clone + 'static>(
                 .width(Length::Fill)
                 .class(cosmic::style::Text::Default),
         )
-        .push(menu_widget)
+        .push_maybe(editable.then_some(menu_widget))
         .align_y(Alignment::Center)
         .spacing(8);
 
@@ -982,6 +1454,7 @@ fn render_document_card<'a, Message: Clone + 'static>(
 fn render_collection_card<'a, Message: Clone + 'static>(
     collection: &Collection,
     is_selected: bool,
+    capabilities: StoreCapabilities,
     server_index: usize,
     tenant: String,
     database: String,
@@ -1009,7 +1482,7 @@ fn render_collection_card<'a, Message: Clone + 'static>(
         .padding(4)
         .on_press(on_message(BrowserMsg::ToggleMenu(menu_id.clone())));
 
-    // Popover content (delete menu)
+    // Popover content (collection actions)
     let delete_msg = on_message(BrowserMsg::RequestDeleteCollection {
         server_index,
         tenant: tenant.clone(),
@@ -1017,13 +1490,86 @@ fn render_collection_card<'a, Message: Clone + 'static>(
         collection_id: collection_id_for_delete,
         collection_name: collection_name_for_delete,
     });
+    let edit_msg = on_message(BrowserMsg::RequestEditCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection: collection.clone(),
+    });
+    let export_msg = on_message(BrowserMsg::RequestExportCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection: collection.clone(),
+    });
+    let import_msg = on_message(BrowserMsg::RequestImportCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection: collection.clone(),
+    });
+    let copy_msg = on_message(BrowserMsg::RequestCopyCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection: collection.clone(),
+    });
+    let compare_msg = on_message(BrowserMsg::RequestCompareCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection_name: collection.name.clone(),
+    });
+    let visualize_msg = on_message(BrowserMsg::RequestVisualizeCollection {
+        server_index,
+        tenant: tenant.clone(),
+        database: database.clone(),
+        collection: collection.clone(),
+    });
+    // Read-only sources can still be exported, compared and plotted, but
+    // copies need the embedding of every document
+    let editable = capabilities.writable;
     let menu_content: Element<'a, Message> = widget::container(
-        widget::button::destructive("Delete")
-            .on_press(delete_msg)
-            .width(Length::Fill),
+        widget::column::with_capacity(7)
+            .push_maybe(editable.then(|| {
+                widget::button::standard("Rename / Edit metadata")
+                    .on_press(edit_msg)
+                    .width(Length::Fill)
+            }))
+            .push_maybe(editable.then(|| {
+                widget::button::standard("Import...")
+                    .on_press(import_msg)
+                    .width(Length::Fill)
+            }))
+            .push(
+                widget::button::standard("Export...")
+                    .on_press(export_msg)
+                    .width(Length::Fill),
+            )
+            .push_maybe(capabilities.embeddings.then(|| {
+                widget::button::standard("Copy to...")
+                    .on_press(copy_msg)
+                    .width(Length::Fill)
+            }))
+            .push(
+                widget::button::standard("Compare with...")
+                    .on_press(compare_msg)
+                    .width(Length::Fill),
+            )
+            .push(
+                widget::button::standard("Visualize...")
+                    .on_press(visualize_msg)
+                    .width(Length::Fill),
+            )
+            .push_maybe(editable.then(|| {
+                widget::button::destructive("Delete")
+                    .on_press(delete_msg)
+                    .width(Length::Fill)
+            }))
+            .spacing(4),
     )
     .padding(4)
-    .width(Length::Fixed(120.0))
+    .width(Length::Fixed(220.0))
     .class(cosmic::style::Container::Card)
     .into();
 
@@ -1082,6 +1628,7 @@ fn render_collection_card<'a, Message: Clone + 'static>(
 fn render_database_card<'a, Message: Clone + 'static>(
     name: &str,
     is_selected: bool,
+    capabilities: StoreCapabilities,
     server_index: usize,
     tenant: String,
     open_menu: &Option<MenuItemId>,
@@ -1089,7 +1636,7 @@ fn render_database_card<'a, Message: Clone + 'static>(
 ) -> Element<'a, Message> {
     let db_name = name.to_string();
     let db_name_display = db_name.clone();
-    let db_name_for_delete = db_name.clone();
+    let db_name_for_menu = db_name.clone();
 
     // Create menu item ID for this database
     let menu_id = MenuItemId::Database {
@@ -1105,19 +1652,44 @@ fn render_database_card<'a, Message: Clone + 'static>(
         .padding(4)
         .on_press(on_message(BrowserMsg::ToggleMenu(menu_id.clone())));
 
-    // Popover content (delete menu)
+    // Popover content (database actions)
+    let backup_msg = on_message(BrowserMsg::RequestBackupDatabase {
+        server_index,
+        tenant: tenant.clone(),
+        name: db_name_for_menu.clone(),
+    });
+    let restore_msg = on_message(BrowserMsg::RequestRestoreDatabase {
+        server_index,
+        tenant: tenant.clone(),
+        name: db_name_for_menu.clone(),
+    });
     let delete_msg = on_message(BrowserMsg::RequestDeleteDatabase {
         server_index,
         tenant: tenant.clone(),
-        name: db_name_for_delete,
+        name: db_name_for_menu,
     });
+    // Read-only sources can still be backed up
     let menu_content: Element<'a, Message> = widget::container(
-        widget::button::destructive("Delete")
-            .on_press(delete_msg)
-            .width(Length::Fill),
+        widget::column::with_capacity(3)
+            .push(
+                widget::button::standard("Back Up...")
+                    .on_press(backup_msg)
+                    .width(Length::Fill),
+            )
+            .push_maybe(capabilities.writable.then(|| {
+                widget::button::standard("Restore...")
+                    .on_press(restore_msg)
+                    .width(Length::Fill)
+            }))
+            .push_maybe(capabilities.manage_databases.then(|| {
+                widget::button::destructive("Delete")
+                    .on_press(delete_msg)
+                    .width(Length::Fill)
+            }))
+            .spacing(4),
     )
     .padding(4)
-    .width(Length::Fixed(120.0))
+    .width(Length::Fixed(160.0))
     .class(cosmic::style::Container::Card)
     .into();
 
@@ -1159,15 +1731,40 @@ fn render_database_card<'a, Message: Clone + 'static>(
 }
 
 /// Renders the document preview panel.
-fn render_document_preview<'a, Message: 'static>(
+fn render_document_preview<'a, Message: Clone + 'static>(
     doc: &'a Document,
+    state: &'a BrowserState,
+    on_message: impl Fn(BrowserMsg) -> Message + Copy + 'a,
     space_s: u16,
     height: Length,
 ) -> Element<'a, Message> {
-    let mut content = widget::column::with_capacity(6).spacing(space_s);
+    let editing = state.editing_document.as_ref();
+    let embedding_loading = state.embedding_loading;
+    let embedding_error = state.embedding_error.as_deref();
+    // Documents of read-only sources can't be edited
+    let editable = state
+        .preview_collection
+        .as_ref()
+        .is_none_or(|collection| state.capabilities(collection.server_index).writable);
+
+    let mut content = widget::column::with_capacity(10).spacing(space_s);
 
-    // Document ID
-    content = content.push(widget::text::title4("Document ID"));
+    // Document ID, with the edit toggle
+    let edit_button: Element<'a, Message> = if editing.is_some() {
+        widget::button::standard("Cancel")
+            .on_press(on_message(BrowserMsg::CancelEditDocument))
+            .into()
+    } else {
+        widget::button::standard("Edit")
+            .on_press(on_message(BrowserMsg::StartEditDocument))
+            .into()
+    };
+    content = content.push(
+        widget::row::with_capacity(2)
+            .push(widget::text::title4("Document ID").width(Length::Fill))
+            .push_maybe(editable.then_some(edit_button))
+            .align_y(Alignment::Center),
+    );
     content = content.push(
         widget::container(widget::text::body(&doc.id))
             .padding(space_s)
@@ -1175,39 +1772,252 @@ fn render_document_preview<'a, Message: 'static>(
             .class(cosmic::style::Container::Card),
     );
 
-    // Document content
-    content = content.push(widget::text::title4("Content"));
-    let doc_content = doc.document.as_deref().unwrap_or("[No content]");
+    if let Some(form) = editing {
+        // Edit mode: text and metadata become editable
+        content = content.push(widget::text::title4("Content"));
+        content = content.push(
+            widget::text_input("Document text", &form.text)
+                .on_input(move |s| {
+                    on_message(BrowserMsg::DocumentForm(DocumentFormMsg::TextChanged(s)))
+                })
+                .width(Length::Fill),
+        );
+        content = content.push(widget::text::caption(
+            "Changing the text does not update the embedding.",
+        ));
+
+        content = content.push(widget::text::title4("Metadata"));
+        content = content.push(metadata_editor::view(
+            &form.metadata,
+            move |msg| on_message(BrowserMsg::DocumentForm(DocumentFormMsg::Metadata(msg))),
+            space_s,
+        ));
+
+        if let Some(ref error) = form.error {
+            content = content
+                .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        let can_save = !form.saving && form.metadata.build().is_ok();
+        content = content.push(
+            widget::button::suggested(if form.saving { "Saving..." } else { "Save" })
+                .on_press_maybe(can_save.then(|| on_message(BrowserMsg::SaveDocumentEdit))),
+        );
+    } else {
+        // Document content
+        content = content.push(widget::text::title4("Content"));
+        let doc_content = doc.document.as_deref().unwrap_or("[No content]");
+        content = content.push(
+            widget::container(widget::text::body(doc_content))
+                .padding(space_s)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        );
+
+        // Metadata
+        if let Some(ref metadata) = doc.metadata {
+            if !metadata.is_empty() {
+                content = content.push(widget::text::title4("Metadata"));
+
+                let mut metadata_col = widget::column::with_capacity(metadata.len()).spacing(4);
+                for (key, value) in metadata {
+                    let row = widget::row::with_capacity(2)
+                        .push(widget::text::body(format!("{}:", key)).width(Length::Fixed(120.0)))
+                        .push(widget::text::caption(value.to_string()))
+                        .spacing(8);
+                    metadata_col = metadata_col.push(row);
+                }
+
+                content = content.push(
+                    widget::container(metadata_col)
+                        .padding(space_s)
+                        .width(Length::Fill)
+                        .class(cosmic::style::Container::Card),
+                );
+            }
+        }
+    }
+
+    // URI and loaded data (multimodal collections)
+    if let Some(ref uri) = doc.uri {
+        content = content.push(widget::text::title4("URI"));
+        content = content.push(
+            widget::container(widget::text::body(uri))
+                .padding(space_s)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        );
+    }
+    if let Some(ref data) = doc.data {
+        let data_preview = truncate_chars(&data.to_string(), 500);
+        content = content.push(widget::text::title4("Data"));
+        content = content.push(
+            widget::container(widget::text::caption(data_preview))
+                .padding(space_s)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        );
+    }
+
+    // Embedding
+    content = content.push(widget::text::title4("Embedding"));
+    let embedding_content: Element<'a, Message> = match (&doc.embedding, embedding_error) {
+        (Some(embedding), _) => {
+            let json = serde_json::to_string(embedding).unwrap_or_default();
+            widget::column::with_capacity(4)
+                .push(widget::text::body(format!(
+                    "{} dimensions, L2 norm {:.4}",
+                    embedding.len(),
+                    l2_norm(embedding)
+                )))
+                .push(widget::text::body(sparkline(embedding, SPARKLINE_WIDTH)))
+                .push(
+                    widget::row::with_capacity(2)
+                        .push(
+                            widget::button::standard("Copy as JSON")
+                                .on_press(on_message(BrowserMsg::CopyToClipboard(json))),
+                        )
+                        .push(widget::button::standard("Find Similar").on_press_maybe(
+                            (!state.similar_loading).then(|| on_message(BrowserMsg::FindSimilar)),
+                        ))
+                        .spacing(8),
+                )
+                .spacing(8)
+                .into()
+        }
+        (None, Some(error)) => widget::text::caption(format!("Failed to load: {}", error))
+            .class(cosmic::style::Text::Accent)
+            .into(),
+        (None, None) if embedding_loading => widget::text::caption("Loading...").into(),
+        (None, None) => widget::text::caption("[No embedding]").into(),
+    };
     content = content.push(
-        widget::container(widget::text::body(doc_content))
+        widget::container(embedding_content)
             .padding(space_s)
             .width(Length::Fill)
             .class(cosmic::style::Container::Card),
     );
 
-    // Metadata
-    if let Some(ref metadata) = doc.metadata {
-        if !metadata.is_empty() {
-            content = content.push(widget::text::title4("Metadata"));
-
-            let mut metadata_col = widget::column::with_capacity(metadata.len()).spacing(4);
-            for (key, value) in metadata {
-                let row = widget::row::with_capacity(2)
-                    .push(widget::text::body(format!("{}:", key)).width(Length::Fixed(120.0)))
-                    .push(widget::text::caption(value.to_string()))
-                    .spacing(8);
-                metadata_col = metadata_col.push(row);
+    // Nearest neighbours, closest first
+    let similar_content: Option<Element<'a, Message>> = match &state.similar {
+        _ if state.similar_loading => Some(widget::text::caption("Searching...").into()),
+        Some(Ok(matches)) if matches.is_empty() => {
+            Some(widget::text::caption("No other documents").into())
+        }
+        Some(Ok(matches)) => {
+            let mut column = widget::column::with_capacity(matches.len()).spacing(4);
+            for found in matches {
+                let distance = found
+                    .distance
+                    .map(|distance| format!("{:.4}", distance))
+                    .unwrap_or_default();
+                column = column.push(
+                    widget::row::with_capacity(2)
+                        .push(widget::text::body(found.document.id.as_str()).width(Length::Fill))
+                        .push(widget::text::caption(distance))
+                        .spacing(8),
+                );
             }
-
-            content = content.push(
-                widget::container(metadata_col)
-                    .padding(space_s)
-                    .width(Length::Fill)
-                    .class(cosmic::style::Container::Card),
-            );
+            Some(column.into())
         }
+        Some(Err(error)) => Some(
+            widget::text::caption(format!("Search failed: {}", error))
+                .class(cosmic::style::Text::Accent)
+                .into(),
+        ),
+        None => None,
+    };
+    if let Some(similar_content) = similar_content {
+        content = content.push(widget::text::title4("Similar Documents"));
+        content = content.push(
+            widget::container(similar_content)
+                .padding(space_s)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        );
+    }
+
+    widget::scrollable(content)
+        .width(Length::Fixed(350.0))
+        .height(height)
+        .into()
+}
+
+/// Number of similar documents listed in the preview
+pub const SIMILAR_COUNT: usize = 10;
+
+/// Number of bars in an embedding sparkline
+pub(super) const SPARKLINE_WIDTH: usize = 40;
+
+/// Euclidean (L2) norm of a vector.
+pub(super) fn l2_norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+/// Renders a vector as a row of block characters, averaging values into
+/// `width` buckets and scaling them between the minimum and maximum.
+pub(super) fn sparkline(vector: &[f32], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if vector.is_empty() || width == 0 {
+        return String::new();
     }
 
+    let bucket_size = vector.len().div_ceil(width);
+    let buckets: Vec<f32> = vector
+        .chunks(bucket_size)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect();
+
+    let min = buckets.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = buckets.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+
+    buckets
+        .iter()
+        .map(|value| {
+            if range <= f32::EPSILON {
+                BARS[BARS.len() / 2]
+            } else {
+                let level = ((value - min) / range * (BARS.len() - 1) as f32).round() as usize;
+                BARS[level.min(BARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// Truncates a string to at most `max` characters, adding an ellipsis.
+pub(super) fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}...", text.chars().take(max).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+/// Renders the filter panel for the documents column.
+fn render_filter_panel<'a, Message: Clone + 'static>(
+    state: &'a BrowserState,
+    on_message: impl Fn(BrowserMsg) -> Message + Copy + 'a,
+    space_s: u16,
+    height: Length,
+) -> Element<'a, Message> {
+    let close_button = widget::button::icon(icon::from_name("window-close-symbolic"))
+        .class(cosmic::theme::Button::Icon)
+        .on_press(on_message(BrowserMsg::CloseFilter));
+
+    let content = widget::column::with_capacity(2)
+        .push(
+            widget::container(close_button)
+                .width(Length::Fill)
+                .align_x(Horizontal::Right),
+        )
+        .push(filter_builder::view(
+            &state.filter,
+            move |msg| on_message(BrowserMsg::Filter(msg)),
+            space_s,
+        ))
+        .spacing(space_s);
+
     widget::scrollable(content)
         .width(Length::Fixed(350.0))
         .height(height)
@@ -1242,41 +2052,25 @@ fn render_add_server_form<'a, Message: Clone + 'static>(
             .width(Length::Fixed(280.0)),
     );
 
-    // Auth token
-    content = content.push(widget::text::body("Auth Token (optional)"));
+    // Authentication
+    content = content.push(widget::text::body("Authentication"));
     content = content.push(
-        widget::secure_input("Token", &form.auth_token, None, true)
-            .on_input(move |s| on_message(BrowserMsg::AddServerAuthTokenChanged(s)))
-            .width(Length::Fixed(280.0)),
+        widget::container(auth_form::view(
+            &form.auth,
+            move |msg| on_message(BrowserMsg::AddServerAuth(msg)),
+            space_s,
+        ))
+        .width(Length::Fixed(280.0)),
     );
 
-    // Auth header type
-    content = content.push(widget::text::body("Auth Header Type"));
+    // TLS and proxy
     content = content.push(
-        widget::row::with_capacity(2)
-            .push(
-                widget::button::text("Bearer")
-                    .class(if form.auth_header_type == "authorization" {
-                        cosmic::theme::Button::Suggested
-                    } else {
-                        cosmic::theme::Button::Standard
-                    })
-                    .on_press(on_message(BrowserMsg::AddServerAuthHeaderTypeChanged(
-                        "authorization".to_string(),
-                    ))),
-            )
-            .push(
-                widget::button::text("X-Chroma-Token")
-                    .class(if form.auth_header_type == "x-chroma-token" {
-                        cosmic::theme::Button::Suggested
-                    } else {
-                        cosmic::theme::Button::Standard
-                    })
-                    .on_press(on_message(BrowserMsg::AddServerAuthHeaderTypeChanged(
-                        "x-chroma-token".to_string(),
-                    ))),
-            )
-            .spacing(space_s),
+        widget::container(connection_form::view(
+            &form.connection,
+            move |msg| on_message(BrowserMsg::AddServerConnection(msg)),
+            space_s,
+        ))
+        .width(Length::Fixed(280.0)),
     );
 
     // Status message
@@ -1370,22 +2164,245 @@ fn render_dialog<'a, Message: Clone + 'static>(
                 .into()
         }
 
+        // Form dialog for adding a document
+        BrowserDialog::AddDocument { form, .. } => {
+            let form_msg = move |msg| on_message(BrowserMsg::DocumentForm(msg));
+
+            let mut dialog_content = widget::column::with_capacity(12)
+                .push(widget::text::body("Document ID"))
+                .push(
+                    widget::text_input("doc-1", &form.id)
+                        .on_input(move |s| form_msg(DocumentFormMsg::IdChanged(s)))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(widget::text::body("Text"))
+                .push(
+                    widget::text_input("Document text", &form.text)
+                        .on_input(move |s| form_msg(DocumentFormMsg::TextChanged(s)))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(widget::text::body("Embedding (optional)"))
+                .push(
+                    widget::text_input("[0.1, 0.2, ...]", &form.embedding)
+                        .on_input(move |s| form_msg(DocumentFormMsg::EmbeddingChanged(s)))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(widget::text::caption(
+                    "Leave empty if the server computes embeddings for this collection.",
+                ))
+                .push(widget::text::body("Metadata"))
+                .push(metadata_editor::view(
+                    &form.metadata,
+                    move |msg| form_msg(DocumentFormMsg::Metadata(msg)),
+                    space_s,
+                ))
+                .push(
+                    widget::checkbox("Replace if the ID already exists", form.upsert)
+                        .on_toggle(move |v| form_msg(DocumentFormMsg::UpsertToggled(v))),
+                )
+                .spacing(space_s);
+
+            if let Some(ref error) = form.error {
+                dialog_content = dialog_content
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            dialog_content = dialog_content.push(
+                widget::row::with_capacity(2)
+                    .push(
+                        widget::button::standard("Cancel")
+                            .on_press(on_message(BrowserMsg::DialogCancel)),
+                    )
+                    .push(
+                        widget::button::suggested(if form.saving { "Saving..." } else { "Add" })
+                            .on_press_maybe(
+                                (!form.saving).then(|| on_message(BrowserMsg::DialogConfirm)),
+                            ),
+                    )
+                    .spacing(space_s),
+            );
+
+            widget::dialog()
+                .title("Add Document")
+                .control(dialog_content)
+                .into()
+        }
+
+        // Rename / edit metadata dialog for a collection
+        BrowserDialog::EditCollection {
+            original_name,
+            name,
+            metadata,
+            saving,
+            error,
+            ..
+        } => {
+            // Chroma rejects empty metadata, so it cannot be cleared entirely
+            let cleared = metadata.is_cleared();
+            let can_save =
+                !*saving && !name.trim().is_empty() && metadata.build().is_ok() && !cleared;
+
+            let mut dialog_content = widget::column::with_capacity(7)
+                .push(widget::text::body("Name"))
+                .push(
+                    widget::text_input(original_name.as_str(), name)
+                        .on_input(move |s| on_message(BrowserMsg::DialogInputChanged(s)))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(widget::text::body("Metadata"))
+                .push(metadata_editor::view(
+                    metadata,
+                    move |msg| on_message(BrowserMsg::EditCollectionMetadata(msg)),
+                    space_s,
+                ))
+                .spacing(space_s);
+
+            if cleared {
+                dialog_content = dialog_content.push(widget::text::caption(
+                    "A collection's metadata cannot be removed entirely. Keep at least one field.",
+                ));
+            }
+
+            if let Some(error) = error {
+                dialog_content = dialog_content
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            dialog_content = dialog_content.push(
+                widget::row::with_capacity(2)
+                    .push(
+                        widget::button::standard("Cancel")
+                            .on_press(on_message(BrowserMsg::DialogCancel)),
+                    )
+                    .push(
+                        widget::button::suggested(if *saving { "Saving..." } else { "Save" })
+                            .on_press_maybe(
+                                can_save.then(|| on_message(BrowserMsg::DialogConfirm)),
+                            ),
+                    )
+                    .spacing(space_s),
+            );
+
+            widget::dialog()
+                .title("Edit Collection")
+                .control(dialog_content)
+                .into()
+        }
+
+        // Name plus advanced options for a new collection
+        BrowserDialog::AddCollection {
+            name,
+            options,
+            error,
+            ..
+        } => {
+            let valid = !name.trim().is_empty() && options.build().is_ok();
+            let confirm = valid.then(|| on_message(BrowserMsg::DialogConfirm));
+            let import = valid.then(|| on_message(BrowserMsg::ImportIntoNewCollection));
+
+            let mut dialog_content = widget::column::with_capacity(5)
+                .push(
+                    widget::text_input("Collection name", name)
+                        .on_input(move |s| on_message(BrowserMsg::DialogInputChanged(s)))
+                        .on_submit(move |_| on_message(BrowserMsg::DialogConfirm))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(collection_options::view(
+                    options,
+                    move |msg| on_message(BrowserMsg::CollectionOptions(msg)),
+                    space_s,
+                ))
+                .spacing(space_s);
+
+            if let Some(error) = error {
+                dialog_content = dialog_content
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            dialog_content = dialog_content.push(
+                widget::row::with_capacity(3)
+                    .push(
+                        widget::button::standard("Cancel")
+                            .on_press(on_message(BrowserMsg::DialogCancel)),
+                    )
+                    .push(widget::button::standard("Import from File...").on_press_maybe(import))
+                    .push(widget::button::suggested("Create").on_press_maybe(confirm))
+                    .spacing(space_s),
+            );
+
+            widget::dialog()
+                .title("Add Collection")
+                .control(dialog_content)
+                .into()
+        }
+
+        // Path of a local persist directory, checked before it is added
+        BrowserDialog::OpenLocalDirectory {
+            path,
+            checking,
+            error,
+        } => {
+            let confirm = (!*checking && !path.trim().is_empty())
+                .then(|| on_message(BrowserMsg::DialogConfirm));
+
+            let mut dialog_content = widget::column::with_capacity(4)
+                .push(
+                    widget::text_input("/path/to/chroma", path)
+                        .on_input(move |s| on_message(BrowserMsg::DialogInputChanged(s)))
+                        .on_submit(move |_| on_message(BrowserMsg::DialogConfirm))
+                        .width(Length::Fixed(400.0)),
+                )
+                .push(widget::text::caption(
+                    "The directory containing chroma.sqlite3. It is opened read-only.",
+                ))
+                .spacing(space_s);
+
+            if let Some(error) = error {
+                dialog_content = dialog_content
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            dialog_content = dialog_content.push(
+                widget::row::with_capacity(2)
+                    .push(
+                        widget::button::standard("Cancel")
+                            .on_press(on_message(BrowserMsg::DialogCancel)),
+                    )
+                    .push(
+                        widget::button::suggested(if *checking { "Opening..." } else { "Open" })
+                            .on_press_maybe(confirm),
+                    )
+                    .spacing(space_s),
+            );
+
+            widget::dialog()
+                .title("Open Local Directory")
+                .control(dialog_content)
+                .into()
+        }
+
         // Input dialogs for adding items
         _ => {
             let (title, placeholder) = match dialog {
                 BrowserDialog::AddServer { .. } => ("Add Server", "Server name"),
                 BrowserDialog::AddTenant { .. } => ("Add Tenant", "Tenant name"),
                 BrowserDialog::AddDatabase { .. } => ("Add Database", "Database name"),
-                BrowserDialog::AddCollection { .. } => ("Add Collection", "Collection name"),
-                BrowserDialog::ConfirmCreateTenant { .. } => unreachable!(),
+                BrowserDialog::AddCollection { .. }
+                | BrowserDialog::OpenLocalDirectory { .. }
+                | BrowserDialog::ConfirmCreateTenant { .. }
+                | BrowserDialog::AddDocument { .. }
+                | BrowserDialog::EditCollection { .. } => unreachable!(),
             };
 
             let value = match dialog {
                 BrowserDialog::AddServer { name } => name,
                 BrowserDialog::AddTenant { name, .. } => name,
                 BrowserDialog::AddDatabase { name, .. } => name,
-                BrowserDialog::AddCollection { name, .. } => name,
-                BrowserDialog::ConfirmCreateTenant { .. } => unreachable!(),
+                BrowserDialog::AddCollection { .. }
+                | BrowserDialog::OpenLocalDirectory { .. }
+                | BrowserDialog::ConfirmCreateTenant { .. }
+                | BrowserDialog::AddDocument { .. }
+                | BrowserDialog::EditCollection { .. } => unreachable!(),
             };
 
             let dialog_content = widget::column::with_capacity(2)
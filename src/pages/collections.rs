@@ -4,6 +4,7 @@
 
 use crate::app::{AppModel, ConnectionStatus, Message};
 use crate::fl;
+use crate::widgets::collection_options;
 use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::{Alignment, Length};
 use cosmic::prelude::*;
@@ -126,7 +127,7 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
         let dialog: Element<'_, Message> = widget::dialog()
             .title(fl!("new-collection"))
             .control(
-                widget::column::with_capacity(2)
+                widget::column::with_capacity(3)
                     .push(widget::text::body(fl!("collection-name")))
                     .push(
                         widget::text_input(
@@ -136,10 +137,16 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
                         .on_input(Message::NewCollectionNameChanged)
                         .width(Length::Fill),
                     )
+                    .push(collection_options::view(
+                        &app.new_collection_options,
+                        Message::NewCollectionOptions,
+                        space_s,
+                    ))
                     .spacing(4),
             )
             .primary_action(widget::button::suggested(fl!("create")).on_press_maybe(
-                if !app.new_collection_name.is_empty() {
+                if !app.new_collection_name.is_empty() && app.new_collection_options.build().is_ok()
+                {
                     Some(Message::CreateCollection)
                 } else {
                     None
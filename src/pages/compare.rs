@@ -0,0 +1,777 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Compare page: the differences between two collections.
+//!
+//! The collections are chosen by server, tenant, database and name, so they
+//! can live on different servers. Running the comparison is left to the app,
+//! which owns the tasks; this page only tracks its state and shows the
+//! differences it found.
+
+use super::browser::{SPARKLINE_WIDTH, l2_norm, sparkline, truncate_chars};
+use crate::api::{CancelToken, Document};
+use crate::config::ServerConfig;
+use crate::fl;
+use crate::transfer::{
+    CollectionDiff, CollectionSource, CompareProgress, DEFAULT_TOLERANCE, DiffEntry, DiffKind,
+    Difference, metadata_changes,
+};
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget::{self, icon};
+use std::collections::BTreeSet;
+
+/// Differences listed per page of the table
+const PAGE_SIZE: usize = 100;
+
+/// One of the two collections being compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// A collection chosen by where it lives and its name
+#[derive(Debug, Clone, Default)]
+pub struct CollectionPicker {
+    /// Index in the config of the server
+    pub server: usize,
+    pub tenant: String,
+    pub database: String,
+    pub name: String,
+}
+
+/// Where a comparison stands.
+#[derive(Debug, Clone)]
+pub enum CompareStatus {
+    /// Choosing the collections
+    Ready,
+    /// Comparing, or done comparing
+    Comparing {
+        progress: CompareProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the compare page.
+#[derive(Debug, Clone)]
+pub struct CompareState {
+    /// All configured servers, by index in the config
+    pub servers: Vec<ServerConfig>,
+    /// Names of `servers` for the dropdowns
+    pub server_names: Vec<String>,
+    pub a: CollectionPicker,
+    pub b: CollectionPicker,
+    /// Largest difference between embedding values that counts as equal
+    pub tolerance: String,
+    pub status: CompareStatus,
+    /// Names of collections A and B as of the last comparison
+    pub compared: [String; 2],
+    /// Kind of differences listed, or every difference
+    pub filter: Option<DiffKind>,
+    /// Page of the listed differences
+    pub page: usize,
+    /// ID of the difference shown side by side
+    pub selected: Option<String>,
+}
+
+/// Messages emitted by the compare page.
+#[derive(Debug, Clone)]
+pub enum CompareMsg {
+    ServerSelected(Side, usize),
+    TenantChanged(Side, String),
+    DatabaseChanged(Side, String),
+    NameChanged(Side, String),
+    /// Exchange collections A and B
+    Swap,
+    ToleranceChanged(String),
+    /// Start comparing
+    Start,
+    Progress(CompareProgress),
+    /// Stop the running comparison
+    Cancel,
+    /// List only one kind of differences, or all of them
+    FilterSelected(Option<DiffKind>),
+    PrevPage,
+    NextPage,
+    /// Show a difference side by side, or hide it again
+    Select(String),
+}
+
+impl CompareState {
+    pub fn new(servers: &[ServerConfig]) -> Self {
+        let mut state = Self {
+            servers: Vec::new(),
+            server_names: Vec::new(),
+            a: CollectionPicker::default(),
+            b: CollectionPicker::default(),
+            tolerance: DEFAULT_TOLERANCE.to_string(),
+            status: CompareStatus::Ready,
+            compared: Default::default(),
+            filter: None,
+            page: 0,
+            selected: None,
+        };
+        state.set_servers(servers);
+        state.select_server(Side::A, 0);
+        state.select_server(Side::B, 0);
+        state
+    }
+
+    /// Takes over the configured servers, which may have changed since the
+    /// page was last shown.
+    pub fn set_servers(&mut self, servers: &[ServerConfig]) {
+        self.servers = servers.to_vec();
+        self.server_names = servers.iter().map(|server| server.name.clone()).collect();
+        for side in [Side::A, Side::B] {
+            if self.picker(side).server >= self.servers.len() {
+                self.select_server(side, 0);
+            }
+        }
+    }
+
+    /// Chooses a collection as A, keeping B on the same database unless a
+    /// collection was already chosen there.
+    pub fn set_a(&mut self, server: usize, tenant: String, database: String, name: String) {
+        if self.is_running() {
+            return;
+        }
+        self.a = CollectionPicker {
+            server,
+            tenant,
+            database,
+            name,
+        };
+        if self.b.name.trim().is_empty() {
+            self.b = CollectionPicker {
+                name: String::new(),
+                ..self.a.clone()
+            };
+        }
+    }
+
+    /// Handles a page message.
+    pub fn update(&mut self, message: CompareMsg) {
+        match message {
+            CompareMsg::ServerSelected(side, index) => self.select_server(side, index),
+            CompareMsg::TenantChanged(side, value) => self.picker_mut(side).tenant = value,
+            CompareMsg::DatabaseChanged(side, value) => self.picker_mut(side).database = value,
+            CompareMsg::NameChanged(side, value) => self.picker_mut(side).name = value,
+            CompareMsg::Swap => std::mem::swap(&mut self.a, &mut self.b),
+            CompareMsg::ToleranceChanged(value) => self.tolerance = value,
+            CompareMsg::Start => {
+                self.compared = [
+                    self.a.name.trim().to_string(),
+                    self.b.name.trim().to_string(),
+                ];
+                self.filter = None;
+                self.page = 0;
+                self.selected = None;
+                self.status = CompareStatus::Comparing {
+                    progress: CompareProgress::Running {
+                        done: 0,
+                        total: None,
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            CompareMsg::Progress(update) => {
+                if let CompareStatus::Comparing { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            CompareMsg::Cancel => {
+                if let CompareStatus::Comparing { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            CompareMsg::FilterSelected(filter) => {
+                self.filter = filter;
+                self.page = 0;
+            }
+            CompareMsg::PrevPage => self.page = self.page.saturating_sub(1),
+            CompareMsg::NextPage => {
+                let entries = self.entries().len();
+                if (self.page + 1) * PAGE_SIZE < entries {
+                    self.page += 1;
+                }
+            }
+            CompareMsg::Select(id) => {
+                self.selected = match self.selected {
+                    Some(ref selected) if *selected == id => None,
+                    _ => Some(id),
+                };
+            }
+        }
+    }
+
+    /// Whether a comparison is running.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, CompareStatus::Comparing { progress, .. } if !progress.is_done())
+    }
+
+    /// The tolerance as entered, if it is a valid one.
+    pub fn tolerance(&self) -> Option<f32> {
+        self.tolerance
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|tolerance| tolerance.is_finite() && *tolerance >= 0.0)
+    }
+
+    /// Whether two different collections and a tolerance are chosen.
+    pub fn is_valid(&self) -> bool {
+        let complete = |picker: &CollectionPicker| {
+            picker.server < self.servers.len()
+                && !picker.tenant.trim().is_empty()
+                && !picker.database.trim().is_empty()
+                && !picker.name.trim().is_empty()
+        };
+        let key = |picker: &CollectionPicker| {
+            (
+                picker.server,
+                picker.tenant.trim().to_string(),
+                picker.database.trim().to_string(),
+                picker.name.trim().to_string(),
+            )
+        };
+        complete(&self.a)
+            && complete(&self.b)
+            && key(&self.a) != key(&self.b)
+            && self.tolerance().is_some()
+    }
+
+    /// A chosen collection; its ID is looked up when the comparison starts.
+    pub fn source(&self, side: Side) -> Option<CollectionSource> {
+        let picker = self.picker(side);
+        Some(CollectionSource {
+            server: self.servers.get(picker.server)?.clone(),
+            tenant: picker.tenant.trim().to_string(),
+            database: picker.database.trim().to_string(),
+            collection_id: String::new(),
+            collection_name: picker.name.trim().to_string(),
+        })
+    }
+
+    /// Differences found by the finished comparison.
+    pub fn diff(&self) -> Option<&CollectionDiff> {
+        match &self.status {
+            CompareStatus::Comparing {
+                progress: CompareProgress::Finished(diff),
+                ..
+            } => Some(diff),
+            _ => None,
+        }
+    }
+
+    /// Differences of the chosen kind.
+    fn entries(&self) -> Vec<&DiffEntry> {
+        self.diff()
+            .map(|diff| {
+                diff.entries
+                    .iter()
+                    .filter(|entry| self.filter.is_none_or(|kind| entry.is(kind)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn picker(&self, side: Side) -> &CollectionPicker {
+        match side {
+            Side::A => &self.a,
+            Side::B => &self.b,
+        }
+    }
+
+    fn picker_mut(&mut self, side: Side) -> &mut CollectionPicker {
+        match side {
+            Side::A => &mut self.a,
+            Side::B => &mut self.b,
+        }
+    }
+
+    fn select_server(&mut self, side: Side, index: usize) {
+        let Some(server) = self.servers.get(index) else {
+            return;
+        };
+        let (tenant, database) = (server.tenant.clone(), server.database.clone());
+        let picker = self.picker_mut(side);
+        picker.server = index;
+        picker.tenant = tenant;
+        picker.database = database;
+    }
+}
+
+/// Label of a kind of differences.
+fn kind_label(kind: DiffKind) -> String {
+    match kind {
+        DiffKind::OnlyInA => fl!("compare-only-in-a"),
+        DiffKind::OnlyInB => fl!("compare-only-in-b"),
+        DiffKind::Document => fl!("compare-text-differs"),
+        DiffKind::Metadata => fl!("compare-metadata-differs"),
+        DiffKind::Embedding => fl!("compare-embedding-differs"),
+    }
+}
+
+/// Short description of how a document differs.
+fn difference_label(difference: Difference) -> String {
+    match difference {
+        Difference::OnlyInA => fl!("compare-only-in-a"),
+        Difference::OnlyInB => fl!("compare-only-in-b"),
+        Difference::Changed(changes) => [
+            (changes.document, fl!("compare-changed-text")),
+            (changes.metadata, fl!("compare-changed-metadata")),
+            (changes.embedding, fl!("compare-changed-embedding")),
+        ]
+        .into_iter()
+        .filter(|(changed, _)| *changed)
+        .map(|(_, label)| label)
+        .collect::<Vec<_>>()
+        .join(", "),
+    }
+}
+
+/// Accent color for what differs from the other side.
+fn text_class(changed: bool) -> cosmic::style::Text {
+    if changed {
+        cosmic::style::Text::Accent
+    } else {
+        cosmic::style::Text::Default
+    }
+}
+
+/// Text describing the progress of a comparison.
+fn progress_label(progress: &CompareProgress) -> String {
+    match progress {
+        CompareProgress::Running {
+            done,
+            total: Some(total),
+        } => fl!("compare-read-of", done = *done, total = *total),
+        CompareProgress::Running { done, total: None } => fl!("compare-read", done = *done),
+        CompareProgress::Finished(diff) => fl!(
+            "compare-finished",
+            differ = diff.entries.len(),
+            identical = diff.identical
+        ),
+        CompareProgress::Cancelled => fl!("compare-cancelled"),
+        CompareProgress::Failed(error) => fl!("compare-failed", error = error),
+    }
+}
+
+/// Renders the inputs choosing one of the collections.
+fn render_picker<'a, Message: Clone + 'static>(
+    state: &'a CompareState,
+    side: Side,
+    on_message: impl Fn(CompareMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let picker = state.picker(side);
+    let field = |label: String, input: Element<'a, Message>| {
+        widget::row::with_capacity(2)
+            .push(widget::text::body(label).width(Length::Fixed(90.0)))
+            .push(input)
+            .spacing(space_s)
+            .align_y(Alignment::Center)
+    };
+    let input = move |placeholder: String,
+                      value: &'a str,
+                      message: fn(Side, String) -> CompareMsg|
+          -> Element<'a, Message> {
+        widget::text_input(placeholder, value)
+            .on_input(move |s| on_message(message(side, s)))
+            .width(Length::Fill)
+            .into()
+    };
+
+    let column = widget::column::with_capacity(5)
+        .push(widget::text::title4(match side {
+            Side::A => fl!("compare-collection-a"),
+            Side::B => fl!("compare-collection-b"),
+        }))
+        .push(field(
+            fl!("compare-server"),
+            widget::dropdown(
+                &state.server_names,
+                Some(picker.server).filter(|index| *index < state.server_names.len()),
+                move |index| on_message(CompareMsg::ServerSelected(side, index)),
+            )
+            .width(Length::Fill)
+            .into(),
+        ))
+        .push(field(
+            fl!("tenant"),
+            input(fl!("tenant"), &picker.tenant, CompareMsg::TenantChanged),
+        ))
+        .push(field(
+            fl!("database"),
+            input(
+                fl!("database"),
+                &picker.database,
+                CompareMsg::DatabaseChanged,
+            ),
+        ))
+        .push(field(
+            fl!("compare-collection"),
+            input(
+                fl!("compare-collection-placeholder"),
+                &picker.name,
+                CompareMsg::NameChanged,
+            ),
+        ))
+        .spacing(space_s);
+
+    widget::container(column)
+        .padding(space_s)
+        .width(Length::Fill)
+        .class(cosmic::style::Container::Card)
+        .into()
+}
+
+/// Renders the buttons narrowing the table to a kind of differences.
+fn render_filters<'a, Message: Clone + 'static>(
+    state: &'a CompareState,
+    diff: &'a CollectionDiff,
+    on_message: impl Fn(CompareMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let button = |label: String, filter: Option<DiffKind>| {
+        let button = if state.filter == filter {
+            widget::button::suggested(label)
+        } else {
+            widget::button::standard(label)
+        };
+        button.on_press(on_message(CompareMsg::FilterSelected(filter)))
+    };
+
+    let mut row = widget::row::with_capacity(DiffKind::ALL.len() + 1)
+        .push(button(
+            format!("{} ({})", fl!("compare-all"), diff.entries.len()),
+            None,
+        ))
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+    // Embeddings that were not read cannot differ
+    let kinds = DiffKind::ALL
+        .into_iter()
+        .filter(|kind| diff.embeddings_compared || *kind != DiffKind::Embedding);
+    for kind in kinds {
+        row = row.push(button(
+            format!("{} ({})", kind_label(kind), diff.count(kind)),
+            Some(kind),
+        ));
+    }
+    row.into()
+}
+
+/// Renders the listed differences with pagination.
+fn render_table<'a, Message: Clone + 'static>(
+    state: &'a CompareState,
+    on_message: impl Fn(CompareMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let entries = state.entries();
+    // The document columns make way for the side-by-side panes
+    let compact = state.selected.is_some();
+
+    let cell = |text: String, portion: u16| {
+        widget::text::caption(text).width(Length::FillPortion(portion))
+    };
+    let summary = |document: Option<&Document>| match document {
+        Some(document) => match &document.document {
+            Some(text) => truncate_chars(text, 80),
+            None => fl!("compare-no-content"),
+        },
+        None => "—".to_string(),
+    };
+
+    let mut header = widget::row::with_capacity(4)
+        .push(widget::text::heading(fl!("compare-id")).width(Length::FillPortion(2)))
+        .spacing(space_s);
+    if !compact {
+        header = header
+            .push(
+                widget::text::heading(format!("A: {}", state.compared[0]))
+                    .width(Length::FillPortion(3)),
+            )
+            .push(
+                widget::text::heading(format!("B: {}", state.compared[1]))
+                    .width(Length::FillPortion(3)),
+            );
+    }
+    header = header
+        .push(widget::text::heading(fl!("compare-differences")).width(Length::FillPortion(2)));
+
+    let start = state.page * PAGE_SIZE;
+    let mut rows = widget::column::with_capacity(PAGE_SIZE).spacing(2);
+    for entry in entries.iter().skip(start).take(PAGE_SIZE).copied() {
+        let mut row = widget::row::with_capacity(4)
+            .push(widget::text::body(entry.id.as_str()).width(Length::FillPortion(2)))
+            .spacing(space_s)
+            .align_y(Alignment::Center);
+        if !compact {
+            row = row
+                .push(cell(summary(entry.a.as_ref()), 3))
+                .push(cell(summary(entry.b.as_ref()), 3));
+        }
+        row = row.push(cell(difference_label(entry.difference), 2));
+
+        let container_class = if state.selected.as_ref() == Some(&entry.id) {
+            cosmic::style::Container::Primary
+        } else {
+            cosmic::style::Container::Card
+        };
+        rows = rows.push(
+            widget::mouse_area(
+                widget::container(row)
+                    .padding([4, 8])
+                    .width(Length::Fill)
+                    .class(container_class),
+            )
+            .on_press(on_message(CompareMsg::Select(entry.id.clone()))),
+        );
+    }
+    if entries.is_empty() {
+        rows = rows.push(widget::text::body(fl!("compare-no-differences")));
+    }
+
+    let total_pages = entries.len().div_ceil(PAGE_SIZE).max(1);
+    let pagination = widget::row::with_capacity(3)
+        .push(
+            widget::button::icon(icon::from_name("go-previous-symbolic"))
+                .class(cosmic::theme::Button::Standard)
+                .on_press_maybe((state.page > 0).then(|| on_message(CompareMsg::PrevPage))),
+        )
+        .push(widget::text::body(format!(
+            "{} {} / {}",
+            fl!("page"),
+            state.page + 1,
+            total_pages
+        )))
+        .push(
+            widget::button::icon(icon::from_name("go-next-symbolic"))
+                .class(cosmic::theme::Button::Standard)
+                .on_press_maybe(
+                    (state.page + 1 < total_pages).then(|| on_message(CompareMsg::NextPage)),
+                ),
+        )
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+
+    widget::column::with_capacity(3)
+        .push(header.padding([0, 8]))
+        .push(
+            widget::scrollable(rows)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .push(pagination)
+        .spacing(space_s)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+/// Renders one side of a difference, marking what differs from the other
+/// side in the accent color.
+fn render_document_pane<'a, Message: Clone + 'static>(
+    title: String,
+    document: Option<&'a Document>,
+    other: Option<&'a Document>,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let card = |content: Element<'a, Message>| {
+        widget::container(content)
+            .padding(space_s)
+            .width(Length::Fill)
+            .class(cosmic::style::Container::Card)
+    };
+
+    let mut content = widget::column::with_capacity(8)
+        .push(widget::text::title4(title))
+        .spacing(space_s);
+    let Some(document) = document else {
+        content = content.push(widget::text::caption(fl!("compare-not-in-collection")));
+        return widget::scrollable(content)
+            .width(Length::Fixed(350.0))
+            .height(Length::Fill)
+            .into();
+    };
+    // Without a counterpart nothing is marked
+    let other = other.unwrap_or(document);
+
+    content = content.push(widget::text::title4(fl!("document-content")));
+    content = content.push(card(
+        match document.document.as_deref() {
+            Some(text) => widget::text::body(text),
+            None => widget::text::body(fl!("compare-no-content")),
+        }
+        .class(text_class(document.document != other.document))
+        .into(),
+    ));
+
+    let changed_keys: BTreeSet<String> =
+        metadata_changes(document.metadata.as_ref(), other.metadata.as_ref());
+    content = content.push(widget::text::title4(fl!("metadata")));
+    let metadata: Element<'a, Message> = match &document.metadata {
+        Some(metadata) if !metadata.is_empty() => {
+            let mut keys: Vec<&String> = metadata.keys().collect();
+            keys.sort();
+            let mut column = widget::column::with_capacity(keys.len()).spacing(4);
+            for key in keys {
+                let changed = changed_keys.contains(key);
+                column = column.push(
+                    widget::row::with_capacity(2)
+                        .push(
+                            widget::text::body(format!("{}:", key))
+                                .width(Length::Fixed(120.0))
+                                .class(text_class(changed)),
+                        )
+                        .push(
+                            widget::text::caption(metadata[key].to_string())
+                                .class(text_class(changed)),
+                        )
+                        .spacing(8),
+                );
+            }
+            column.into()
+        }
+        _ => widget::text::caption(fl!("compare-no-metadata"))
+            .class(text_class(!changed_keys.is_empty()))
+            .into(),
+    };
+    content = content.push(card(metadata));
+
+    content = content.push(widget::text::title4(fl!("compare-embedding")));
+    let embedding: Element<'a, Message> = match (&document.embedding, &other.embedding) {
+        (Some(embedding), other_embedding) => {
+            let mut column = widget::column::with_capacity(3)
+                .push(widget::text::body(fl!(
+                    "compare-embedding-summary",
+                    dimensions = embedding.len(),
+                    norm = format!("{:.4}", l2_norm(embedding))
+                )))
+                .push(widget::text::body(sparkline(embedding, SPARKLINE_WIDTH)))
+                .spacing(8);
+            if let Some(other_embedding) = other_embedding
+                && other_embedding.len() == embedding.len()
+            {
+                let largest = embedding
+                    .iter()
+                    .zip(other_embedding)
+                    .map(|(x, y)| (x - y).abs())
+                    .fold(0.0, f32::max);
+                if largest > 0.0 {
+                    column = column.push(
+                        widget::text::caption(fl!(
+                            "compare-largest-difference",
+                            difference = format!("{:.6}", largest)
+                        ))
+                        .class(cosmic::style::Text::Accent),
+                    );
+                }
+            }
+            column.into()
+        }
+        (None, other_embedding) => widget::text::caption(fl!("compare-no-embedding"))
+            .class(text_class(other_embedding.is_some()))
+            .into(),
+    };
+    content = content.push(card(embedding));
+
+    widget::scrollable(content)
+        .width(Length::Fixed(350.0))
+        .height(Length::Fill)
+        .into()
+}
+
+/// View for the Compare page
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a CompareState,
+    on_message: impl Fn(CompareMsg) -> Message + Copy + 'static,
+    space_s: u16,
+    space_m: u16,
+) -> Element<'a, Message> {
+    let pickers = widget::row::with_capacity(3)
+        .push(render_picker(state, Side::A, on_message, space_s))
+        .push(
+            widget::button::standard(fl!("compare-swap"))
+                .on_press_maybe((!state.is_running()).then(|| on_message(CompareMsg::Swap))),
+        )
+        .push(render_picker(state, Side::B, on_message, space_s))
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+
+    let action = if state.is_running() {
+        widget::button::standard(fl!("cancel")).on_press(on_message(CompareMsg::Cancel))
+    } else {
+        widget::button::suggested(fl!("compare-start"))
+            .on_press_maybe(state.is_valid().then(|| on_message(CompareMsg::Start)))
+    };
+    let mut toolbar = widget::row::with_capacity(4)
+        .push(widget::text::body(fl!("compare-tolerance")))
+        .push(
+            widget::text_input(fl!("compare-tolerance-placeholder"), &state.tolerance)
+                .on_input(move |s| on_message(CompareMsg::ToleranceChanged(s)))
+                .width(Length::Fixed(120.0)),
+        )
+        .push(action)
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+    if let CompareStatus::Comparing { progress, .. } = &state.status {
+        if let Some(fraction) = progress.fraction()
+            && !progress.is_done()
+        {
+            toolbar =
+                toolbar.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fixed(200.0)));
+        }
+        let label = widget::text::body(progress_label(progress));
+        toolbar = toolbar.push(match progress {
+            CompareProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+            _ => label,
+        });
+    }
+
+    let mut content = widget::column::with_capacity(6)
+        .push(widget::text::title1(fl!("compare-title")))
+        .push(widget::text::caption(fl!("compare-description")))
+        .push(pickers)
+        .push(toolbar)
+        .spacing(space_m)
+        .height(Length::Fill);
+
+    if let Some(diff) = state.diff() {
+        if !diff.embeddings_compared {
+            content = content.push(widget::text::caption(fl!("compare-embeddings-skipped")));
+        }
+        content = content.push(render_filters(state, diff, on_message, space_s));
+
+        let selected = state
+            .selected
+            .as_ref()
+            .and_then(|id| diff.entries.iter().find(|entry| entry.id == *id));
+        let mut results = widget::row::with_capacity(3)
+            .push(render_table(state, on_message, space_s))
+            .spacing(space_m)
+            .height(Length::Fill);
+        if let Some(entry) = selected {
+            results = results
+                .push(render_document_pane(
+                    format!("A: {}", state.compared[0]),
+                    entry.a.as_ref(),
+                    entry.b.as_ref(),
+                    space_s,
+                ))
+                .push(render_document_pane(
+                    format!("B: {}", state.compared[1]),
+                    entry.b.as_ref(),
+                    entry.a.as_ref(),
+                    space_s,
+                ));
+        }
+        content = content.push(results);
+    }
+
+    widget::container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -10,6 +10,7 @@ use cosmic::prelude::*;
 use cosmic::widget::{self, icon};
 
 use super::widgets::{connection_status_badge, document_card};
+use crate::widgets::filter_builder;
 
 /// View for the Documents page (when a collection is selected)
 pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message> {
@@ -31,6 +32,15 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
 
     let refresh_button = widget::button::standard(fl!("refresh")).on_press(Message::FetchDocuments);
 
+    // Highlight the filter toggle while a filter is applied
+    let filter_label = if app.documents_filter.is_active() {
+        fl!("filter-active")
+    } else {
+        fl!("filter")
+    };
+    let filter_button =
+        widget::button::standard(filter_label).on_press(Message::ToggleDocumentsFilter);
+
     // Show page info in toolbar with total count if available
     let page_info = if let Some(total) = app.documents_total {
         widget::text::body(format!(
@@ -52,8 +62,17 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
         ))
     };
 
-    let toolbar = widget::row::with_capacity(3)
+    // Exports what the filter currently shows, or the whole collection
+    let export_button = widget::button::standard(fl!("export-collection")).on_press_maybe(
+        app.selected_collection
+            .clone()
+            .map(Message::ExportCollection),
+    );
+
+    let toolbar = widget::row::with_capacity(4)
         .push(refresh_button)
+        .push(filter_button)
+        .push(export_button)
         .push(page_info)
         .spacing(space_s)
         .align_y(Alignment::Center);
@@ -63,7 +82,9 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
             ConnectionStatus::Disconnected => fl!("not-connected"),
             ConnectionStatus::Connecting => fl!("loading-documents"),
             ConnectionStatus::Connected => {
-                if app.documents_page > 0 {
+                if app.documents_filter.is_active() {
+                    fl!("no-matching-documents")
+                } else if app.documents_page > 0 {
                     fl!("no-more-documents")
                 } else {
                     fl!("no-documents")
@@ -130,6 +151,32 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
             .into()
     };
 
+    // Show the filter panel beside the list when toggled
+    let content: Element<'_, Message> = if app.show_documents_filter {
+        let filter_panel = widget::container(
+            widget::scrollable(filter_builder::view(
+                &app.documents_filter,
+                Message::DocumentsFilter,
+                space_s,
+            ))
+            .height(Length::Fill),
+        )
+        .padding(space_s)
+        .width(Length::Fixed(340.0))
+        .height(Length::Fill)
+        .class(cosmic::style::Container::Card);
+
+        widget::row::with_capacity(2)
+            .push(content)
+            .push(filter_panel)
+            .spacing(space_m)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    } else {
+        content
+    };
+
     // Build the main page content
     let main_content = widget::column::with_capacity(3)
         .push(header)
@@ -5,7 +5,9 @@
 
 pub mod browser;
 pub mod collections;
+pub mod compare;
 pub mod dashboard;
 pub mod documents;
 pub mod settings;
+pub mod visualize;
 pub mod widgets;
@@ -3,7 +3,9 @@
 //! Settings page view for the Chromatic application.
 
 use crate::app::{AppModel, Message, SettingsStatus};
+use crate::credentials::VaultState;
 use crate::fl;
+use crate::widgets::{auth_form, connection_form};
 use cosmic::iced::{Alignment, Length};
 use cosmic::prelude::*;
 use cosmic::widget::{self, icon};
@@ -61,47 +63,23 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
                 ),
         )
         .add(
-            cosmic::widget::settings::item::builder(fl!("auth-token"))
-                .description(fl!("auth-token-description"))
+            cosmic::widget::settings::item::builder(fl!("auth-method"))
+                .description(fl!("auth-method-description"))
                 .flex_control(
-                    widget::secure_input(
-                        fl!("auth-token-placeholder"),
-                        &app.auth_token_input,
-                        None,
-                        true,
-                    )
-                    .on_input(Message::AuthTokenChanged)
-                    .width(Length::Fixed(300.0)),
+                    widget::container(auth_form::view(&app.auth_form, Message::Auth, space_s))
+                        .width(Length::Fixed(300.0)),
                 ),
         )
         .add(
-            cosmic::widget::settings::item::builder(fl!("auth-header-type"))
-                .description(fl!("auth-header-type-description"))
+            cosmic::widget::settings::item::builder(fl!("tls-proxy"))
+                .description(fl!("tls-proxy-description"))
                 .flex_control(
-                    widget::row::with_capacity(2)
-                        .push(
-                            widget::button::text("Authorization: Bearer")
-                                .class(if app.auth_header_type_input == "authorization" {
-                                    cosmic::theme::Button::Suggested
-                                } else {
-                                    cosmic::theme::Button::Standard
-                                })
-                                .on_press(Message::AuthHeaderTypeChanged(
-                                    "authorization".to_string(),
-                                )),
-                        )
-                        .push(
-                            widget::button::text("X-Chroma-Token")
-                                .class(if app.auth_header_type_input == "x-chroma-token" {
-                                    cosmic::theme::Button::Suggested
-                                } else {
-                                    cosmic::theme::Button::Standard
-                                })
-                                .on_press(Message::AuthHeaderTypeChanged(
-                                    "x-chroma-token".to_string(),
-                                )),
-                        )
-                        .spacing(space_s),
+                    widget::container(connection_form::view(
+                        &app.connection_form,
+                        Message::Connection,
+                        space_s,
+                    ))
+                    .width(Length::Fixed(300.0)),
                 ),
         )
         .add(
@@ -205,6 +183,129 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
                 }),
         );
 
+    // Timeouts and retries
+    let limits_section = cosmic::widget::settings::section()
+        .title(fl!("connection-limits"))
+        .add(
+            cosmic::widget::settings::item::builder(fl!("connect-timeout"))
+                .description(fl!("connect-timeout-description"))
+                .flex_control(limit_input(
+                    &app.connect_timeout_input,
+                    Message::ConnectTimeoutChanged,
+                )),
+        )
+        .add(
+            cosmic::widget::settings::item::builder(fl!("request-timeout"))
+                .description(fl!("request-timeout-description"))
+                .flex_control(limit_input(
+                    &app.request_timeout_input,
+                    Message::RequestTimeoutChanged,
+                )),
+        )
+        .add(
+            cosmic::widget::settings::item::builder(fl!("max-retries"))
+                .description(fl!("max-retries-description"))
+                .flex_control(limit_input(
+                    &app.max_retries_input,
+                    Message::MaxRetriesChanged,
+                )),
+        );
+
+    // Credential vault
+    let vault_status = match app.vault_state {
+        VaultState::Missing => fl!("vault-status-missing"),
+        VaultState::Locked => fl!("vault-status-locked"),
+        VaultState::Unlocked => fl!("vault-status-unlocked"),
+    };
+    let mut vault_column = widget::column::with_capacity(3).spacing(space_s);
+    match app.vault_state {
+        VaultState::Unlocked => {
+            vault_column = vault_column
+                .push(widget::button::standard(fl!("vault-lock")).on_press(Message::LockVault));
+        }
+        VaultState::Missing => {
+            let passphrase = &app.vault_passphrase_input;
+            let confirmed = !passphrase.is_empty() && app.vault_confirm_input == *passphrase;
+            let label = if app.vault_unlocking {
+                fl!("vault-creating")
+            } else {
+                fl!("vault-create")
+            };
+            vault_column = vault_column
+                .push(passphrase_input(
+                    fl!("vault-passphrase"),
+                    passphrase,
+                    Message::VaultPassphraseChanged,
+                    Message::CreateVault,
+                ))
+                .push(
+                    widget::row::with_capacity(2)
+                        .push(passphrase_input(
+                            fl!("vault-passphrase-confirm"),
+                            &app.vault_confirm_input,
+                            Message::VaultConfirmChanged,
+                            Message::CreateVault,
+                        ))
+                        .push(widget::button::suggested(label).on_press_maybe(
+                            (!app.vault_unlocking && confirmed).then_some(Message::CreateVault),
+                        ))
+                        .spacing(space_s)
+                        .align_y(Alignment::Center),
+                );
+            if !app.vault_confirm_input.is_empty() && !confirmed {
+                vault_column = vault_column.push(
+                    widget::text::caption(fl!("vault-passphrase-mismatch"))
+                        .class(cosmic::style::Text::Accent),
+                );
+            }
+        }
+        VaultState::Locked => {
+            let label = if app.vault_unlocking {
+                fl!("vault-unlocking")
+            } else {
+                fl!("vault-unlock")
+            };
+            vault_column = vault_column.push(
+                widget::row::with_capacity(2)
+                    .push(passphrase_input(
+                        fl!("vault-passphrase"),
+                        &app.vault_passphrase_input,
+                        Message::VaultPassphraseChanged,
+                        Message::UnlockVault,
+                    ))
+                    .push(
+                        widget::button::suggested(label).on_press_maybe(
+                            (!app.vault_unlocking && !app.vault_passphrase_input.is_empty())
+                                .then_some(Message::UnlockVault),
+                        ),
+                    )
+                    .spacing(space_s)
+                    .align_y(Alignment::Center),
+            );
+        }
+    }
+    if let Some(ref error) = app.vault_error {
+        vault_column = vault_column.push(
+            widget::text::caption(format!("{}: {}", fl!("error"), error))
+                .class(cosmic::style::Text::Accent),
+        );
+    }
+
+    let vault_section = cosmic::widget::settings::section()
+        .title(fl!("credential-vault"))
+        .add(
+            cosmic::widget::settings::item::builder(fl!("vault-status"))
+                .description(vault_status)
+                .flex_control(vault_column),
+        )
+        .add(
+            cosmic::widget::settings::item::builder(fl!("secret-references"))
+                .description(fl!("secret-references-description"))
+                .control(widget::text::caption(
+                    "env:CHROMA_TOKEN\nfile:/path/to/token",
+                )),
+        );
+
     // Add delete button if there's more than one server
     if app.config.servers.len() > 1 {
         server_section = server_section.add(
@@ -251,10 +352,12 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
     }
 
     widget::scrollable(
-        widget::column::with_capacity(4)
+        widget::column::with_capacity(6)
             .push(header)
             .push(servers_section)
             .push(server_section)
+            .push(limits_section)
+            .push(vault_section)
             .push(buttons)
             .spacing(space_m)
             .width(Length::Fill),
@@ -262,3 +365,25 @@ pub fn view(app: &AppModel, space_s: u16, space_m: u16) -> Element<'_, Message>
     .height(Length::Fill)
     .into()
 }
+
+/// Small numeric input for a timeout or retry count
+fn limit_input(value: &str, on_input: fn(String) -> Message) -> Element<'_, Message> {
+    widget::text_input("", value)
+        .on_input(on_input)
+        .width(Length::Fixed(100.0))
+        .into()
+}
+
+/// Hidden input for a vault passphrase, submitting `on_submit` on Enter
+fn passphrase_input(
+    placeholder: String,
+    value: &str,
+    on_input: fn(String) -> Message,
+    on_submit: Message,
+) -> Element<'_, Message> {
+    widget::secure_input(placeholder, value, None, true)
+        .on_input(on_input)
+        .on_submit(move |_| on_submit.clone())
+        .width(Length::Fixed(200.0))
+        .into()
+}
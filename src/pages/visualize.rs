@@ -0,0 +1,602 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Visualize page: the embeddings of a collection as a 2D scatter plot.
+//!
+//! A sample of the collection is projected to two dimensions and drawn as
+//! dots, colored by a metadata key. Fetching the sample and projecting it are
+//! left to the app, which owns the tasks; this page only tracks their state.
+
+use crate::api::Document;
+use crate::fl;
+use crate::projection::Method;
+use crate::transfer::CollectionSource;
+use cosmic::iced::widget::{Stack, container};
+use cosmic::iced::{Alignment, Background, Border, Color, Length, Padding};
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::collections::{BTreeSet, HashMap};
+
+/// Most documents fetched and projected
+pub const SAMPLE_SIZE: usize = 1000;
+
+/// Dropdown labels of `Method::ALL`
+const METHOD_LABELS: [&str; 2] = ["PCA", "t-SNE"];
+
+/// Colors of the most common values of a key, in order
+const PALETTE: [(u8, u8, u8); 9] = [
+    (0x4e, 0x79, 0xa7),
+    (0xf2, 0x8e, 0x2b),
+    (0xe1, 0x57, 0x59),
+    (0x76, 0xb7, 0xb2),
+    (0x59, 0xa1, 0x4f),
+    (0xed, 0xc9, 0x48),
+    (0xb0, 0x7a, 0xa1),
+    (0xff, 0x9d, 0xa7),
+    (0x9c, 0x75, 0x5f),
+];
+
+/// Color of the remaining values
+const OTHER: (u8, u8, u8) = (0xba, 0xb0, 0xac);
+
+/// Color of documents without the key
+const MISSING: (u8, u8, u8) = (0x79, 0x70, 0x6e);
+
+/// Ends of the scale numeric values are colored on
+const SCALE: [(u8, u8, u8); 2] = [(0x3b, 0x52, 0x8b), (0xfd, 0xe7, 0x25)];
+
+const DOT_SIZE: f32 = 8.0;
+
+const SELECTED_DOT_SIZE: f32 = 14.0;
+
+const LEGEND_WIDTH: f32 = 220.0;
+
+/// Smallest width and height of the plot
+const MIN_PLOT_SIZE: f32 = 240.0;
+
+/// Where the plot stands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VisualizeStatus {
+    /// No collection chosen yet
+    Empty,
+    /// Fetching the sample
+    Loading,
+    /// Projecting the sample with the chosen method
+    Projecting,
+    Ready,
+    Failed(String),
+}
+
+/// State of the visualize page.
+#[derive(Debug, Clone)]
+pub struct VisualizeState {
+    pub source: Option<CollectionSource>,
+    pub method: Method,
+    pub status: VisualizeStatus,
+    /// Samples fetched so far, telling projections of the current one apart
+    pub sample: usize,
+    /// Sampled documents that have an embedding
+    pub documents: Vec<Document>,
+    /// Documents in the collection
+    pub total: usize,
+    /// Sampled documents left out for lack of an embedding
+    pub skipped: usize,
+    /// Position of each of `documents`, once projected
+    pub points: Vec<[f32; 2]>,
+    /// Metadata keys of the sampled documents, sorted
+    pub keys: Vec<String>,
+    /// `keys` after an entry for not coloring, for the dropdown
+    pub key_labels: Vec<String>,
+    /// Metadata key the points are colored by
+    pub color_key: Option<String>,
+    /// Index in `documents` of the point shown in the preview
+    pub selected: Option<usize>,
+}
+
+/// Messages emitted by the visualize page.
+#[derive(Debug, Clone)]
+pub enum VisualizeMsg {
+    /// The sample of the collection with this ID was fetched, with the number
+    /// of documents in the collection
+    Loaded(String, Result<(Vec<Document>, usize), String>),
+    MethodSelected(usize),
+    /// The sample with this number was projected with a method
+    Projected(usize, Method, Result<Vec<[f32; 2]>, String>),
+    /// Index into the key dropdown
+    ColorSelected(usize),
+    /// Show the document of a point in the preview
+    PointSelected(usize),
+    /// Fetch a new sample
+    Reload,
+}
+
+impl Default for VisualizeState {
+    fn default() -> Self {
+        Self {
+            source: None,
+            method: Method::default(),
+            status: VisualizeStatus::Empty,
+            sample: 0,
+            documents: Vec::new(),
+            total: 0,
+            skipped: 0,
+            points: Vec::new(),
+            keys: Vec::new(),
+            key_labels: vec![fl!("visualize-color-none")],
+            color_key: None,
+            selected: None,
+        }
+    }
+}
+
+impl VisualizeState {
+    /// Chooses the collection to plot, to be fetched next.
+    pub fn open(&mut self, source: CollectionSource) {
+        *self = Self {
+            source: Some(source),
+            method: self.method,
+            sample: self.sample,
+            color_key: self.color_key.take(),
+            status: VisualizeStatus::Loading,
+            ..Self::default()
+        };
+    }
+
+    /// Handles a page message.
+    pub fn update(&mut self, message: VisualizeMsg) {
+        match message {
+            VisualizeMsg::Loaded(collection_id, result) => {
+                // A sample of a collection shown before is of no use any more
+                let current = self
+                    .source
+                    .as_ref()
+                    .is_some_and(|source| source.collection_id == collection_id);
+                if !current || self.status != VisualizeStatus::Loading {
+                    return;
+                }
+                match result {
+                    Ok((documents, total)) => self.set_documents(documents, total),
+                    Err(error) => self.status = VisualizeStatus::Failed(error),
+                }
+            }
+            VisualizeMsg::MethodSelected(index) => {
+                if let Some(method) = Method::ALL.get(index).copied()
+                    && method != self.method
+                {
+                    self.method = method;
+                    if matches!(
+                        self.status,
+                        VisualizeStatus::Ready | VisualizeStatus::Projecting
+                    ) && !self.documents.is_empty()
+                    {
+                        self.status = VisualizeStatus::Projecting;
+                    }
+                }
+            }
+            VisualizeMsg::Projected(sample, method, result) => {
+                // Projections of an earlier sample or with a method chosen
+                // before are dropped
+                if sample != self.sample
+                    || method != self.method
+                    || self.status != VisualizeStatus::Projecting
+                {
+                    return;
+                }
+                match result {
+                    Ok(points) if points.len() != self.documents.len() => {
+                        self.status = VisualizeStatus::Failed(fl!(
+                            "visualize-position-mismatch",
+                            positions = points.len(),
+                            documents = self.documents.len()
+                        ));
+                    }
+                    Ok(points) => {
+                        self.points = points;
+                        self.status = VisualizeStatus::Ready;
+                    }
+                    Err(error) => self.status = VisualizeStatus::Failed(error),
+                }
+            }
+            VisualizeMsg::ColorSelected(index) => {
+                self.color_key = index
+                    .checked_sub(1)
+                    .and_then(|index| self.keys.get(index))
+                    .cloned();
+            }
+            VisualizeMsg::PointSelected(index) => {
+                if index < self.documents.len() {
+                    self.selected = Some(index);
+                }
+            }
+            VisualizeMsg::Reload => {
+                if let Some(source) = self.source.take() {
+                    self.open(source);
+                }
+            }
+        }
+    }
+
+    /// Whether the sample is being fetched or projected.
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self.status,
+            VisualizeStatus::Loading | VisualizeStatus::Projecting
+        )
+    }
+
+    /// Embeddings of the sampled documents, in order.
+    pub fn embeddings(&self) -> Vec<Vec<f32>> {
+        self.documents
+            .iter()
+            .filter_map(|document| document.embedding.clone())
+            .collect()
+    }
+
+    /// Document of the point shown in the preview.
+    pub fn selected_document(&self) -> Option<&Document> {
+        self.documents.get(self.selected?)
+    }
+
+    fn set_documents(&mut self, documents: Vec<Document>, total: usize) {
+        let sampled = documents.len();
+        self.sample += 1;
+        self.documents = documents
+            .into_iter()
+            .filter(|document| {
+                document
+                    .embedding
+                    .as_ref()
+                    .is_some_and(|embedding| !embedding.is_empty())
+            })
+            .collect();
+        self.total = total;
+        self.skipped = sampled - self.documents.len();
+
+        let keys: BTreeSet<&String> = self
+            .documents
+            .iter()
+            .filter_map(|document| document.metadata.as_ref())
+            .flat_map(|metadata| metadata.keys())
+            .collect();
+        self.keys = keys.into_iter().cloned().collect();
+        self.key_labels = std::iter::once(fl!("visualize-color-none"))
+            .chain(self.keys.iter().cloned())
+            .collect();
+        // Keep coloring by the same key if the new sample has it
+        if self
+            .color_key
+            .as_ref()
+            .is_some_and(|key| !self.keys.contains(key))
+        {
+            self.color_key = None;
+        }
+
+        self.points.clear();
+        self.selected = None;
+        self.status = if self.documents.is_empty() {
+            VisualizeStatus::Ready
+        } else {
+            VisualizeStatus::Projecting
+        };
+    }
+
+    /// Color of every point, with the legend explaining them.
+    fn coloring(&self) -> (Vec<Color>, Vec<(String, Color)>) {
+        let rgb = |(r, g, b): (u8, u8, u8)| Color::from_rgb8(r, g, b);
+        let Some(key) = &self.color_key else {
+            return (vec![rgb(PALETTE[0]); self.documents.len()], Vec::new());
+        };
+        let values: Vec<Option<&serde_json::Value>> = self
+            .documents
+            .iter()
+            .map(|document| document.metadata.as_ref().and_then(|m| m.get(key)))
+            .collect();
+        let missing = values.iter().filter(|value| value.is_none()).count();
+
+        // Numbers with more values than colors are placed on a scale
+        let numbers: Vec<f64> = values.iter().flatten().filter_map(|v| v.as_f64()).collect();
+        let distinct: BTreeSet<u64> = numbers.iter().map(|n| n.to_bits()).collect();
+        if numbers.len() + missing == values.len() && distinct.len() > PALETTE.len() {
+            let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let [low, high] = SCALE.map(rgb);
+            let colors = values
+                .iter()
+                .map(|value| match value.and_then(|v| v.as_f64()) {
+                    Some(n) => mix(low, high, ((n - min) / (max - min)) as f32),
+                    None => rgb(MISSING),
+                })
+                .collect();
+            let mut legend = vec![(min.to_string(), low), (max.to_string(), high)];
+            if missing > 0 {
+                legend.push((fl!("visualize-missing", count = missing), rgb(MISSING)));
+            }
+            return (colors, legend);
+        }
+
+        let label = |value: &serde_json::Value| match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for value in values.iter().flatten() {
+            *counts.entry(label(value)).or_default() += 1;
+        }
+        let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let palette: HashMap<&str, Color> = ranked
+            .iter()
+            .zip(PALETTE)
+            .map(|((value, _), color)| (value.as_str(), rgb(color)))
+            .collect();
+        let colors = values
+            .iter()
+            .map(|value| match value {
+                Some(value) => palette
+                    .get(label(value).as_str())
+                    .copied()
+                    .unwrap_or(rgb(OTHER)),
+                None => rgb(MISSING),
+            })
+            .collect();
+
+        let mut legend: Vec<(String, Color)> = ranked
+            .iter()
+            .zip(PALETTE)
+            .map(|((value, count), color)| (format!("{} ({})", value, count), rgb(color)))
+            .collect();
+        let others: usize = ranked.iter().skip(PALETTE.len()).map(|(_, n)| n).sum();
+        if others > 0 {
+            legend.push((fl!("visualize-other", count = others), rgb(OTHER)));
+        }
+        if missing > 0 {
+            legend.push((fl!("visualize-missing", count = missing), rgb(MISSING)));
+        }
+        (colors, legend)
+    }
+}
+
+/// Color part of the way from `a` to `b`.
+fn mix(a: Color, b: Color, amount: f32) -> Color {
+    let amount = if amount.is_finite() { amount } else { 0.5 };
+    Color::from_rgb(
+        a.r + (b.r - a.r) * amount,
+        a.g + (b.g - a.g) * amount,
+        a.b + (b.b - a.b) * amount,
+    )
+}
+
+/// A round swatch of a color.
+fn swatch<'a, Message: 'static>(color: Color, size: f32, outlined: bool) -> Element<'a, Message> {
+    widget::container(widget::Space::new(Length::Fixed(size), Length::Fixed(size)))
+        .class(cosmic::style::Container::custom(move |_theme| {
+            container::Style {
+                background: Some(Background::Color(color)),
+                border: Border {
+                    color: Color::WHITE,
+                    width: if outlined { 2.0 } else { 0.0 },
+                    radius: (size / 2.0).into(),
+                },
+                ..Default::default()
+            }
+        }))
+        .into()
+}
+
+/// Text describing the sample and where it stands.
+fn status_label(state: &VisualizeState) -> String {
+    match &state.status {
+        VisualizeStatus::Empty => String::new(),
+        VisualizeStatus::Loading => fl!("visualize-loading"),
+        VisualizeStatus::Projecting => fl!(
+            "visualize-projecting",
+            count = state.documents.len(),
+            method = METHOD_LABELS[Method::ALL
+                .iter()
+                .position(|m| *m == state.method)
+                .unwrap_or(0)]
+        ),
+        VisualizeStatus::Failed(error) => fl!("visualize-failed", error = error),
+        VisualizeStatus::Ready => {
+            let mut label = if state.documents.len() + state.skipped < state.total {
+                fl!(
+                    "visualize-sampled",
+                    count = state.documents.len(),
+                    total = state.total
+                )
+            } else {
+                fl!("visualize-documents", count = state.documents.len())
+            };
+            if state.skipped > 0 {
+                let skipped = fl!("visualize-skipped", count = state.skipped);
+                label = format!("{}, {}", label, skipped);
+            }
+            label
+        }
+    }
+}
+
+/// Renders the points at their projected positions.
+fn render_plot<'a, Message: Clone + 'static>(
+    state: &'a VisualizeState,
+    colors: &[Color],
+    on_message: impl Fn(VisualizeMsg) -> Message + Copy + 'static,
+    width: f32,
+    height: f32,
+) -> Element<'a, Message> {
+    let bounds = |axis: usize| {
+        state
+            .points
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+                (min.min(point[axis]), max.max(point[axis]))
+            })
+    };
+    let ((min_x, max_x), (min_y, max_y)) = (bounds(0), bounds(1));
+    // Points without spread along an axis sit in the middle
+    let scale = |value: f32, min: f32, max: f32| {
+        if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        }
+    };
+
+    let dot = |index: usize, size: f32, selected: bool| -> Element<'a, Message> {
+        let [x, y] = state.points[index];
+        let left =
+            scale(x, min_x, max_x) * (width - SELECTED_DOT_SIZE) + (SELECTED_DOT_SIZE - size) / 2.0;
+        let top = (1.0 - scale(y, min_y, max_y)) * (height - SELECTED_DOT_SIZE)
+            + (SELECTED_DOT_SIZE - size) / 2.0;
+        widget::container(
+            widget::mouse_area(swatch(colors[index], size, selected))
+                .on_press(on_message(VisualizeMsg::PointSelected(index))),
+        )
+        .padding(Padding {
+            top,
+            right: 0.0,
+            bottom: 0.0,
+            left,
+        })
+        .into()
+    };
+
+    let mut layers: Vec<Element<'a, Message>> = Vec::with_capacity(state.points.len() + 2);
+    layers.push(
+        widget::container(widget::Space::new(Length::Fill, Length::Fill))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .class(cosmic::style::Container::Card)
+            .into(),
+    );
+    let count = state.points.len();
+    for index in (0..count).filter(|index| state.selected != Some(*index)) {
+        layers.push(dot(index, DOT_SIZE, false));
+    }
+    // The selected point is drawn last so nothing covers it
+    if let Some(index) = state.selected.filter(|index| *index < count) {
+        layers.push(dot(index, SELECTED_DOT_SIZE, true));
+    }
+
+    Stack::with_children(layers)
+        .width(Length::Fixed(width))
+        .height(Length::Fixed(height))
+        .into()
+}
+
+/// Renders what the colors stand for and the selected document.
+fn render_legend<'a, Message: Clone + 'static>(
+    state: &'a VisualizeState,
+    legend: Vec<(String, Color)>,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut column = widget::column::with_capacity(legend.len() + 3).spacing(space_s);
+    if let Some(key) = &state.color_key {
+        column = column.push(widget::text::heading(key.as_str()));
+    }
+    for (label, color) in legend {
+        column = column.push(
+            widget::row::with_capacity(2)
+                .push(swatch(color, DOT_SIZE + 2.0, false))
+                .push(widget::text::caption(label))
+                .spacing(space_s)
+                .align_y(Alignment::Center),
+        );
+    }
+    if let Some(document) = state.selected_document() {
+        column = column
+            .push(widget::text::heading(fl!("visualize-selected")))
+            .push(widget::text::caption(document.id.as_str()));
+    }
+
+    widget::scrollable(column)
+        .width(Length::Fixed(LEGEND_WIDTH))
+        .height(Length::Fill)
+        .into()
+}
+
+/// View for the Visualize page
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a VisualizeState,
+    on_message: impl Fn(VisualizeMsg) -> Message + Copy + 'static,
+    space_s: u16,
+    space_m: u16,
+    window_width: f32,
+    window_height: f32,
+) -> Element<'a, Message> {
+    let Some(source) = &state.source else {
+        return widget::container(
+            widget::column::with_capacity(2)
+                .push(widget::text::title1(fl!("visualize")))
+                .push(widget::text::body(fl!("visualize-hint")))
+                .spacing(space_m),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into();
+    };
+
+    let method = Method::ALL.iter().position(|m| *m == state.method);
+    let color = state
+        .color_key
+        .as_ref()
+        .and_then(|key| state.keys.iter().position(|k| k == key))
+        .map_or(0, |index| index + 1);
+    let label = widget::text::body(status_label(state));
+    let toolbar = widget::row::with_capacity(6)
+        .push(widget::text::body(fl!("visualize-projection")))
+        .push(
+            widget::dropdown(&METHOD_LABELS, method, move |index| {
+                on_message(VisualizeMsg::MethodSelected(index))
+            })
+            .width(Length::Fixed(120.0)),
+        )
+        .push(widget::text::body(fl!("visualize-color-by")))
+        .push(
+            widget::dropdown(&state.key_labels, Some(color), move |index| {
+                on_message(VisualizeMsg::ColorSelected(index))
+            })
+            .width(Length::Fixed(200.0)),
+        )
+        .push(
+            widget::button::standard(fl!("visualize-reload"))
+                .on_press_maybe((!state.is_busy()).then(|| on_message(VisualizeMsg::Reload))),
+        )
+        .push(match state.status {
+            VisualizeStatus::Failed(_) => label.class(cosmic::style::Text::Accent),
+            _ => label,
+        })
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+
+    let mut content = widget::column::with_capacity(4)
+        .push(widget::text::title1(fl!(
+            "visualize-title",
+            collection = source.collection_name.as_str()
+        )))
+        .push(widget::text::caption(fl!("visualize-description")))
+        .push(toolbar)
+        .spacing(space_m)
+        .height(Length::Fill);
+
+    if state.status == VisualizeStatus::Ready {
+        if state.points.is_empty() {
+            content = content.push(widget::text::body(fl!("visualize-no-embeddings")));
+        } else {
+            let width = (window_width - LEGEND_WIDTH - 3.0 * f32::from(space_m)).max(MIN_PLOT_SIZE);
+            let height = (window_height - 260.0).max(MIN_PLOT_SIZE);
+            let (colors, legend) = state.coloring();
+            content = content.push(
+                widget::row::with_capacity(2)
+                    .push(render_plot(state, &colors, on_message, width, height))
+                    .push(render_legend(state, legend, space_s))
+                    .spacing(space_m),
+            );
+        }
+    }
+
+    widget::container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
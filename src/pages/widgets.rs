@@ -168,14 +168,17 @@ pub fn collection_card(collection: &Collection, space_s: u16) -> Element<'_, Mes
         .spacing(4)
         .width(Length::Fill);
 
-    // Delete button only
+    let export_button = widget::button::standard(fl!("export-collection"))
+        .on_press(Message::ExportCollection(collection.clone()));
+
     let delete_button = widget::button::icon(icon::from_name("user-trash-symbolic"))
         .on_press(Message::RequestDeleteCollection(collection_for_delete))
         .class(cosmic::theme::Button::Destructive);
 
-    // Main row with info and delete button
-    let card_content = widget::row::with_capacity(2)
+    // Main row with info, export and delete buttons
+    let card_content = widget::row::with_capacity(3)
         .push(info_column)
+        .push(export_button)
         .push(delete_button)
         .spacing(space_s)
         .align_y(Alignment::Center);
@@ -0,0 +1,382 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Projecting embeddings to two dimensions for plotting.
+//!
+//! Both methods run locally. PCA keeps the two directions along which the
+//! embeddings vary most, which is fast and preserves global structure.
+//! t-SNE arranges the points so that neighbours stay neighbours, which shows
+//! clusters better but takes time quadratic in the number of points.
+
+/// Power iterations per principal component
+const POWER_ITERATIONS: usize = 100;
+
+/// Neighbours each point is expected to have in t-SNE
+const PERPLEXITY: f64 = 30.0;
+
+/// Gradient descent steps of t-SNE
+const TSNE_ITERATIONS: usize = 500;
+
+/// Steps at the start of t-SNE during which clusters are pulled together
+const EXAGGERATION_ITERATIONS: usize = 100;
+
+const EXAGGERATION: f64 = 12.0;
+
+/// Ways to project embeddings to two dimensions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Method {
+    #[default]
+    Pca,
+    Tsne,
+}
+
+impl Method {
+    pub const ALL: [Self; 2] = [Self::Pca, Self::Tsne];
+}
+
+/// Projects embeddings on the blocking thread pool.
+pub async fn project_embeddings(
+    embeddings: Vec<Vec<f32>>,
+    method: Method,
+) -> Result<Vec<[f32; 2]>, String> {
+    tokio::task::spawn_blocking(move || project(&embeddings, method))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Position of every embedding in two dimensions, in the same order.
+pub fn project(embeddings: &[Vec<f32>], method: Method) -> Result<Vec<[f32; 2]>, String> {
+    let dimensions = embeddings
+        .first()
+        .map(Vec::len)
+        .ok_or("No embeddings to project")?;
+    if embeddings
+        .iter()
+        .any(|embedding| embedding.len() != dimensions)
+    {
+        return Err("The embeddings have different dimensions".to_string());
+    }
+
+    let pca = pca(embeddings);
+    let points = match method {
+        Method::Pca => pca,
+        // t-SNE starts from the PCA layout, so the same data always gives the same plot
+        Method::Tsne => tsne(embeddings, &pca),
+    };
+    Ok(points
+        .into_iter()
+        .map(|[x, y]| [x as f32, y as f32])
+        .collect())
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Removes the components of `vector` along each of the unit vectors `basis`
+fn orthogonalize(vector: &mut [f64], basis: &[&[f64]]) {
+    for direction in basis {
+        let projection = dot(vector, direction);
+        for (v, d) in vector.iter_mut().zip(direction.iter()) {
+            *v -= projection * d;
+        }
+    }
+}
+
+/// Scales `vector` to unit length, returning false if it has none
+fn normalize(vector: &mut [f64]) -> bool {
+    let norm = dot(vector, vector).sqrt();
+    if norm <= f64::EPSILON {
+        return false;
+    }
+    vector.iter_mut().for_each(|v| *v /= norm);
+    true
+}
+
+/// Coordinates along the first two principal components
+fn pca(embeddings: &[Vec<f32>]) -> Vec<[f64; 2]> {
+    let count = embeddings.len() as f64;
+    let mut mean = vec![0.0; embeddings[0].len()];
+    for embedding in embeddings {
+        for (m, v) in mean.iter_mut().zip(embedding) {
+            *m += f64::from(*v) / count;
+        }
+    }
+    let centered: Vec<Vec<f64>> = embeddings
+        .iter()
+        .map(|embedding| {
+            embedding
+                .iter()
+                .zip(&mean)
+                .map(|(v, m)| f64::from(*v) - m)
+                .collect()
+        })
+        .collect();
+
+    let first = principal_component(&centered, &[]);
+    let second = principal_component(&centered, &[&first]);
+    centered
+        .iter()
+        .map(|row| [dot(row, &first), dot(row, &second)])
+        .collect()
+}
+
+/// Direction of largest variance orthogonal to the directions already
+/// `found`, by power iteration; all zeros if the rows have no variance left
+fn principal_component(rows: &[Vec<f64>], found: &[&[f64]]) -> Vec<f64> {
+    let dimensions = rows[0].len();
+    // A fixed start keeps projections of the same data identical
+    let mut direction: Vec<f64> = (0..dimensions)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 } / (i + 1) as f64)
+        .collect();
+
+    for _ in 0..POWER_ITERATIONS {
+        orthogonalize(&mut direction, found);
+        if !normalize(&mut direction) {
+            return vec![0.0; dimensions];
+        }
+        let mut next = vec![0.0; dimensions];
+        for row in rows {
+            let weight = dot(row, &direction);
+            for (n, r) in next.iter_mut().zip(row) {
+                *n += weight * r;
+            }
+        }
+        direction = next;
+    }
+
+    orthogonalize(&mut direction, found);
+    if !normalize(&mut direction) {
+        return vec![0.0; dimensions];
+    }
+    direction
+}
+
+/// Squared euclidean distances between all pairs, row by row
+fn squared_distances(embeddings: &[Vec<f32>]) -> Vec<f64> {
+    let n = embeddings.len();
+    let mut distances = vec![0.0; n * n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let distance: f64 = embeddings[i]
+                .iter()
+                .zip(&embeddings[j])
+                .map(|(a, b)| f64::from(a - b).powi(2))
+                .sum();
+            distances[i * n + j] = distance;
+            distances[j * n + i] = distance;
+        }
+    }
+    distances
+}
+
+/// Symmetric probabilities of points picking each other as neighbours, with
+/// the spread around each point chosen to match the perplexity
+fn affinities(distances: &[f64], n: usize, perplexity: f64) -> Vec<f64> {
+    let target_entropy = perplexity.ln();
+    let mut p = vec![0.0; n * n];
+
+    for i in 0..n {
+        let row = &distances[i * n..(i + 1) * n];
+        // Measuring from the nearest neighbour keeps large distances from underflowing
+        let nearest = (0..n)
+            .filter(|&j| j != i)
+            .map(|j| row[j])
+            .fold(f64::INFINITY, f64::min);
+
+        let (mut beta, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+        for _ in 0..50 {
+            let mut sum = 0.0;
+            let mut weighted = 0.0;
+            for j in (0..n).filter(|&j| j != i) {
+                let value = (-(row[j] - nearest) * beta).exp();
+                p[i * n + j] = value;
+                sum += value;
+                weighted += (row[j] - nearest) * value;
+            }
+            let entropy = sum.ln() + beta * weighted / sum;
+            if (entropy - target_entropy).abs() < 1e-5 {
+                break;
+            }
+            // Too many neighbours means the spread has to shrink
+            if entropy > target_entropy {
+                low = beta;
+                beta = if high.is_finite() {
+                    (beta + high) / 2.0
+                } else {
+                    beta * 2.0
+                };
+            } else {
+                high = beta;
+                beta = (beta + low) / 2.0;
+            }
+        }
+
+        let sum: f64 = p[i * n..(i + 1) * n].iter().sum();
+        p[i * n..(i + 1) * n].iter_mut().for_each(|v| *v /= sum);
+    }
+
+    let mut symmetric = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            symmetric[i * n + j] = ((p[i * n + j] + p[j * n + i]) / (2.0 * n as f64)).max(1e-12);
+        }
+    }
+    symmetric
+}
+
+/// Exact t-SNE starting from `start`
+fn tsne(embeddings: &[Vec<f32>], start: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let n = embeddings.len();
+    if n < 3 {
+        return start.to_vec();
+    }
+    let perplexity = PERPLEXITY.min((n - 1) as f64 / 3.0);
+    let p = affinities(&squared_distances(embeddings), n, perplexity);
+    // Larger steps for more points; a fixed large rate scatters small sets
+    let learning_rate = (n as f64 / EXAGGERATION / 4.0).max(50.0);
+
+    // The start is shrunk so the first steps can still rearrange it freely
+    let spread = (start.iter().map(|[x, _]| x * x).sum::<f64>() / n as f64).sqrt();
+    let scale = if spread > f64::EPSILON {
+        1e-4 / spread
+    } else {
+        0.0
+    };
+    let mut y: Vec<[f64; 2]> = start
+        .iter()
+        .enumerate()
+        .map(|(i, [a, b])| {
+            // Identical starts would never separate
+            let jitter = 1e-6 * (i as f64).sin();
+            [a * scale + jitter, b * scale - jitter]
+        })
+        .collect();
+    let mut update = vec![[0.0; 2]; n];
+    let mut gains = vec![[1.0; 2]; n];
+    let mut q = vec![0.0; n * n];
+
+    for iteration in 0..TSNE_ITERATIONS {
+        let (exaggeration, momentum) = if iteration < EXAGGERATION_ITERATIONS {
+            (EXAGGERATION, 0.5)
+        } else {
+            (1.0, 0.8)
+        };
+
+        let mut total = 0.0;
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let dx = y[i][0] - y[j][0];
+                let dy = y[i][1] - y[j][1];
+                let value = 1.0 / (1.0 + dx * dx + dy * dy);
+                q[i * n + j] = value;
+                q[j * n + i] = value;
+                total += 2.0 * value;
+            }
+        }
+
+        for i in 0..n {
+            let mut gradient = [0.0; 2];
+            for j in (0..n).filter(|&j| j != i) {
+                let similarity = q[i * n + j];
+                let force = (exaggeration * p[i * n + j] - similarity / total) * similarity;
+                gradient[0] += 4.0 * force * (y[i][0] - y[j][0]);
+                gradient[1] += 4.0 * force * (y[i][1] - y[j][1]);
+            }
+            for axis in 0..2 {
+                // Steps keep growing while the gradient keeps its direction
+                gains[i][axis] = if (gradient[axis] > 0.0) != (update[i][axis] > 0.0) {
+                    gains[i][axis] + 0.2
+                } else {
+                    (gains[i][axis] * 0.8_f64).max(0.01)
+                };
+                update[i][axis] =
+                    momentum * update[i][axis] - learning_rate * gains[i][axis] * gradient[axis];
+            }
+        }
+
+        let mut mean = [0.0; 2];
+        for (point, step) in y.iter_mut().zip(&update) {
+            point[0] += step[0];
+            point[1] += step[1];
+            mean[0] += point[0] / n as f64;
+            mean[1] += point[1] / n as f64;
+        }
+        for point in &mut y {
+            point[0] -= mean[0];
+            point[1] -= mean[1];
+        }
+    }
+    y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two groups of points far apart from each other
+    fn clusters() -> Vec<Vec<f32>> {
+        (0..20)
+            .map(|i| {
+                let center = if i < 10 { 0.0 } else { 10.0 };
+                (0..5)
+                    .map(|d| center + ((i * 7 + d * 3) % 5) as f32 * 0.1)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_pca_follows_largest_variance() {
+        // Points along (1, 1, 0) with a little spread along z
+        let embeddings: Vec<Vec<f32>> = (0..10)
+            .map(|i| {
+                let t = i as f32;
+                vec![t, t, if i % 2 == 0 { 0.1 } else { -0.1 }]
+            })
+            .collect();
+        let points = project(&embeddings, Method::Pca).unwrap();
+
+        let xs: Vec<f32> = points.iter().map(|[x, _]| *x).collect();
+        let increasing = xs.windows(2).all(|w| w[0] < w[1]);
+        let decreasing = xs.windows(2).all(|w| w[0] > w[1]);
+        assert!(increasing || decreasing, "{:?}", xs);
+        assert!((xs[9] - xs[0]).abs() > 12.0);
+        assert!(points.iter().all(|[_, y]| y.abs() < 0.2));
+    }
+
+    #[test]
+    fn test_tsne_keeps_clusters_apart() {
+        let points = project(&clusters(), Method::Tsne).unwrap();
+        let centroid = |range: std::ops::Range<usize>| {
+            let count = range.len() as f32;
+            range.fold([0.0, 0.0], |[x, y], i| {
+                [x + points[i][0] / count, y + points[i][1] / count]
+            })
+        };
+        let (first, second) = (centroid(0..10), centroid(10..20));
+        for (i, point) in points.iter().enumerate() {
+            let (own, other) = if i < 10 {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            assert!(distance(*point, own) < distance(*point, other));
+        }
+    }
+
+    #[test]
+    fn test_project_rejects_mixed_dimensions() {
+        assert!(project(&[], Method::Pca).is_err());
+        assert!(project(&[vec![1.0, 2.0], vec![1.0]], Method::Pca).is_err());
+        // A single point or identical points still get a position
+        assert_eq!(
+            project(&[vec![1.0, 2.0]], Method::Tsne).unwrap(),
+            [[0.0, 0.0]]
+        );
+        assert_eq!(project(&vec![vec![3.0]; 4], Method::Tsne).unwrap().len(), 4);
+    }
+}
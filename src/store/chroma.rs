@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A Chroma server as a `VectorStore`.
+
+use super::{DocumentStream, StoreFuture, VectorStore};
+use crate::api::{
+    BatchReport, CancelToken, ChromaClient, Collection, CreateCollectionOptions, Document,
+    DocumentFilter, Identity, Include, ModifyCollectionRequest, QueryMatch, QueryRequest,
+    ServerInfo, StreamOptions,
+};
+
+impl VectorStore for ChromaClient {
+    fn server_info(&self) -> StoreFuture<'_, ServerInfo> {
+        Box::pin(self.get_server_info())
+    }
+
+    fn list_tenants(&self) -> StoreFuture<'_, Option<Vec<String>>> {
+        Box::pin(async {
+            let tenants = ChromaClient::list_tenants(self).await?;
+            Ok(tenants.map(|tenants| tenants.into_iter().map(|t| t.name).collect()))
+        })
+    }
+
+    fn list_databases<'a>(&'a self, tenant: &'a str) -> StoreFuture<'a, Vec<String>> {
+        Box::pin(async move {
+            let databases = ChromaClient::list_databases(self, tenant).await?;
+            Ok(databases.into_iter().map(|db| db.name).collect())
+        })
+    }
+
+    fn list_collections<'a>(
+        &'a self,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Collection>> {
+        Box::pin(ChromaClient::list_collections(self, tenant, database))
+    }
+
+    fn get_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        filter: &'a DocumentFilter,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>> {
+        Box::pin(ChromaClient::get_documents(
+            self,
+            collection_id,
+            limit,
+            offset,
+            filter,
+            tenant,
+            database,
+        ))
+    }
+
+    fn get_documents_by_ids<'a>(
+        &'a self,
+        collection_id: &'a str,
+        ids: Vec<String>,
+        include: Vec<Include>,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>> {
+        Box::pin(ChromaClient::get_documents_by_ids(
+            self,
+            collection_id,
+            ids,
+            include,
+            tenant,
+            database,
+        ))
+    }
+
+    fn count_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        filter: &'a DocumentFilter,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, usize> {
+        Box::pin(ChromaClient::count_documents(
+            self,
+            collection_id,
+            filter,
+            tenant,
+            database,
+        ))
+    }
+
+    fn stream_documents(
+        &self,
+        collection_id: &str,
+        tenant: &str,
+        database: &str,
+        options: StreamOptions,
+        cancel: CancelToken,
+    ) -> DocumentStream {
+        Box::pin(ChromaClient::stream_documents(
+            self,
+            collection_id,
+            tenant,
+            database,
+            options,
+            cancel,
+        ))
+    }
+
+    fn identity(&self) -> StoreFuture<'_, Option<Identity>> {
+        Box::pin(self.get_user_identity())
+    }
+
+    fn tenant_database_status<'a>(
+        &'a self,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, (bool, bool)> {
+        Box::pin(async move { Ok(self.check_tenant_database_status(tenant, database).await) })
+    }
+
+    fn query_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        request: &'a QueryRequest,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Vec<QueryMatch>>> {
+        Box::pin(ChromaClient::query_documents(
+            self,
+            collection_id,
+            request,
+            tenant,
+            database,
+        ))
+    }
+
+    fn create_tenant<'a>(&'a self, tenant: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move { ChromaClient::create_tenant(self, tenant).await.map(|_| ()) })
+    }
+
+    fn create_database<'a>(&'a self, tenant: &'a str, database: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            ChromaClient::create_database(self, tenant, database)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn delete_database<'a>(&'a self, tenant: &'a str, database: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(ChromaClient::delete_database(self, tenant, database))
+    }
+
+    fn create_collection<'a>(
+        &'a self,
+        name: &'a str,
+        options: &'a CreateCollectionOptions,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Collection> {
+        Box::pin(ChromaClient::create_collection(
+            self, name, options, tenant, database,
+        ))
+    }
+
+    fn modify_collection<'a>(
+        &'a self,
+        collection_id: &'a str,
+        request: &'a ModifyCollectionRequest,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(ChromaClient::modify_collection(
+            self,
+            collection_id,
+            request,
+            tenant,
+            database,
+        ))
+    }
+
+    fn delete_collection<'a>(
+        &'a self,
+        name: &'a str,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(ChromaClient::delete_collection(
+            self, name, tenant, database,
+        ))
+    }
+
+    fn add_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        documents: &'a [Document],
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        Box::pin(ChromaClient::add_documents(
+            self,
+            collection_id,
+            documents,
+            tenant,
+            database,
+        ))
+    }
+
+    fn upsert_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        documents: &'a [Document],
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        Box::pin(ChromaClient::upsert_documents(
+            self,
+            collection_id,
+            documents,
+            tenant,
+            database,
+        ))
+    }
+
+    fn update_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        documents: &'a [Document],
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        Box::pin(ChromaClient::update_documents(
+            self,
+            collection_id,
+            documents,
+            tenant,
+            database,
+        ))
+    }
+
+    fn delete_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        document_ids: Option<Vec<String>>,
+        filter: &'a DocumentFilter,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        Box::pin(ChromaClient::delete_documents(
+            self,
+            collection_id,
+            document_ids,
+            filter,
+            tenant,
+            database,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{ApiVersion, DEFAULT_DATABASE, DEFAULT_TENANT, MockServer};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_client_as_store() {
+        let server = MockServer::start(ApiVersion::V1).await;
+        let id = server.add_collection("notes");
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        let (tenant, database) = (DEFAULT_TENANT, DEFAULT_DATABASE);
+
+        let documents = [Document {
+            id: "a".to_string(),
+            document: Some("hello".to_string()),
+            ..Default::default()
+        }];
+        store
+            .add_documents(&id, &documents, tenant, database)
+            .await
+            .unwrap()
+            .into_result()
+            .unwrap();
+
+        assert_eq!(store.list_tenants().await.unwrap(), None);
+        assert_eq!(
+            store.list_databases(tenant).await.unwrap(),
+            [DEFAULT_DATABASE]
+        );
+        assert_eq!(
+            store
+                .tenant_database_status(tenant, "missing")
+                .await
+                .unwrap(),
+            (true, false)
+        );
+        let collections = store.list_collections(tenant, database).await.unwrap();
+        assert_eq!(collections[0].name, "notes");
+        let all = DocumentFilter::default();
+        assert_eq!(
+            store
+                .count_documents(&id, &all, tenant, database)
+                .await
+                .unwrap(),
+            1
+        );
+        let page = store
+            .get_documents(&id, Some(10), None, &all, tenant, database)
+            .await
+            .unwrap();
+        assert_eq!(page[0].document.as_deref(), Some("hello"));
+    }
+}
@@ -0,0 +1,706 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A Chroma persist directory read directly, without a server.
+//!
+//! Chroma keeps tenants, databases, collections and the text and metadata of
+//! every record in `chroma.sqlite3`. Embeddings are first written to the
+//! `embeddings_queue` table and later moved into binary HNSW segment folders,
+//! which are not read here, so a record only shows its embedding while the
+//! queue still holds it. The catalog is opened read-only.
+
+use super::{DocumentStream, StoreFuture, VectorStore};
+use crate::api::{
+    CancelToken, ChromaError, Collection, Document, DocumentFilter, Include, Metadata, Operator,
+    ServerInfo, StreamOptions, Where, WhereDocument, paginate,
+};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Params, Row, params_from_iter};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// File name of the catalog in a persist directory
+const CATALOG: &str = "chroma.sqlite3";
+/// Metadata key the text of a record is stored under
+const DOCUMENT_KEY: &str = "chroma:document";
+/// Metadata key the URI of a record is stored under
+const URI_KEY: &str = "chroma:uri";
+/// Page size when no limit is given, as on a server
+const DEFAULT_LIMIT: usize = 100;
+
+/// Records of the collection bound to the first parameter
+const RECORDS: &str = "FROM embeddings e JOIN segments s ON s.id = e.segment_id \
+                       WHERE s.collection = ? AND s.scope = 'METADATA'";
+
+const COLLECTIONS: &str = "SELECT c.id, c.name FROM collections c \
+                           JOIN databases d ON d.id = c.database_id \
+                           WHERE d.tenant_id = ? AND d.name = ? ORDER BY c.name";
+
+/// The catalog of a persist directory
+#[derive(Debug, Clone)]
+pub struct LocalStore {
+    catalog: PathBuf,
+}
+
+impl LocalStore {
+    /// Opens a persist directory, failing if it has no catalog in a known layout
+    pub fn open(directory: &Path) -> Result<Self, ChromaError> {
+        let catalog = directory.join(CATALOG);
+        if !catalog.is_file() {
+            return Err(ChromaError::Storage(format!(
+                "{} does not contain {}",
+                directory.display(),
+                CATALOG
+            )));
+        }
+
+        let store = Self { catalog };
+        let connection = store.connect()?;
+        for sql in [COLLECTIONS, "SELECT id FROM tenants"] {
+            connection
+                .prepare(sql)
+                .map_err(|e| ChromaError::Storage(format!("Unsupported catalog layout: {}", e)))?;
+        }
+        Ok(store)
+    }
+
+    /// A page of records in ID order with the requested fields
+    fn page(
+        &self,
+        collection_id: String,
+        limit: usize,
+        offset: usize,
+        filter: DocumentFilter,
+        include: Vec<Include>,
+    ) -> StoreFuture<'static, Vec<Document>> {
+        self.read(move |connection| {
+            let mut params = vec![SqlValue::Text(collection_id.clone())];
+            let condition = filter_sql(&filter, &mut params)?;
+            params.push(SqlValue::Integer(limit as i64));
+            params.push(SqlValue::Integer(offset as i64));
+            let sql = format!(
+                "SELECT e.id, e.embedding_id {}{} ORDER BY e.id LIMIT ? OFFSET ?",
+                RECORDS, condition
+            );
+            load_documents(connection, &sql, params, &collection_id, &include)
+                .map_err(storage_error)
+        })
+    }
+
+    fn connect(&self) -> Result<Connection, ChromaError> {
+        Connection::open_with_flags(
+            &self.catalog,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(storage_error)
+    }
+
+    /// Runs a read against its own connection on the blocking thread pool
+    fn read<T, F>(&self, read: F) -> StoreFuture<'static, T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, ChromaError> + Send + 'static,
+    {
+        let store = self.clone();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || read(&store.connect()?))
+                .await
+                .map_err(|e| ChromaError::Storage(e.to_string()))?
+        })
+    }
+}
+
+impl VectorStore for LocalStore {
+    fn server_info(&self) -> StoreFuture<'_, ServerInfo> {
+        self.read(|connection| {
+            connection
+                .query_row("SELECT COUNT(*) FROM collections", [], |_| Ok(()))
+                .map_err(storage_error)?;
+            Ok(ServerInfo {
+                version: format!("SQLite {}", rusqlite::version()),
+                heartbeat_ns: 0,
+                api_version: "local".to_string(),
+            })
+        })
+    }
+
+    fn list_tenants(&self) -> StoreFuture<'_, Option<Vec<String>>> {
+        self.read(|connection| {
+            strings(connection, "SELECT id FROM tenants ORDER BY id", []).map(Some)
+        })
+    }
+
+    fn list_databases<'a>(&'a self, tenant: &'a str) -> StoreFuture<'a, Vec<String>> {
+        let tenant = tenant.to_string();
+        self.read(move |connection| {
+            strings(
+                connection,
+                "SELECT name FROM databases WHERE tenant_id = ? ORDER BY name",
+                [tenant],
+            )
+        })
+    }
+
+    fn list_collections<'a>(
+        &'a self,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Collection>> {
+        let params = [tenant.to_string(), database.to_string()];
+        self.read(move |connection| list_collections(connection, params).map_err(storage_error))
+    }
+
+    fn get_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        filter: &'a DocumentFilter,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>> {
+        let include = vec![Include::Documents, Include::Metadatas];
+        self.page(
+            collection_id.to_string(),
+            limit.unwrap_or(DEFAULT_LIMIT),
+            offset.unwrap_or(0),
+            filter.clone(),
+            include,
+        )
+    }
+
+    fn get_documents_by_ids<'a>(
+        &'a self,
+        collection_id: &'a str,
+        ids: Vec<String>,
+        include: Vec<Include>,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>> {
+        let collection_id = collection_id.to_string();
+        self.read(move |connection| {
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let placeholders = vec!["?"; ids.len()].join(", ");
+            let sql = format!(
+                "SELECT e.id, e.embedding_id {} AND e.embedding_id IN ({}) ORDER BY e.id",
+                RECORDS, placeholders
+            );
+            let params = std::iter::once(collection_id.clone())
+                .chain(ids)
+                .map(SqlValue::Text)
+                .collect();
+            load_documents(connection, &sql, params, &collection_id, &include)
+                .map_err(storage_error)
+        })
+    }
+
+    fn count_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        filter: &'a DocumentFilter,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, usize> {
+        let collection_id = collection_id.to_string();
+        let filter = filter.clone();
+        self.read(move |connection| {
+            let mut params = vec![SqlValue::Text(collection_id)];
+            let condition = filter_sql(&filter, &mut params)?;
+            let sql = format!("SELECT COUNT(*) {}{}", RECORDS, condition);
+            let count: i64 = connection
+                .query_row(&sql, params_from_iter(params), |row| row.get(0))
+                .map_err(storage_error)?;
+            Ok(count as usize)
+        })
+    }
+
+    fn stream_documents(
+        &self,
+        collection_id: &str,
+        _tenant: &str,
+        _database: &str,
+        options: StreamOptions,
+        cancel: CancelToken,
+    ) -> DocumentStream {
+        let store = self.clone();
+        let collection_id = collection_id.to_string();
+        let (offset, page_size) = (options.offset, options.page_size);
+        let count = {
+            let (store, collection_id) = (store.clone(), collection_id.clone());
+            let filter = options.filter.clone();
+            Box::pin(async move { store.count_documents(&collection_id, &filter, "", "").await })
+        };
+        let fetch = move |offset, limit| {
+            store.page(
+                collection_id.clone(),
+                limit,
+                offset,
+                options.filter.clone(),
+                options.include.clone(),
+            )
+        };
+        Box::pin(paginate(count, fetch, offset, page_size, cancel))
+    }
+}
+
+fn storage_error(error: rusqlite::Error) -> ChromaError {
+    ChromaError::Storage(error.to_string())
+}
+
+/// First column of every row of a query
+fn strings(
+    connection: &Connection,
+    sql: &str,
+    params: impl Params,
+) -> Result<Vec<String>, ChromaError> {
+    let mut statement = connection.prepare(sql).map_err(storage_error)?;
+    let rows = statement
+        .query_map(params, |row| row.get(0))
+        .map_err(storage_error)?;
+    rows.collect::<Result<_, _>>().map_err(storage_error)
+}
+
+fn list_collections(
+    connection: &Connection,
+    params: [String; 2],
+) -> rusqlite::Result<Vec<Collection>> {
+    let mut statement = connection.prepare(COLLECTIONS)?;
+    let mut metadata = connection.prepare(
+        "SELECT key, str_value, int_value, float_value, bool_value \
+         FROM collection_metadata WHERE collection_id = ?",
+    )?;
+
+    let rows = statement.query_map(params, |row| Ok((row.get(0)?, row.get(1)?)))?;
+    let mut collections = Vec::new();
+    for row in rows {
+        let (id, name): (String, String) = row?;
+        let entries = metadata
+            .query_map([&id], |row| Ok((row.get(0)?, metadata_value(row)?)))?
+            .collect::<rusqlite::Result<Metadata>>()?;
+        collections.push(Collection {
+            id,
+            name,
+            metadata: (!entries.is_empty()).then_some(entries),
+            configuration: None,
+        });
+    }
+    Ok(collections)
+}
+
+/// Runs a query selecting the row and embedding IDs of records and reads
+/// the requested fields of each
+fn load_documents(
+    connection: &Connection,
+    sql: &str,
+    params: Vec<SqlValue>,
+    collection_id: &str,
+    include: &[Include],
+) -> rusqlite::Result<Vec<Document>> {
+    let mut statement = connection.prepare(sql)?;
+    let records = statement
+        .query_map(params_from_iter(params), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut fields = connection.prepare(
+        "SELECT key, string_value, int_value, float_value, bool_value \
+         FROM embedding_metadata WHERE id = ?",
+    )?;
+    let mut documents = Vec::with_capacity(records.len());
+    for (row_id, id) in records {
+        let mut document = Document {
+            id,
+            ..Default::default()
+        };
+        let mut metadata = Metadata::new();
+        let entries = fields.query_map([row_id], |row| {
+            Ok((row.get::<_, String>(0)?, metadata_value(row)?))
+        })?;
+        for entry in entries {
+            let (key, value) = entry?;
+            match key.as_str() {
+                DOCUMENT_KEY if include.contains(&Include::Documents) => {
+                    document.document = value.as_str().map(str::to_string);
+                }
+                URI_KEY if include.contains(&Include::Uris) => {
+                    document.uri = value.as_str().map(str::to_string);
+                }
+                // Other reserved keys hold Chroma's own bookkeeping
+                _ if key.starts_with("chroma:") => {}
+                _ => {
+                    metadata.insert(key, value);
+                }
+            }
+        }
+        if include.contains(&Include::Metadatas) && !metadata.is_empty() {
+            document.metadata = Some(metadata);
+        }
+        if include.contains(&Include::Embeddings) {
+            document.embedding = queued_embedding(connection, collection_id, &document.id)?;
+        }
+        documents.push(document);
+    }
+    Ok(documents)
+}
+
+/// Value of a metadata row whose typed columns start at the second column
+fn metadata_value(row: &Row) -> rusqlite::Result<Value> {
+    if let Some(text) = row.get::<_, Option<String>>(1)? {
+        return Ok(Value::String(text));
+    }
+    if let Some(flag) = row.get::<_, Option<bool>>(4)? {
+        return Ok(Value::Bool(flag));
+    }
+    if let Some(number) = row.get::<_, Option<i64>>(2)? {
+        return Ok(Value::from(number));
+    }
+    Ok(row
+        .get::<_, Option<f64>>(3)?
+        .map(Value::from)
+        .unwrap_or(Value::Null))
+}
+
+/// The embedding of a record if the write-ahead queue still holds it
+fn queued_embedding(
+    connection: &Connection,
+    collection_id: &str,
+    id: &str,
+) -> rusqlite::Result<Option<Vec<f32>>> {
+    let has_queue: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master \
+         WHERE type = 'table' AND name = 'embeddings_queue')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_queue {
+        return Ok(None);
+    }
+
+    // Topics end with the collection ID, e.g. "persistent://default/default/<id>"
+    let vector: Option<Vec<u8>> = connection
+        .query_row(
+            "SELECT vector FROM embeddings_queue \
+             WHERE id = ?1 AND topic LIKE '%' || ?2 \
+             AND vector IS NOT NULL AND encoding = 'FLOAT32' \
+             ORDER BY seq_id DESC LIMIT 1",
+            [id, collection_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(vector.map(|bytes| {
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect()
+    }))
+}
+
+/// SQL conditions on the record `e` for a filter, to append to `RECORDS`
+fn filter_sql(filter: &DocumentFilter, params: &mut Vec<SqlValue>) -> Result<String, ChromaError> {
+    let mut sql = String::new();
+    if let Some(ref where_filter) = filter.where_filter {
+        sql.push_str(&format!(" AND {}", where_sql(where_filter, params)?));
+    }
+    if let Some(ref where_document) = filter.where_document {
+        sql.push_str(&format!(" AND {}", document_sql(where_document, params)?));
+    }
+    Ok(sql)
+}
+
+/// Condition for a metadata filter. Like on a server, `$ne` and `$nin`
+/// also match records without the key.
+fn where_sql(filter: &Where, params: &mut Vec<SqlValue>) -> Result<String, ChromaError> {
+    let (filters, separator) = match filter {
+        Where::Field {
+            key,
+            operator,
+            value,
+        } => {
+            let values = match value {
+                Value::Array(values) => values.as_slice(),
+                value => std::slice::from_ref(value),
+            };
+            let symbol = match operator {
+                Operator::Gt => ">",
+                Operator::Gte => ">=",
+                Operator::Lt => "<",
+                Operator::Lte => "<=",
+                _ => "=",
+            };
+
+            params.push(SqlValue::Text(key.clone()));
+            let mut comparisons = Vec::with_capacity(values.len());
+            for value in values {
+                let (column, value) = metadata_column(key, value)?;
+                comparisons.push(format!("{} {} ?", column, symbol));
+                params.push(value);
+            }
+            if comparisons.is_empty() {
+                // `$in` with an empty list matches nothing
+                comparisons.push("0".to_string());
+            }
+
+            let exists = format!(
+                "EXISTS (SELECT 1 FROM embedding_metadata m \
+                 WHERE m.id = e.id AND m.key = ? AND ({}))",
+                comparisons.join(" OR ")
+            );
+            return Ok(match operator {
+                Operator::Ne | Operator::Nin => format!("NOT {}", exists),
+                _ => exists,
+            });
+        }
+        Where::And(filters) => (filters, " AND "),
+        Where::Or(filters) => (filters, " OR "),
+    };
+
+    let operands = filters
+        .iter()
+        .map(|filter| where_sql(filter, params))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("({})", operands.join(separator)))
+}
+
+/// Column a metadata value is stored in, and the value as a parameter
+fn metadata_column(key: &str, value: &Value) -> Result<(&'static str, SqlValue), ChromaError> {
+    match value {
+        Value::String(text) => Ok(("m.string_value", SqlValue::Text(text.clone()))),
+        Value::Bool(flag) => Ok(("m.bool_value", SqlValue::Integer(*flag as i64))),
+        Value::Number(number) => {
+            let value = match number.as_i64() {
+                Some(number) => SqlValue::Integer(number),
+                None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+            };
+            Ok(("COALESCE(m.int_value, m.float_value)", value))
+        }
+        _ => Err(ChromaError::InvalidRequest(format!(
+            "Value for '{}' must be a string, number or boolean",
+            key
+        ))),
+    }
+}
+
+/// Condition for a document text filter
+fn document_sql(filter: &WhereDocument, params: &mut Vec<SqlValue>) -> Result<String, ChromaError> {
+    let (filters, separator) = match filter {
+        WhereDocument::Contains(text) | WhereDocument::NotContains(text) => {
+            params.push(SqlValue::Text(DOCUMENT_KEY.to_string()));
+            params.push(SqlValue::Text(text.clone()));
+            let exists = "EXISTS (SELECT 1 FROM embedding_metadata m \
+                          WHERE m.id = e.id AND m.key = ? AND instr(m.string_value, ?) > 0)";
+            return Ok(match filter {
+                WhereDocument::NotContains(_) => format!("NOT {}", exists),
+                _ => exists.to_string(),
+            });
+        }
+        WhereDocument::Regex(_) | WhereDocument::NotRegex(_) => {
+            return Err(ChromaError::InvalidRequest(
+                "Regular expressions are not supported for local directories".to_string(),
+            ));
+        }
+        WhereDocument::And(filters) => (filters, " AND "),
+        WhereDocument::Or(filters) => (filters, " OR "),
+    };
+
+    let operands = filters
+        .iter()
+        .map(|filter| document_sql(filter, params))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("({})", operands.join(separator)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    /// A persist directory with the catalog tables the store reads
+    fn persist_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("chromatic-local-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let connection = Connection::open(directory.join(CATALOG)).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE tenants (id TEXT PRIMARY KEY);
+                 CREATE TABLE databases (id TEXT PRIMARY KEY, name TEXT, tenant_id TEXT);
+                 CREATE TABLE collections (id TEXT PRIMARY KEY, name TEXT,
+                     dimension INTEGER, database_id TEXT);
+                 CREATE TABLE collection_metadata (collection_id TEXT, key TEXT,
+                     str_value TEXT, int_value INTEGER, float_value REAL, bool_value INTEGER);
+                 CREATE TABLE segments (id TEXT PRIMARY KEY, type TEXT, scope TEXT,
+                     collection TEXT);
+                 CREATE TABLE embeddings (id INTEGER PRIMARY KEY, segment_id TEXT,
+                     embedding_id TEXT, seq_id BLOB);
+                 CREATE TABLE embedding_metadata (id INTEGER, key TEXT, string_value TEXT,
+                     int_value INTEGER, float_value REAL, bool_value INTEGER);
+                 CREATE TABLE embeddings_queue (seq_id INTEGER PRIMARY KEY, operation INTEGER,
+                     topic TEXT, id TEXT, vector BLOB, encoding TEXT, metadata TEXT);
+
+                 INSERT INTO tenants VALUES ('default_tenant');
+                 INSERT INTO databases VALUES ('db', 'default_database', 'default_tenant');
+                 INSERT INTO collections VALUES ('c1', 'notes', 2, 'db');
+                 INSERT INTO collection_metadata VALUES ('c1', 'owner', 'ci', NULL, NULL, NULL);
+                 INSERT INTO segments VALUES ('m1', 'sqlite', 'METADATA', 'c1');
+                 INSERT INTO segments VALUES ('v1', 'hnsw', 'VECTOR', 'c1');
+                 INSERT INTO embeddings VALUES (1, 'm1', 'a', NULL), (2, 'm1', 'b', NULL),
+                     (3, 'm1', 'c', NULL);
+                 INSERT INTO embedding_metadata VALUES
+                     (1, 'chroma:document', 'rust is fast', NULL, NULL, NULL),
+                     (1, 'page', NULL, 1, NULL, NULL),
+                     (1, 'draft', NULL, NULL, NULL, 1),
+                     (2, 'chroma:document', 'chroma stores vectors', NULL, NULL, NULL),
+                     (2, 'page', NULL, 5, NULL, NULL),
+                     (2, 'score', NULL, NULL, 0.5, NULL),
+                     (3, 'chroma:document', 'no page here', NULL, NULL, NULL);",
+            )
+            .unwrap();
+        let vector: Vec<u8> = [0.25f32, -1.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        connection
+            .execute(
+                "INSERT INTO embeddings_queue VALUES (1, 0, 'persistent://default/default/c1', \
+                 'a', ?, 'FLOAT32', NULL)",
+                [vector],
+            )
+            .unwrap();
+        directory
+    }
+
+    fn filter(where_filter: Value) -> DocumentFilter {
+        DocumentFilter {
+            where_filter: Some(Where::from_value(&where_filter).unwrap()),
+            where_document: None,
+        }
+    }
+
+    async fn ids(store: &LocalStore, filter: &DocumentFilter) -> Vec<String> {
+        store
+            .get_documents("c1", None, None, filter, "", "")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|document| document.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_browse_catalog() {
+        let directory = persist_directory("browse");
+        let store = LocalStore::open(&directory).unwrap();
+
+        assert_eq!(
+            store.list_tenants().await.unwrap(),
+            Some(vec!["default_tenant".to_string()])
+        );
+        assert_eq!(
+            store.list_databases("default_tenant").await.unwrap(),
+            ["default_database"]
+        );
+        let collections = store
+            .list_collections("default_tenant", "default_database")
+            .await
+            .unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(collections[0].name, "notes");
+        assert_eq!(
+            collections[0].metadata,
+            Some([("owner".to_string(), json!("ci"))].into())
+        );
+
+        let all = DocumentFilter::default();
+        let page = store
+            .get_documents("c1", Some(2), Some(0), &all, "", "")
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].document.as_deref(), Some("rust is fast"));
+        let metadata = page[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["page"], json!(1));
+        assert_eq!(metadata["draft"], json!(true));
+        assert!(!metadata.contains_key(DOCUMENT_KEY));
+        assert_eq!(page[1].metadata.as_ref().unwrap()["score"], json!(0.5));
+        assert_eq!(store.count_documents("c1", &all, "", "").await.unwrap(), 3);
+
+        let details = store
+            .get_documents_by_ids(
+                "c1",
+                vec!["a".to_string(), "b".to_string()],
+                vec![Include::Documents, Include::Embeddings],
+                "",
+                "",
+            )
+            .await
+            .unwrap();
+        assert_eq!(details[0].embedding, Some(vec![0.25, -1.0]));
+        assert_eq!(details[0].metadata, None);
+        assert_eq!(details[1].embedding, None);
+
+        let options = StreamOptions {
+            page_size: 2,
+            include: vec![Include::Embeddings],
+            ..Default::default()
+        };
+        let batches: Vec<_> = store
+            .stream_documents("c1", "", "", options, CancelToken::new())
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(batches.len(), 2);
+        assert_eq!((batches[1].fetched, batches[1].total), (3, Some(3)));
+        assert_eq!(batches[0].documents[0].embedding, Some(vec![0.25, -1.0]));
+
+        assert!(store.delete_collection("notes", "", "").await.is_err());
+        assert!(LocalStore::open(&std::env::temp_dir().join("chromatic-missing")).is_err());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        let directory = persist_directory("filters");
+        let store = LocalStore::open(&directory).unwrap();
+
+        assert_eq!(
+            ids(&store, &filter(json!({"page": {"$gt": 2}}))).await,
+            ["b"]
+        );
+        assert_eq!(
+            ids(&store, &filter(json!({"page": {"$ne": 5}}))).await,
+            ["a", "c"]
+        );
+        assert_eq!(
+            ids(&store, &filter(json!({"page": {"$in": [1, 5]}}))).await,
+            ["a", "b"]
+        );
+        assert_eq!(ids(&store, &filter(json!({"draft": true}))).await, ["a"]);
+        assert_eq!(
+            ids(
+                &store,
+                &filter(json!({"$or": [{"draft": true}, {"score": {"$gte": 0.5}}]}))
+            )
+            .await,
+            ["a", "b"]
+        );
+
+        let text = DocumentFilter {
+            where_filter: None,
+            where_document: Some(WhereDocument::NotContains("page".to_string())),
+        };
+        assert_eq!(ids(&store, &text).await, ["a", "b"]);
+        assert_eq!(store.count_documents("c1", &text, "", "").await.unwrap(), 2);
+
+        let regex = DocumentFilter {
+            where_filter: None,
+            where_document: Some(WhereDocument::Regex("^rust".to_string())),
+        };
+        assert!(store.count_documents("c1", &regex, "", "").await.is_err());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
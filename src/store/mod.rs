@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Backends the browser reads collections from.
+//!
+//! The pages only talk to a `VectorStore`, so sources other than a Chroma
+//! server can be browsed in the same tenant, database and collection columns.
+//! Backends without tenants or databases report a single one of each, and
+//! read-only backends keep the default implementations of the write methods,
+//! which fail without doing anything.
+
+mod chroma;
+mod local;
+
+use crate::api::{
+    BatchReport, CancelToken, ChromaError, ClientRegistry, Collection, CreateCollectionOptions,
+    Document, DocumentBatch, DocumentFilter, Identity, Include, ModifyCollectionRequest,
+    QueryMatch, QueryRequest, ServerInfo, StreamOptions,
+};
+use crate::config::ServerConfig;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use local::LocalStore;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Result of a backend operation
+pub type StoreFuture<'a, T> = BoxFuture<'a, Result<T, ChromaError>>;
+
+/// Pages of a whole collection, see `VectorStore::stream_documents`
+pub type DocumentStream = BoxStream<'static, Result<DocumentBatch, ChromaError>>;
+
+/// What a backend supports beyond listing and reading documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreCapabilities {
+    /// Collections and documents can be created, changed and deleted
+    pub writable: bool,
+    /// Tenants and databases can be created and deleted
+    pub manage_databases: bool,
+    /// The embedding of every document can be read
+    pub embeddings: bool,
+}
+
+impl StoreCapabilities {
+    pub const FULL: Self = Self {
+        writable: true,
+        manage_databases: true,
+        embeddings: true,
+    };
+
+    /// A persist directory, read-only and holding only the embeddings that
+    /// are not indexed yet
+    pub const LOCAL: Self = Self {
+        writable: false,
+        manage_databases: false,
+        embeddings: false,
+    };
+}
+
+/// A source of collections and documents
+pub trait VectorStore: fmt::Debug + Send + Sync {
+    /// Version and health of the backend
+    fn server_info(&self) -> StoreFuture<'_, ServerInfo>;
+
+    /// All tenants, or `None` if the backend does not allow listing them
+    fn list_tenants(&self) -> StoreFuture<'_, Option<Vec<String>>>;
+
+    /// Names of the databases of a tenant
+    fn list_databases<'a>(&'a self, tenant: &'a str) -> StoreFuture<'a, Vec<String>>;
+
+    fn list_collections<'a>(
+        &'a self,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Collection>>;
+
+    /// A page of documents with their contents and metadata, optionally filtered
+    fn get_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        filter: &'a DocumentFilter,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>>;
+
+    /// Specific documents, including the requested fields
+    fn get_documents_by_ids<'a>(
+        &'a self,
+        collection_id: &'a str,
+        ids: Vec<String>,
+        include: Vec<Include>,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, Vec<Document>>;
+
+    /// Number of documents in a collection, counting only matches if a filter is given
+    fn count_documents<'a>(
+        &'a self,
+        collection_id: &'a str,
+        filter: &'a DocumentFilter,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, usize>;
+
+    /// Every document of a collection with the requested fields, one page per
+    /// item; the stream ends after the first error or once `cancel` is triggered
+    fn stream_documents(
+        &self,
+        collection_id: &str,
+        tenant: &str,
+        database: &str,
+        options: StreamOptions,
+        cancel: CancelToken,
+    ) -> DocumentStream;
+
+    /// The authenticated caller, if the backend knows one
+    fn identity(&self) -> StoreFuture<'_, Option<Identity>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Whether a tenant and a database within it exist
+    fn tenant_database_status<'a>(
+        &'a self,
+        tenant: &'a str,
+        database: &'a str,
+    ) -> StoreFuture<'a, (bool, bool)> {
+        Box::pin(async move {
+            let tenants = self.list_tenants().await?.unwrap_or_default();
+            if !tenants.iter().any(|name| name == tenant) {
+                return Ok((false, false));
+            }
+            let databases = self.list_databases(tenant).await?;
+            Ok((true, databases.iter().any(|name| name == database)))
+        })
+    }
+
+    /// Nearest neighbours of each query embedding, closest first
+    fn query_documents<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _request: &'a QueryRequest,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, Vec<Vec<QueryMatch>>> {
+        unsupported("Queries are")
+    }
+
+    fn create_tenant<'a>(&'a self, _tenant: &'a str) -> StoreFuture<'a, ()> {
+        unsupported("Creating tenants is")
+    }
+
+    fn create_database<'a>(&'a self, _tenant: &'a str, _database: &'a str) -> StoreFuture<'a, ()> {
+        unsupported("Creating databases is")
+    }
+
+    fn delete_database<'a>(&'a self, _tenant: &'a str, _database: &'a str) -> StoreFuture<'a, ()> {
+        unsupported("Deleting databases is")
+    }
+
+    fn create_collection<'a>(
+        &'a self,
+        _name: &'a str,
+        _options: &'a CreateCollectionOptions,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, Collection> {
+        read_only()
+    }
+
+    /// Rename a collection and/or replace its metadata
+    fn modify_collection<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _request: &'a ModifyCollectionRequest,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, ()> {
+        read_only()
+    }
+
+    /// Delete a collection by name
+    fn delete_collection<'a>(
+        &'a self,
+        _name: &'a str,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, ()> {
+        read_only()
+    }
+
+    /// Add new documents; a batch fails if any of its IDs already exist
+    fn add_documents<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _documents: &'a [Document],
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        read_only()
+    }
+
+    /// Add documents, replacing any with the same IDs
+    fn upsert_documents<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _documents: &'a [Document],
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        read_only()
+    }
+
+    /// Change fields of existing documents
+    fn update_documents<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _documents: &'a [Document],
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        read_only()
+    }
+
+    /// Delete documents by IDs and/or filter
+    fn delete_documents<'a>(
+        &'a self,
+        _collection_id: &'a str,
+        _document_ids: Option<Vec<String>>,
+        _filter: &'a DocumentFilter,
+        _tenant: &'a str,
+        _database: &'a str,
+    ) -> StoreFuture<'a, BatchReport> {
+        read_only()
+    }
+}
+
+/// Failure of an operation the backend does not offer
+fn unsupported<'a, T: Send + 'a>(what: &str) -> StoreFuture<'a, T> {
+    let error = ChromaError::InvalidRequest(format!("{} not supported by this source", what));
+    Box::pin(async move { Err(error) })
+}
+
+/// Failure of a write to a read-only backend
+fn read_only<'a, T: Send + 'a>() -> StoreFuture<'a, T> {
+    Box::pin(async {
+        Err(ChromaError::InvalidRequest(
+            "This source is read-only".to_string(),
+        ))
+    })
+}
+
+/// What the backend of a server supports, known without connecting
+pub fn capabilities(server: &ServerConfig) -> StoreCapabilities {
+    if server.local_path().is_some() {
+        StoreCapabilities::LOCAL
+    } else {
+        StoreCapabilities::FULL
+    }
+}
+
+/// The backend of a server, reusing its connection if there is one
+pub async fn open(server: &ServerConfig) -> Result<Arc<dyn VectorStore>, ChromaError> {
+    if let Some(directory) = server.local_path() {
+        return open_local(directory).await;
+    }
+    let client = ClientRegistry::global().client(server).await?;
+    Ok(Arc::new(client))
+}
+
+/// The backend of a server, connecting afresh, e.g. to test changed settings
+pub async fn reopen(server: &ServerConfig) -> Result<Arc<dyn VectorStore>, ChromaError> {
+    if let Some(directory) = server.local_path() {
+        return open_local(directory).await;
+    }
+    let client = ClientRegistry::global().reconnect(server).await?;
+    Ok(Arc::new(client))
+}
+
+/// Opens a persist directory on the blocking thread pool
+async fn open_local(directory: PathBuf) -> Result<Arc<dyn VectorStore>, ChromaError> {
+    let store = tokio::task::spawn_blocking(move || LocalStore::open(&directory))
+        .await
+        .map_err(|e| ChromaError::Storage(e.to_string()))??;
+    Ok(Arc::new(store))
+}
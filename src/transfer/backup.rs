@@ -0,0 +1,955 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Backing up every collection of a database to a single archive, and
+//! restoring it on any server.
+//!
+//! An archive is a JSONL file: a header line, then for every collection a line
+//! with its definition followed by one line per document with its metadata and
+//! embedding, and a last line with the number of collections and documents, so
+//! that an archive cut short is noticed. Restoring reads it line by line, so
+//! archives of any size can be restored without loading them whole.
+
+use super::{Record, TransferProgress, copy_options, failed, file_error};
+use crate::api::{
+    BatchReport, CancelToken, ChromaError, Collection, CreateCollectionOptions, Document, Include,
+    ModifyCollectionRequest, StreamOptions,
+};
+use crate::config::ServerConfig;
+use crate::store::{self, DocumentStream, VectorStore};
+use futures_util::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+
+/// Version of the archive format, raised when older versions can no longer
+/// read what is written
+const BACKUP_VERSION: u32 = 1;
+
+/// Documents per upsert request while restoring
+const RESTORE_BATCH_SIZE: usize = 500;
+
+/// A database on a configured server
+#[derive(Debug, Clone)]
+pub struct DatabaseSource {
+    pub server: ServerConfig,
+    pub tenant: String,
+    pub database: String,
+}
+
+/// One line of an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Entry {
+    /// The first line
+    Backup {
+        version: u32,
+        tenant: String,
+        database: String,
+        /// RFC 3339 time the backup started
+        created: String,
+    },
+    /// A collection, followed by its documents
+    Collection {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<BTreeMap<String, Value>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        configuration: Option<Map<String, Value>>,
+    },
+    Document(Record),
+    /// The last line, counting what came before it
+    End {
+        collections: usize,
+        documents: usize,
+    },
+}
+
+/// What to do with an archived collection whose name is taken in the target database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the existing collection and leave the archived one out
+    #[default]
+    Skip,
+    /// Delete the existing collection and restore the archived one in its place
+    Overwrite,
+    /// Restore the archived collection under a free name
+    Rename,
+}
+
+/// A collection stored in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedCollection {
+    pub name: String,
+    pub documents: usize,
+}
+
+/// Contents of an archive, as shown before restoring it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupSummary {
+    /// Where the backup was taken
+    pub tenant: String,
+    pub database: String,
+    pub created: String,
+    pub collections: Vec<ArchivedCollection>,
+}
+
+impl BackupSummary {
+    /// Documents that a restore would write, given the collections already
+    /// in the target database
+    pub fn documents_to_restore(&self, existing: &HashSet<String>, conflict: Conflict) -> usize {
+        self.collections
+            .iter()
+            .filter(|collection| conflict != Conflict::Skip || !existing.contains(&collection.name))
+            .map(|collection| collection.documents)
+            .sum()
+    }
+}
+
+/// Suggested file name for a backup of `database`, with today's date.
+pub fn backup_file_name(database: &str) -> String {
+    format!(
+        "{}-{}.chromatic-backup.jsonl",
+        database,
+        chrono::Local::now().format("%Y-%m-%d")
+    )
+}
+
+/// Write every collection of a database with its documents, metadata and
+/// embeddings to an archive at `path`.
+///
+/// An existing file is replaced. A backup that is cancelled or fails is
+/// removed again, as a partial archive would restore without complaint.
+pub fn backup_database(
+    source: DatabaseSource,
+    path: PathBuf,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        match store::open(&source.server).await {
+            Ok(store) => backup_collections(store, source, path, cancel).left_stream(),
+            Err(e) => failed(e.to_string()).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// A backup with its file open
+struct Backup {
+    store: Arc<dyn VectorStore>,
+    source: DatabaseSource,
+    /// Collections still to write
+    collections: VecDeque<Collection>,
+    /// Documents of the collection being written
+    documents: Option<DocumentStream>,
+    file: File,
+    path: PathBuf,
+    cancel: CancelToken,
+    /// Collections started so far
+    archived: usize,
+    written: usize,
+    total: Option<usize>,
+}
+
+/// Back up from a store that is already open
+fn backup_collections(
+    store: Arc<dyn VectorStore>,
+    source: DatabaseSource,
+    path: PathBuf,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        let collections = match store
+            .list_collections(&source.tenant, &source.database)
+            .await
+        {
+            Ok(collections) => collections,
+            Err(e) => return failed(e.to_string()).right_stream(),
+        };
+        // Progress is only informational, so a failed count does not stop the backup
+        let mut total = Some(0);
+        for collection in &collections {
+            let count = store
+                .count_documents(
+                    &collection.id,
+                    &Default::default(),
+                    &source.tenant,
+                    &source.database,
+                )
+                .await;
+            total = total.zip(count.ok()).map(|(total, count)| total + count);
+        }
+
+        let mut file = match File::create(&path).await {
+            Ok(file) => file,
+            Err(e) => return failed(file_error(&path, e)).right_stream(),
+        };
+        let header = Entry::Backup {
+            version: BACKUP_VERSION,
+            tenant: source.tenant.clone(),
+            database: source.database.clone(),
+            created: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Err(e) = file.write_all(&line(&header)).await {
+            return failed(file_error(&path, e)).right_stream();
+        }
+
+        let backup = Backup {
+            store,
+            source,
+            collections: collections.into(),
+            documents: None,
+            file,
+            path,
+            cancel,
+            archived: 0,
+            written: 0,
+            total,
+        };
+        backup.run().left_stream()
+    })
+    .flatten()
+}
+
+impl Backup {
+    /// Writes page by page, ending with the outcome
+    fn run(self) -> impl Stream<Item = TransferProgress> + Send + 'static {
+        stream::unfold(Some(self), |backup| async move {
+            let mut backup = backup?;
+            let outcome = match backup.next_page().await {
+                Ok(true) => {
+                    let progress = TransferProgress::Running {
+                        done: backup.written,
+                        total: backup.total,
+                    };
+                    return Some((progress, Some(backup)));
+                }
+                Ok(false) => backup.finish().await,
+                Err(e) => {
+                    backup.discard().await;
+                    TransferProgress::Failed(e)
+                }
+            };
+            Some((outcome, None))
+        })
+    }
+
+    /// Writes the next page of documents, starting the next collection as
+    /// needed; `false` once every collection is written or the backup is cancelled
+    async fn next_page(&mut self) -> Result<bool, String> {
+        loop {
+            if let Some(documents) = &mut self.documents {
+                match documents.next().await {
+                    Some(Ok(batch)) => {
+                        let mut lines = Vec::new();
+                        for document in batch.documents {
+                            lines.extend(line(&Entry::Document(Record::from(document))));
+                            self.written += 1;
+                        }
+                        self.write(&lines).await?;
+                        return Ok(true);
+                    }
+                    Some(Err(e)) => return Err(e.to_string()),
+                    None => self.documents = None,
+                }
+            }
+            if self.cancel.is_cancelled() {
+                return Ok(false);
+            }
+            let Some(collection) = self.collections.pop_front() else {
+                return Ok(false);
+            };
+
+            let options = copy_options(&collection);
+            let definition = Entry::Collection {
+                name: collection.name,
+                metadata: options
+                    .metadata
+                    .map(|metadata| metadata.into_iter().collect()),
+                configuration: options.configuration,
+            };
+            self.write(&line(&definition)).await?;
+            self.archived += 1;
+            let options = StreamOptions {
+                include: vec![
+                    Include::Documents,
+                    Include::Metadatas,
+                    Include::Embeddings,
+                    Include::Uris,
+                ],
+                ..Default::default()
+            };
+            self.documents = Some(self.store.stream_documents(
+                &collection.id,
+                &self.source.tenant,
+                &self.source.database,
+                options,
+                self.cancel.clone(),
+            ));
+        }
+    }
+
+    async fn write(&mut self, lines: &[u8]) -> Result<(), String> {
+        self.file
+            .write_all(lines)
+            .await
+            .map_err(|e| file_error(&self.path, e))
+    }
+
+    async fn finish(mut self) -> TransferProgress {
+        if self.cancel.is_cancelled() {
+            let done = self.written;
+            self.discard().await;
+            return TransferProgress::Cancelled { done };
+        }
+        let end = Entry::End {
+            collections: self.archived,
+            documents: self.written,
+        };
+        let written = match self.file.write_all(&line(&end)).await {
+            Ok(()) => self.file.flush().await,
+            Err(e) => Err(e),
+        };
+        match written {
+            Ok(()) => TransferProgress::Finished { done: self.written },
+            Err(e) => {
+                let error = file_error(&self.path, e);
+                self.discard().await;
+                TransferProgress::Failed(error)
+            }
+        }
+    }
+
+    /// Removes the incomplete archive
+    async fn discard(self) {
+        drop(self.file);
+        let _ = tokio::fs::remove_file(&self.path).await;
+    }
+}
+
+/// A line of an archive, with its newline
+fn line(entry: &Entry) -> Vec<u8> {
+    // Entries hold nothing that fails to serialize
+    let mut line = serde_json::to_vec(entry).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+/// Reads the header of an archive and counts the documents of each
+/// collection, checking that every line can be restored.
+pub async fn read_backup(path: PathBuf) -> Result<BackupSummary, String> {
+    let mut reader = ArchiveReader::open(&path).await?;
+    let Some(Entry::Backup {
+        tenant,
+        database,
+        created,
+        ..
+    }) = reader.header.clone()
+    else {
+        return Err(not_a_backup(&path));
+    };
+
+    let mut summary = BackupSummary {
+        tenant,
+        database,
+        created,
+        collections: Vec::new(),
+    };
+    while let Some(entry) = reader.next().await? {
+        match entry {
+            Entry::Collection { name, .. } => summary
+                .collections
+                .push(ArchivedCollection { name, documents: 0 }),
+            Entry::Document(_) => match summary.collections.last_mut() {
+                Some(collection) => collection.documents += 1,
+                None => return Err(reader.error("Document outside a collection")),
+            },
+            Entry::Backup { .. } => return Err(reader.error("Unexpected header")),
+            Entry::End { .. } => unreachable!("the reader checks the end itself"),
+        }
+    }
+    Ok(summary)
+}
+
+/// Reads the entries of an archive after its header, up to its last line.
+///
+/// The last line is checked against what was read instead of being returned,
+/// and an archive without it is an error.
+struct ArchiveReader {
+    lines: Lines<BufReader<File>>,
+    line: usize,
+    header: Option<Entry>,
+    /// Collections and documents read so far
+    collections: usize,
+    documents: usize,
+    ended: bool,
+}
+
+impl ArchiveReader {
+    async fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .await
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let mut reader = Self {
+            lines: BufReader::new(file).lines(),
+            line: 0,
+            header: None,
+            collections: 0,
+            documents: 0,
+            ended: false,
+        };
+        match reader.next().await {
+            Ok(Some(Entry::Backup { version, .. })) if version > BACKUP_VERSION => Err(format!(
+                "The backup was written by a newer version (format {})",
+                version
+            )),
+            Ok(Some(header @ Entry::Backup { .. })) => {
+                reader.header = Some(header);
+                Ok(reader)
+            }
+            _ => Err(not_a_backup(path)),
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Entry>, String> {
+        loop {
+            let text = self
+                .lines
+                .next_line()
+                .await
+                .map_err(|e| self.error(&e.to_string()))?;
+            let Some(text) = text else {
+                // Only the header may be read before the last line is checked
+                if !self.ended && self.header.is_some() {
+                    return Err("The backup is incomplete".to_string());
+                }
+                return Ok(None);
+            };
+            self.line += 1;
+            if text.trim().is_empty() {
+                continue;
+            }
+            if self.ended {
+                return Err(self.error("Unexpected line after the end of the backup"));
+            }
+            let entry = serde_json::from_str(&text).map_err(|e| self.error(&e.to_string()))?;
+            match entry {
+                Entry::Collection { .. } => self.collections += 1,
+                Entry::Document(_) => self.documents += 1,
+                Entry::End {
+                    collections,
+                    documents,
+                } => {
+                    if (collections, documents) != (self.collections, self.documents) {
+                        return Err(self.error(&format!(
+                            "The backup should hold {} collections with {} documents, \
+                             but holds {} with {}",
+                            collections, documents, self.collections, self.documents
+                        )));
+                    }
+                    self.ended = true;
+                    continue;
+                }
+                Entry::Backup { .. } => {}
+            }
+            return Ok(Some(entry));
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Line {}: {}", self.line, message)
+    }
+}
+
+fn not_a_backup(path: &Path) -> String {
+    format!("{} is not a Chromatic backup", path.display())
+}
+
+/// Recreate the collections of an archive in a database, then upsert their
+/// documents in batches.
+///
+/// Collections whose name is taken are handled as `conflict` says. A restore
+/// that stops early leaves the collections restored so far in place.
+pub fn restore_database(
+    path: PathBuf,
+    target: DatabaseSource,
+    conflict: Conflict,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        match store::open(&target.server).await {
+            Ok(store) => restore_collections(store, path, target, conflict, cancel).left_stream(),
+            Err(e) => failed(e.to_string()).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// A restore reading through its archive
+struct Restore {
+    store: Arc<dyn VectorStore>,
+    target: DatabaseSource,
+    conflict: Conflict,
+    reader: ArchiveReader,
+    /// Names taken in the target database, including restored collections
+    taken: HashSet<String>,
+    /// Collection the documents being read go to; `None` while skipping one
+    collection: Option<String>,
+    batch: Vec<Document>,
+    cancel: CancelToken,
+    done: usize,
+    total: usize,
+}
+
+/// Restore into a store that is already open
+fn restore_collections(
+    store: Arc<dyn VectorStore>,
+    path: PathBuf,
+    target: DatabaseSource,
+    conflict: Conflict,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        // Reading the archive once up front catches damage before anything is written
+        let summary = match read_backup(path.clone()).await {
+            Ok(summary) => summary,
+            Err(e) => return failed(e).right_stream(),
+        };
+        let taken: HashSet<String> = match store
+            .list_collections(&target.tenant, &target.database)
+            .await
+        {
+            Ok(collections) => collections.into_iter().map(|c| c.name).collect(),
+            Err(e) => return failed(e.to_string()).right_stream(),
+        };
+        let reader = match ArchiveReader::open(&path).await {
+            Ok(reader) => reader,
+            Err(e) => return failed(e).right_stream(),
+        };
+
+        let restore = Restore {
+            store,
+            target,
+            conflict,
+            reader,
+            total: summary.documents_to_restore(&taken, conflict),
+            taken,
+            collection: None,
+            batch: Vec::new(),
+            cancel,
+            done: 0,
+        };
+        restore.run().left_stream()
+    })
+    .flatten()
+}
+
+impl Restore {
+    /// Restores batch by batch, ending with the outcome
+    fn run(self) -> impl Stream<Item = TransferProgress> + Send + 'static {
+        stream::unfold(Some(self), |restore| async move {
+            let mut restore = restore?;
+            if restore.cancel.is_cancelled() {
+                let done = restore.done;
+                return Some((TransferProgress::Cancelled { done }, None));
+            }
+            let outcome = match restore.next_batch().await {
+                Ok(true) => {
+                    let progress = TransferProgress::Running {
+                        done: restore.done,
+                        total: Some(restore.total),
+                    };
+                    return Some((progress, Some(restore)));
+                }
+                Ok(false) => TransferProgress::Finished { done: restore.done },
+                Err(e) => TransferProgress::Failed(e),
+            };
+            Some((outcome, None))
+        })
+    }
+
+    /// Reads up to the next full batch or collection and writes it; `false`
+    /// once the archive is restored
+    async fn next_batch(&mut self) -> Result<bool, String> {
+        loop {
+            match self.reader.next().await? {
+                Some(Entry::Document(record)) => {
+                    if self.collection.is_some() {
+                        self.batch.push(record.into());
+                    }
+                    if self.batch.len() >= RESTORE_BATCH_SIZE {
+                        self.flush().await?;
+                        return Ok(true);
+                    }
+                }
+                Some(Entry::Collection {
+                    name,
+                    metadata,
+                    configuration,
+                }) => {
+                    let flushed = self.flush().await?;
+                    let options = CreateCollectionOptions {
+                        metadata: metadata.map(|metadata| metadata.into_iter().collect()),
+                        configuration,
+                        ..Default::default()
+                    };
+                    self.collection = self
+                        .create(&name, &options)
+                        .await
+                        .map_err(|e| format!("Collection '{}': {}", name, e))?;
+                    if flushed {
+                        return Ok(true);
+                    }
+                }
+                Some(Entry::Backup { .. }) => return Err(self.reader.error("Unexpected header")),
+                Some(Entry::End { .. }) => unreachable!("the reader checks the end itself"),
+                None => return self.flush().await,
+            }
+        }
+    }
+
+    /// Creates an archived collection as `conflict` says, returning its ID,
+    /// or `None` if it is skipped
+    async fn create(
+        &mut self,
+        name: &str,
+        options: &CreateCollectionOptions,
+    ) -> Result<Option<String>, ChromaError> {
+        let (tenant, database) = (&self.target.tenant, &self.target.database);
+        let conflict = self.taken.contains(name).then_some(self.conflict);
+        let created_name = match conflict {
+            Some(Conflict::Skip) => return Ok(None),
+            // An overwritten collection is only deleted once its replacement
+            // exists, so a collection the server refuses loses nothing
+            Some(Conflict::Overwrite | Conflict::Rename) => free_name(name, &self.taken),
+            None => name.to_string(),
+        };
+        let collection = self
+            .store
+            .create_collection(&created_name, options, tenant, database)
+            .await?;
+        self.taken.insert(created_name.clone());
+        if conflict == Some(Conflict::Overwrite) {
+            self.store.delete_collection(name, tenant, database).await?;
+            let rename = ModifyCollectionRequest {
+                new_name: Some(name.to_string()),
+                ..Default::default()
+            };
+            self.store
+                .modify_collection(&collection.id, &rename, tenant, database)
+                .await?;
+            self.taken.remove(&created_name);
+        }
+        Ok(Some(collection.id))
+    }
+
+    /// Upserts the documents read so far; `false` if there were none
+    async fn flush(&mut self) -> Result<bool, String> {
+        let Some(collection_id) = &self.collection else {
+            return Ok(false);
+        };
+        if self.batch.is_empty() {
+            return Ok(false);
+        }
+        self.store
+            .upsert_documents(
+                collection_id,
+                &self.batch,
+                &self.target.tenant,
+                &self.target.database,
+            )
+            .await
+            .and_then(BatchReport::into_result)
+            .map_err(|e| e.to_string())?;
+        self.done += self.batch.len();
+        self.batch.clear();
+        Ok(true)
+    }
+}
+
+/// `name` with the first suffix that is not taken yet
+fn free_name(name: &str, taken: &HashSet<String>) -> String {
+    let mut candidate = format!("{}-restored", name);
+    let mut suffix = 1;
+    while taken.contains(&candidate) {
+        suffix += 1;
+        candidate = format!("{}-restored-{}", name, suffix);
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{
+        ApiVersion, DEFAULT_DATABASE, DEFAULT_TENANT, MockServer, page_document,
+    };
+    use serde_json::json;
+
+    fn database() -> DatabaseSource {
+        DatabaseSource {
+            server: Default::default(),
+            tenant: DEFAULT_TENANT.to_string(),
+            database: DEFAULT_DATABASE.to_string(),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "chromatic-backup-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn documents(prefix: &str, count: usize) -> Vec<Document> {
+        (0..count as i64)
+            .map(|i| {
+                let id = format!("{}-{}", prefix, i);
+                page_document(&id, &format!("{} text {}", prefix, i), i)
+            })
+            .collect()
+    }
+
+    async fn backup(server: &MockServer, path: &Path) -> Vec<TransferProgress> {
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        backup_collections(store, database(), path.to_path_buf(), CancelToken::new())
+            .collect()
+            .await
+    }
+
+    async fn restore(
+        server: &MockServer,
+        path: &Path,
+        conflict: Conflict,
+    ) -> Vec<TransferProgress> {
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        restore_collections(
+            store,
+            path.to_path_buf(),
+            database(),
+            conflict,
+            CancelToken::new(),
+        )
+        .collect()
+        .await
+    }
+
+    #[test]
+    fn test_free_name() {
+        let taken: HashSet<String> = ["notes", "notes-restored", "notes-restored-2"]
+            .map(String::from)
+            .into();
+        assert_eq!(free_name("notes", &taken), "notes-restored-3");
+        assert_eq!(free_name("other", &taken), "other-restored");
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_on_another_server() {
+        let source = MockServer::start(ApiVersion::V1).await;
+        let notes = source.add_collection("notes");
+        source.insert(&notes, &documents("note", 3));
+        source.add_collection("empty");
+        let path = temp_path("roundtrip");
+
+        let progress = backup(&source, &path).await;
+        assert_eq!(
+            progress.last(),
+            Some(&TransferProgress::Finished { done: 3 })
+        );
+        let summary = read_backup(path.clone()).await.unwrap();
+        assert_eq!(summary.database, DEFAULT_DATABASE);
+        assert_eq!(
+            summary.collections,
+            [
+                ArchivedCollection {
+                    name: "notes".to_string(),
+                    documents: 3
+                },
+                ArchivedCollection {
+                    name: "empty".to_string(),
+                    documents: 0
+                },
+            ]
+        );
+
+        let target = MockServer::start(ApiVersion::V2).await;
+        let progress = restore(&target, &path, Conflict::Skip).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            progress,
+            [
+                TransferProgress::Running {
+                    done: 3,
+                    total: Some(3)
+                },
+                TransferProgress::Finished { done: 3 },
+            ]
+        );
+
+        let store: Arc<dyn VectorStore> = Arc::new(target.client());
+        let collections = store
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        let names: Vec<&str> = collections.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["notes", "empty"]);
+        let restored = store
+            .get_documents_by_ids(
+                &collections[0].id,
+                vec!["note-1".to_string()],
+                vec![Include::Documents, Include::Metadatas, Include::Embeddings],
+                DEFAULT_TENANT,
+                DEFAULT_DATABASE,
+            )
+            .await
+            .unwrap();
+        let expected = &documents("note", 3)[1];
+        assert_eq!(restored[0].document, expected.document);
+        assert_eq!(restored[0].metadata, expected.metadata);
+        assert_eq!(restored[0].embedding, expected.embedding);
+    }
+
+    #[tokio::test]
+    async fn test_restore_conflicts() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let notes = server.add_collection("notes");
+        server.insert(&notes, &documents("note", 2));
+        let path = temp_path("conflicts");
+        backup(&server, &path).await;
+        server.insert(&notes, &documents("extra", 1));
+
+        let skipped = restore(&server, &path, Conflict::Skip).await;
+        assert_eq!(skipped, [TransferProgress::Finished { done: 0 }]);
+        assert_eq!(server.ids(&notes).len(), 3);
+
+        let renamed = restore(&server, &path, Conflict::Rename).await;
+        assert_eq!(
+            renamed.last(),
+            Some(&TransferProgress::Finished { done: 2 })
+        );
+
+        let overwritten = restore(&server, &path, Conflict::Overwrite).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            overwritten.last(),
+            Some(&TransferProgress::Finished { done: 2 })
+        );
+
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        let collections = store
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        let mut names: Vec<&str> = collections.iter().map(|c| c.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["notes", "notes-restored"]);
+        let restored = collections.iter().find(|c| c.name == "notes").unwrap();
+        assert_eq!(server.ids(&restored.id), ["note-0", "note-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_configuration() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let notes = server.add_collection("notes");
+        server.insert(&notes, &documents("note", 2));
+        server.set_configuration(
+            &notes,
+            json!({"hnsw": {"space": "cosine", "ef_search": 50}, "spann": null}),
+        );
+        let path = temp_path("configuration");
+        backup(&server, &path).await;
+
+        let overwritten = restore(&server, &path, Conflict::Overwrite).await;
+        assert_eq!(
+            overwritten.last(),
+            Some(&TransferProgress::Finished { done: 2 })
+        );
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        let collections = store
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(collections[0].name, "notes");
+        assert_eq!(
+            collections[0].configuration,
+            json!({"hnsw": {"space": "cosine", "ef_search": 50}})
+                .as_object()
+                .cloned()
+        );
+        let restored = collections[0].id.clone();
+
+        // A collection the server refuses leaves the one it would replace alone
+        let archive = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("\"configuration\":{", "\"configuration\":{\"spann\":null,");
+        std::fs::write(&path, archive).unwrap();
+        let refused = restore(&server, &path, Conflict::Overwrite).await;
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(refused[..], [TransferProgress::Failed(_)]));
+        let collections = store
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        assert_eq!(collections.len(), 1);
+        assert_eq!(server.ids(&restored), ["note-0", "note-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_backup_rejects_other_files() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, "{\"id\":\"a\",\"document\":\"x\"}\n").unwrap();
+        let error = read_backup(path.clone()).await.unwrap_err();
+        assert!(error.ends_with("is not a Chromatic backup"));
+
+        let header = "{\"type\":\"backup\",\"version\":1,\"tenant\":\"t\",\"database\":\"d\",\
+                      \"created\":\"2026-01-01T00:00:00Z\"}";
+        std::fs::write(
+            &path,
+            format!("{}\n{{\"type\":\"document\",\"id\":\"a\"}}\n", header),
+        )
+        .unwrap();
+        let error = read_backup(path.clone()).await.unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error, "Line 2: Document outside a collection");
+    }
+
+    #[tokio::test]
+    async fn test_truncated_backup_is_rejected() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let notes = server.add_collection("notes");
+        server.insert(&notes, &documents("note", 3));
+        let path = temp_path("truncated");
+        backup(&server, &path).await;
+        let archive = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = archive.lines().collect();
+        assert!(lines.last().unwrap().starts_with("{\"type\":\"end\""));
+
+        // Cut off after a whole document
+        std::fs::write(&path, lines[..lines.len() - 2].join("\n")).unwrap();
+        let error = read_backup(path.clone()).await.unwrap_err();
+        assert_eq!(error, "The backup is incomplete");
+        let progress = restore(&server, &path, Conflict::Rename).await;
+        assert_eq!(
+            progress,
+            [TransferProgress::Failed(
+                "The backup is incomplete".to_string()
+            )]
+        );
+
+        // Lost a document but kept the end
+        let mut damaged = lines.clone();
+        damaged.remove(2);
+        std::fs::write(&path, damaged.join("\n")).unwrap();
+        let error = read_backup(path.clone()).await.unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            error,
+            "Line 5: The backup should hold 1 collections with 3 documents, but holds 1 with 2"
+        );
+    }
+}
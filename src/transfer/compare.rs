@@ -0,0 +1,561 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Comparing two collections document by document.
+//!
+//! The collections can live on different servers. Documents are matched by
+//! ID, so the comparison tells which IDs only one side has and how the
+//! documents both sides have differ.
+
+use super::CollectionSource;
+use crate::api::{
+    CancelToken, Document, DocumentBatch, DocumentFilter, Include, Metadata, StreamOptions,
+};
+use crate::store::{self, VectorStore};
+use futures_util::stream::BoxStream;
+use futures_util::{Stream, StreamExt, stream};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Largest difference between two embedding values that still counts as equal
+pub const DEFAULT_TOLERANCE: f32 = 1e-4;
+
+/// What differs between two documents with the same ID
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub document: bool,
+    pub metadata: bool,
+    pub embedding: bool,
+}
+
+impl Changes {
+    /// Compares two documents, treating embedding values as equal within `tolerance`
+    pub fn between(a: &Document, b: &Document, tolerance: f32) -> Self {
+        Self {
+            document: a.document != b.document,
+            metadata: !metadata_changes(a.metadata.as_ref(), b.metadata.as_ref()).is_empty(),
+            embedding: embeddings_differ(a.embedding.as_deref(), b.embedding.as_deref(), tolerance),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.document || self.metadata || self.embedding)
+    }
+}
+
+/// How a document differs between collection A and collection B
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    OnlyInA,
+    OnlyInB,
+    Changed(Changes),
+}
+
+/// Kinds of differences the results can be narrowed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    OnlyInA,
+    OnlyInB,
+    Document,
+    Metadata,
+    Embedding,
+}
+
+impl DiffKind {
+    pub const ALL: [Self; 5] = [
+        Self::OnlyInA,
+        Self::OnlyInB,
+        Self::Document,
+        Self::Metadata,
+        Self::Embedding,
+    ];
+}
+
+/// A document that is not the same in both collections
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    pub id: String,
+    pub difference: Difference,
+    /// The document in collection A, if it has one with this ID
+    pub a: Option<Document>,
+    /// The document in collection B, if it has one with this ID
+    pub b: Option<Document>,
+}
+
+impl DiffEntry {
+    /// Whether the entry shows this kind of difference
+    pub fn is(&self, kind: DiffKind) -> bool {
+        match (self.difference, kind) {
+            (Difference::OnlyInA, DiffKind::OnlyInA) | (Difference::OnlyInB, DiffKind::OnlyInB) => {
+                true
+            }
+            (Difference::Changed(changes), DiffKind::Document) => changes.document,
+            (Difference::Changed(changes), DiffKind::Metadata) => changes.metadata,
+            (Difference::Changed(changes), DiffKind::Embedding) => changes.embedding,
+            _ => false,
+        }
+    }
+}
+
+/// Outcome of comparing two collections
+#[derive(Debug, Clone, Default)]
+pub struct CollectionDiff {
+    /// Documents that differ, sorted by ID
+    pub entries: Vec<DiffEntry>,
+    /// Documents that are the same in both collections
+    pub identical: usize,
+    /// Whether embeddings were compared, which needs both sources to read
+    /// every embedding
+    pub embeddings_compared: bool,
+}
+
+impl CollectionDiff {
+    /// Number of entries showing this kind of difference
+    pub fn count(&self, kind: DiffKind) -> usize {
+        self.entries.iter().filter(|entry| entry.is(kind)).count()
+    }
+}
+
+/// State of a running comparison
+#[derive(Debug, Clone)]
+pub enum CompareProgress {
+    /// Documents read from both collections so far, and how many they hold if
+    /// they could be counted
+    Running { done: usize, total: Option<usize> },
+    /// Both collections were read
+    Finished(CollectionDiff),
+    /// Stopped on request
+    Cancelled,
+    /// Stopped by an error
+    Failed(String),
+}
+
+impl CompareProgress {
+    /// Whether the comparison has stopped
+    pub fn is_done(&self) -> bool {
+        !matches!(self, Self::Running { .. })
+    }
+
+    /// Fraction of the documents read so far, between 0 and 1, if known
+    pub fn fraction(&self) -> Option<f32> {
+        match self {
+            Self::Running { total: Some(0), .. } | Self::Finished(_) => Some(1.0),
+            Self::Running {
+                done,
+                total: Some(total),
+            } => Some((*done as f32 / *total as f32).min(1.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Metadata keys that are missing on one side or hold different values.
+///
+/// Missing metadata counts as empty, and numbers are compared by value so
+/// that `1` and `1.0` are the same.
+pub fn metadata_changes(a: Option<&Metadata>, b: Option<&Metadata>) -> BTreeSet<String> {
+    let empty = Metadata::new();
+    let (a, b) = (a.unwrap_or(&empty), b.unwrap_or(&empty));
+    a.keys()
+        .chain(b.keys())
+        .filter(|key| match (a.get(*key), b.get(*key)) {
+            (Some(x), Some(y)) => !values_equal(x, y),
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+/// Whether two embeddings differ in length or by more than `tolerance` in any value
+fn embeddings_differ(a: Option<&[f32]>, b: Option<&[f32]>, tolerance: f32) -> bool {
+    match (a, b) {
+        (None, None) => false,
+        (Some(a), Some(b)) => {
+            a.len() != b.len() || a.iter().zip(b).any(|(x, y)| (x - y).abs() > tolerance)
+        }
+        _ => true,
+    }
+}
+
+/// Compare every document of collection `a` with collection `b`.
+///
+/// Collections without an ID are looked up by name. Collection A is held in
+/// memory while B is read, and the stream ends with the differences once
+/// both were read. Embeddings are left out unless both sources can read
+/// every one of them.
+pub fn compare_collections(
+    a: CollectionSource,
+    b: CollectionSource,
+    tolerance: f32,
+    cancel: CancelToken,
+) -> impl Stream<Item = CompareProgress> + Send + 'static {
+    stream::once(async move {
+        let embeddings = [&a.server, &b.server]
+            .into_iter()
+            .all(|server| store::capabilities(server).embeddings);
+        let stores = match store::open(&a.server).await {
+            Ok(first) => store::open(&b.server).await.map(|second| (first, second)),
+            Err(e) => Err(e),
+        };
+        match stores {
+            Ok((first, second)) => {
+                compare_documents(first, second, a, b, embeddings, tolerance, cancel).left_stream()
+            }
+            Err(e) => stream::iter([CompareProgress::Failed(e.to_string())]).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// A comparison with both collections found
+struct Compare {
+    /// Pages of A, then pages of B, each marked with whether it is from B
+    pages: BoxStream<'static, (bool, Result<DocumentBatch, String>)>,
+    total: Option<usize>,
+    tolerance: f32,
+    cancel: CancelToken,
+    read: usize,
+    /// Documents of A not yet matched in B
+    unmatched: HashMap<String, Document>,
+    diff: CollectionDiff,
+}
+
+/// Compare collections of stores that are already open
+fn compare_documents(
+    first: Arc<dyn VectorStore>,
+    second: Arc<dyn VectorStore>,
+    a: CollectionSource,
+    b: CollectionSource,
+    embeddings: bool,
+    tolerance: f32,
+    cancel: CancelToken,
+) -> impl Stream<Item = CompareProgress> + Send + 'static {
+    stream::once(async move {
+        let found = match find_collection(&*first, a).await {
+            Ok(a) => find_collection(&*second, b).await.map(|b| (a, b)),
+            Err(e) => Err(e),
+        };
+        let (a, b) = match found {
+            Ok(found) => found,
+            Err(e) => return stream::iter([CompareProgress::Failed(e)]).right_stream(),
+        };
+
+        let filter = DocumentFilter::default();
+        let counts = (
+            first
+                .count_documents(&a.collection_id, &filter, &a.tenant, &a.database)
+                .await,
+            second
+                .count_documents(&b.collection_id, &filter, &b.tenant, &b.database)
+                .await,
+        );
+        let total = match counts {
+            (Ok(a), Ok(b)) => Some(a + b),
+            _ => None,
+        };
+
+        let mut include = vec![Include::Documents, Include::Metadatas];
+        if embeddings {
+            include.push(Include::Embeddings);
+        }
+        let read = |store: &dyn VectorStore, source: &CollectionSource, from_b: bool| {
+            let options = StreamOptions {
+                include: include.clone(),
+                ..Default::default()
+            };
+            store
+                .stream_documents(
+                    &source.collection_id,
+                    &source.tenant,
+                    &source.database,
+                    options,
+                    cancel.clone(),
+                )
+                .map(move |page| (from_b, page.map_err(|e| e.to_string())))
+        };
+        let pages = read(&*first, &a, false)
+            .chain(read(&*second, &b, true))
+            .boxed();
+
+        let compare = Compare {
+            pages,
+            total,
+            tolerance,
+            cancel,
+            read: 0,
+            unmatched: HashMap::new(),
+            diff: CollectionDiff {
+                embeddings_compared: embeddings,
+                ..Default::default()
+            },
+        };
+        compare.run().left_stream()
+    })
+    .flatten()
+}
+
+/// Looks up the ID of a collection known only by name
+async fn find_collection(
+    store: &dyn VectorStore,
+    mut source: CollectionSource,
+) -> Result<CollectionSource, String> {
+    if !source.collection_id.is_empty() {
+        return Ok(source);
+    }
+    let collection = store
+        .list_collections(&source.tenant, &source.database)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|collection| collection.name == source.collection_name);
+    match collection {
+        Some(collection) => {
+            source.collection_id = collection.id;
+            Ok(source)
+        }
+        None => Err(format!(
+            "No collection '{}' in {}/{}",
+            source.collection_name, source.tenant, source.database
+        )),
+    }
+}
+
+impl Compare {
+    /// Reads page by page, ending with the outcome
+    fn run(self) -> impl Stream<Item = CompareProgress> + Send + 'static {
+        stream::unfold(Some(self), |compare| async move {
+            let mut compare = compare?;
+            let outcome = match compare.pages.next().await {
+                Some((from_b, Ok(batch))) => {
+                    compare.read += batch.documents.len();
+                    for document in batch.documents {
+                        if from_b {
+                            compare.match_document(document);
+                        } else {
+                            compare.unmatched.insert(document.id.clone(), document);
+                        }
+                    }
+                    let progress = CompareProgress::Running {
+                        done: compare.read,
+                        total: compare.total,
+                    };
+                    return Some((progress, Some(compare)));
+                }
+                Some((_, Err(e))) => CompareProgress::Failed(e),
+                None if compare.cancel.is_cancelled() => CompareProgress::Cancelled,
+                None => CompareProgress::Finished(compare.finish()),
+            };
+            Some((outcome, None))
+        })
+    }
+
+    /// Compares a document of B with the document of A with the same ID
+    fn match_document(&mut self, b: Document) {
+        let entry = match self.unmatched.remove(&b.id) {
+            Some(a) => {
+                let changes = Changes::between(&a, &b, self.tolerance);
+                if changes.is_empty() {
+                    self.diff.identical += 1;
+                    return;
+                }
+                DiffEntry {
+                    id: b.id.clone(),
+                    difference: Difference::Changed(changes),
+                    a: Some(a),
+                    b: Some(b),
+                }
+            }
+            None => DiffEntry {
+                id: b.id.clone(),
+                difference: Difference::OnlyInB,
+                a: None,
+                b: Some(b),
+            },
+        };
+        self.diff.entries.push(entry);
+    }
+
+    /// The differences, once every document of B was matched
+    fn finish(mut self) -> CollectionDiff {
+        self.diff
+            .entries
+            .extend(self.unmatched.into_values().map(|a| DiffEntry {
+                id: a.id.clone(),
+                difference: Difference::OnlyInA,
+                a: Some(a),
+                b: None,
+            }));
+        self.diff.entries.sort_by(|x, y| x.id.cmp(&y.id));
+        self.diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{ApiVersion, MockServer, collection_source, page_document};
+    use serde_json::json;
+
+    fn collection(name: &str) -> CollectionSource {
+        collection_source("", name)
+    }
+
+    #[test]
+    fn test_metadata_changes() {
+        let a: Metadata = [
+            ("page".to_string(), json!(1)),
+            ("source".to_string(), json!("a.pdf")),
+        ]
+        .into();
+        let mut b = a.clone();
+        b.insert("page".to_string(), json!(1.0));
+        assert!(metadata_changes(Some(&a), Some(&b)).is_empty());
+        assert!(metadata_changes(None, Some(&Metadata::new())).is_empty());
+
+        b.insert("source".to_string(), json!("b.pdf"));
+        b.insert("lang".to_string(), json!("en"));
+        b.remove("page");
+        assert_eq!(
+            metadata_changes(Some(&a), Some(&b)),
+            BTreeSet::from(["lang".to_string(), "page".to_string(), "source".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compare_collections_between_servers() {
+        let old = MockServer::start(ApiVersion::V1).await;
+        let new = MockServer::start(ApiVersion::V2).await;
+        let a = old.add_collection("notes");
+        let b = new.add_collection("notes-reingested");
+        old.insert(
+            &a,
+            &[
+                page_document("same", "text", 1),
+                page_document("nearly-same", "text", 1),
+                page_document("text", "old text", 1),
+                page_document("metadata", "text", 1),
+                page_document("embedding", "text", 1),
+                page_document("removed", "text", 1),
+            ],
+        );
+        new.insert(
+            &b,
+            &[
+                Document {
+                    metadata: Some([("page".to_string(), json!(1.0))].into()),
+                    ..page_document("same", "text", 1)
+                },
+                Document {
+                    embedding: Some(vec![1.00001, 0.5]),
+                    ..page_document("nearly-same", "text", 1)
+                },
+                page_document("text", "new text", 1),
+                Document {
+                    embedding: Some(vec![1.0, 0.5]),
+                    ..page_document("metadata", "text", 2)
+                },
+                page_document("embedding", "text", 2),
+                page_document("added", "text", 1),
+            ],
+        );
+
+        let progress: Vec<CompareProgress> = compare_documents(
+            Arc::new(old.client()),
+            Arc::new(new.client()),
+            collection("notes"),
+            collection("notes-reingested"),
+            true,
+            DEFAULT_TOLERANCE,
+            CancelToken::new(),
+        )
+        .collect()
+        .await;
+        assert!(matches!(
+            progress[0],
+            CompareProgress::Running {
+                done: 6,
+                total: Some(12)
+            }
+        ));
+        let Some(CompareProgress::Finished(diff)) = progress.last() else {
+            panic!("comparison did not finish: {:?}", progress.last());
+        };
+
+        assert_eq!(diff.identical, 2);
+        let differences: Vec<(&str, Difference)> = diff
+            .entries
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry.difference))
+            .collect();
+        let changed = |document, metadata, embedding| {
+            Difference::Changed(Changes {
+                document,
+                metadata,
+                embedding,
+            })
+        };
+        assert_eq!(
+            differences,
+            [
+                ("added", Difference::OnlyInB),
+                ("embedding", changed(false, true, true)),
+                ("metadata", changed(false, true, false)),
+                ("removed", Difference::OnlyInA),
+                ("text", changed(true, false, false)),
+            ]
+        );
+        assert_eq!(diff.count(DiffKind::Metadata), 2);
+        assert_eq!(diff.count(DiffKind::OnlyInA), 1);
+        let removed = &diff.entries[3];
+        assert!(removed.a.is_some() && removed.b.is_none());
+        assert!(diff.embeddings_compared);
+
+        // Without embeddings only the text and metadata are compared
+        let progress: Vec<CompareProgress> = compare_documents(
+            Arc::new(old.client()),
+            Arc::new(new.client()),
+            collection("notes"),
+            collection("notes-reingested"),
+            false,
+            DEFAULT_TOLERANCE,
+            CancelToken::new(),
+        )
+        .collect()
+        .await;
+        let Some(CompareProgress::Finished(diff)) = progress.last() else {
+            panic!("comparison did not finish: {:?}", progress.last());
+        };
+        assert!(!diff.embeddings_compared);
+        assert_eq!(diff.identical, 2);
+        assert_eq!(diff.count(DiffKind::Embedding), 0);
+        assert_eq!(diff.count(DiffKind::Metadata), 2);
+    }
+
+    #[tokio::test]
+    async fn test_compare_missing_collection() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        server.add_collection("notes");
+        let progress: Vec<CompareProgress> = compare_documents(
+            Arc::new(server.client()),
+            Arc::new(server.client()),
+            collection("notes"),
+            collection("archive"),
+            true,
+            DEFAULT_TOLERANCE,
+            CancelToken::new(),
+        )
+        .collect()
+        .await;
+        assert!(matches!(
+            &progress[..],
+            [CompareProgress::Failed(error)] if error.contains("archive")
+        ));
+    }
+}
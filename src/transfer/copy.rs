@@ -0,0 +1,451 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Copying a collection to another server, tenant or database.
+
+use super::{CollectionSource, TransferProgress, failed};
+use crate::api::{
+    BatchReport, CancelToken, ChromaError, Collection, CreateCollectionOptions, Include,
+    StreamOptions,
+};
+use crate::store::{self, DocumentStream, VectorStore};
+use futures_util::{Stream, StreamExt, stream};
+use std::sync::Arc;
+
+/// How to copy a collection
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// Settings for creating the target collection
+    pub create: CreateCollectionOptions,
+    /// An earlier attempt to carry on after. The copy then reuses the
+    /// collection that attempt created if it still exists.
+    pub resume: Option<Resume>,
+}
+
+/// A copy that stopped after creating its target collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resume {
+    /// ID of the collection the copy created
+    pub collection_id: String,
+    /// Documents copied into it
+    pub copied: usize,
+}
+
+/// What a running copy reports
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyUpdate {
+    /// The target collection was created with this ID, so the copy can be
+    /// resumed into it
+    Created(String),
+    Progress(TransferProgress),
+}
+
+/// Settings that create a collection with the same metadata and configuration
+/// as `collection`
+pub fn copy_options(collection: &Collection) -> CreateCollectionOptions {
+    // Servers return unset settings as null, which they reject on create
+    let configuration = collection.configuration.as_ref().map(|configuration| {
+        configuration
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    });
+    CreateCollectionOptions {
+        metadata: collection.metadata.clone(),
+        configuration,
+        ..Default::default()
+    }
+}
+
+/// Copy every document of a collection with its metadata and embedding into
+/// a new collection named `target.collection_name`.
+///
+/// Documents are upserted page by page as they are read, so a copy that
+/// failed can be resumed with `CopyOptions::resume` set to the collection it
+/// created and the documents it had copied. Sources that cannot read every
+/// embedding are refused.
+pub fn copy_collection(
+    source: CollectionSource,
+    target: CollectionSource,
+    options: CopyOptions,
+    cancel: CancelToken,
+) -> impl Stream<Item = CopyUpdate> + Send + 'static {
+    stream::once(async move {
+        if !store::capabilities(&source.server).embeddings {
+            let error = "Copying needs the embedding of every document, which a local directory does not hold";
+            return failed(error.to_string())
+                .map(CopyUpdate::Progress)
+                .right_stream();
+        }
+        let stores = match store::open(&source.server).await {
+            Ok(from) => store::open(&target.server).await.map(|to| (from, to)),
+            Err(e) => Err(e),
+        };
+        match stores {
+            Ok((from, to)) => {
+                copy_documents(from, to, source, target, options, cancel).left_stream()
+            }
+            Err(e) => failed(e.to_string())
+                .map(CopyUpdate::Progress)
+                .right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// A copy with its target collection in place
+struct Copy {
+    documents: DocumentStream,
+    target: Arc<dyn VectorStore>,
+    collection: CollectionSource,
+    cancel: CancelToken,
+    copied: usize,
+}
+
+/// Copy between stores that are already open
+fn copy_documents(
+    from: Arc<dyn VectorStore>,
+    to: Arc<dyn VectorStore>,
+    source: CollectionSource,
+    mut target: CollectionSource,
+    options: CopyOptions,
+    cancel: CancelToken,
+) -> impl Stream<Item = CopyUpdate> + Send + 'static {
+    stream::once(async move {
+        let created = match target_collection(&*to, &target, &options).await {
+            Ok((collection, created)) => {
+                target.collection_id = collection.id;
+                created
+            }
+            Err(e) => {
+                return failed(e.to_string())
+                    .map(CopyUpdate::Progress)
+                    .right_stream();
+            }
+        };
+
+        // A new collection holds none of the documents an earlier attempt copied
+        let offset = if created {
+            0
+        } else {
+            options.resume.as_ref().map_or(0, |resume| resume.copied)
+        };
+        let stream_options = StreamOptions {
+            include: vec![
+                Include::Documents,
+                Include::Metadatas,
+                Include::Embeddings,
+                Include::Uris,
+            ],
+            offset,
+            ..Default::default()
+        };
+        let documents = from.stream_documents(
+            &source.collection_id,
+            &source.tenant,
+            &source.database,
+            stream_options,
+            cancel.clone(),
+        );
+        let copy = Copy {
+            documents,
+            target: to,
+            collection: target,
+            cancel,
+            copied: offset,
+        };
+        let created = created.then(|| CopyUpdate::Created(copy.collection.collection_id.clone()));
+        stream::iter(created)
+            .chain(copy.run().map(CopyUpdate::Progress))
+            .left_stream()
+    })
+    .flatten()
+}
+
+/// Creates the target collection, or finds the one an earlier attempt created
+/// when resuming, along with whether it was created
+async fn target_collection(
+    store: &dyn VectorStore,
+    target: &CollectionSource,
+    options: &CopyOptions,
+) -> Result<(Collection, bool), ChromaError> {
+    // Only the collection the copy created itself is written to again; one
+    // that merely has the same name belongs to someone else
+    if let Some(resume) = &options.resume {
+        let existing = store
+            .list_collections(&target.tenant, &target.database)
+            .await?
+            .into_iter()
+            .find(|collection| collection.id == resume.collection_id);
+        if let Some(collection) = existing {
+            return Ok((collection, false));
+        }
+    }
+    let collection = store
+        .create_collection(
+            &target.collection_name,
+            &options.create,
+            &target.tenant,
+            &target.database,
+        )
+        .await?;
+    Ok((collection, true))
+}
+
+impl Copy {
+    /// Upserts page by page, ending with the outcome
+    fn run(self) -> impl Stream<Item = TransferProgress> + Send + 'static {
+        stream::unfold(Some(self), |copy| async move {
+            let mut copy = copy?;
+            let outcome = match copy.documents.next().await {
+                Some(Ok(batch)) => {
+                    let written = copy
+                        .target
+                        .upsert_documents(
+                            &copy.collection.collection_id,
+                            &batch.documents,
+                            &copy.collection.tenant,
+                            &copy.collection.database,
+                        )
+                        .await
+                        .and_then(BatchReport::into_result);
+                    match written {
+                        Ok(()) => {
+                            copy.copied = batch.fetched;
+                            let progress = TransferProgress::Running {
+                                done: copy.copied,
+                                total: batch.total,
+                            };
+                            return Some((progress, Some(copy)));
+                        }
+                        Err(e) => TransferProgress::Failed(e.to_string()),
+                    }
+                }
+                Some(Err(e)) => TransferProgress::Failed(e.to_string()),
+                None if copy.cancel.is_cancelled() => {
+                    TransferProgress::Cancelled { done: copy.copied }
+                }
+                None => TransferProgress::Finished { done: copy.copied },
+            };
+            Some((outcome, None))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Document;
+    use crate::api::mock_server::{
+        ApiVersion, DEFAULT_DATABASE, DEFAULT_TENANT, Fault, MockServer,
+        collection_source as collection, page_document,
+    };
+    use crate::config::ServerConfig;
+    use serde_json::json;
+    use std::path::Path;
+
+    async fn copy(
+        from: &MockServer,
+        to: &MockServer,
+        source_id: &str,
+        options: CopyOptions,
+    ) -> Vec<TransferProgress> {
+        copy_documents(
+            Arc::new(from.client()),
+            Arc::new(to.client()),
+            collection(source_id, "notes"),
+            collection("", "promoted"),
+            options,
+            CancelToken::new(),
+        )
+        .filter_map(|update| async move {
+            match update {
+                CopyUpdate::Progress(progress) => Some(progress),
+                CopyUpdate::Created(_) => None,
+            }
+        })
+        .collect()
+        .await
+    }
+
+    #[test]
+    fn test_copy_options() {
+        let collection = Collection {
+            id: "c1".to_string(),
+            name: "notes".to_string(),
+            metadata: Some([("hnsw:space".to_string(), json!("cosine"))].into()),
+            configuration: json!({"hnsw": {"space": "cosine"}, "spann": null})
+                .as_object()
+                .cloned(),
+        };
+        let options = copy_options(&collection);
+        assert_eq!(options.metadata, collection.metadata);
+        assert_eq!(
+            options.configuration,
+            json!({"hnsw": {"space": "cosine"}}).as_object().cloned()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_copy_collection_between_servers() {
+        let staging = MockServer::start(ApiVersion::V1).await;
+        let production = MockServer::start(ApiVersion::V2).await;
+        let id = staging.add_collection("notes");
+        let documents: Vec<Document> = (0..3)
+            .map(|i| page_document(&format!("doc-{}", i), &format!("text {}", i), i))
+            .collect();
+        staging.insert(&id, &documents);
+
+        let options = CopyOptions {
+            create: CreateCollectionOptions {
+                metadata: Some([("stage".to_string(), json!("curated"))].into()),
+                ..Default::default()
+            },
+            resume: None,
+        };
+        let progress = copy(&staging, &production, &id, options.clone()).await;
+        assert_eq!(
+            progress,
+            [
+                TransferProgress::Running {
+                    done: 3,
+                    total: Some(3)
+                },
+                TransferProgress::Finished { done: 3 },
+            ]
+        );
+
+        let client: Arc<dyn VectorStore> = Arc::new(production.client());
+        let collections = client
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        assert_eq!(collections[0].name, "promoted");
+        assert_eq!(collections[0].metadata, options.create.metadata);
+        let copied = client
+            .get_documents_by_ids(
+                &collections[0].id,
+                vec!["doc-2".to_string()],
+                vec![Include::Documents, Include::Metadatas, Include::Embeddings],
+                DEFAULT_TENANT,
+                DEFAULT_DATABASE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(copied[0].document, documents[2].document);
+        assert_eq!(copied[0].metadata, documents[2].metadata);
+        assert_eq!(copied[0].embedding, documents[2].embedding);
+
+        // Without resuming, an existing collection is left alone
+        let again = copy(&staging, &production, &id, options).await;
+        assert!(matches!(again[..], [TransferProgress::Failed(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_copy_from_local_directory_is_refused() {
+        let production = MockServer::start(ApiVersion::V2).await;
+        let source = CollectionSource {
+            server: ServerConfig::local(Path::new("/nonexistent")),
+            ..collection("c1", "notes")
+        };
+        let mut target = collection("", "promoted");
+        target.server.server_url = production.url().to_string();
+
+        let updates: Vec<CopyUpdate> =
+            copy_collection(source, target, CopyOptions::default(), CancelToken::new())
+                .collect()
+                .await;
+        assert!(matches!(
+            &updates[..],
+            [CopyUpdate::Progress(TransferProgress::Failed(error))] if error.contains("embedding")
+        ));
+        assert!(production.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_failed_copy() {
+        let staging = MockServer::start(ApiVersion::V2).await;
+        let production = MockServer::start(ApiVersion::V2).await;
+        let id = staging.add_collection("notes");
+        let documents: Vec<Document> = (0..3)
+            .map(|i| Document {
+                id: format!("doc-{}", i),
+                embedding: Some(vec![i as f32]),
+                ..Default::default()
+            })
+            .collect();
+        staging.insert(&id, &documents);
+        let target_id = production.add_collection("promoted");
+        production.insert(&target_id, &documents[..1]);
+
+        production.fail_always(Some(Fault::Status(500)));
+        let options = CopyOptions {
+            resume: Some(Resume {
+                collection_id: target_id.clone(),
+                copied: 1,
+            }),
+            ..Default::default()
+        };
+        let failed = copy(&staging, &production, &id, options.clone()).await;
+        assert!(matches!(failed[..], [TransferProgress::Failed(_)]));
+
+        production.fail_always(None);
+        let progress = copy(&staging, &production, &id, options).await;
+        assert_eq!(
+            progress.last(),
+            Some(&TransferProgress::Finished { done: 3 })
+        );
+        assert_eq!(production.ids(&target_id), ["doc-0", "doc-1", "doc-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_without_target_copies_everything() {
+        let staging = MockServer::start(ApiVersion::V2).await;
+        let production = MockServer::start(ApiVersion::V2).await;
+        let id = staging.add_collection("notes");
+        let documents: Vec<Document> = (0..3)
+            .map(|i| Document {
+                id: format!("doc-{}", i),
+                embedding: Some(vec![i as f32]),
+                ..Default::default()
+            })
+            .collect();
+        staging.insert(&id, &documents);
+
+        // The collection of the earlier attempt is gone, so nothing is skipped
+        let options = CopyOptions {
+            resume: Some(Resume {
+                collection_id: "deleted".to_string(),
+                copied: 2,
+            }),
+            ..Default::default()
+        };
+        let updates: Vec<CopyUpdate> = copy_documents(
+            Arc::new(staging.client()),
+            Arc::new(production.client()),
+            collection(&id, "notes"),
+            collection("", "promoted"),
+            options.clone(),
+            CancelToken::new(),
+        )
+        .collect()
+        .await;
+        let Some(CopyUpdate::Created(created)) = updates.first() else {
+            panic!("no collection created: {:?}", updates);
+        };
+        assert_eq!(
+            updates.last(),
+            Some(&CopyUpdate::Progress(TransferProgress::Finished {
+                done: 3
+            }))
+        );
+        assert_eq!(production.ids(created), ["doc-0", "doc-1", "doc-2"]);
+
+        // A collection that only has the same name is not written to
+        let other = MockServer::start(ApiVersion::V2).await;
+        let unrelated = other.add_collection("promoted");
+        let progress = copy(&staging, &other, &id, options).await;
+        assert!(matches!(progress[..], [TransferProgress::Failed(_)]));
+        assert!(other.ids(&unrelated).is_empty());
+    }
+}
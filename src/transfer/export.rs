@@ -0,0 +1,283 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Writing a collection to a JSONL file.
+
+use super::{CollectionSource, Record, TransferProgress, failed, file_error};
+use crate::api::{CancelToken, Document, DocumentFilter, Include, StreamOptions};
+use crate::store::{self, DocumentStream, VectorStore};
+use futures_util::{Stream, StreamExt, stream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// What to write to an export file
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Only export documents matching this filter
+    pub filter: DocumentFilter,
+    /// Write the embedding of every document
+    pub embeddings: bool,
+}
+
+/// An export with its file open
+struct Export {
+    documents: DocumentStream,
+    file: File,
+    path: PathBuf,
+    cancel: CancelToken,
+    written: usize,
+}
+
+/// Write every document of a collection to a JSONL file, one `Record` per line.
+///
+/// An existing file is replaced. Progress is reported after every page, and
+/// the file is removed again if the export is cancelled or fails.
+pub fn export_collection(
+    source: CollectionSource,
+    path: PathBuf,
+    options: ExportOptions,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        match store::open(&source.server).await {
+            Ok(store) => export_documents(store, source, path, options, cancel).left_stream(),
+            Err(e) => failed(e.to_string()).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// Export from a store that is already open
+fn export_documents(
+    store: Arc<dyn VectorStore>,
+    source: CollectionSource,
+    path: PathBuf,
+    options: ExportOptions,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    let mut include = vec![Include::Documents, Include::Metadatas, Include::Uris];
+    if options.embeddings {
+        include.push(Include::Embeddings);
+    }
+    let stream_options = StreamOptions {
+        include,
+        filter: options.filter,
+        ..Default::default()
+    };
+    let documents = store.stream_documents(
+        &source.collection_id,
+        &source.tenant,
+        &source.database,
+        stream_options,
+        cancel.clone(),
+    );
+
+    stream::once(async move {
+        match File::create(&path).await {
+            Ok(file) => {
+                let export = Export {
+                    documents,
+                    file,
+                    path,
+                    cancel,
+                    written: 0,
+                };
+                export.run().left_stream()
+            }
+            Err(e) => failed(file_error(&path, e)).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+impl Export {
+    /// Writes page by page, ending with the outcome
+    fn run(self) -> impl Stream<Item = TransferProgress> + Send + 'static {
+        stream::unfold(Some(self), |export| async move {
+            let mut export = export?;
+            let error = match export.documents.next().await {
+                Some(Ok(batch)) => match export.write(batch.documents).await {
+                    Ok(()) => {
+                        let progress = TransferProgress::Running {
+                            done: export.written,
+                            total: batch.total,
+                        };
+                        return Some((progress, Some(export)));
+                    }
+                    Err(e) => e,
+                },
+                Some(Err(e)) => e.to_string(),
+                None => return Some((export.finish().await, None)),
+            };
+            export.discard().await;
+            Some((TransferProgress::Failed(error), None))
+        })
+    }
+
+    async fn write(&mut self, documents: Vec<Document>) -> Result<(), String> {
+        let count = documents.len();
+        let mut lines = Vec::new();
+        for document in documents {
+            serde_json::to_writer(&mut lines, &Record::from(document))
+                .map_err(|e| e.to_string())?;
+            lines.push(b'\n');
+        }
+        self.file
+            .write_all(&lines)
+            .await
+            .map_err(|e| file_error(&self.path, e))?;
+        self.written += count;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> TransferProgress {
+        if self.cancel.is_cancelled() {
+            let done = self.written;
+            self.discard().await;
+            return TransferProgress::Cancelled { done };
+        }
+        match self.file.flush().await {
+            Ok(()) => TransferProgress::Finished { done: self.written },
+            Err(e) => {
+                let error = file_error(&self.path, e);
+                self.discard().await;
+                TransferProgress::Failed(error)
+            }
+        }
+    }
+
+    /// Removes the partial file, as it would look complete to whoever reads
+    /// it later
+    async fn discard(self) {
+        drop(self.file);
+        let _ = tokio::fs::remove_file(&self.path).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Where;
+    use crate::api::mock_server::{ApiVersion, MockServer, collection_source, page_document};
+    use serde_json::json;
+
+    async fn export(
+        server: &MockServer,
+        collection_id: &str,
+        options: ExportOptions,
+        cancel: CancelToken,
+    ) -> (Vec<TransferProgress>, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "chromatic-export-{}-{}.jsonl",
+            collection_id,
+            std::process::id()
+        ));
+        let source = collection_source(collection_id, "notes");
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        let progress = export_documents(store, source, path.clone(), options, cancel)
+            .collect()
+            .await;
+        (progress, path)
+    }
+
+    #[tokio::test]
+    async fn test_export_collection() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        let id = server.add_collection("notes");
+        server.insert(
+            &id,
+            &[
+                page_document("a", "first", 1),
+                page_document("b", "second", 2),
+            ],
+        );
+
+        let options = ExportOptions {
+            embeddings: true,
+            ..Default::default()
+        };
+        let (progress, path) = export(&server, &id, options, CancelToken::new()).await;
+        assert_eq!(
+            progress,
+            [
+                TransferProgress::Running {
+                    done: 2,
+                    total: Some(2)
+                },
+                TransferProgress::Finished { done: 2 },
+            ]
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"id":"a","document":"first","metadata":{"page":1},"embedding":[1.0,0.5]}"#,
+                r#"{"id":"b","document":"second","metadata":{"page":2},"embedding":[2.0,0.5]}"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_filtered_without_embeddings() {
+        let server = MockServer::start(ApiVersion::V1).await;
+        let id = server.add_collection("notes");
+        server.insert(
+            &id,
+            &[
+                page_document("a", "first", 1),
+                page_document("b", "second", 2),
+            ],
+        );
+
+        let options = ExportOptions {
+            filter: DocumentFilter {
+                where_filter: Some(Where::from_value(&json!({"page": {"$gt": 1}})).unwrap()),
+                where_document: None,
+            },
+            embeddings: false,
+        };
+        let (progress, path) = export(&server, &id, options, CancelToken::new()).await;
+        assert_eq!(
+            progress.last(),
+            Some(&TransferProgress::Finished { done: 1 })
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let record: Record = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(record.id, "b");
+        assert_eq!(record.embedding, None);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_export_removes_file() {
+        let server = MockServer::start(ApiVersion::V1).await;
+        let id = server.add_collection("notes");
+        server.insert(&id, &[page_document("a", "first", 1)]);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let (progress, path) = export(&server, &id, ExportOptions::default(), cancel).await;
+        assert_eq!(progress, [TransferProgress::Cancelled { done: 0 }]);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_failed_export_removes_file() {
+        let server = MockServer::start(ApiVersion::V2).await;
+
+        let (progress, path) = export(
+            &server,
+            "missing",
+            ExportOptions::default(),
+            CancelToken::new(),
+        )
+        .await;
+        assert!(matches!(progress.as_slice(), [TransferProgress::Failed(_)]));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,660 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Reading documents from JSONL, CSV or a folder of text files and upserting
+//! them into a collection.
+//!
+//! Files are read whole, then turned into documents by `ImportFile::plan`,
+//! which skips rows that Chroma would reject and says why, so the whole file
+//! can be checked before anything is written.
+
+use super::{CollectionSource, Record, TransferProgress, failed};
+use crate::api::{BatchReport, CancelToken, CreateCollectionOptions, Document, Metadata};
+use crate::store::{self, VectorStore};
+use futures_util::{Stream, StreamExt, stream};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Documents per upsert request; the client splits them further if the
+/// server accepts fewer at once
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Extensions of the files read from a folder
+const TEXT_EXTENSIONS: [&str; 2] = ["txt", "md"];
+
+/// Header names recognised for each mapped column, compared case-insensitively
+const ID_HEADERS: [&str; 1] = ["id"];
+const DOCUMENT_HEADERS: [&str; 4] = ["document", "text", "content", "body"];
+const EMBEDDING_HEADERS: [&str; 3] = ["embedding", "embeddings", "vector"];
+
+/// Contents of a file or folder chosen for import
+#[derive(Debug, Clone)]
+pub enum ImportFile {
+    /// Records of a JSONL file in the export format, by line
+    Jsonl(Vec<(usize, Result<Record, String>)>),
+    /// A table whose columns are mapped to document fields
+    Csv {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Text files by their path within the folder, which becomes their ID
+    Folder(Vec<(String, Result<String, String>)>),
+}
+
+/// Which CSV column holds each document field; the others become metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    /// `None` numbers the rows instead
+    pub id: Option<usize>,
+    pub document: Option<usize>,
+    pub embedding: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Picks columns by their usual header names
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+        };
+        Self {
+            id: find(&ID_HEADERS),
+            document: find(&DOCUMENT_HEADERS),
+            embedding: find(&EMBEDDING_HEADERS),
+        }
+    }
+
+    fn is_mapped(&self, column: usize) -> bool {
+        [self.id, self.document, self.embedding].contains(&Some(column))
+    }
+}
+
+/// Documents ready to import, and the rows that were skipped
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub documents: Vec<Document>,
+    /// Why each skipped row was skipped, labelled with its line, row or file
+    pub problems: Vec<String>,
+}
+
+impl ImportFile {
+    /// Column headers of a CSV file; other formats have none
+    pub fn headers(&self) -> &[String] {
+        match self {
+            ImportFile::Csv { headers, .. } => headers,
+            _ => &[],
+        }
+    }
+
+    /// Converts and validates every row
+    pub fn plan(&self, mapping: &ColumnMapping) -> ImportPlan {
+        match self {
+            ImportFile::Jsonl(lines) => validate(lines.iter().map(|(line, record)| {
+                let document = record.clone().map(Document::from);
+                (format!("Line {}", line), document)
+            })),
+            ImportFile::Csv { headers, rows } => {
+                validate(rows.iter().enumerate().map(|(index, row)| {
+                    let document = csv_document(headers, row, mapping, index + 1);
+                    (format!("Row {}", index + 1), document)
+                }))
+            }
+            ImportFile::Folder(files) => validate(files.iter().map(|(path, text)| {
+                let document = text.clone().map(|text| Document {
+                    id: path.clone(),
+                    document: Some(text),
+                    ..Default::default()
+                });
+                (path.clone(), document)
+            })),
+        }
+    }
+}
+
+/// Reads a file or folder on the blocking thread pool; `.csv` files are
+/// read as CSV, other files as JSONL
+pub async fn read_import_file(path: PathBuf) -> Result<ImportFile, String> {
+    tokio::task::spawn_blocking(move || read_path(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn read_path(path: &Path) -> Result<ImportFile, String> {
+    let read_error = |e: std::io::Error| format!("Could not read {}: {}", path.display(), e);
+
+    if path.is_dir() {
+        let mut files = Vec::new();
+        collect_text_files(path, path, &mut files).map_err(read_error)?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        return Ok(ImportFile::Folder(files));
+    }
+
+    let text = std::fs::read_to_string(path).map_err(read_error)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        let mut rows = parse_csv(text)?.into_iter();
+        let headers = rows.next().ok_or("The CSV file is empty")?;
+        return Ok(ImportFile::Csv {
+            headers,
+            rows: rows.collect(),
+        });
+    }
+
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            (
+                index + 1,
+                serde_json::from_str(line).map_err(|e| e.to_string()),
+            )
+        })
+        .collect();
+    Ok(ImportFile::Jsonl(lines))
+}
+
+/// Adds the text files below `directory`, named by their path from `root`.
+///
+/// Links to directories are not followed, as they could lead back up the tree.
+fn collect_text_files(
+    root: &Path,
+    directory: &Path,
+    files: &mut Vec<(String, Result<String, String>)>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_text_files(root, &path, files)?;
+            continue;
+        }
+        if file_type.is_symlink() && path.is_dir() {
+            continue;
+        }
+        let is_text = path.extension().is_some_and(|extension| {
+            TEXT_EXTENSIONS
+                .iter()
+                .any(|text| extension.eq_ignore_ascii_case(text))
+        });
+        if !is_text {
+            continue;
+        }
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let text = std::fs::read_to_string(&path).map_err(|e| e.to_string());
+        files.push((name, text));
+    }
+    Ok(())
+}
+
+/// Splits CSV text into rows of fields, with RFC 4180 quoting
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("The CSV file ends inside a quoted field".to_string());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    // Blank lines are not rows
+    rows.retain(|row| row.len() > 1 || row.first().is_some_and(|field| !field.is_empty()));
+    Ok(rows)
+}
+
+/// The document in a CSV row; `number` counts data rows from 1
+fn csv_document(
+    headers: &[String],
+    row: &[String],
+    mapping: &ColumnMapping,
+    number: usize,
+) -> Result<Document, String> {
+    if row.len() > headers.len() {
+        return Err(format!(
+            "{} fields, but the header has {}",
+            row.len(),
+            headers.len()
+        ));
+    }
+    let cell = |column: Option<usize>| {
+        column
+            .and_then(|column| row.get(column))
+            .filter(|cell| !cell.trim().is_empty())
+    };
+
+    let id = match mapping.id {
+        Some(column) => cell(Some(column)).map(|id| id.trim().to_string()),
+        None => Some(number.to_string()),
+    };
+    let embedding = match cell(mapping.embedding) {
+        Some(cell) => Some(
+            serde_json::from_str(cell)
+                .map_err(|_| "The embedding must be a JSON array of numbers".to_string())?,
+        ),
+        None => None,
+    };
+    let metadata: Metadata = headers
+        .iter()
+        .enumerate()
+        .filter(|(column, _)| !mapping.is_mapped(*column))
+        .filter_map(|(column, header)| {
+            let value = cell(Some(column))?;
+            Some((header.trim().to_string(), cell_value(value.trim())))
+        })
+        .collect();
+
+    Ok(Document {
+        id: id.unwrap_or_default(),
+        document: cell(mapping.document).cloned(),
+        metadata: (!metadata.is_empty()).then_some(metadata),
+        embedding,
+        ..Default::default()
+    })
+}
+
+/// Metadata value of a CSV cell: a boolean or number if it reads as one
+fn cell_value(cell: &str) -> Value {
+    match cell {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => cell
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| {
+                cell.parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .map(Value::from)
+            })
+            .unwrap_or_else(|| Value::String(cell.to_string())),
+    }
+}
+
+/// Keeps the rows Chroma would accept
+fn validate(rows: impl Iterator<Item = (String, Result<Document, String>)>) -> ImportPlan {
+    let mut plan = ImportPlan::default();
+    let mut ids = HashSet::new();
+    let mut dimension = None;
+
+    for (label, document) in rows {
+        let checked = document.and_then(|mut document| {
+            check_document(&mut document, &ids, &mut dimension)?;
+            Ok(document)
+        });
+        match checked {
+            Ok(document) => {
+                ids.insert(document.id.clone());
+                plan.documents.push(document);
+            }
+            Err(e) => plan.problems.push(format!("{}: {}", label, e)),
+        }
+    }
+    plan
+}
+
+/// Checks a document against the IDs and embeddings of the rows before it.
+///
+/// `dimension` holds the embedding size of the first accepted row, or `None`
+/// inside when that row had no embedding.
+fn check_document(
+    document: &mut Document,
+    ids: &HashSet<String>,
+    dimension: &mut Option<Option<usize>>,
+) -> Result<(), String> {
+    if document.id.is_empty() {
+        return Err("Missing ID".to_string());
+    }
+    if ids.contains(&document.id) {
+        return Err(format!("Duplicate ID '{}'", document.id));
+    }
+    if document.document.is_none() && document.embedding.is_none() && document.uri.is_none() {
+        return Err("No document text, embedding or URI".to_string());
+    }
+    for (key, value) in document.metadata.iter().flatten() {
+        if !matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_)) {
+            return Err(format!(
+                "Value for '{}' must be a string, number or boolean",
+                key
+            ));
+        }
+    }
+    // Chroma rejects empty metadata objects
+    if document.metadata.as_ref().is_some_and(Metadata::is_empty) {
+        document.metadata = None;
+    }
+    // Chroma takes embeddings for every document in a write or for none
+    match (*dimension, &document.embedding) {
+        (None, embedding) => *dimension = Some(embedding.as_ref().map(Vec::len)),
+        (Some(None), Some(_)) => return Err("Unexpected embedding".to_string()),
+        (Some(Some(_)), None) => return Err("Missing embedding".to_string()),
+        (Some(Some(expected)), Some(embedding)) if embedding.len() != expected => {
+            return Err(format!(
+                "Embedding has {} dimensions, expected {}",
+                embedding.len(),
+                expected
+            ));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Upsert documents into a collection in batches, creating it first if
+/// `create` is given.
+///
+/// The import stops at the first batch that fails; batches written before it
+/// are kept, and upserting the same documents again is harmless.
+pub fn import_documents(
+    target: CollectionSource,
+    create: Option<CreateCollectionOptions>,
+    documents: Vec<Document>,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        match store::open(&target.server).await {
+            Ok(store) => upsert_documents(store, target, create, documents, cancel).left_stream(),
+            Err(e) => failed(e.to_string()).right_stream(),
+        }
+    })
+    .flatten()
+}
+
+/// An import into a collection that exists
+struct Import {
+    store: Arc<dyn VectorStore>,
+    target: CollectionSource,
+    documents: Vec<Document>,
+    cancel: CancelToken,
+    done: usize,
+}
+
+/// Import into a store that is already open
+fn upsert_documents(
+    store: Arc<dyn VectorStore>,
+    mut target: CollectionSource,
+    create: Option<CreateCollectionOptions>,
+    documents: Vec<Document>,
+    cancel: CancelToken,
+) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::once(async move {
+        if let Some(options) = create {
+            let created = store
+                .create_collection(
+                    &target.collection_name,
+                    &options,
+                    &target.tenant,
+                    &target.database,
+                )
+                .await;
+            match created {
+                Ok(collection) => target.collection_id = collection.id,
+                Err(e) => {
+                    return failed(e.to_string()).right_stream();
+                }
+            }
+        }
+        let import = Import {
+            store,
+            target,
+            documents,
+            cancel,
+            done: 0,
+        };
+        import.run().left_stream()
+    })
+    .flatten()
+}
+
+impl Import {
+    /// Upserts batch by batch, ending with the outcome
+    fn run(self) -> impl Stream<Item = TransferProgress> + Send + 'static {
+        stream::unfold(Some(self), |import| async move {
+            let mut import = import?;
+            let total = import.documents.len();
+            if import.done == total {
+                return Some((TransferProgress::Finished { done: total }, None));
+            }
+            if import.cancel.is_cancelled() {
+                let done = import.done;
+                return Some((TransferProgress::Cancelled { done }, None));
+            }
+
+            let end = (import.done + IMPORT_BATCH_SIZE).min(total);
+            let result = import
+                .store
+                .upsert_documents(
+                    &import.target.collection_id,
+                    &import.documents[import.done..end],
+                    &import.target.tenant,
+                    &import.target.database,
+                )
+                .await
+                .and_then(BatchReport::into_result);
+            match result {
+                Ok(()) => {
+                    import.done = end;
+                    let progress = TransferProgress::Running {
+                        done: end,
+                        total: Some(total),
+                    };
+                    Some((progress, Some(import)))
+                }
+                Err(e) => Some((TransferProgress::Failed(e.to_string()), None)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock_server::{
+        ApiVersion, DEFAULT_DATABASE, DEFAULT_TENANT, MockServer, collection_source,
+    };
+    use serde_json::json;
+
+    fn csv(text: &str) -> ImportFile {
+        let mut rows = parse_csv(text).unwrap().into_iter();
+        ImportFile::Csv {
+            headers: rows.next().unwrap(),
+            rows: rows.collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("id,text\r\n1,\"a, \"\"quoted\"\"\nline\"\n\n2,plain").unwrap();
+        assert_eq!(
+            rows,
+            [
+                vec!["id", "text"],
+                vec!["1", "a, \"quoted\"\nline"],
+                vec!["2", "plain"],
+            ]
+        );
+        assert!(parse_csv("id\n\"open").is_err());
+    }
+
+    #[test]
+    fn test_csv_plan() {
+        let file = csv("ID,Text,page,draft,score,vector\n\
+             a,first,1,true,0.5,\"[1, 2]\"\n\
+             b,second,,false,high,\n\
+             a,again,3,,,\n\
+             ,no id,4,,,\n\
+             c,bad vector,5,,,\"[1, 2, 3]\"\n\
+             d,,,,,\n\
+             e,fifth,,,high,\"[3, 4]\"\n");
+        let mapping = ColumnMapping::guess(file.headers());
+        assert_eq!(
+            mapping,
+            ColumnMapping {
+                id: Some(0),
+                document: Some(1),
+                embedding: Some(5),
+            }
+        );
+
+        let plan = file.plan(&mapping);
+        let ids: Vec<&str> = plan.documents.iter().map(|doc| doc.id.as_str()).collect();
+        assert_eq!(ids, ["a", "e"]);
+        assert_eq!(
+            plan.documents[0].metadata,
+            Some(Metadata::from([
+                ("page".to_string(), json!(1)),
+                ("draft".to_string(), json!(true)),
+                ("score".to_string(), json!(0.5)),
+            ]))
+        );
+        assert_eq!(plan.documents[0].embedding, Some(vec![1.0, 2.0]));
+        assert_eq!(
+            plan.documents[1].metadata.as_ref().unwrap()["score"],
+            "high"
+        );
+        assert_eq!(
+            plan.problems,
+            [
+                "Row 2: Missing embedding",
+                "Row 3: Duplicate ID 'a'",
+                "Row 4: Missing ID",
+                "Row 5: Embedding has 3 dimensions, expected 2",
+                "Row 6: No document text, embedding or URI",
+            ]
+        );
+
+        // Without an ID column, rows are numbered
+        let numbered = file.plan(&ColumnMapping {
+            id: None,
+            ..mapping
+        });
+        assert_eq!(numbered.documents[1].id, "7");
+    }
+
+    #[test]
+    fn test_jsonl_and_folder_plan() {
+        let directory =
+            std::env::temp_dir().join(format!("chromatic-import-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("notes")).unwrap();
+        std::fs::write(directory.join("readme.md"), "# Title").unwrap();
+        std::fs::write(directory.join("notes/a.TXT"), "note").unwrap();
+        std::fs::write(directory.join("image.png"), "binary").unwrap();
+        // A link back up the tree is not followed
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&directory, directory.join("notes/loop")).unwrap();
+        let lines = "{\"id\":\"a\",\"document\":\"x\",\"metadata\":{\"tags\":[1]}}\n\n\
+                     {\"id\":\"b\",\"document\":\"y\",\"metadata\":{}}\n\
+                     not json\n";
+        std::fs::write(directory.join("data.jsonl"), lines).unwrap();
+
+        let ImportFile::Folder(files) = read_path(&directory.join("notes")).unwrap() else {
+            panic!("expected a folder");
+        };
+        assert_eq!(files, [("a.TXT".to_string(), Ok("note".to_string()))]);
+
+        let folder = read_path(&directory)
+            .unwrap()
+            .plan(&ColumnMapping::default());
+        let ids: Vec<&str> = folder.documents.iter().map(|doc| doc.id.as_str()).collect();
+        assert_eq!(ids, ["notes/a.TXT", "readme.md"]);
+
+        let jsonl = read_path(&directory.join("data.jsonl"))
+            .unwrap()
+            .plan(&ColumnMapping::default());
+        assert_eq!(jsonl.documents.len(), 1);
+        assert_eq!(jsonl.documents[0].metadata, None);
+        assert_eq!(jsonl.problems.len(), 2);
+        assert!(jsonl.problems[0].starts_with("Line 1: Value for 'tags'"));
+        assert!(jsonl.problems[1].starts_with("Line 4: "));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_into_new_collection() {
+        let server = MockServer::start(ApiVersion::V2).await;
+        server.set_max_batch_size(Some(300));
+        let store: Arc<dyn VectorStore> = Arc::new(server.client());
+        let documents: Vec<Document> = (0..700)
+            .map(|i| Document {
+                id: format!("doc-{:03}", i),
+                document: Some(format!("text {}", i)),
+                ..Default::default()
+            })
+            .collect();
+        let target = collection_source("", "seeded");
+
+        let progress: Vec<_> = upsert_documents(
+            store.clone(),
+            target,
+            Some(CreateCollectionOptions::default()),
+            documents,
+            CancelToken::new(),
+        )
+        .collect()
+        .await;
+        assert_eq!(
+            progress,
+            [
+                TransferProgress::Running {
+                    done: 500,
+                    total: Some(700)
+                },
+                TransferProgress::Running {
+                    done: 700,
+                    total: Some(700)
+                },
+                TransferProgress::Finished { done: 700 },
+            ]
+        );
+
+        let collections = store
+            .list_collections(DEFAULT_TENANT, DEFAULT_DATABASE)
+            .await
+            .unwrap();
+        assert_eq!(collections[0].name, "seeded");
+        assert_eq!(server.ids(&collections[0].id).len(), 700);
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Moving documents between collections and files, and comparing collections.
+//!
+//! Transfers run as streams of `TransferProgress` so the app can show how far
+//! they got, and stop before their next request once their `CancelToken` is
+//! triggered.
+
+mod backup;
+mod compare;
+mod copy;
+mod export;
+mod import;
+
+pub use backup::{
+    BackupSummary, Conflict, DatabaseSource, backup_database, backup_file_name, read_backup,
+    restore_database,
+};
+pub use compare::{
+    CollectionDiff, CompareProgress, DEFAULT_TOLERANCE, DiffEntry, DiffKind, Difference,
+    compare_collections, metadata_changes,
+};
+pub use copy::{CopyOptions, CopyUpdate, Resume, copy_collection, copy_options};
+pub use export::{ExportOptions, export_collection};
+pub use import::{ColumnMapping, ImportFile, ImportPlan, import_documents, read_import_file};
+
+use crate::api::Document;
+use crate::config::ServerConfig;
+use futures_util::{Stream, stream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A collection on a configured server
+#[derive(Debug, Clone)]
+pub struct CollectionSource {
+    pub server: ServerConfig,
+    pub tenant: String,
+    pub database: String,
+    pub collection_id: String,
+    pub collection_name: String,
+}
+
+/// One document as a line of a JSONL file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    /// Sorted by key so exports of the same data are identical
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+impl From<Document> for Record {
+    fn from(document: Document) -> Self {
+        Self {
+            id: document.id,
+            document: document.document,
+            metadata: document
+                .metadata
+                .map(|metadata| metadata.into_iter().collect()),
+            embedding: document.embedding,
+            uri: document.uri,
+        }
+    }
+}
+
+impl From<Record> for Document {
+    fn from(record: Record) -> Self {
+        Self {
+            id: record.id,
+            document: record.document,
+            metadata: record
+                .metadata
+                .map(|metadata| metadata.into_iter().collect()),
+            embedding: record.embedding,
+            uri: record.uri,
+            ..Default::default()
+        }
+    }
+}
+
+/// State of a running transfer
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferProgress {
+    /// Documents handled so far, and how many there are if they could be counted
+    Running { done: usize, total: Option<usize> },
+    /// Every document was handled
+    Finished { done: usize },
+    /// Stopped on request after handling some documents
+    Cancelled { done: usize },
+    /// Stopped by an error
+    Failed(String),
+}
+
+impl TransferProgress {
+    /// Whether the transfer has stopped
+    pub fn is_done(&self) -> bool {
+        !matches!(self, Self::Running { .. })
+    }
+
+    /// Fraction of the documents handled so far, between 0 and 1, if known
+    pub fn fraction(&self) -> Option<f32> {
+        match self {
+            Self::Running { total: Some(0), .. } | Self::Finished { .. } => Some(1.0),
+            Self::Running {
+                done,
+                total: Some(total),
+            } => Some((*done as f32 / *total as f32).min(1.0)),
+            _ => None,
+        }
+    }
+}
+
+/// A transfer that stopped before it started
+fn failed(error: String) -> impl Stream<Item = TransferProgress> + Send + 'static {
+    stream::iter([TransferProgress::Failed(error)])
+}
+
+fn file_error(path: &Path, error: std::io::Error) -> String {
+    format!("Could not write {}: {}", path.display(), error)
+}
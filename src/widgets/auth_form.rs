@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Editor for how a server authenticates requests.
+//!
+//! Shared by the settings page and the add server form. Fields of every
+//! method are kept while switching between them, so switching back does not
+//! lose what was typed.
+
+use super::filter_builder::toggle_class;
+use crate::api::AuthMethod;
+use crate::fl;
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget::{self, icon};
+
+/// The kinds of authentication offered in the form.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthKind {
+    #[default]
+    None,
+    Bearer,
+    ChromaToken,
+    Basic,
+    Headers,
+    Command,
+}
+
+impl AuthKind {
+    pub const ALL: [AuthKind; 6] = [
+        AuthKind::None,
+        AuthKind::Bearer,
+        AuthKind::ChromaToken,
+        AuthKind::Basic,
+        AuthKind::Headers,
+        AuthKind::Command,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            AuthKind::None => fl!("auth-kind-none"),
+            AuthKind::Bearer => fl!("auth-kind-bearer"),
+            AuthKind::ChromaToken => fl!("auth-kind-chroma-token"),
+            AuthKind::Basic => fl!("auth-kind-basic"),
+            AuthKind::Headers => fl!("auth-kind-headers"),
+            AuthKind::Command => fl!("auth-kind-command"),
+        }
+    }
+}
+
+/// A custom header being edited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderRow {
+    pub name: String,
+    pub value: String,
+}
+
+/// State of the authentication form.
+#[derive(Debug, Clone, Default)]
+pub struct AuthForm {
+    pub kind: AuthKind,
+    /// Token for Bearer and X-Chroma-Token
+    pub token: String,
+    pub username: String,
+    pub password: String,
+    pub headers: Vec<HeaderRow>,
+    /// Shell command printing a token
+    pub command: String,
+}
+
+/// Messages emitted by the authentication form.
+#[derive(Debug, Clone)]
+pub enum AuthMsg {
+    KindChanged(AuthKind),
+    TokenChanged(String),
+    UsernameChanged(String),
+    PasswordChanged(String),
+    AddHeader,
+    RemoveHeader(usize),
+    HeaderNameChanged(usize, String),
+    HeaderValueChanged(usize, String),
+    CommandChanged(String),
+}
+
+impl AuthForm {
+    /// Creates a form showing a saved authentication method.
+    pub fn from_method(auth: &AuthMethod) -> Self {
+        match auth {
+            AuthMethod::None => Self::default(),
+            AuthMethod::Bearer { token } => Self {
+                kind: AuthKind::Bearer,
+                token: token.clone(),
+                ..Self::default()
+            },
+            AuthMethod::ChromaToken { token } => Self {
+                kind: AuthKind::ChromaToken,
+                token: token.clone(),
+                ..Self::default()
+            },
+            AuthMethod::Basic { username, password } => Self {
+                kind: AuthKind::Basic,
+                username: username.clone(),
+                password: password.clone(),
+                ..Self::default()
+            },
+            AuthMethod::Headers { headers } => Self {
+                kind: AuthKind::Headers,
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| HeaderRow {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                ..Self::default()
+            },
+            AuthMethod::Command { command } => Self {
+                kind: AuthKind::Command,
+                command: command.clone(),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Handles a form message.
+    pub fn update(&mut self, message: AuthMsg) {
+        match message {
+            AuthMsg::KindChanged(kind) => {
+                self.kind = kind;
+                if kind == AuthKind::Headers && self.headers.is_empty() {
+                    self.headers.push(HeaderRow::default());
+                }
+            }
+            AuthMsg::TokenChanged(token) => self.token = token,
+            AuthMsg::UsernameChanged(username) => self.username = username,
+            AuthMsg::PasswordChanged(password) => self.password = password,
+            AuthMsg::AddHeader => self.headers.push(HeaderRow::default()),
+            AuthMsg::RemoveHeader(index) => {
+                if index < self.headers.len() {
+                    self.headers.remove(index);
+                }
+            }
+            AuthMsg::HeaderNameChanged(index, name) => {
+                if let Some(row) = self.headers.get_mut(index) {
+                    row.name = name;
+                }
+            }
+            AuthMsg::HeaderValueChanged(index, value) => {
+                if let Some(row) = self.headers.get_mut(index) {
+                    row.value = value;
+                }
+            }
+            AuthMsg::CommandChanged(command) => self.command = command,
+        }
+    }
+
+    /// Builds the authentication method to save.
+    pub fn build(&self) -> Result<AuthMethod, String> {
+        let token = self.token.trim().to_string();
+        match self.kind {
+            AuthKind::None => Ok(AuthMethod::None),
+            AuthKind::Bearer => Ok(AuthMethod::Bearer { token }),
+            AuthKind::ChromaToken => Ok(AuthMethod::ChromaToken { token }),
+            AuthKind::Basic => {
+                if self.username.trim().is_empty() {
+                    return Err(fl!("auth-username-required"));
+                }
+                Ok(AuthMethod::Basic {
+                    username: self.username.trim().to_string(),
+                    password: self.password.clone(),
+                })
+            }
+            AuthKind::Headers => {
+                let mut headers = Vec::with_capacity(self.headers.len());
+                for row in &self.headers {
+                    let name = row.name.trim();
+                    if name.is_empty() && row.value.trim().is_empty() {
+                        continue;
+                    }
+                    if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                        return Err(fl!("auth-invalid-header", name = format!("{:?}", name)));
+                    }
+                    headers.push((name.to_string(), row.value.trim().to_string()));
+                }
+                Ok(AuthMethod::Headers { headers })
+            }
+            AuthKind::Command => match self.command.trim() {
+                "" => Err(fl!("auth-command-required")),
+                command => Ok(AuthMethod::Command {
+                    command: command.to_string(),
+                }),
+            },
+        }
+    }
+}
+
+/// Renders the authentication form.
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a AuthForm,
+    on_message: impl Fn(AuthMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut content = widget::column::with_capacity(8).spacing(space_s);
+
+    // Two rows of kinds so the choice fits the narrow add server form
+    for kinds in AuthKind::ALL.chunks(3) {
+        let mut row = widget::row::with_capacity(kinds.len())
+            .spacing(4)
+            .align_y(Alignment::Center);
+        for &kind in kinds {
+            row = row.push(
+                widget::button::text(kind.label())
+                    .class(toggle_class(state.kind == kind))
+                    .on_press(on_message(AuthMsg::KindChanged(kind))),
+            );
+        }
+        content = content.push(row);
+    }
+
+    match state.kind {
+        AuthKind::None => {}
+        AuthKind::Bearer | AuthKind::ChromaToken => {
+            content = content.push(
+                widget::secure_input(fl!("auth-token"), &state.token, None, true)
+                    .on_input(move |s| on_message(AuthMsg::TokenChanged(s)))
+                    .width(Length::Fill),
+            );
+        }
+        AuthKind::Basic => {
+            content = content
+                .push(
+                    widget::text_input(fl!("auth-username"), &state.username)
+                        .on_input(move |s| on_message(AuthMsg::UsernameChanged(s)))
+                        .width(Length::Fill),
+                )
+                .push(
+                    widget::secure_input(fl!("auth-password"), &state.password, None, true)
+                        .on_input(move |s| on_message(AuthMsg::PasswordChanged(s)))
+                        .width(Length::Fill),
+                );
+        }
+        AuthKind::Headers => {
+            for (index, row) in state.headers.iter().enumerate() {
+                content = content.push(
+                    widget::row::with_capacity(3)
+                        .push(
+                            widget::text_input(fl!("auth-header-name"), &row.name)
+                                .on_input(move |s| on_message(AuthMsg::HeaderNameChanged(index, s)))
+                                .width(Length::Fixed(120.0)),
+                        )
+                        .push(
+                            widget::secure_input(fl!("auth-header-value"), &row.value, None, true)
+                                .on_input(move |s| {
+                                    on_message(AuthMsg::HeaderValueChanged(index, s))
+                                })
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .class(cosmic::theme::Button::Icon)
+                                .on_press(on_message(AuthMsg::RemoveHeader(index))),
+                        )
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                );
+            }
+            content = content.push(
+                widget::button::text(fl!("auth-add-header"))
+                    .on_press(on_message(AuthMsg::AddHeader)),
+            );
+        }
+        AuthKind::Command => {
+            content = content
+                .push(
+                    widget::text_input(fl!("auth-command-placeholder"), &state.command)
+                        .on_input(move |s| on_message(AuthMsg::CommandChanged(s)))
+                        .width(Length::Fill),
+                )
+                .push(widget::text::caption(fl!("auth-command-description")));
+        }
+    }
+
+    if let Err(error) = state.build() {
+        content = content.push(widget::text::caption(error).class(cosmic::style::Text::Accent));
+    }
+
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_round_trips_saved_method() {
+        let saved = AuthMethod::Basic {
+            username: "staging".to_string(),
+            password: "secret".to_string(),
+        };
+        assert_eq!(AuthForm::from_method(&saved).build(), Ok(saved));
+
+        let mut form = AuthForm::default();
+        form.update(AuthMsg::KindChanged(AuthKind::Headers));
+        form.update(AuthMsg::HeaderNameChanged(0, "X-Api-Key".to_string()));
+        form.update(AuthMsg::HeaderValueChanged(0, "abc".to_string()));
+        form.update(AuthMsg::AddHeader);
+        assert_eq!(
+            form.build(),
+            Ok(AuthMethod::Headers {
+                headers: vec![("X-Api-Key".to_string(), "abc".to_string())],
+            })
+        );
+
+        form.update(AuthMsg::HeaderNameChanged(1, "bad header".to_string()));
+        assert!(form.build().is_err());
+        form.update(AuthMsg::KindChanged(AuthKind::Command));
+        assert!(form.build().is_err());
+    }
+}
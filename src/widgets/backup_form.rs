@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dialog for backing up a database to an archive.
+//!
+//! The dialog asks for the file, then shows the progress of the running
+//! backup. Choosing the file and starting the backup are left to the app,
+//! which owns the tasks; this form only tracks their state.
+
+use crate::api::CancelToken;
+use crate::transfer::{DatabaseSource, TransferProgress};
+use cosmic::iced::Length;
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::path::PathBuf;
+
+/// Where a backup stands.
+#[derive(Debug, Clone)]
+pub enum BackupStatus {
+    /// Waiting for the user to choose a file
+    Ready,
+    /// Waiting for the save dialog
+    ChoosingFile,
+    /// Writing to a file, or done writing
+    Writing {
+        path: PathBuf,
+        progress: TransferProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the backup dialog.
+#[derive(Debug, Clone)]
+pub struct BackupForm {
+    pub source: DatabaseSource,
+    pub status: BackupStatus,
+    /// Error from the save dialog
+    pub error: Option<String>,
+}
+
+/// Messages emitted by the backup dialog.
+#[derive(Debug, Clone)]
+pub enum BackupMsg {
+    /// Open the save dialog
+    ChooseFile,
+    /// The save dialog closed, with the chosen file unless it was cancelled
+    FileChosen(Result<Option<PathBuf>, String>),
+    Progress(TransferProgress),
+    /// Stop the running backup
+    Cancel,
+    /// Close the dialog
+    Close,
+}
+
+impl BackupForm {
+    pub fn new(source: DatabaseSource) -> Self {
+        Self {
+            source,
+            status: BackupStatus::Ready,
+            error: None,
+        }
+    }
+
+    /// Handles a dialog message.
+    pub fn update(&mut self, message: BackupMsg) {
+        match message {
+            BackupMsg::ChooseFile => {
+                self.status = BackupStatus::ChoosingFile;
+                self.error = None;
+            }
+            BackupMsg::FileChosen(Ok(Some(path))) => {
+                self.status = BackupStatus::Writing {
+                    path,
+                    progress: TransferProgress::Running {
+                        done: 0,
+                        total: None,
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            BackupMsg::FileChosen(Ok(None)) => self.status = BackupStatus::Ready,
+            BackupMsg::FileChosen(Err(error)) => {
+                self.status = BackupStatus::Ready;
+                self.error = Some(error);
+            }
+            BackupMsg::Progress(update) => {
+                if let BackupStatus::Writing { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            BackupMsg::Cancel => {
+                if let BackupStatus::Writing { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            BackupMsg::Close => {}
+        }
+    }
+
+    /// Whether a backup is writing and cannot be closed yet.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, BackupStatus::Writing { progress, .. } if !progress.is_done())
+    }
+}
+
+/// Text describing the progress of a backup.
+fn progress_label(progress: &TransferProgress, path: &std::path::Path) -> String {
+    match progress {
+        TransferProgress::Running {
+            done,
+            total: Some(total),
+        } => format!("Backed up {} of {} documents", done, total),
+        TransferProgress::Running { done, total: None } => {
+            format!("Backed up {} documents", done)
+        }
+        TransferProgress::Finished { done } => {
+            format!("Backed up {} documents to {}", done, path.display())
+        }
+        TransferProgress::Cancelled { .. } => "Backup cancelled".to_string(),
+        TransferProgress::Failed(error) => format!("Backup failed: {}", error),
+    }
+}
+
+/// Renders the backup dialog.
+pub fn view<'a, Message: Clone + 'static>(
+    form: &'a BackupForm,
+    on_message: impl Fn(BackupMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let dialog = widget::dialog().title(format!("Back Up '{}'", form.source.database));
+
+    let BackupStatus::Writing { path, progress, .. } = &form.status else {
+        let mut controls = widget::column::with_capacity(2)
+            .push(widget::text::body(
+                "Writes every collection with its configuration, documents, metadata and \
+                 embeddings to a single file, which can be restored on any server.",
+            ))
+            .spacing(space_s);
+        if let Some(ref error) = form.error {
+            controls = controls
+                .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        let choosing = matches!(form.status, BackupStatus::ChoosingFile);
+        return dialog
+            .control(controls)
+            .primary_action(
+                widget::button::suggested(if choosing {
+                    "Choosing..."
+                } else {
+                    "Choose File..."
+                })
+                .on_press_maybe((!choosing).then(|| on_message(BackupMsg::ChooseFile))),
+            )
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(BackupMsg::Close)),
+            )
+            .into();
+    };
+
+    let mut controls = widget::column::with_capacity(2).spacing(space_s);
+    if let Some(fraction) = progress.fraction() {
+        controls = controls.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fill));
+    }
+    let label = widget::text::body(progress_label(progress, path));
+    controls = controls.push(match progress {
+        TransferProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+        _ => label,
+    });
+
+    let dialog = dialog.control(controls);
+    if progress.is_done() {
+        dialog
+            .primary_action(
+                widget::button::suggested("Close").on_press(on_message(BackupMsg::Close)),
+            )
+            .into()
+    } else {
+        dialog
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(BackupMsg::Cancel)),
+            )
+            .into()
+    }
+}
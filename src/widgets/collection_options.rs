@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Advanced options for creating a collection.
+//!
+//! Covers the distance function, HNSW index parameters, initial metadata and
+//! extra entries for the v2 `configuration` object. Empty fields are left to
+//! the server defaults.
+
+use super::filter_builder::toggle_class;
+use super::metadata_editor::{self, MetadataEditor, MetadataMsg};
+use crate::api::{CreateCollectionOptions, DistanceSpace};
+use crate::fl;
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget;
+
+/// State of the advanced collection options section.
+#[derive(Debug, Clone, Default)]
+pub struct CollectionOptionsForm {
+    pub expanded: bool,
+    /// `None` uses the server default
+    pub space: Option<DistanceSpace>,
+    pub ef_construction: String,
+    pub ef_search: String,
+    pub max_neighbors: String,
+    pub metadata: MetadataEditor,
+    /// Raw JSON object merged into the v2 `configuration`
+    pub configuration: String,
+}
+
+/// Messages emitted by the collection options section.
+#[derive(Debug, Clone)]
+pub enum CollectionOptionsMsg {
+    ToggleExpanded,
+    SpaceChanged(Option<DistanceSpace>),
+    EfConstructionChanged(String),
+    EfSearchChanged(String),
+    MaxNeighborsChanged(String),
+    Metadata(MetadataMsg),
+    ConfigurationChanged(String),
+}
+
+impl CollectionOptionsForm {
+    /// Handles an options message.
+    pub fn update(&mut self, message: CollectionOptionsMsg) {
+        match message {
+            CollectionOptionsMsg::ToggleExpanded => self.expanded = !self.expanded,
+            CollectionOptionsMsg::SpaceChanged(space) => self.space = space,
+            CollectionOptionsMsg::EfConstructionChanged(value) => self.ef_construction = value,
+            CollectionOptionsMsg::EfSearchChanged(value) => self.ef_search = value,
+            CollectionOptionsMsg::MaxNeighborsChanged(value) => self.max_neighbors = value,
+            CollectionOptionsMsg::Metadata(msg) => self.metadata.update(msg),
+            CollectionOptionsMsg::ConfigurationChanged(value) => self.configuration = value,
+        }
+    }
+
+    /// Builds the options to send with the create request.
+    pub fn build(&self) -> Result<CreateCollectionOptions, String> {
+        let configuration = match self.configuration.trim() {
+            "" => None,
+            raw => match serde_json::from_str(raw) {
+                Ok(serde_json::Value::Object(map)) => Some(map),
+                Ok(_) => return Err(fl!("collection-options-not-object")),
+                Err(e) => {
+                    return Err(fl!(
+                        "collection-options-invalid-json",
+                        error = e.to_string()
+                    ));
+                }
+            },
+        };
+
+        Ok(CreateCollectionOptions {
+            metadata: self.metadata.build()?,
+            space: self.space,
+            ef_construction: parse_param("ef_construction", &self.ef_construction)?,
+            ef_search: parse_param("ef_search", &self.ef_search)?,
+            max_neighbors: parse_param("M", &self.max_neighbors)?,
+            configuration,
+        })
+    }
+}
+
+/// Parses an optional positive integer parameter.
+fn parse_param(name: &str, raw: &str) -> Result<Option<u32>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    match raw.parse::<u32>() {
+        Ok(value) if value > 0 => Ok(Some(value)),
+        _ => Err(fl!("collection-options-not-positive", name = name)),
+    }
+}
+
+/// Human-readable label for a distance space option.
+fn space_label(space: Option<DistanceSpace>) -> String {
+    match space {
+        None => fl!("collection-options-space-default"),
+        Some(DistanceSpace::L2) => fl!("collection-options-space-l2"),
+        Some(DistanceSpace::Cosine) => fl!("collection-options-space-cosine"),
+        Some(DistanceSpace::Ip) => fl!("collection-options-space-ip"),
+    }
+}
+
+/// Renders the collapsible advanced options section.
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a CollectionOptionsForm,
+    on_message: impl Fn(CollectionOptionsMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let toggle = widget::button::text(if state.expanded {
+        fl!("collection-options-hide")
+    } else {
+        fl!("collection-options-show")
+    })
+    .on_press(on_message(CollectionOptionsMsg::ToggleExpanded));
+
+    if !state.expanded {
+        return toggle.into();
+    }
+
+    let mut content = widget::column::with_capacity(10)
+        .push(toggle)
+        .spacing(space_s);
+
+    // Distance function
+    let mut spaces = widget::row::with_capacity(4)
+        .spacing(4)
+        .align_y(Alignment::Center);
+    for space in std::iter::once(None).chain(DistanceSpace::ALL.map(Some)) {
+        spaces = spaces.push(
+            widget::button::text(space_label(space))
+                .class(toggle_class(state.space == space))
+                .on_press(on_message(CollectionOptionsMsg::SpaceChanged(space))),
+        );
+    }
+    content = content
+        .push(widget::text::body(fl!("collection-options-space")))
+        .push(spaces)
+        .push(widget::text::caption(fl!("collection-options-space-fixed")));
+
+    // HNSW parameters
+    let param = |label: String, value: &'a str, on_input: fn(String) -> CollectionOptionsMsg| {
+        widget::column::with_capacity(2)
+            .push(widget::text::caption(label))
+            .push(
+                widget::text_input(fl!("collection-options-default"), value)
+                    .on_input(move |s| on_message(on_input(s)))
+                    .width(Length::Fill),
+            )
+            .spacing(4)
+            .width(Length::Fill)
+    };
+    content = content
+        .push(widget::text::body(fl!("collection-options-hnsw")))
+        .push(
+            widget::row::with_capacity(3)
+                .push(param(
+                    "ef_construction".to_string(),
+                    &state.ef_construction,
+                    CollectionOptionsMsg::EfConstructionChanged,
+                ))
+                .push(param(
+                    "ef_search".to_string(),
+                    &state.ef_search,
+                    CollectionOptionsMsg::EfSearchChanged,
+                ))
+                .push(param(
+                    fl!("collection-options-max-neighbors"),
+                    &state.max_neighbors,
+                    CollectionOptionsMsg::MaxNeighborsChanged,
+                ))
+                .spacing(space_s),
+        );
+
+    // Metadata
+    content = content
+        .push(widget::text::body(fl!("metadata")))
+        .push(metadata_editor::view(
+            &state.metadata,
+            move |msg| on_message(CollectionOptionsMsg::Metadata(msg)),
+            space_s,
+        ));
+
+    // Raw v2 configuration
+    content = content
+        .push(widget::text::body(fl!("collection-options-configuration")))
+        .push(
+            widget::text_input(
+                "{\"hnsw\": {\"sync_threshold\": 1000}}",
+                &state.configuration,
+            )
+            .on_input(move |s| on_message(CollectionOptionsMsg::ConfigurationChanged(s)))
+            .width(Length::Fill),
+        );
+
+    // Metadata errors are shown by the metadata editor itself
+    if state.metadata.build().is_ok()
+        && let Err(error) = state.build()
+    {
+        content = content.push(widget::text::caption(error).class(cosmic::style::Text::Accent));
+    }
+
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_validates_fields() {
+        let mut form = CollectionOptionsForm::default();
+        assert_eq!(form.build().unwrap(), CreateCollectionOptions::default());
+
+        form.update(CollectionOptionsMsg::SpaceChanged(Some(
+            DistanceSpace::Cosine,
+        )));
+        form.update(CollectionOptionsMsg::MaxNeighborsChanged("32".to_string()));
+        form.update(CollectionOptionsMsg::EfSearchChanged("0".to_string()));
+        assert!(form.build().is_err());
+
+        form.update(CollectionOptionsMsg::EfSearchChanged(String::new()));
+        form.update(CollectionOptionsMsg::ConfigurationChanged(
+            "[1]".to_string(),
+        ));
+        assert!(form.build().is_err());
+
+        form.update(CollectionOptionsMsg::ConfigurationChanged(String::new()));
+        let options = form.build().unwrap();
+        assert_eq!(options.space, Some(DistanceSpace::Cosine));
+        assert_eq!(options.max_neighbors, Some(32));
+        assert_eq!(options.ef_search, None);
+    }
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TLS and proxy settings of a server.
+//!
+//! Shown collapsed under the authentication settings, since most servers
+//! need none of it. Turning off certificate verification is flagged with a
+//! warning badge here and next to the server in the browser.
+
+use crate::api::ConnectionOptions;
+use crate::fl;
+use cosmic::iced::{Alignment, Color, Length};
+use cosmic::prelude::*;
+use cosmic::widget::{self, icon};
+
+/// State of the TLS and proxy section.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionForm {
+    pub expanded: bool,
+    pub ca_bundle: String,
+    pub client_cert: String,
+    pub client_key: String,
+    pub accept_invalid_certs: bool,
+    pub http_proxy: String,
+    pub https_proxy: String,
+}
+
+/// Messages emitted by the TLS and proxy section.
+#[derive(Debug, Clone)]
+pub enum ConnectionMsg {
+    ToggleExpanded,
+    CaBundleChanged(String),
+    ClientCertChanged(String),
+    ClientKeyChanged(String),
+    AcceptInvalidCertsToggled(bool),
+    HttpProxyChanged(String),
+    HttpsProxyChanged(String),
+}
+
+impl ConnectionForm {
+    /// Creates a form showing saved settings, expanded if any are set.
+    pub fn from_options(options: &ConnectionOptions) -> Self {
+        Self {
+            expanded: *options != ConnectionOptions::default(),
+            ca_bundle: options.ca_bundle.clone(),
+            client_cert: options.client_cert.clone(),
+            client_key: options.client_key.clone(),
+            accept_invalid_certs: options.accept_invalid_certs,
+            http_proxy: options.http_proxy.clone(),
+            https_proxy: options.https_proxy.clone(),
+        }
+    }
+
+    /// Handles a form message.
+    pub fn update(&mut self, message: ConnectionMsg) {
+        match message {
+            ConnectionMsg::ToggleExpanded => self.expanded = !self.expanded,
+            ConnectionMsg::CaBundleChanged(path) => self.ca_bundle = path,
+            ConnectionMsg::ClientCertChanged(path) => self.client_cert = path,
+            ConnectionMsg::ClientKeyChanged(path) => self.client_key = path,
+            ConnectionMsg::AcceptInvalidCertsToggled(accept) => self.accept_invalid_certs = accept,
+            ConnectionMsg::HttpProxyChanged(url) => self.http_proxy = url,
+            ConnectionMsg::HttpsProxyChanged(url) => self.https_proxy = url,
+        }
+    }
+
+    /// Builds the settings to save.
+    pub fn build(&self) -> Result<ConnectionOptions, String> {
+        let client_cert = self.client_cert.trim().to_string();
+        let client_key = self.client_key.trim().to_string();
+        if client_cert.is_empty() != client_key.is_empty() {
+            return Err(fl!("tls-client-cert-incomplete"));
+        }
+
+        Ok(ConnectionOptions {
+            ca_bundle: self.ca_bundle.trim().to_string(),
+            client_cert,
+            client_key,
+            accept_invalid_certs: self.accept_invalid_certs,
+            http_proxy: parse_proxy(fl!("tls-http-proxy"), &self.http_proxy)?,
+            https_proxy: parse_proxy(fl!("tls-https-proxy"), &self.https_proxy)?,
+        })
+    }
+}
+
+/// Checks that a proxy is empty or a URL.
+fn parse_proxy(name: String, raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    let is_url = matches!(reqwest::Url::parse(raw), Ok(url) if url.has_host());
+    if !raw.is_empty() && !is_url {
+        return Err(fl!("tls-proxy-invalid", name = name));
+    }
+    Ok(raw.to_string())
+}
+
+/// Badge for servers whose certificates are not verified.
+pub fn insecure_badge<'a, Message: 'static>() -> Element<'a, Message> {
+    let warning = cosmic::style::Text::Color(Color::from_rgb(0.9, 0.3, 0.2));
+    widget::row::with_capacity(2)
+        .push(icon::from_name("dialog-warning-symbolic").size(14))
+        .push(widget::text::caption(fl!("tls-insecure")).class(warning))
+        .spacing(4)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+/// Renders the collapsible TLS and proxy section.
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a ConnectionForm,
+    on_message: impl Fn(ConnectionMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut toggle = widget::row::with_capacity(2)
+        .push(
+            widget::button::text(if state.expanded {
+                fl!("tls-hide")
+            } else {
+                fl!("tls-show")
+            })
+            .on_press(on_message(ConnectionMsg::ToggleExpanded)),
+        )
+        .spacing(space_s)
+        .align_y(Alignment::Center);
+    if state.accept_invalid_certs {
+        toggle = toggle.push(insecure_badge());
+    }
+
+    if !state.expanded {
+        return toggle.into();
+    }
+
+    let field = |label: String,
+                 placeholder: &'a str,
+                 value: &'a str,
+                 on_input: fn(String) -> ConnectionMsg| {
+        widget::column::with_capacity(2)
+            .push(widget::text::caption(label))
+            .push(
+                widget::text_input(placeholder, value)
+                    .on_input(move |s| on_message(on_input(s)))
+                    .width(Length::Fill),
+            )
+            .spacing(4)
+    };
+
+    let mut content = widget::column::with_capacity(10)
+        .push(toggle)
+        .push(field(
+            fl!("tls-ca-bundle"),
+            "/etc/ssl/private-ca.pem",
+            &state.ca_bundle,
+            ConnectionMsg::CaBundleChanged,
+        ))
+        .push(field(
+            fl!("tls-client-cert"),
+            "/path/to/client.pem",
+            &state.client_cert,
+            ConnectionMsg::ClientCertChanged,
+        ))
+        .push(field(
+            fl!("tls-client-key"),
+            "/path/to/client-key.pem",
+            &state.client_key,
+            ConnectionMsg::ClientKeyChanged,
+        ))
+        .push(
+            widget::checkbox(fl!("tls-accept-invalid-certs"), state.accept_invalid_certs)
+                .on_toggle(move |v| on_message(ConnectionMsg::AcceptInvalidCertsToggled(v))),
+        )
+        .spacing(space_s);
+
+    if state.accept_invalid_certs {
+        content = content.push(
+            widget::text::caption(fl!("tls-insecure-warning")).class(cosmic::style::Text::Accent),
+        );
+    }
+
+    content = content
+        .push(field(
+            fl!("tls-http-proxy"),
+            "http://proxy:3128",
+            &state.http_proxy,
+            ConnectionMsg::HttpProxyChanged,
+        ))
+        .push(field(
+            fl!("tls-https-proxy"),
+            "http://proxy:3128",
+            &state.https_proxy,
+            ConnectionMsg::HttpsProxyChanged,
+        ))
+        .push(widget::text::caption(fl!("tls-proxy-environment")));
+
+    if let Err(error) = state.build() {
+        content = content.push(widget::text::caption(error).class(cosmic::style::Text::Accent));
+    }
+
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_validates_fields() {
+        let mut form = ConnectionForm::default();
+        assert_eq!(form.build(), Ok(ConnectionOptions::default()));
+
+        form.update(ConnectionMsg::ClientCertChanged(
+            "/certs/client.pem".to_string(),
+        ));
+        assert!(form.build().is_err());
+        form.update(ConnectionMsg::ClientKeyChanged(
+            "/certs/client-key.pem".to_string(),
+        ));
+        assert!(form.build().is_ok());
+
+        form.update(ConnectionMsg::HttpsProxyChanged("proxy:3128".to_string()));
+        assert!(form.build().is_err());
+        form.update(ConnectionMsg::HttpsProxyChanged(
+            " http://proxy:3128 ".to_string(),
+        ));
+        assert_eq!(form.build().unwrap().https_proxy, "http://proxy:3128");
+
+        let saved = form.build().unwrap();
+        assert!(ConnectionForm::from_options(&saved).expanded);
+    }
+}
@@ -0,0 +1,356 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dialog for copying a collection to another server, tenant or database.
+//!
+//! The dialog collects the target, then shows the progress of the running
+//! copy and offers to resume it if it fails. Starting the copy is left to the
+//! app, which owns the tasks; this form only tracks its state.
+
+use crate::api::{CancelToken, CreateCollectionOptions};
+use crate::config::ServerConfig;
+use crate::transfer::{CollectionSource, CopyOptions, Resume, TransferProgress};
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget;
+
+/// Where a copy stands.
+#[derive(Debug, Clone)]
+pub enum CopyStatus {
+    /// Choosing the target
+    Ready,
+    /// Copying, or done copying
+    Copying {
+        progress: TransferProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the copy dialog.
+#[derive(Debug, Clone)]
+pub struct CopyForm {
+    pub source: CollectionSource,
+    /// Index in the config of the source server
+    pub source_index: usize,
+    /// Metadata and configuration of the source collection
+    pub create: CreateCollectionOptions,
+    /// Servers that can be written to, by index in the config
+    pub servers: Vec<(usize, ServerConfig)>,
+    /// Names of `servers` for the dropdown
+    pub server_names: Vec<String>,
+    /// Position of the target in `servers`
+    pub selected: usize,
+    pub tenant: String,
+    pub database: String,
+    pub name: String,
+    /// Documents copied before the copy stopped
+    pub copied: usize,
+    /// ID of the collection the copy created, which it can be resumed into
+    pub created: Option<String>,
+    /// The earlier attempt the running copy carries on after
+    pub resume: Option<Resume>,
+    pub status: CopyStatus,
+}
+
+/// Messages emitted by the copy dialog.
+#[derive(Debug, Clone)]
+pub enum CopyMsg {
+    ServerSelected(usize),
+    TenantChanged(String),
+    DatabaseChanged(String),
+    NameChanged(String),
+    /// Start copying into a new collection
+    Start,
+    /// Carry on after the documents already copied
+    Resume,
+    /// The copy created its target collection with this ID
+    Created(String),
+    Progress(TransferProgress),
+    /// Stop the running copy
+    Cancel,
+    /// Close the dialog
+    Close,
+}
+
+impl CopyForm {
+    /// Creates the dialog state, targeting the source's own server if it can
+    /// be written to. `servers` are all configured servers; read-only ones are
+    /// not offered.
+    pub fn new(
+        source: CollectionSource,
+        create: CreateCollectionOptions,
+        source_index: usize,
+        servers: &[ServerConfig],
+    ) -> Self {
+        let servers: Vec<(usize, ServerConfig)> = servers
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, server)| server.local_path().is_none())
+            .collect();
+        let selected = servers
+            .iter()
+            .position(|(index, _)| *index == source_index)
+            .unwrap_or_default();
+        let mut form = Self {
+            name: source.collection_name.clone(),
+            source,
+            source_index,
+            create,
+            server_names: servers
+                .iter()
+                .map(|(_, server)| server.name.clone())
+                .collect(),
+            servers,
+            selected,
+            tenant: String::new(),
+            database: String::new(),
+            copied: 0,
+            created: None,
+            resume: None,
+            status: CopyStatus::Ready,
+        };
+        form.select_server(selected);
+        form
+    }
+
+    /// Handles a dialog message.
+    pub fn update(&mut self, message: CopyMsg) {
+        match message {
+            CopyMsg::ServerSelected(index) => self.select_server(index),
+            CopyMsg::TenantChanged(value) => self.tenant = value,
+            CopyMsg::DatabaseChanged(value) => self.database = value,
+            CopyMsg::NameChanged(value) => self.name = value,
+            CopyMsg::Start | CopyMsg::Resume => {
+                self.resume = match message {
+                    CopyMsg::Resume => {
+                        // Without a collection of its own there is nothing to resume
+                        let Some(collection_id) = self.created.clone() else {
+                            return;
+                        };
+                        Some(Resume {
+                            collection_id,
+                            copied: self.copied,
+                        })
+                    }
+                    _ => {
+                        self.created = None;
+                        None
+                    }
+                };
+                self.copied = self.resume.as_ref().map_or(0, |resume| resume.copied);
+                self.status = CopyStatus::Copying {
+                    progress: TransferProgress::Running {
+                        done: self.copied,
+                        total: None,
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            CopyMsg::Created(collection_id) => self.created = Some(collection_id),
+            CopyMsg::Progress(update) => {
+                if let TransferProgress::Running { done, .. }
+                | TransferProgress::Finished { done }
+                | TransferProgress::Cancelled { done } = update
+                {
+                    self.copied = done;
+                }
+                if let CopyStatus::Copying { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            CopyMsg::Cancel => {
+                if let CopyStatus::Copying { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            CopyMsg::Close => {}
+        }
+    }
+
+    /// Whether the copy is running and cannot be closed yet.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, CopyStatus::Copying { progress, .. } if !progress.is_done())
+    }
+
+    /// Whether a stopped copy can carry on in the collection it created.
+    pub fn can_resume(&self) -> bool {
+        self.created.is_some()
+    }
+
+    /// Whether the target is complete and differs from the source.
+    pub fn is_valid(&self) -> bool {
+        let Some((index, _)) = self.servers.get(self.selected) else {
+            return false;
+        };
+        let target = (self.tenant.trim(), self.database.trim(), self.name.trim());
+        let source = (
+            self.source.tenant.as_str(),
+            self.source.database.as_str(),
+            self.source.collection_name.as_str(),
+        );
+        !target.0.is_empty()
+            && !target.1.is_empty()
+            && !target.2.is_empty()
+            && (*index != self.source_index || target != source)
+    }
+
+    /// Index in the config of the target server.
+    pub fn target_index(&self) -> Option<usize> {
+        self.servers.get(self.selected).map(|(index, _)| *index)
+    }
+
+    /// The collection to copy to; its ID is only known once it is created.
+    pub fn target(&self) -> Option<CollectionSource> {
+        let (_, server) = self.servers.get(self.selected)?;
+        Some(CollectionSource {
+            server: server.clone(),
+            tenant: self.tenant.trim().to_string(),
+            database: self.database.trim().to_string(),
+            collection_id: String::new(),
+            collection_name: self.name.trim().to_string(),
+        })
+    }
+
+    /// Options for the copy as currently started.
+    pub fn options(&self) -> CopyOptions {
+        CopyOptions {
+            create: self.create.clone(),
+            resume: self.resume.clone(),
+        }
+    }
+
+    fn select_server(&mut self, index: usize) {
+        if let Some((_, server)) = self.servers.get(index) {
+            self.selected = index;
+            self.tenant = server.tenant.clone();
+            self.database = server.database.clone();
+        }
+    }
+}
+
+/// Text describing the progress of a copy.
+fn progress_label(progress: &TransferProgress, name: &str) -> String {
+    match progress {
+        TransferProgress::Running {
+            done,
+            total: Some(total),
+        } => format!("Copied {} of {} documents", done, total),
+        TransferProgress::Running { done, total: None } => {
+            format!("Copied {} documents", done)
+        }
+        TransferProgress::Finished { done } => {
+            format!("Copied {} documents to '{}'", done, name)
+        }
+        TransferProgress::Cancelled { done } => {
+            format!("Copy cancelled after {} documents", done)
+        }
+        TransferProgress::Failed(error) => format!("Copy failed: {}", error),
+    }
+}
+
+/// Renders the copy dialog.
+pub fn view<'a, Message: Clone + 'static>(
+    form: &'a CopyForm,
+    on_message: impl Fn(CopyMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let dialog = widget::dialog().title(format!("Copy '{}'", form.source.collection_name));
+
+    let CopyStatus::Copying { progress, .. } = &form.status else {
+        let field = |label: &'a str, input: Element<'a, Message>| {
+            widget::row::with_capacity(2)
+                .push(widget::text::body(label).width(Length::Fixed(120.0)))
+                .push(input)
+                .spacing(space_s)
+                .align_y(Alignment::Center)
+        };
+        let mut controls = widget::column::with_capacity(5)
+            .push(widget::text::body(
+                "Creates a collection with the same metadata and configuration, then copies \
+                 every document with its metadata and embedding.",
+            ))
+            .spacing(space_s);
+        if form.servers.is_empty() {
+            controls = controls.push(
+                widget::text::caption("No server can be written to")
+                    .class(cosmic::style::Text::Accent),
+            );
+        } else {
+            controls = controls
+                .push(field(
+                    "Server",
+                    widget::dropdown(&form.server_names, Some(form.selected), move |index| {
+                        on_message(CopyMsg::ServerSelected(index))
+                    })
+                    .width(Length::Fixed(260.0))
+                    .into(),
+                ))
+                .push(field(
+                    "Tenant",
+                    widget::text_input("Tenant", &form.tenant)
+                        .on_input(move |s| on_message(CopyMsg::TenantChanged(s)))
+                        .width(Length::Fixed(260.0))
+                        .into(),
+                ))
+                .push(field(
+                    "Database",
+                    widget::text_input("Database", &form.database)
+                        .on_input(move |s| on_message(CopyMsg::DatabaseChanged(s)))
+                        .width(Length::Fixed(260.0))
+                        .into(),
+                ))
+                .push(field(
+                    "Collection",
+                    widget::text_input("Collection name", &form.name)
+                        .on_input(move |s| on_message(CopyMsg::NameChanged(s)))
+                        .width(Length::Fixed(260.0))
+                        .into(),
+                ));
+        }
+
+        return dialog
+            .control(controls)
+            .primary_action(
+                widget::button::suggested("Copy")
+                    .on_press_maybe(form.is_valid().then(|| on_message(CopyMsg::Start))),
+            )
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(CopyMsg::Close)),
+            )
+            .into();
+    };
+
+    let mut controls = widget::column::with_capacity(2).spacing(space_s);
+    if let Some(fraction) = progress.fraction() {
+        controls = controls.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fill));
+    }
+    let label = widget::text::body(progress_label(progress, &form.name));
+    controls = controls.push(match progress {
+        TransferProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+        _ => label,
+    });
+
+    let dialog = dialog.control(controls);
+    match progress {
+        TransferProgress::Running { .. } => dialog
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(CopyMsg::Cancel)),
+            )
+            .into(),
+        // Documents copied so far are kept, so the copy can carry on after them
+        TransferProgress::Cancelled { .. } | TransferProgress::Failed(_) if form.can_resume() => {
+            dialog
+                .primary_action(
+                    widget::button::suggested("Resume").on_press(on_message(CopyMsg::Resume)),
+                )
+                .secondary_action(
+                    widget::button::standard("Close").on_press(on_message(CopyMsg::Close)),
+                )
+                .into()
+        }
+        // Finished, or stopped before it had a collection to carry on in
+        _ => dialog
+            .primary_action(widget::button::suggested("Close").on_press(on_message(CopyMsg::Close)))
+            .into(),
+    }
+}
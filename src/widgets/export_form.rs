@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dialog for exporting a collection to a JSONL file.
+//!
+//! The dialog collects the options, then shows the progress of the running
+//! export. Choosing the file and starting the export are left to the app,
+//! which owns the tasks; this form only tracks their state.
+
+use crate::api::{CancelToken, DocumentFilter};
+use crate::transfer::{CollectionSource, ExportOptions, TransferProgress};
+use cosmic::iced::Length;
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::path::PathBuf;
+
+/// Where an export stands.
+#[derive(Debug, Clone)]
+pub enum ExportStatus {
+    /// Choosing options
+    Ready,
+    /// Waiting for the save dialog
+    ChoosingFile,
+    /// Writing to a file, or done writing
+    Writing {
+        path: PathBuf,
+        progress: TransferProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the export dialog.
+#[derive(Debug, Clone)]
+pub struct ExportForm {
+    pub source: CollectionSource,
+    /// Filter applied to the collection's document list when the dialog opened
+    pub filter: DocumentFilter,
+    pub use_filter: bool,
+    pub embeddings: bool,
+    pub status: ExportStatus,
+    /// Error from the save dialog
+    pub error: Option<String>,
+}
+
+/// Messages emitted by the export dialog.
+#[derive(Debug, Clone)]
+pub enum ExportMsg {
+    UseFilterToggled(bool),
+    EmbeddingsToggled(bool),
+    /// Open the save dialog
+    ChooseFile,
+    /// The save dialog closed, with the chosen file unless it was cancelled
+    FileChosen(Result<Option<PathBuf>, String>),
+    Progress(TransferProgress),
+    /// Stop the running export
+    Cancel,
+    /// Close the dialog
+    Close,
+}
+
+impl ExportForm {
+    /// Creates the dialog state, offering to apply the filter if one is set.
+    pub fn new(source: CollectionSource, filter: DocumentFilter) -> Self {
+        Self {
+            source,
+            use_filter: !filter.is_empty(),
+            filter,
+            embeddings: false,
+            status: ExportStatus::Ready,
+            error: None,
+        }
+    }
+
+    /// Handles a dialog message.
+    pub fn update(&mut self, message: ExportMsg) {
+        match message {
+            ExportMsg::UseFilterToggled(value) => self.use_filter = value,
+            ExportMsg::EmbeddingsToggled(value) => self.embeddings = value,
+            ExportMsg::ChooseFile => {
+                self.status = ExportStatus::ChoosingFile;
+                self.error = None;
+            }
+            ExportMsg::FileChosen(Ok(Some(path))) => {
+                self.status = ExportStatus::Writing {
+                    path,
+                    progress: TransferProgress::Running {
+                        done: 0,
+                        total: None,
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            ExportMsg::FileChosen(Ok(None)) => self.status = ExportStatus::Ready,
+            ExportMsg::FileChosen(Err(error)) => {
+                self.status = ExportStatus::Ready;
+                self.error = Some(error);
+            }
+            ExportMsg::Progress(update) => {
+                if let ExportStatus::Writing { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            ExportMsg::Cancel => {
+                if let ExportStatus::Writing { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            ExportMsg::Close => {}
+        }
+    }
+
+    /// Options for the export as currently chosen.
+    pub fn options(&self) -> ExportOptions {
+        ExportOptions {
+            filter: if self.use_filter {
+                self.filter.clone()
+            } else {
+                DocumentFilter::default()
+            },
+            embeddings: self.embeddings,
+        }
+    }
+
+    /// Whether an export is writing and cannot be closed yet.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, ExportStatus::Writing { progress, .. } if !progress.is_done())
+    }
+
+    /// Suggested file name for the save dialog.
+    pub fn file_name(&self) -> String {
+        format!("{}.jsonl", self.source.collection_name)
+    }
+}
+
+/// Text describing the progress of an export.
+fn progress_label(progress: &TransferProgress, path: &std::path::Path) -> String {
+    match progress {
+        TransferProgress::Running {
+            done,
+            total: Some(total),
+        } => format!("Exported {} of {} documents", done, total),
+        TransferProgress::Running { done, total: None } => {
+            format!("Exported {} documents", done)
+        }
+        TransferProgress::Finished { done } => {
+            format!("Exported {} documents to {}", done, path.display())
+        }
+        TransferProgress::Cancelled { done } => {
+            format!("Export cancelled after {} documents", done)
+        }
+        TransferProgress::Failed(error) => format!("Export failed: {}", error),
+    }
+}
+
+/// Renders the export dialog.
+pub fn view<'a, Message: Clone + 'static>(
+    form: &'a ExportForm,
+    on_message: impl Fn(ExportMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let dialog = widget::dialog().title(format!("Export '{}'", form.source.collection_name));
+
+    let ExportStatus::Writing { path, progress, .. } = &form.status else {
+        let mut controls = widget::column::with_capacity(4)
+            .push(widget::text::body(
+                "Writes every document to a JSONL file, one JSON object per line with the \
+                 id, document, metadata and URI.",
+            ))
+            .spacing(space_s);
+        if !form.filter.is_empty() {
+            controls = controls.push(
+                widget::checkbox(
+                    "Only documents matching the current filter",
+                    form.use_filter,
+                )
+                .on_toggle(move |v| on_message(ExportMsg::UseFilterToggled(v))),
+            );
+        }
+        controls = controls.push(
+            widget::checkbox("Include embeddings", form.embeddings)
+                .on_toggle(move |v| on_message(ExportMsg::EmbeddingsToggled(v))),
+        );
+        if let Some(ref error) = form.error {
+            controls = controls
+                .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+        }
+
+        let choosing = matches!(form.status, ExportStatus::ChoosingFile);
+        return dialog
+            .control(controls)
+            .primary_action(
+                widget::button::suggested(if choosing {
+                    "Choosing..."
+                } else {
+                    "Choose File..."
+                })
+                .on_press_maybe((!choosing).then(|| on_message(ExportMsg::ChooseFile))),
+            )
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(ExportMsg::Close)),
+            )
+            .into();
+    };
+
+    let mut controls = widget::column::with_capacity(2).spacing(space_s);
+    if let Some(fraction) = progress.fraction() {
+        controls = controls.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fill));
+    }
+    let label = widget::text::body(progress_label(progress, path));
+    controls = controls.push(match progress {
+        TransferProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+        _ => label,
+    });
+
+    let dialog = dialog.control(controls);
+    if progress.is_done() {
+        dialog
+            .primary_action(
+                widget::button::suggested("Close").on_press(on_message(ExportMsg::Close)),
+            )
+            .into()
+    } else {
+        dialog
+            .secondary_action(
+                widget::button::standard("Cancel").on_press(on_message(ExportMsg::Cancel)),
+            )
+            .into()
+    }
+}
@@ -0,0 +1,543 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Filter builder panel for narrowing down document listings.
+//!
+//! The builder combines metadata conditions with AND/OR and an optional
+//! "document contains" text filter. A raw JSON mode accepts any filter
+//! Chroma understands, validated as you type.
+
+use crate::api::{Document, DocumentFilter, Operator, Where, WhereDocument};
+use crate::fl;
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget::{self, icon};
+use serde_json::Value;
+
+/// How the conditions of the builder are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Combinator {
+    /// All conditions must match (`$and`)
+    #[default]
+    All,
+    /// Any condition may match (`$or`)
+    Any,
+}
+
+/// Editing mode of the filter panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterMode {
+    #[default]
+    Builder,
+    Json,
+}
+
+/// Number of documents matching the applied filter.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum MatchCount {
+    #[default]
+    Unknown,
+    Counting,
+    Count(usize),
+    Error(String),
+}
+
+/// A single `key <operator> value` condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterCondition {
+    pub key: String,
+    pub operator: Operator,
+    /// Raw value text; lists for `in`/`not in` are comma separated
+    pub value: String,
+}
+
+impl FilterCondition {
+    fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            operator: Operator::Eq,
+            value: String::new(),
+        }
+    }
+}
+
+/// State of the filter builder panel.
+#[derive(Debug, Clone, Default)]
+pub struct FilterBuilder {
+    pub conditions: Vec<FilterCondition>,
+    pub combinator: Combinator,
+    /// Text the document content must contain (empty to ignore)
+    pub document_contains: String,
+    pub mode: FilterMode,
+    /// Raw JSON being edited in JSON mode
+    pub json: String,
+    /// Validation error for the raw JSON, if any
+    pub json_error: Option<String>,
+    /// Metadata keys seen in the loaded documents
+    pub keys: Vec<String>,
+    /// Match count for the applied filter
+    pub match_count: MatchCount,
+    /// The filter currently applied to the listing
+    pub applied: DocumentFilter,
+}
+
+/// Messages emitted by the filter builder panel.
+#[derive(Debug, Clone)]
+pub enum FilterMsg {
+    AddCondition,
+    AddConditionForKey(String),
+    RemoveCondition(usize),
+    KeyChanged(usize, String),
+    /// Advance the operator of a condition to the next one
+    CycleOperator(usize),
+    ValueChanged(usize, String),
+    CombinatorChanged(Combinator),
+    DocumentContainsChanged(String),
+    SetMode(FilterMode),
+    JsonChanged(String),
+    Apply,
+    Clear,
+}
+
+impl FilterBuilder {
+    /// Records the metadata keys present in a page of documents.
+    pub fn add_keys_from(&mut self, documents: &[Document]) {
+        for metadata in documents.iter().filter_map(|d| d.metadata.as_ref()) {
+            for key in metadata.keys() {
+                if !self.keys.contains(key) {
+                    self.keys.push(key.clone());
+                }
+            }
+        }
+        self.keys.sort();
+    }
+
+    /// Returns true if a non-empty filter is applied.
+    pub fn is_active(&self) -> bool {
+        !self.applied.is_empty()
+    }
+
+    /// Handles a panel message.
+    ///
+    /// Returns the new filter when it was applied or cleared, so the caller
+    /// can refetch the listing and its match count.
+    pub fn update(&mut self, message: FilterMsg) -> Option<DocumentFilter> {
+        match message {
+            FilterMsg::AddCondition => self.conditions.push(FilterCondition::new("")),
+            FilterMsg::AddConditionForKey(key) => self.conditions.push(FilterCondition::new(key)),
+            FilterMsg::RemoveCondition(index) => {
+                if index < self.conditions.len() {
+                    self.conditions.remove(index);
+                }
+            }
+            FilterMsg::KeyChanged(index, key) => {
+                if let Some(condition) = self.conditions.get_mut(index) {
+                    condition.key = key;
+                }
+            }
+            FilterMsg::CycleOperator(index) => {
+                if let Some(condition) = self.conditions.get_mut(index) {
+                    let position = Operator::ALL
+                        .iter()
+                        .position(|op| *op == condition.operator)
+                        .unwrap_or(0);
+                    condition.operator = Operator::ALL[(position + 1) % Operator::ALL.len()];
+                }
+            }
+            FilterMsg::ValueChanged(index, value) => {
+                if let Some(condition) = self.conditions.get_mut(index) {
+                    condition.value = value;
+                }
+            }
+            FilterMsg::CombinatorChanged(combinator) => self.combinator = combinator,
+            FilterMsg::DocumentContainsChanged(text) => self.document_contains = text,
+            FilterMsg::SetMode(mode) => {
+                // Seed the JSON editor with whatever the builder currently describes
+                if mode == FilterMode::Json
+                    && self.mode == FilterMode::Builder
+                    && let Ok(filter) = self.build()
+                {
+                    self.json = filter_to_json(&filter);
+                    self.json_error = None;
+                }
+                self.mode = mode;
+            }
+            FilterMsg::JsonChanged(json) => {
+                self.json_error = parse_json_filter(&json).err();
+                self.json = json;
+            }
+            FilterMsg::Apply => {
+                let filter = self.build().ok()?;
+                self.applied = filter.clone();
+                self.match_count = MatchCount::Counting;
+                return Some(filter);
+            }
+            FilterMsg::Clear => {
+                self.conditions.clear();
+                self.document_contains.clear();
+                self.json.clear();
+                self.json_error = None;
+                self.applied = DocumentFilter::default();
+                self.match_count = MatchCount::Counting;
+                return Some(DocumentFilter::default());
+            }
+        }
+        None
+    }
+
+    /// Sets the match count from a count request.
+    pub fn set_match_count(&mut self, result: Result<usize, String>) {
+        self.match_count = match result {
+            Ok(count) => MatchCount::Count(count),
+            Err(e) => MatchCount::Error(e),
+        };
+    }
+
+    /// Builds the filter described by the current mode.
+    pub fn build(&self) -> Result<DocumentFilter, String> {
+        if self.mode == FilterMode::Json {
+            return parse_json_filter(&self.json);
+        }
+
+        let mut conditions = Vec::with_capacity(self.conditions.len());
+        for (index, condition) in self.conditions.iter().enumerate() {
+            let key = condition.key.trim();
+            if key.is_empty() {
+                return Err(fl!("filter-condition-no-key", number = index + 1));
+            }
+            let value = if condition.operator.takes_list() {
+                Value::Array(
+                    condition
+                        .value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(parse_scalar)
+                        .collect(),
+                )
+            } else {
+                parse_scalar(condition.value.trim())
+            };
+            if matches!(
+                condition.operator,
+                Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte
+            ) && !value.is_number()
+            {
+                return Err(format!("'{}' needs a numeric value", key));
+            }
+            conditions.push(Where::field(key, condition.operator, value));
+        }
+
+        let where_filter = match self.combinator {
+            Combinator::All => Where::all(conditions),
+            Combinator::Any => Where::any(conditions),
+        };
+        let text = self.document_contains.trim();
+        let where_document = if text.is_empty() {
+            None
+        } else {
+            Some(WhereDocument::Contains(text.to_string()))
+        };
+
+        Ok(DocumentFilter {
+            where_filter,
+            where_document,
+        })
+    }
+}
+
+/// Parses a raw value: numbers and booleans keep their type, quotes force a string.
+pub(crate) fn parse_scalar(raw: &str) -> Value {
+    if let Some(quoted) = raw
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return Value::String(quoted.to_string());
+    }
+    match raw {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => serde_json::from_str::<serde_json::Number>(raw)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+/// Parses raw JSON into a filter.
+///
+/// Accepts either a bare `where` clause or an object with `where` and/or
+/// `where_document` keys.
+fn parse_json_filter(json: &str) -> Result<DocumentFilter, String> {
+    if json.trim().is_empty() {
+        return Ok(DocumentFilter::default());
+    }
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| fl!("filter-invalid-json", error = e.to_string()))?;
+    let object = value.as_object().ok_or_else(|| fl!("filter-not-object"))?;
+
+    let is_combined =
+        !object.is_empty() && object.keys().all(|k| k == "where" || k == "where_document");
+    if !is_combined {
+        return Ok(DocumentFilter {
+            where_filter: Some(Where::from_value(&value)?),
+            where_document: None,
+        });
+    }
+
+    Ok(DocumentFilter {
+        where_filter: object.get("where").map(Where::from_value).transpose()?,
+        where_document: object
+            .get("where_document")
+            .map(WhereDocument::from_value)
+            .transpose()?,
+    })
+}
+
+/// Renders a filter as the combined JSON accepted by the JSON editor.
+fn filter_to_json(filter: &DocumentFilter) -> String {
+    let mut object = serde_json::Map::new();
+    if let Some(ref where_filter) = filter.where_filter {
+        object.insert("where".to_string(), where_filter.to_value());
+    }
+    if let Some(ref where_document) = filter.where_document {
+        object.insert("where_document".to_string(), where_document.to_value());
+    }
+    if object.is_empty() {
+        return String::new();
+    }
+    serde_json::to_string(&Value::Object(object)).unwrap_or_default()
+}
+
+/// Short label for an operator button.
+fn operator_label(operator: Operator) -> String {
+    match operator {
+        Operator::Eq => "=".to_string(),
+        Operator::Ne => "≠".to_string(),
+        Operator::Gt => ">".to_string(),
+        Operator::Gte => "≥".to_string(),
+        Operator::Lt => "<".to_string(),
+        Operator::Lte => "≤".to_string(),
+        Operator::In => fl!("filter-in"),
+        Operator::Nin => fl!("filter-not-in"),
+    }
+}
+
+/// Picks the suggested style for the active option of a toggle group.
+pub(crate) fn toggle_class(active: bool) -> cosmic::theme::Button {
+    if active {
+        cosmic::theme::Button::Suggested
+    } else {
+        cosmic::theme::Button::Standard
+    }
+}
+
+/// Renders the filter builder panel.
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a FilterBuilder,
+    on_message: impl Fn(FilterMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut content = widget::column::with_capacity(12).spacing(space_s);
+
+    // Header with mode switch
+    content = content.push(
+        widget::row::with_capacity(3)
+            .push(widget::text::title4(fl!("filter")).width(Length::Fill))
+            .push(
+                widget::button::text(fl!("filter-builder"))
+                    .class(toggle_class(state.mode == FilterMode::Builder))
+                    .on_press(on_message(FilterMsg::SetMode(FilterMode::Builder))),
+            )
+            .push(
+                widget::button::text(fl!("filter-json"))
+                    .class(toggle_class(state.mode == FilterMode::Json))
+                    .on_press(on_message(FilterMsg::SetMode(FilterMode::Json))),
+            )
+            .spacing(4)
+            .align_y(Alignment::Center),
+    );
+
+    match state.mode {
+        FilterMode::Builder => {
+            content = content.push(
+                widget::row::with_capacity(3)
+                    .push(widget::text::body(fl!("filter-match")))
+                    .push(
+                        widget::button::text(fl!("filter-match-all"))
+                            .class(toggle_class(state.combinator == Combinator::All))
+                            .on_press(on_message(FilterMsg::CombinatorChanged(Combinator::All))),
+                    )
+                    .push(
+                        widget::button::text(fl!("filter-match-any"))
+                            .class(toggle_class(state.combinator == Combinator::Any))
+                            .on_press(on_message(FilterMsg::CombinatorChanged(Combinator::Any))),
+                    )
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+            );
+
+            for (index, condition) in state.conditions.iter().enumerate() {
+                let placeholder = if condition.operator.takes_list() {
+                    fl!("filter-list-placeholder")
+                } else {
+                    fl!("filter-value-placeholder")
+                };
+                content = content.push(
+                    widget::row::with_capacity(4)
+                        .push(
+                            widget::text_input(fl!("filter-key-placeholder"), &condition.key)
+                                .on_input(move |s| on_message(FilterMsg::KeyChanged(index, s)))
+                                .width(Length::Fixed(100.0)),
+                        )
+                        .push(
+                            widget::button::text(operator_label(condition.operator))
+                                .on_press(on_message(FilterMsg::CycleOperator(index))),
+                        )
+                        .push(
+                            widget::text_input(placeholder, &condition.value)
+                                .on_input(move |s| on_message(FilterMsg::ValueChanged(index, s)))
+                                .on_submit(move |_| on_message(FilterMsg::Apply))
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .class(cosmic::theme::Button::Icon)
+                                .on_press(on_message(FilterMsg::RemoveCondition(index))),
+                        )
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                );
+            }
+
+            content = content.push(
+                widget::button::text(fl!("filter-add-condition"))
+                    .on_press(on_message(FilterMsg::AddCondition)),
+            );
+
+            // Known metadata keys as shortcuts for new conditions
+            if !state.keys.is_empty() {
+                content = content.push(
+                    widget::text::caption(fl!("filter-metadata-keys"))
+                        .class(cosmic::style::Text::Accent),
+                );
+                let key_buttons: Vec<Element<'a, Message>> = state
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        widget::button::text(key.as_str())
+                            .on_press(on_message(FilterMsg::AddConditionForKey(key.clone())))
+                            .into()
+                    })
+                    .collect();
+                content = content.push(
+                    widget::flex_row(key_buttons)
+                        .row_spacing(4)
+                        .column_spacing(4),
+                );
+            }
+
+            content = content.push(widget::text::body(fl!("filter-document-contains")));
+            content = content.push(
+                widget::text_input(fl!("filter-text-placeholder"), &state.document_contains)
+                    .on_input(move |s| on_message(FilterMsg::DocumentContainsChanged(s)))
+                    .on_submit(move |_| on_message(FilterMsg::Apply))
+                    .width(Length::Fill),
+            );
+        }
+        FilterMode::Json => {
+            content = content.push(widget::text::caption(fl!(
+                "filter-json-description",
+                combined = "{\"where\": ..., \"where_document\": ...}"
+            )));
+            content = content.push(
+                widget::text_input("{\"source\": {\"$eq\": \"wiki\"}}", &state.json)
+                    .on_input(move |s| on_message(FilterMsg::JsonChanged(s)))
+                    .on_submit(move |_| on_message(FilterMsg::Apply))
+                    .width(Length::Fill),
+            );
+            content = content.push(match state.json_error {
+                Some(ref error) => {
+                    widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent)
+                }
+                None => widget::text::caption(fl!("filter-valid")),
+            });
+        }
+    }
+
+    // Validation error from the builder, if the current conditions are incomplete
+    let build_result = state.build();
+    if let (FilterMode::Builder, Err(error)) = (state.mode, &build_result) {
+        content =
+            content.push(widget::text::caption(error.clone()).class(cosmic::style::Text::Accent));
+    }
+
+    let match_info = match &state.match_count {
+        MatchCount::Unknown => String::new(),
+        MatchCount::Counting => fl!("filter-counting"),
+        MatchCount::Count(count) if state.is_active() => fl!("filter-matching", count = *count),
+        MatchCount::Count(count) => fl!("filter-documents", count = *count),
+        MatchCount::Error(e) => fl!("filter-count-failed", error = e),
+    };
+    if !match_info.is_empty() {
+        content = content.push(widget::text::caption(match_info));
+    }
+
+    content = content.push(
+        widget::row::with_capacity(2)
+            .push(
+                widget::button::standard(fl!("filter-clear"))
+                    .on_press(on_message(FilterMsg::Clear)),
+            )
+            .push(
+                widget::button::suggested(fl!("filter-apply"))
+                    .on_press_maybe(build_result.is_ok().then(|| on_message(FilterMsg::Apply))),
+            )
+            .spacing(space_s),
+    );
+
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_from_conditions() {
+        let mut builder = FilterBuilder::default();
+        builder.update(FilterMsg::AddConditionForKey("source".to_string()));
+        builder.update(FilterMsg::ValueChanged(0, "wiki".to_string()));
+        builder.update(FilterMsg::AddConditionForKey("page".to_string()));
+        builder.update(FilterMsg::CycleOperator(1)); // = -> ≠
+        builder.update(FilterMsg::CycleOperator(1)); // ≠ -> >
+        builder.update(FilterMsg::ValueChanged(1, "3".to_string()));
+        builder.update(FilterMsg::DocumentContainsChanged("rust".to_string()));
+
+        let filter = builder.update(FilterMsg::Apply).expect("filter is valid");
+        assert_eq!(
+            filter.where_filter.unwrap().to_value(),
+            json!({"$and": [{"source": {"$eq": "wiki"}}, {"page": {"$gt": 3}}]})
+        );
+        assert_eq!(
+            filter.where_document,
+            Some(WhereDocument::Contains("rust".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_json_mode_validation() {
+        let mut builder = FilterBuilder::default();
+        builder.update(FilterMsg::SetMode(FilterMode::Json));
+        builder.update(FilterMsg::JsonChanged("{\"page\": {\"$gt\":".to_string()));
+        assert!(builder.json_error.is_some());
+        assert!(builder.update(FilterMsg::Apply).is_none());
+
+        builder.update(FilterMsg::JsonChanged(
+            "{\"where_document\": {\"$contains\": \"rust\"}}".to_string(),
+        ));
+        assert!(builder.json_error.is_none());
+        let filter = builder.update(FilterMsg::Apply).expect("filter is valid");
+        assert!(filter.where_filter.is_none());
+        assert!(filter.where_document.is_some());
+    }
+}
@@ -0,0 +1,430 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dialog for importing documents from a file or folder into a collection.
+//!
+//! The dialog walks through choosing the source, mapping CSV columns and
+//! previewing what will be imported, then shows the progress of the upserts.
+//! Opening the file chooser, reading the file and importing are left to the
+//! app, which owns the tasks; this form only tracks their state.
+
+use crate::api::{CancelToken, CreateCollectionOptions};
+use crate::transfer::{CollectionSource, ColumnMapping, ImportFile, ImportPlan, TransferProgress};
+use cosmic::iced::Length;
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::path::PathBuf;
+
+/// Documents shown in the preview
+const PREVIEW_ROWS: usize = 5;
+
+/// Skipped rows listed in the preview
+const PREVIEW_PROBLEMS: usize = 5;
+
+/// Characters of document text shown per preview row
+const SNIPPET_CHARS: usize = 80;
+
+/// Where an import stands.
+#[derive(Debug, Clone)]
+pub enum ImportStatus {
+    /// Choosing what to import
+    Ready,
+    /// Waiting for the open dialog
+    ChoosingFile,
+    /// Reading the chosen file or folder
+    Reading,
+    /// Showing what will be imported
+    Preview,
+    /// Upserting, or done upserting
+    Importing {
+        progress: TransferProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the import dialog.
+#[derive(Debug, Clone)]
+pub struct ImportForm {
+    pub server_index: usize,
+    pub target: CollectionSource,
+    /// Options for creating the target collection, if it does not exist yet
+    pub create: Option<CreateCollectionOptions>,
+    pub path: Option<PathBuf>,
+    pub file: Option<ImportFile>,
+    /// Dropdown entries for the ID column, starting with row numbers
+    pub id_options: Vec<String>,
+    /// Dropdown entries for the other columns, starting with none
+    pub column_options: Vec<String>,
+    pub mapping: ColumnMapping,
+    pub plan: ImportPlan,
+    pub status: ImportStatus,
+    /// Error from the open dialog or from reading the file
+    pub error: Option<String>,
+}
+
+/// Messages emitted by the import dialog.
+#[derive(Debug, Clone)]
+pub enum ImportMsg {
+    /// Open the file chooser for a JSONL or CSV file
+    ChooseFile,
+    /// Open the file chooser for a folder of text files
+    ChooseFolder,
+    /// The open dialog closed, with the chosen path unless it was cancelled
+    FileChosen(Result<Option<PathBuf>, String>),
+    /// The chosen file or folder was read
+    FileRead(Result<ImportFile, String>),
+    /// Dropdown indices; the first entry of each leaves the field unmapped
+    IdColumnSelected(usize),
+    DocumentColumnSelected(usize),
+    EmbeddingColumnSelected(usize),
+    /// Go back to choosing a file
+    Back,
+    /// Start upserting the planned documents
+    Start,
+    Progress(TransferProgress),
+    /// Stop the running import
+    Cancel,
+    /// Close the dialog
+    Close,
+}
+
+impl ImportForm {
+    /// Creates the dialog state for importing into `target`, which is created
+    /// first if `create` is given.
+    pub fn new(
+        server_index: usize,
+        target: CollectionSource,
+        create: Option<CreateCollectionOptions>,
+    ) -> Self {
+        Self {
+            server_index,
+            target,
+            create,
+            path: None,
+            file: None,
+            id_options: Vec::new(),
+            column_options: Vec::new(),
+            mapping: ColumnMapping::default(),
+            plan: ImportPlan::default(),
+            status: ImportStatus::Ready,
+            error: None,
+        }
+    }
+
+    /// Handles a dialog message.
+    pub fn update(&mut self, message: ImportMsg) {
+        match message {
+            ImportMsg::ChooseFile | ImportMsg::ChooseFolder => {
+                self.status = ImportStatus::ChoosingFile;
+                self.error = None;
+            }
+            ImportMsg::FileChosen(Ok(Some(path))) => {
+                self.path = Some(path);
+                self.status = ImportStatus::Reading;
+            }
+            ImportMsg::FileChosen(Ok(None)) => self.status = ImportStatus::Ready,
+            ImportMsg::FileChosen(Err(error)) | ImportMsg::FileRead(Err(error)) => {
+                self.status = ImportStatus::Ready;
+                self.error = Some(error);
+            }
+            ImportMsg::FileRead(Ok(file)) => {
+                let headers = file.headers();
+                self.mapping = ColumnMapping::guess(headers);
+                self.id_options = std::iter::once("(row number)".to_string())
+                    .chain(headers.iter().cloned())
+                    .collect();
+                self.column_options = std::iter::once("(none)".to_string())
+                    .chain(headers.iter().cloned())
+                    .collect();
+                self.plan = file.plan(&self.mapping);
+                self.file = Some(file);
+                self.status = ImportStatus::Preview;
+            }
+            ImportMsg::IdColumnSelected(index) => {
+                self.mapping.id = index.checked_sub(1);
+                self.replan();
+            }
+            ImportMsg::DocumentColumnSelected(index) => {
+                self.mapping.document = index.checked_sub(1);
+                self.replan();
+            }
+            ImportMsg::EmbeddingColumnSelected(index) => {
+                self.mapping.embedding = index.checked_sub(1);
+                self.replan();
+            }
+            ImportMsg::Back => {
+                self.file = None;
+                self.plan = ImportPlan::default();
+                self.status = ImportStatus::Ready;
+            }
+            ImportMsg::Start => {
+                self.status = ImportStatus::Importing {
+                    progress: TransferProgress::Running {
+                        done: 0,
+                        total: Some(self.plan.documents.len()),
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            ImportMsg::Progress(update) => {
+                if let ImportStatus::Importing { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            ImportMsg::Cancel => {
+                if let ImportStatus::Importing { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            ImportMsg::Close => {}
+        }
+    }
+
+    /// Whether an import is upserting and cannot be closed yet.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, ImportStatus::Importing { progress, .. } if !progress.is_done())
+    }
+
+    fn replan(&mut self) {
+        if let Some(file) = &self.file {
+            self.plan = file.plan(&self.mapping);
+        }
+    }
+}
+
+/// Text describing the progress of an import.
+fn progress_label(progress: &TransferProgress, collection: &str) -> String {
+    match progress {
+        TransferProgress::Running {
+            done,
+            total: Some(total),
+        } => format!("Imported {} of {} documents", done, total),
+        TransferProgress::Running { done, total: None } => {
+            format!("Imported {} documents", done)
+        }
+        TransferProgress::Finished { done } => {
+            format!("Imported {} documents into '{}'", done, collection)
+        }
+        TransferProgress::Cancelled { done } => {
+            format!("Import cancelled after {} documents", done)
+        }
+        TransferProgress::Failed(error) => format!("Import failed: {}", error),
+    }
+}
+
+/// First line of a document's text, shortened to fit a preview row.
+fn snippet(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > SNIPPET_CHARS || line.len() < text.trim_end().len() {
+        format!(
+            "{}...",
+            line.chars().take(SNIPPET_CHARS).collect::<String>()
+        )
+    } else {
+        line.to_string()
+    }
+}
+
+/// Dropdown for one mapped CSV column, with its label.
+fn column_dropdown<'a, Message: Clone + 'static>(
+    label: &'a str,
+    options: &'a [String],
+    column: Option<usize>,
+    on_select: impl Fn(usize) -> Message + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let selected = Some(column.map_or(0, |column| column + 1));
+    widget::row::with_capacity(2)
+        .push(widget::text::body(label).width(Length::Fixed(160.0)))
+        .push(widget::dropdown(options, selected, on_select).width(Length::Fixed(200.0)))
+        .spacing(space_s)
+        .align_y(cosmic::iced::Alignment::Center)
+        .into()
+}
+
+/// Renders the first documents to import and the rows that will be skipped.
+fn preview<'a, Message: Clone + 'static>(
+    form: &'a ImportForm,
+    on_message: impl Fn(ImportMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut content = widget::column::with_capacity(8).spacing(space_s);
+
+    if matches!(form.file, Some(ImportFile::Csv { .. })) {
+        content = content
+            .push(column_dropdown(
+                "ID column",
+                &form.id_options,
+                form.mapping.id,
+                move |index| on_message(ImportMsg::IdColumnSelected(index)),
+                space_s,
+            ))
+            .push(column_dropdown(
+                "Document column",
+                &form.column_options,
+                form.mapping.document,
+                move |index| on_message(ImportMsg::DocumentColumnSelected(index)),
+                space_s,
+            ))
+            .push(column_dropdown(
+                "Embedding column",
+                &form.column_options,
+                form.mapping.embedding,
+                move |index| on_message(ImportMsg::EmbeddingColumnSelected(index)),
+                space_s,
+            ))
+            .push(widget::text::caption(
+                "Other columns become metadata; numbers and true/false are stored as such.",
+            ));
+    }
+
+    let mut rows = widget::column::with_capacity(PREVIEW_ROWS).spacing(4);
+    for document in form.plan.documents.iter().take(PREVIEW_ROWS) {
+        let mut row =
+            widget::column::with_capacity(3).push(widget::text::heading(document.id.as_str()));
+        if let Some(text) = &document.document {
+            row = row.push(widget::text::caption(snippet(text)));
+        } else if let Some(embedding) = &document.embedding {
+            row = row.push(widget::text::caption(format!(
+                "Embedding with {} dimensions",
+                embedding.len()
+            )));
+        }
+        if let Some(metadata) = &document.metadata {
+            let json = serde_json::to_string(metadata).unwrap_or_default();
+            row = row.push(widget::text::caption(snippet(&json)));
+        }
+        rows = rows.push(widget::container(row).padding(4).width(Length::Fill));
+    }
+    if form.plan.documents.is_empty() {
+        rows = rows.push(widget::text::body("Nothing to import"));
+    }
+    content = content.push(widget::text::title4("Preview")).push(rows);
+
+    let problems = &form.plan.problems;
+    if !problems.is_empty() {
+        let mut skipped = widget::column::with_capacity(PREVIEW_PROBLEMS + 2)
+            .push(widget::text::title4(format!(
+                "{} rows skipped",
+                problems.len()
+            )))
+            .spacing(4);
+        for problem in problems.iter().take(PREVIEW_PROBLEMS) {
+            skipped = skipped
+                .push(widget::text::caption(problem.as_str()).class(cosmic::style::Text::Accent));
+        }
+        if problems.len() > PREVIEW_PROBLEMS {
+            skipped = skipped.push(widget::text::caption(format!(
+                "and {} more",
+                problems.len() - PREVIEW_PROBLEMS
+            )));
+        }
+        content = content.push(skipped);
+    }
+
+    widget::scrollable(content).height(Length::Shrink).into()
+}
+
+/// Renders the import dialog.
+pub fn view<'a, Message: Clone + 'static>(
+    form: &'a ImportForm,
+    on_message: impl Fn(ImportMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let title = if form.create.is_some() {
+        format!(
+            "Import into New Collection '{}'",
+            form.target.collection_name
+        )
+    } else {
+        format!("Import into '{}'", form.target.collection_name)
+    };
+    let dialog = widget::dialog().title(title);
+    let cancel = widget::button::standard("Cancel").on_press(on_message(ImportMsg::Close));
+
+    match &form.status {
+        ImportStatus::Ready | ImportStatus::ChoosingFile => {
+            let mut controls = widget::column::with_capacity(2)
+                .push(widget::text::body(
+                    "Upserts documents from a JSONL file in the export format, a CSV file \
+                     with one document per row, or a folder of .txt and .md files named by \
+                     their path.",
+                ))
+                .spacing(space_s);
+            if let Some(ref error) = form.error {
+                controls = controls
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            let choosing = matches!(form.status, ImportStatus::ChoosingFile);
+            dialog
+                .control(controls)
+                .primary_action(
+                    widget::button::suggested(if choosing {
+                        "Choosing..."
+                    } else {
+                        "Choose File..."
+                    })
+                    .on_press_maybe((!choosing).then(|| on_message(ImportMsg::ChooseFile))),
+                )
+                .secondary_action(cancel)
+                .tertiary_action(
+                    widget::button::text("Choose Folder...")
+                        .on_press_maybe((!choosing).then(|| on_message(ImportMsg::ChooseFolder))),
+                )
+                .into()
+        }
+
+        ImportStatus::Reading => {
+            let path = form
+                .path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            dialog
+                .control(widget::text::body(format!("Reading {}...", path)))
+                .secondary_action(cancel)
+                .into()
+        }
+
+        ImportStatus::Preview => {
+            let count = form.plan.documents.len();
+            dialog
+                .control(preview(form, on_message, space_s))
+                .primary_action(
+                    widget::button::suggested(format!("Import {} documents", count))
+                        .on_press_maybe((count > 0).then(|| on_message(ImportMsg::Start))),
+                )
+                .secondary_action(cancel)
+                .tertiary_action(widget::button::text("Back").on_press(on_message(ImportMsg::Back)))
+                .into()
+        }
+
+        ImportStatus::Importing { progress, .. } => {
+            let mut controls = widget::column::with_capacity(2).spacing(space_s);
+            if let Some(fraction) = progress.fraction() {
+                controls =
+                    controls.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fill));
+            }
+            let label = widget::text::body(progress_label(progress, &form.target.collection_name));
+            controls = controls.push(match progress {
+                TransferProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+                _ => label,
+            });
+
+            let dialog = dialog.control(controls);
+            if progress.is_done() {
+                dialog
+                    .primary_action(
+                        widget::button::suggested("Close").on_press(on_message(ImportMsg::Close)),
+                    )
+                    .into()
+            } else {
+                dialog
+                    .secondary_action(
+                        widget::button::standard("Cancel").on_press(on_message(ImportMsg::Cancel)),
+                    )
+                    .into()
+            }
+        }
+    }
+}
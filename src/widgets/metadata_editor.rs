@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Editable key/value list for document and collection metadata.
+//!
+//! Values are typed the same way as in the filter builder: numbers and
+//! booleans keep their type, quotes force a string, and JSON arrays or
+//! objects are kept as JSON.
+
+use super::filter_builder::parse_scalar;
+use crate::api::Metadata;
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget::{self, icon};
+use serde_json::Value;
+
+/// A single metadata entry being edited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataRow {
+    pub key: String,
+    pub value: String,
+}
+
+/// State of the metadata editor.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataEditor {
+    pub rows: Vec<MetadataRow>,
+    /// Keys present when editing started
+    original_keys: Vec<String>,
+}
+
+/// Messages emitted by the metadata editor.
+#[derive(Debug, Clone)]
+pub enum MetadataMsg {
+    AddRow,
+    RemoveRow(usize),
+    KeyChanged(usize, String),
+    ValueChanged(usize, String),
+}
+
+impl MetadataEditor {
+    /// Creates an editor pre-filled with existing metadata.
+    pub fn from_metadata(metadata: Option<&Metadata>) -> Self {
+        let mut rows: Vec<MetadataRow> = metadata
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| MetadataRow {
+                key: key.clone(),
+                value: format_value(value),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.key.cmp(&b.key));
+        let original_keys = rows.iter().map(|row| row.key.clone()).collect();
+
+        Self {
+            rows,
+            original_keys,
+        }
+    }
+
+    /// Handles an editor message.
+    pub fn update(&mut self, message: MetadataMsg) {
+        match message {
+            MetadataMsg::AddRow => self.rows.push(MetadataRow {
+                key: String::new(),
+                value: String::new(),
+            }),
+            MetadataMsg::RemoveRow(index) => {
+                if index < self.rows.len() {
+                    self.rows.remove(index);
+                }
+            }
+            MetadataMsg::KeyChanged(index, key) => {
+                if let Some(row) = self.rows.get_mut(index) {
+                    row.key = key;
+                }
+            }
+            MetadataMsg::ValueChanged(index, value) => {
+                if let Some(row) = self.rows.get_mut(index) {
+                    row.value = value;
+                }
+            }
+        }
+    }
+
+    /// Builds the edited metadata, or `None` if there are no entries.
+    ///
+    /// Rows with neither key nor value are ignored.
+    pub fn build(&self) -> Result<Option<Metadata>, String> {
+        let mut metadata = Metadata::new();
+        for row in &self.rows {
+            let key = row.key.trim();
+            if key.is_empty() {
+                if row.value.trim().is_empty() {
+                    continue;
+                }
+                return Err(format!("Value '{}' has no key", row.value.trim()));
+            }
+            if metadata
+                .insert(key.to_string(), parse_value(row.value.trim()))
+                .is_some()
+            {
+                return Err(format!("Duplicate metadata key '{}'", key));
+            }
+        }
+        Ok((!metadata.is_empty()).then_some(metadata))
+    }
+
+    /// Whether every entry the editor started with was removed and none added
+    pub fn is_cleared(&self) -> bool {
+        !self.original_keys.is_empty() && matches!(self.build(), Ok(None))
+    }
+
+    /// Builds the metadata for an update, setting removed keys to `null`
+    /// so the server deletes them instead of keeping the old values.
+    pub fn build_update(&self) -> Result<Option<Metadata>, String> {
+        let mut metadata = self.build()?.unwrap_or_default();
+        for key in &self.original_keys {
+            metadata.entry(key.clone()).or_insert(Value::Null);
+        }
+        Ok((!metadata.is_empty()).then_some(metadata))
+    }
+}
+
+/// Formats a value for editing, quoting strings that would otherwise change type.
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(text) if parse_value(text) != *value => format!("\"{}\"", text),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Parses an edited value, keeping JSON arrays and objects as JSON.
+fn parse_value(raw: &str) -> Value {
+    if (raw.starts_with('[') || raw.starts_with('{'))
+        && let Ok(value) = serde_json::from_str(raw)
+    {
+        return value;
+    }
+    parse_scalar(raw)
+}
+
+/// Renders the metadata editor.
+pub fn view<'a, Message: Clone + 'static>(
+    state: &'a MetadataEditor,
+    on_message: impl Fn(MetadataMsg) -> Message + Copy + 'a,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut content = widget::column::with_capacity(state.rows.len() + 2).spacing(space_s);
+
+    for (index, row) in state.rows.iter().enumerate() {
+        content = content.push(
+            widget::row::with_capacity(3)
+                .push(
+                    widget::text_input("key", &row.key)
+                        .on_input(move |s| on_message(MetadataMsg::KeyChanged(index, s)))
+                        .width(Length::Fixed(120.0)),
+                )
+                .push(
+                    widget::text_input("value", &row.value)
+                        .on_input(move |s| on_message(MetadataMsg::ValueChanged(index, s)))
+                        .width(Length::Fill),
+                )
+                .push(
+                    widget::button::icon(icon::from_name("list-remove-symbolic"))
+                        .class(cosmic::theme::Button::Icon)
+                        .on_press(on_message(MetadataMsg::RemoveRow(index))),
+                )
+                .spacing(4)
+                .align_y(Alignment::Center),
+        );
+    }
+
+    content = content
+        .push(widget::button::text("+ Metadata field").on_press(on_message(MetadataMsg::AddRow)));
+
+    if let Err(error) = state.build() {
+        content = content.push(widget::text::caption(error).class(cosmic::style::Text::Accent));
+    }
+
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_values_keep_their_type() {
+        let metadata: Metadata = [
+            ("page".to_string(), json!(3)),
+            ("draft".to_string(), json!(false)),
+            ("source".to_string(), json!("wiki")),
+            ("year".to_string(), json!("2024")),
+        ]
+        .into_iter()
+        .collect();
+
+        let editor = MetadataEditor::from_metadata(Some(&metadata));
+        assert_eq!(editor.build().unwrap(), Some(metadata));
+    }
+
+    #[test]
+    fn test_update_clears_removed_keys() {
+        let metadata: Metadata = [
+            ("page".to_string(), json!(3)),
+            ("source".to_string(), json!("wiki")),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut editor = MetadataEditor::from_metadata(Some(&metadata));
+        editor.update(MetadataMsg::RemoveRow(0)); // rows are sorted, so this is "page"
+        editor.update(MetadataMsg::AddRow);
+        editor.update(MetadataMsg::KeyChanged(1, "source".to_string()));
+        assert!(editor.build().is_err());
+
+        editor.update(MetadataMsg::KeyChanged(1, "lang".to_string()));
+        editor.update(MetadataMsg::ValueChanged(1, "en".to_string()));
+        let update = editor.build_update().unwrap().unwrap();
+        assert_eq!(update["page"], Value::Null);
+        assert_eq!(update["source"], json!("wiki"));
+        assert_eq!(update["lang"], json!("en"));
+        assert!(!editor.is_cleared());
+
+        editor.update(MetadataMsg::RemoveRow(1));
+        editor.update(MetadataMsg::RemoveRow(0));
+        assert!(editor.is_cleared());
+        assert!(!MetadataEditor::default().is_cleared());
+    }
+}
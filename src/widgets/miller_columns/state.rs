@@ -18,6 +18,8 @@ pub enum ColumnState<D> {
     NotLoaded,
     /// Currently fetching children.
     Loading,
+    /// Still fetching children, after a failed attempt.
+    Retrying,
     /// Children loaded successfully.
     Loaded(Vec<MillerItem<D>>),
     /// Failed to load children.
@@ -33,7 +35,7 @@ impl<D> Default for ColumnState<D> {
 impl<D> ColumnState<D> {
     /// Returns true if children are currently being loaded.
     pub fn is_loading(&self) -> bool {
-        matches!(self, ColumnState::Loading)
+        matches!(self, ColumnState::Loading | ColumnState::Retrying)
     }
 
     /// Returns true if children have been loaded successfully.
@@ -146,6 +148,21 @@ impl<D: Clone> MillerState<D> {
             .insert(parent_id.clone(), ColumnState::Loading);
     }
 
+    /// Mark a loading column as retrying, or back as loading.
+    ///
+    /// Columns that are not loading are left unchanged.
+    pub fn set_retrying(&mut self, parent_id: &ItemId, retrying: bool) {
+        if let Some(state) = self.children.get_mut(parent_id)
+            && state.is_loading()
+        {
+            *state = if retrying {
+                ColumnState::Retrying
+            } else {
+                ColumnState::Loading
+            };
+        }
+    }
+
     /// Mark children as error for a parent item.
     pub fn set_error(&mut self, parent_id: ItemId, error: String) {
         self.children.insert(parent_id, ColumnState::Error(error));
@@ -353,4 +370,23 @@ mod tests {
         );
         assert_eq!(state.visible_column_count(), 2); // Root + children column
     }
+
+    #[test]
+    fn test_set_retrying() {
+        let roots = vec![MillerItem::branch("1", "Item 1", ())];
+        let mut state: MillerState<()> = MillerState::new(roots);
+
+        state.set_loading(&"1".to_string());
+        state.set_retrying(&"1".to_string(), true);
+        assert!(matches!(
+            state.get_column_state(&"1".to_string()),
+            ColumnState::Retrying
+        ));
+        assert!(state.get_column_state(&"1".to_string()).is_loading());
+
+        // Loaded columns are not touched
+        state.set_children("1".to_string(), Vec::new());
+        state.set_retrying(&"1".to_string(), true);
+        assert!(state.get_column_state(&"1".to_string()).is_loaded());
+    }
 }
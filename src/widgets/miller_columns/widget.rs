@@ -194,6 +194,21 @@ where
             .into()
     }
 
+    /// Renders the default view for a column that is retrying to load.
+    fn default_retrying_view() -> Element<'a, Message> {
+        widget::container(
+            widget::column::with_capacity(2)
+                .push(widget::text::body("Retrying..."))
+                .push(widget::text::caption("The server did not respond"))
+                .align_x(Alignment::Center)
+                .spacing(4),
+        )
+        .padding(16)
+        .width(Length::Fill)
+        .align_x(cosmic::iced::alignment::Horizontal::Center)
+        .into()
+    }
+
     /// Renders the default empty view.
     fn default_empty_view() -> Element<'a, Message> {
         widget::container(widget::text::caption("No items"))
@@ -327,6 +342,14 @@ where
             .into()
     }
 
+    /// Renders a column in retrying state.
+    fn render_retrying_column(&self) -> Element<'a, Message> {
+        widget::container(Self::default_retrying_view())
+            .width(self.column_width)
+            .height(self.column_height)
+            .into()
+    }
+
     /// Renders a column in error state.
     fn render_error_column(&self, error: &str) -> Element<'a, Message> {
         widget::container(self.render_error(error))
@@ -376,6 +399,7 @@ where
                         self.render_loading_column()
                     }
                     ColumnState::Loading => self.render_loading_column(),
+                    ColumnState::Retrying => self.render_retrying_column(),
                     ColumnState::Loaded(children) => {
                         self.render_column(col, children, current_path.clone())
                     }
@@ -2,6 +2,17 @@
 
 //! Custom widgets for the Chromatic application.
 
+pub mod auth_form;
+pub mod backup_form;
+pub mod collection_options;
+pub mod connection_form;
+pub mod copy_form;
+pub mod export_form;
+pub mod filter_builder;
+pub mod import_form;
+pub mod metadata_editor;
 pub mod miller_columns;
+pub mod restore_form;
 
+pub use filter_builder::{FilterBuilder, FilterMsg};
 pub use miller_columns::MillerColumns;
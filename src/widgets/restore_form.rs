@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Dialog for restoring a database archive into a database.
+//!
+//! The dialog reads the chosen archive, lists its collections next to the
+//! ones already in the target database, and asks what to do with those whose
+//! name is taken before restoring. Choosing and reading the file and running
+//! the restore are left to the app, which owns the tasks; this form only
+//! tracks their state.
+
+use crate::api::CancelToken;
+use crate::transfer::{BackupSummary, Conflict, DatabaseSource, TransferProgress};
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Ways to handle a taken name, in dropdown order
+const CONFLICTS: [Conflict; 3] = [Conflict::Skip, Conflict::Overwrite, Conflict::Rename];
+
+/// Dropdown labels of `CONFLICTS`
+const CONFLICT_LABELS: [&str; 3] = [
+    "Skip the archived collection",
+    "Overwrite the existing collection",
+    "Restore under a new name",
+];
+
+/// Where a restore stands.
+#[derive(Debug, Clone)]
+pub enum RestoreStatus {
+    /// Waiting for the user to choose a file
+    Ready,
+    /// Waiting for the open dialog
+    ChoosingFile,
+    /// Reading the chosen archive
+    Reading,
+    /// Showing what the archive holds
+    Preview,
+    /// Restoring, or done restoring
+    Restoring {
+        progress: TransferProgress,
+        cancel: CancelToken,
+    },
+}
+
+/// State of the restore dialog.
+#[derive(Debug, Clone)]
+pub struct RestoreForm {
+    pub server_index: usize,
+    pub target: DatabaseSource,
+    pub path: Option<PathBuf>,
+    pub summary: Option<BackupSummary>,
+    /// Collections in the target database, once loaded
+    pub existing: HashSet<String>,
+    pub conflict: Conflict,
+    pub status: RestoreStatus,
+    /// Error from the open dialog or from reading the archive
+    pub error: Option<String>,
+}
+
+/// Messages emitted by the restore dialog.
+#[derive(Debug, Clone)]
+pub enum RestoreMsg {
+    /// Names of the collections in the target database
+    ExistingLoaded(Vec<String>),
+    /// Open the file chooser
+    ChooseFile,
+    /// The open dialog closed, with the chosen file unless it was cancelled
+    FileChosen(Result<Option<PathBuf>, String>),
+    /// The chosen archive was read
+    SummaryRead(Result<BackupSummary, String>),
+    /// Index into the conflict dropdown
+    ConflictSelected(usize),
+    /// Go back to choosing a file
+    Back,
+    /// Start restoring
+    Start,
+    Progress(TransferProgress),
+    /// Stop the running restore
+    Cancel,
+    /// Close the dialog
+    Close,
+}
+
+impl RestoreForm {
+    pub fn new(server_index: usize, target: DatabaseSource) -> Self {
+        Self {
+            server_index,
+            target,
+            path: None,
+            summary: None,
+            existing: HashSet::new(),
+            conflict: Conflict::default(),
+            status: RestoreStatus::Ready,
+            error: None,
+        }
+    }
+
+    /// Handles a dialog message.
+    pub fn update(&mut self, message: RestoreMsg) {
+        match message {
+            RestoreMsg::ExistingLoaded(names) => self.existing = names.into_iter().collect(),
+            RestoreMsg::ChooseFile => {
+                self.status = RestoreStatus::ChoosingFile;
+                self.error = None;
+            }
+            RestoreMsg::FileChosen(Ok(Some(path))) => {
+                self.path = Some(path);
+                self.status = RestoreStatus::Reading;
+            }
+            RestoreMsg::FileChosen(Ok(None)) => self.status = RestoreStatus::Ready,
+            RestoreMsg::FileChosen(Err(error)) | RestoreMsg::SummaryRead(Err(error)) => {
+                self.status = RestoreStatus::Ready;
+                self.error = Some(error);
+            }
+            RestoreMsg::SummaryRead(Ok(summary)) => {
+                self.summary = Some(summary);
+                self.status = RestoreStatus::Preview;
+            }
+            RestoreMsg::ConflictSelected(index) => {
+                if let Some(conflict) = CONFLICTS.get(index) {
+                    self.conflict = *conflict;
+                }
+            }
+            RestoreMsg::Back => {
+                self.summary = None;
+                self.status = RestoreStatus::Ready;
+            }
+            RestoreMsg::Start => {
+                self.status = RestoreStatus::Restoring {
+                    progress: TransferProgress::Running {
+                        done: 0,
+                        total: Some(self.documents_to_restore()),
+                    },
+                    cancel: CancelToken::new(),
+                };
+            }
+            RestoreMsg::Progress(update) => {
+                if let RestoreStatus::Restoring { progress, .. } = &mut self.status {
+                    *progress = update;
+                }
+            }
+            RestoreMsg::Cancel => {
+                if let RestoreStatus::Restoring { cancel, .. } = &self.status {
+                    cancel.cancel();
+                }
+            }
+            RestoreMsg::Close => {}
+        }
+    }
+
+    /// Whether a restore is running and cannot be closed yet.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.status, RestoreStatus::Restoring { progress, .. } if !progress.is_done())
+    }
+
+    /// Documents the restore would write with the chosen conflict handling.
+    pub fn documents_to_restore(&self) -> usize {
+        self.summary.as_ref().map_or(0, |summary| {
+            summary.documents_to_restore(&self.existing, self.conflict)
+        })
+    }
+}
+
+/// Text describing the progress of a restore.
+fn progress_label(progress: &TransferProgress, database: &str) -> String {
+    match progress {
+        TransferProgress::Running {
+            done,
+            total: Some(total),
+        } => format!("Restored {} of {} documents", done, total),
+        TransferProgress::Running { done, total: None } => {
+            format!("Restored {} documents", done)
+        }
+        TransferProgress::Finished { done } => {
+            format!("Restored {} documents into '{}'", done, database)
+        }
+        TransferProgress::Cancelled { done } => {
+            format!("Restore cancelled after {} documents", done)
+        }
+        TransferProgress::Failed(error) => format!("Restore failed: {}", error),
+    }
+}
+
+/// Renders the collections of an archive and how taken names are handled.
+fn preview<'a, Message: Clone + 'static>(
+    form: &'a RestoreForm,
+    summary: &'a BackupSummary,
+    on_message: impl Fn(RestoreMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let mut content = widget::column::with_capacity(4)
+        .push(widget::text::body(format!(
+            "Backup of '{}' in tenant '{}', taken {}",
+            summary.database, summary.tenant, summary.created
+        )))
+        .spacing(space_s);
+
+    let mut collections = widget::column::with_capacity(summary.collections.len()).spacing(4);
+    for collection in &summary.collections {
+        let taken = form.existing.contains(&collection.name);
+        collections =
+            collections.push(
+                widget::row::with_capacity(3)
+                    .push(widget::text::body(collection.name.as_str()).width(Length::Fill))
+                    .push_maybe(taken.then(|| {
+                        widget::text::caption("Exists").class(cosmic::style::Text::Accent)
+                    }))
+                    .push(widget::text::caption(format!(
+                        "{} documents",
+                        collection.documents
+                    )))
+                    .spacing(space_s)
+                    .align_y(Alignment::Center),
+            );
+    }
+    if summary.collections.is_empty() {
+        collections = collections.push(widget::text::body("The backup holds no collections"));
+    }
+    content = content.push(widget::scrollable(collections).height(Length::Shrink));
+
+    let conflicts = summary
+        .collections
+        .iter()
+        .any(|collection| form.existing.contains(&collection.name));
+    if conflicts {
+        let selected = CONFLICTS.iter().position(|c| *c == form.conflict);
+        content = content.push(
+            widget::row::with_capacity(2)
+                .push(widget::text::body("Existing collections"))
+                .push(
+                    widget::dropdown(&CONFLICT_LABELS, selected, move |index| {
+                        on_message(RestoreMsg::ConflictSelected(index))
+                    })
+                    .width(Length::Fixed(280.0)),
+                )
+                .spacing(space_s)
+                .align_y(Alignment::Center),
+        );
+        if form.conflict == Conflict::Overwrite {
+            content = content.push(
+                widget::text::caption(
+                    "Existing collections are deleted with all their documents first.",
+                )
+                .class(cosmic::style::Text::Accent),
+            );
+        }
+    }
+
+    content.into()
+}
+
+/// Renders the restore dialog.
+pub fn view<'a, Message: Clone + 'static>(
+    form: &'a RestoreForm,
+    on_message: impl Fn(RestoreMsg) -> Message + Copy + 'static,
+    space_s: u16,
+) -> Element<'a, Message> {
+    let dialog = widget::dialog().title(format!("Restore into '{}'", form.target.database));
+    let cancel = widget::button::standard("Cancel").on_press(on_message(RestoreMsg::Close));
+
+    match (&form.status, &form.summary) {
+        (RestoreStatus::Preview, Some(summary)) => {
+            let count = form.documents_to_restore();
+            dialog
+                .control(preview(form, summary, on_message, space_s))
+                .primary_action(
+                    widget::button::suggested(format!("Restore {} documents", count))
+                        .on_press(on_message(RestoreMsg::Start)),
+                )
+                .secondary_action(cancel)
+                .tertiary_action(
+                    widget::button::text("Back").on_press(on_message(RestoreMsg::Back)),
+                )
+                .into()
+        }
+
+        (RestoreStatus::Reading, _) => {
+            let path = form
+                .path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            dialog
+                .control(widget::text::body(format!("Reading {}...", path)))
+                .secondary_action(cancel)
+                .into()
+        }
+
+        (RestoreStatus::Restoring { progress, .. }, _) => {
+            let mut controls = widget::column::with_capacity(2).spacing(space_s);
+            if let Some(fraction) = progress.fraction() {
+                controls =
+                    controls.push(widget::progress_bar(0.0..=1.0, fraction).width(Length::Fill));
+            }
+            let label = widget::text::body(progress_label(progress, &form.target.database));
+            controls = controls.push(match progress {
+                TransferProgress::Failed(_) => label.class(cosmic::style::Text::Accent),
+                _ => label,
+            });
+
+            let dialog = dialog.control(controls);
+            if progress.is_done() {
+                dialog
+                    .primary_action(
+                        widget::button::suggested("Close").on_press(on_message(RestoreMsg::Close)),
+                    )
+                    .into()
+            } else {
+                dialog
+                    .secondary_action(
+                        widget::button::standard("Cancel").on_press(on_message(RestoreMsg::Cancel)),
+                    )
+                    .into()
+            }
+        }
+
+        _ => {
+            let mut controls = widget::column::with_capacity(2)
+                .push(widget::text::body(
+                    "Recreates the collections of a backup in this database with their \
+                     configuration, documents, metadata and embeddings.",
+                ))
+                .spacing(space_s);
+            if let Some(ref error) = form.error {
+                controls = controls
+                    .push(widget::text::caption(error.as_str()).class(cosmic::style::Text::Accent));
+            }
+
+            let choosing = matches!(form.status, RestoreStatus::ChoosingFile);
+            dialog
+                .control(controls)
+                .primary_action(
+                    widget::button::suggested(if choosing {
+                        "Choosing..."
+                    } else {
+                        "Choose File..."
+                    })
+                    .on_press_maybe((!choosing).then(|| on_message(RestoreMsg::ChooseFile))),
+                )
+                .secondary_action(cancel)
+                .into()
+        }
+    }
+}